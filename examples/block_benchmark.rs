@@ -0,0 +1,108 @@
+//! Compare per-sample and block processing through an effect chain
+//!
+//! Pushes 512-frame blocks through an 8-effect chain and reports the cost
+//! of both processing paths, then the block cost of each effect on its own.
+//! Costs are given in nanoseconds per sample and as a real-time factor:
+//! how many times faster than real time the audio is processed.
+
+use fundsp_rack::prelude::*;
+use std::time::{Duration, Instant};
+
+const SAMPLE_RATE: f64 = 48000.0;
+const BLOCK_SIZE: usize = 512;
+const ITERATIONS: usize = 2000;
+
+/// The benchmarked effects, in chain order
+const EFFECTS: &[(&str, &[(&str, f32)])] = &[
+    ("hpf", &[("cutoff", 80.0)]),
+    ("eq_3band", &[]),
+    ("compressor", &[("threshold", -18.0)]),
+    ("chorus", &[]),
+    ("phaser", &[]),
+    ("delay", &[("time", 0.25)]),
+    ("lpf", &[("cutoff", 8000.0)]),
+    ("reverb", &[]),
+];
+
+fn build_chain(effects: &[(&str, &[(&str, f32)])]) -> anyhow::Result<EffectChain> {
+    let mut chain =
+        EffectChain::with_registry(EffectRegistry::with_builtin()).with_sample_rate(SAMPLE_RATE);
+    for (name, params) in effects {
+        chain.add(name, params)?;
+    }
+    Ok(chain)
+}
+
+/// Nanoseconds per sample and real-time factor for `elapsed` spent on
+/// `ITERATIONS` blocks
+fn cost(elapsed: Duration) -> (f64, f64) {
+    let samples = (ITERATIONS * BLOCK_SIZE) as f64;
+    let ns_per_sample = elapsed.as_secs_f64() * 1e9 / samples;
+    let realtime = samples / SAMPLE_RATE / elapsed.as_secs_f64();
+    (ns_per_sample, realtime)
+}
+
+fn main() -> anyhow::Result<()> {
+    let in_l: Vec<f32> = (0..BLOCK_SIZE)
+        .map(|i| (i as f32 * 0.0577).sin() * 0.5)
+        .collect();
+    let in_r: Vec<f32> = (0..BLOCK_SIZE)
+        .map(|i| (i as f32 * 0.0431).sin() * 0.5)
+        .collect();
+    let mut out_l = vec![0.0; BLOCK_SIZE];
+    let mut out_r = vec![0.0; BLOCK_SIZE];
+
+    // Per-sample processing
+    let mut chain = build_chain(EFFECTS)?;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for i in 0..BLOCK_SIZE {
+            (out_l[i], out_r[i]) = chain.process(in_l[i], in_r[i]);
+        }
+    }
+    let per_sample = start.elapsed();
+
+    // Block processing
+    let mut chain = build_chain(EFFECTS)?;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        chain.process_block(&in_l, &in_r, &mut out_l, &mut out_r);
+    }
+    let block = start.elapsed();
+
+    println!(
+        "{} effects, {} frames per block at {} Hz",
+        chain.len(),
+        BLOCK_SIZE,
+        SAMPLE_RATE
+    );
+    for (label, elapsed) in [("per-sample", per_sample), ("block", block)] {
+        let (ns, realtime) = cost(elapsed);
+        println!(
+            "{:<12} {:>8.1} ns/sample {:>8.1}x real time",
+            label, ns, realtime
+        );
+    }
+    println!(
+        "{:<12} {:>8.2}x",
+        "speedup",
+        per_sample.as_secs_f64() / block.as_secs_f64()
+    );
+
+    // Each effect on its own, block processing
+    println!();
+    for effect in EFFECTS {
+        let mut chain = build_chain(std::slice::from_ref(effect))?;
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            chain.process_block(&in_l, &in_r, &mut out_l, &mut out_r);
+        }
+        let (ns, realtime) = cost(start.elapsed());
+        println!(
+            "{:<12} {:>8.1} ns/sample {:>8.1}x real time",
+            effect.0, ns, realtime
+        );
+    }
+
+    Ok(())
+}
@@ -24,7 +24,9 @@
 //!     .unwrap();
 //! ```
 
+use super::chain::EffectChain;
 use super::registry::{EffectControls, EffectRegistry};
+use super::EffectId;
 use crate::Result;
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -34,6 +36,7 @@ pub struct EffectBuilder<'a> {
     registry: &'a EffectRegistry,
     effect_name: String,
     parameters: HashMap<String, f32>,
+    id: Option<EffectId>,
 }
 
 impl<'a> EffectBuilder<'a> {
@@ -43,6 +46,7 @@ impl<'a> EffectBuilder<'a> {
             registry,
             effect_name: effect_name.into(),
             parameters: HashMap::new(),
+            id: None,
         }
     }
 
@@ -58,6 +62,17 @@ impl<'a> EffectBuilder<'a> {
         self
     }
 
+    /// Assign a stable ID, used when the effect is added to a chain
+    pub fn with_id(mut self, id: EffectId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// The assigned ID, if any
+    pub fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
     /// Set filter cutoff frequency
     pub fn cutoff(self, cutoff: f32) -> Self {
         self.param("cutoff", cutoff)
@@ -127,12 +142,24 @@ impl<'a> EffectBuilder<'a> {
     pub fn build(self) -> Result<(Box<dyn AudioUnit>, EffectControls)> {
         self.registry.build(&self.effect_name, &self.parameters)
     }
+
+    /// Add the effect to a chain, keeping the ID set with `with_id`
+    ///
+    /// The effect is built from the chain's registry. Returns the index of
+    /// the new effect.
+    pub fn add_to(self, chain: &mut EffectChain) -> Result<usize> {
+        match self.id {
+            Some(id) => chain.add_effect_with_id(id, &self.effect_name, self.parameters),
+            None => chain.add_effect(&self.effect_name, &self.parameters),
+        }
+    }
 }
 
 /// Standalone effect builder (doesn't require a registry reference)
 pub struct Effect {
     effect_name: String,
     parameters: HashMap<String, f32>,
+    id: Option<EffectId>,
 }
 
 impl Effect {
@@ -141,6 +168,7 @@ impl Effect {
         Self {
             effect_name: effect_name.into(),
             parameters: HashMap::new(),
+            id: None,
         }
     }
 
@@ -156,6 +184,17 @@ impl Effect {
         self
     }
 
+    /// Assign a stable ID, used when the effect is added to a chain
+    pub fn with_id(mut self, id: EffectId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// The assigned ID, if any
+    pub fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
     /// Set filter cutoff frequency
     pub fn cutoff(self, cutoff: f32) -> Self {
         self.param("cutoff", cutoff)
@@ -234,6 +273,17 @@ impl Effect {
     ) -> Result<(Box<dyn AudioUnit>, EffectControls)> {
         registry.build(&self.effect_name, &self.parameters)
     }
+
+    /// Add the effect to a chain, keeping the ID set with `with_id`
+    ///
+    /// The effect is built from the chain's registry. Returns the index of
+    /// the new effect.
+    pub fn add_to(self, chain: &mut EffectChain) -> Result<usize> {
+        match self.id {
+            Some(id) => chain.add_effect_with_id(id, &self.effect_name, self.parameters),
+            None => chain.add_effect(&self.effect_name, &self.parameters),
+        }
+    }
 }
 
 /// Extension trait for EffectRegistry to enable fluent API
@@ -282,4 +332,29 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_with_id_adds_to_chain() {
+        let registry = EffectRegistry::with_builtin();
+        let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+        let id = EffectId::new_v4();
+
+        let builder = registry.effect("lpf").cutoff(1200.0).with_id(id);
+        assert_eq!(builder.id(), Some(id));
+        let index = builder.add_to(&mut chain).unwrap();
+        assert_eq!(chain.find_effect_index(id), Some(index));
+        assert_eq!(chain.effects[index].controls.get("cutoff"), Some(1200.0));
+
+        let other = EffectId::new_v4();
+        let index = Effect::new("hpf")
+            .with_id(other)
+            .add_to(&mut chain)
+            .unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(chain.effect_id(1), Some(other));
+
+        // Without an ID the effect is anonymous
+        let index = Effect::new("reverb").add_to(&mut chain).unwrap();
+        assert_eq!(chain.effect_id(index), None);
+    }
 }
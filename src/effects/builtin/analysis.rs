@@ -0,0 +1,258 @@
+//! Analysis effects (transient detection)
+//!
+//! These effects pass audio through unchanged and publish what they detect
+//! through `Shared` meters in their `EffectControls`.
+
+use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
+use super::super::sidechain::{db_to_amplitude, sidechain_peak};
+use fundsp::hacker32::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Release time of the fast (onset) envelope in seconds
+const FAST_RELEASE: f32 = 0.005;
+/// Time constant of the slow (background) envelope in seconds
+const SLOW_TIME: f32 = 0.02;
+/// How far the fast envelope must rise above the background to count as an onset
+const ONSET_RATIO: f32 = 2.0;
+
+/// Onset detector that passes audio through unchanged
+///
+/// An onset is detected when the fast peak envelope rises above `threshold`
+/// and at least 6 dB above the slower background envelope. After a hit no
+/// new onset is reported for `refractory` milliseconds.
+///
+/// On each onset `trigger` goes to 1.0 for the rest of the processed block
+/// (or for `MAX_BUFFER_SIZE` samples when ticked per sample) and `hits` is
+/// incremented, so a host polling once per block sees every hit.
+#[derive(Clone)]
+pub struct TransientDetector {
+    /// Detection threshold in dB
+    pub threshold: Shared,
+    /// Refractory period in milliseconds
+    pub refractory: Shared,
+    /// Trigger output (1.0 while a hit is being reported)
+    pub trigger: Shared,
+    /// Total number of detected hits
+    pub hits: Shared,
+    fast_env: f32,
+    slow_env: f32,
+    refractory_left: usize,
+    trigger_left: usize,
+    sample_rate: f32,
+}
+
+impl TransientDetector {
+    /// Create a new transient detector
+    pub fn new(threshold_db: f32, refractory_ms: f32) -> Self {
+        Self {
+            threshold: shared(threshold_db),
+            refractory: shared(refractory_ms),
+            trigger: shared(0.0),
+            hits: shared(0.0),
+            fast_env: 0.0,
+            slow_env: 0.0,
+            refractory_left: 0,
+            trigger_left: 0,
+            sample_rate: 48000.0,
+        }
+    }
+
+    /// Update the envelopes and return true if this frame starts an onset
+    #[inline]
+    fn detect(&mut self, left: f32, right: f32) -> bool {
+        let level = sidechain_peak(left, right);
+        let fast_release = (-1.0 / (FAST_RELEASE * self.sample_rate)).exp();
+        let slow_coeff = (-1.0 / (SLOW_TIME * self.sample_rate)).exp();
+
+        let background = self.slow_env;
+        self.fast_env = level.max(self.fast_env * fast_release);
+        self.slow_env = level + slow_coeff * (self.slow_env - level);
+
+        if self.refractory_left > 0 {
+            self.refractory_left -= 1;
+            return false;
+        }
+
+        let threshold = db_to_amplitude(self.threshold.value());
+        if self.fast_env > threshold && self.fast_env > background * ONSET_RATIO {
+            let refractory = self.refractory.value().max(0.0) * 0.001 * self.sample_rate;
+            self.refractory_left = refractory as usize;
+            self.hits.set_value(self.hits.value() + 1.0);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl AudioUnit for TransientDetector {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.fast_env = 0.0;
+        self.slow_env = 0.0;
+        self.refractory_left = 0;
+        self.trigger_left = 0;
+        self.trigger.set_value(0.0);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        if self.detect(input[0], input[1]) {
+            self.trigger_left = fundsp::MAX_BUFFER_SIZE;
+            self.trigger.set_value(1.0);
+        } else if self.trigger_left > 0 {
+            self.trigger_left -= 1;
+            if self.trigger_left == 0 {
+                self.trigger.set_value(0.0);
+            }
+        }
+        output[0] = input[0];
+        output[1] = input[1];
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        let mut hit = false;
+        for i in 0..size {
+            let (l, r) = (input.at_f32(0, i), input.at_f32(1, i));
+            hit |= self.detect(l, r);
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+        self.trigger_left = 0;
+        self.trigger.set_value(if hit { 1.0 } else { 0.0 });
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"transient_detect";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Transient detector effect
+pub struct TransientDetectBuilder;
+
+impl EffectBuilder for TransientDetectBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let threshold = params.get("threshold").copied().unwrap_or(-30.0);
+        let refractory = params.get("refractory").copied().unwrap_or(50.0);
+
+        let detector = TransientDetector::new(threshold, refractory);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("threshold".to_string(), detector.threshold.clone());
+        controls
+            .params
+            .insert("refractory".to_string(), detector.refractory.clone());
+        controls
+            .meters
+            .insert("trigger".to_string(), detector.trigger.clone());
+        controls
+            .meters
+            .insert("hits".to_string(), detector.hits.clone());
+
+        (Box::new(detector), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new(
+            "transient_detect",
+            "Transient detector (passes audio, reports onsets)",
+        )
+        .with_param("threshold", -30.0, -80.0, 0.0)
+        .with_param("refractory", 50.0, 1.0, 1000.0)
+        .with_tag("analysis")
+    }
+}
+
+/// Register all analysis effects
+pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
+    registry.register("transient_detect", Arc::new(TransientDetectBuilder));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_once_per_impulse() {
+        let (mut unit, controls) = TransientDetectBuilder.build(&HashMap::new());
+        let mut input = BufferVec::new(2);
+        let mut output = BufferVec::new(2);
+
+        // An impulse every 100ms (75 blocks of 64 samples), 5 impulses total
+        let impulse_every = 75;
+        let mut triggered_blocks = Vec::new();
+        for block in 0..impulse_every * 5 {
+            for i in 0..fundsp::MAX_BUFFER_SIZE {
+                let x = if block % impulse_every == 0 && i == 10 {
+                    0.9
+                } else {
+                    0.0
+                };
+                input.set_f32(0, i, x);
+                input.set_f32(1, i, x);
+            }
+            unit.process(
+                fundsp::MAX_BUFFER_SIZE,
+                &input.buffer_ref(),
+                &mut output.buffer_mut(),
+            );
+
+            // Audio passes through unchanged
+            assert_eq!(output.at_f32(0, 10), input.at_f32(0, 10));
+            if controls.meter("trigger") == Some(1.0) {
+                triggered_blocks.push(block);
+            }
+        }
+
+        assert_eq!(triggered_blocks, vec![0, 75, 150, 225, 300]);
+        assert_eq!(controls.meter("hits"), Some(5.0));
+    }
+
+    #[test]
+    fn test_sustained_tone_triggers_once() {
+        let mut detector = TransientDetector::new(-30.0, 50.0);
+        let mut output = [0.0; 2];
+        for i in 0..48000 {
+            let x = (i as f32 * 0.05).sin() * 0.5;
+            detector.tick(&[x, x], &mut output);
+        }
+        assert_eq!(detector.hits.value(), 1.0);
+    }
+
+    #[test]
+    fn test_quiet_input_below_threshold() {
+        let mut detector = TransientDetector::new(-20.0, 50.0);
+        let mut output = [0.0; 2];
+        for i in 0..4800 {
+            let x = if i % 1000 == 0 { 0.05 } else { 0.0 };
+            detector.tick(&[x, x], &mut output);
+        }
+        assert_eq!(detector.hits.value(), 0.0);
+        assert_eq!(detector.trigger.value(), 0.0);
+    }
+}
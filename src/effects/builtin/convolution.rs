@@ -0,0 +1,515 @@
+//! Convolution reverb (impulse response)
+//!
+//! Convolves the input with a recorded or synthetic impulse response using
+//! uniformly partitioned overlap-save convolution. The impulse response is
+//! split into blocks whose spectra are multiplied with a delay line of input
+//! spectra. The FFT work per sample is fixed by the block size, but the
+//! spectral multiply-adds grow linearly with the number of partitions, so a
+//! response twice as long costs roughly twice as much; a larger partition
+//! size trades latency for fewer partitions. Either way it is far cheaper
+//! than direct convolution. The price is one block of latency, reported through
+//! [`EffectMetadata::latency_samples`] for delay compensation.
+
+use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
+use fundsp::hacker32::*;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+/// Default partition size in samples (also the latency)
+pub const DEFAULT_BLOCK_SIZE: usize = 256;
+/// Smallest partition size, one fundsp processing block
+const MIN_BLOCK_SIZE: usize = 64;
+/// Length of the default synthetic impulse response in seconds
+const DEFAULT_IR_SECONDS: f32 = 1.2;
+
+/// Complex number as (re, im)
+pub(crate) type Complex = (f32, f32);
+
+#[inline]
+fn complex_mul(a: Complex, b: Complex) -> Complex {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+/// In-place radix-2 FFT of a fixed power-of-two size
+#[derive(Clone)]
+pub(crate) struct Fft {
+    /// `e^(-2πik/n)` for `k < n/2`
+    twiddles: Vec<Complex>,
+    bit_reverse: Vec<usize>,
+}
+
+impl Fft {
+    pub(crate) fn new(size: usize) -> Self {
+        let bits = size.trailing_zeros();
+        Self {
+            twiddles: (0..size / 2)
+                .map(|k| {
+                    let angle = -TAU * k as f32 / size as f32;
+                    (angle.cos(), angle.sin())
+                })
+                .collect(),
+            bit_reverse: (0..size)
+                .map(|i| i.reverse_bits() >> (usize::BITS - bits))
+                .collect(),
+        }
+    }
+
+    /// Forward transform, or unscaled inverse transform when `inverse` is set
+    pub(crate) fn transform(&self, data: &mut [Complex], inverse: bool) {
+        let n = data.len();
+        for i in 0..n {
+            let j = self.bit_reverse[i];
+            if i < j {
+                data.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            let stride = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let (re, im) = self.twiddles[k * stride];
+                    let twiddle = if inverse { (re, -im) } else { (re, im) };
+                    let a = data[start + k];
+                    let b = complex_mul(data[start + k + half], twiddle);
+                    data[start + k] = (a.0 + b.0, a.1 + b.1);
+                    data[start + k + half] = (a.0 - b.0, a.1 - b.1);
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+/// Per-channel convolution state
+#[derive(Clone)]
+struct ConvolverChannel {
+    /// Block being filled
+    input: Vec<f32>,
+    /// Last complete block, also the latency-aligned dry signal
+    previous: Vec<f32>,
+    /// Wet output for `previous`
+    output: Vec<f32>,
+    /// Spectra of recent input frames, newest at the shared head
+    history: Vec<Vec<Complex>>,
+}
+
+impl ConvolverChannel {
+    fn new(block_size: usize, partitions: usize) -> Self {
+        Self {
+            input: vec![0.0; block_size],
+            previous: vec![0.0; block_size],
+            output: vec![0.0; block_size],
+            history: vec![vec![(0.0, 0.0); block_size * 2]; partitions],
+        }
+    }
+}
+
+/// Resample `ir` by linear interpolation
+fn resample(ir: &[f32], from: f64, to: f64) -> Vec<f32> {
+    if from == to || ir.is_empty() {
+        return ir.to_vec();
+    }
+    let ratio = to / from;
+    let len = ((ir.len() as f64 * ratio).round() as usize).max(1);
+    (0..len)
+        .map(|i| {
+            let t = i as f64 / ratio;
+            let index = t.floor() as usize;
+            let frac = (t - index as f64) as f32;
+            let a = ir.get(index).copied().unwrap_or(0.0);
+            let b = ir.get(index + 1).copied().unwrap_or(0.0);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Stereo convolution reverb
+///
+/// Both channels are convolved with the same mono impulse response. The
+/// response is resampled when the sample rate differs from its own. Output
+/// is delayed by `block_size` samples; the dry signal is delayed to match.
+#[derive(Clone)]
+pub struct ConvolutionReverb {
+    /// Dry/wet mix
+    pub mix: Shared,
+    ir: Arc<Vec<f32>>,
+    ir_sample_rate: f64,
+    block_size: usize,
+    fft: Fft,
+    /// Spectra of the impulse response partitions at the current sample rate
+    partitions: Vec<Vec<Complex>>,
+    channels: [ConvolverChannel; 2],
+    /// Slot in the channel histories holding the newest input spectrum
+    head: usize,
+    /// Position within the current block
+    pos: usize,
+    /// Scratch buffer for transforms
+    scratch: Vec<Complex>,
+    sample_rate: f64,
+}
+
+impl ConvolutionReverb {
+    /// Create a convolution reverb for an impulse response recorded at
+    /// `ir_sample_rate`
+    ///
+    /// `block_size` is rounded up to a power of two of at least 64.
+    pub fn new(ir: Arc<Vec<f32>>, ir_sample_rate: f64, block_size: usize, mix: f32) -> Self {
+        let block_size = block_size.max(MIN_BLOCK_SIZE).next_power_of_two();
+        let mut reverb = Self {
+            mix: shared(mix),
+            ir,
+            ir_sample_rate,
+            block_size,
+            fft: Fft::new(block_size * 2),
+            partitions: Vec::new(),
+            channels: [
+                ConvolverChannel::new(block_size, 1),
+                ConvolverChannel::new(block_size, 1),
+            ],
+            head: 0,
+            pos: 0,
+            scratch: vec![(0.0, 0.0); block_size * 2],
+            sample_rate: ir_sample_rate,
+        };
+        reverb.prepare();
+        reverb
+    }
+
+    /// Latency in samples
+    pub fn latency_samples(&self) -> usize {
+        self.block_size
+    }
+
+    /// Transform the impulse response partitions and clear the state
+    fn prepare(&mut self) {
+        let ir = resample(&self.ir, self.ir_sample_rate, self.sample_rate);
+        let block_size = self.block_size;
+        self.partitions = ir
+            .chunks(block_size)
+            .map(|chunk| {
+                let mut spectrum = vec![(0.0, 0.0); block_size * 2];
+                for (bin, &x) in spectrum.iter_mut().zip(chunk) {
+                    *bin = (x, 0.0);
+                }
+                self.fft.transform(&mut spectrum, false);
+                spectrum
+            })
+            .collect();
+        if self.partitions.is_empty() {
+            self.partitions.push(vec![(0.0, 0.0); block_size * 2]);
+        }
+
+        let partitions = self.partitions.len();
+        self.channels = [
+            ConvolverChannel::new(block_size, partitions),
+            ConvolverChannel::new(block_size, partitions),
+        ];
+        self.head = 0;
+        self.pos = 0;
+    }
+
+    /// Convolve the block just filled and make it the current output
+    fn process_block(&mut self) {
+        let block_size = self.block_size;
+        let size = block_size * 2;
+        let partitions = self.partitions.len();
+        self.head = (self.head + 1) % partitions;
+
+        for channel in &mut self.channels {
+            // Overlap-save frame: previous block followed by the new one
+            let spectrum = &mut channel.history[self.head];
+            for (bin, &x) in spectrum
+                .iter_mut()
+                .zip(channel.previous.iter().chain(&channel.input))
+            {
+                *bin = (x, 0.0);
+            }
+            self.fft.transform(spectrum, false);
+
+            // Sum input spectra against the partitions; real signals only
+            // need the lower half of the spectrum
+            self.scratch.fill((0.0, 0.0));
+            for (k, partition) in self.partitions.iter().enumerate() {
+                let input = &channel.history[(self.head + partitions - k) % partitions];
+                for bin in 0..=block_size {
+                    let product = complex_mul(input[bin], partition[bin]);
+                    self.scratch[bin].0 += product.0;
+                    self.scratch[bin].1 += product.1;
+                }
+            }
+            for bin in 1..block_size {
+                let (re, im) = self.scratch[bin];
+                self.scratch[size - bin] = (re, -im);
+            }
+            self.fft.transform(&mut self.scratch, true);
+
+            let scale = 1.0 / size as f32;
+            for (out, &(re, _)) in channel.output.iter_mut().zip(&self.scratch[block_size..]) {
+                *out = re * scale;
+            }
+            std::mem::swap(&mut channel.previous, &mut channel.input);
+        }
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let mix = self.mix.value();
+        let pos = self.pos;
+        let mut out = [0.0; 2];
+        for (channel, (input, out)) in self
+            .channels
+            .iter_mut()
+            .zip([left, right].into_iter().zip(&mut out))
+        {
+            *out = channel.previous[pos] * (1.0 - mix) + channel.output[pos] * mix;
+            channel.input[pos] = input;
+        }
+
+        self.pos += 1;
+        if self.pos == self.block_size {
+            self.process_block();
+            self.pos = 0;
+        }
+        (out[0], out[1])
+    }
+}
+
+impl AudioUnit for ConvolutionReverb {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        let partitions = self.partitions.len();
+        self.channels = [
+            ConvolverChannel::new(self.block_size, partitions),
+            ConvolverChannel::new(self.block_size, partitions),
+        ];
+        self.head = 0;
+        self.pos = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.prepare();
+        }
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (left, right) = self.process_frame(input[0], input[1]);
+        output[0] = left;
+        output[1] = right;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (left, right) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, left);
+            output.set_f32(1, i, right);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"convolution_reverb";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        let spectrum = self.block_size * 2 * std::mem::size_of::<Complex>();
+        // Partitions plus a history of the same length per channel
+        std::mem::size_of::<Self>() + spectrum * self.partitions.len() * 3
+    }
+}
+
+/// Convolution reverb builder holding an impulse response
+///
+/// Register an instance built from your own response to replace the default
+/// synthetic room:
+///
+/// ```rust,no_run
+/// use fundsp_rack::effects::builtin::convolution::ConvolutionReverbBuilder;
+/// use fundsp_rack::prelude::*;
+/// use std::sync::Arc;
+///
+/// let ir: Vec<f32> = vec![1.0, 0.5, 0.25];
+/// let mut registry = EffectRegistry::with_builtin();
+/// registry.register("convolution", Arc::new(ConvolutionReverbBuilder::from_ir(ir, 48000.0)));
+/// ```
+#[derive(Clone)]
+pub struct ConvolutionReverbBuilder {
+    ir: Arc<Vec<f32>>,
+    sample_rate: f64,
+    block_size: usize,
+}
+
+impl ConvolutionReverbBuilder {
+    /// Create a builder for a mono impulse response recorded at `sample_rate`
+    pub fn from_ir(ir: Vec<f32>, sample_rate: f64) -> Self {
+        Self {
+            ir: Arc::new(ir),
+            sample_rate,
+            block_size: DEFAULT_BLOCK_SIZE,
+        }
+    }
+
+    /// Load the impulse response from a WAV file, mixing stereo to mono
+    #[cfg(feature = "wav")]
+    pub fn from_wav<P: AsRef<std::path::Path>>(path: P) -> crate::Result<Self> {
+        let buffer = crate::wav::read_wav(path)?;
+        let ir = buffer
+            .left
+            .iter()
+            .zip(&buffer.right)
+            .map(|(l, r)| (l + r) * 0.5)
+            .collect();
+        Ok(Self::from_ir(ir, buffer.sample_rate))
+    }
+
+    /// Builder pattern: set the partition size, which is also the latency
+    ///
+    /// Rounded up to a power of two of at least 64. Larger blocks are
+    /// cheaper for long responses.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(MIN_BLOCK_SIZE).next_power_of_two();
+        self
+    }
+
+    /// Length of the impulse response in samples at its own rate
+    pub fn ir_len(&self) -> usize {
+        self.ir.len()
+    }
+}
+
+impl Default for ConvolutionReverbBuilder {
+    /// Synthetic room: exponentially decaying, gently darkening noise
+    fn default() -> Self {
+        let sample_rate = 44100.0;
+        let len = (DEFAULT_IR_SECONDS * sample_rate as f32) as usize;
+        let mut state = 0x2545_F491_u32;
+        let mut lowpass = 0.0;
+        let mut ir: Vec<f32> = (0..len)
+            .map(|i| {
+                // xorshift noise
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let noise = state as f32 / u32::MAX as f32 * 2.0 - 1.0;
+                let t = i as f32 / len as f32;
+                // High frequencies die away faster than lows
+                let damping = 0.2 + 0.7 * t;
+                lowpass = noise * (1.0 - damping) + lowpass * damping;
+                lowpass * (-6.9 * t).exp()
+            })
+            .collect();
+
+        let energy = ir.iter().map(|x| x * x).sum::<f32>().sqrt();
+        for x in &mut ir {
+            *x *= 0.5 / energy;
+        }
+        Self::from_ir(ir, sample_rate)
+    }
+}
+
+impl EffectBuilder for ConvolutionReverbBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let mix = params.get("mix").copied().unwrap_or(0.3);
+        let reverb =
+            ConvolutionReverb::new(self.ir.clone(), self.sample_rate, self.block_size, mix);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("mix".to_string(), reverb.mix.clone());
+
+        (Box::new(reverb), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("convolution", "Convolution reverb (impulse response)")
+            .with_param("mix", 0.3, 0.0, 1.0)
+            .with_latency(self.block_size)
+            .with_tag("reverb")
+    }
+}
+
+/// Register convolution effects
+pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
+    registry.register("convolution", Arc::new(ConvolutionReverbBuilder::default()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(i: usize) -> f32 {
+        (i as f32 * 0.037).sin() * 0.8 + (i as f32 * 0.41).cos() * 0.1
+    }
+
+    #[test]
+    fn test_unit_impulse_passes_signal_through() {
+        let builder = ConvolutionReverbBuilder::from_ir(vec![1.0], 44100.0);
+        let latency = builder.metadata().latency_samples;
+        assert_eq!(latency, DEFAULT_BLOCK_SIZE);
+
+        let params = HashMap::from([("mix".to_string(), 1.0)]);
+        let (mut unit, _) = builder.build(&params);
+        unit.set_sample_rate(44100.0);
+
+        let mut output = [0.0; 2];
+        for i in 0..4096 {
+            let x = signal(i);
+            unit.tick(&[x, -x], &mut output);
+            let expected = if i >= latency {
+                signal(i - latency)
+            } else {
+                0.0
+            };
+            assert!((output[0] - expected).abs() < 1e-5, "sample {i}");
+            assert!((output[1] + expected).abs() < 1e-5, "sample {i}");
+        }
+    }
+
+    #[test]
+    fn test_long_ir_matches_direct_convolution() {
+        let ir: Vec<f32> = (0..700)
+            .map(|i| (-(i as f32) / 150.0).exp() * signal(i * 7))
+            .collect();
+        let mut reverb = ConvolutionReverb::new(Arc::new(ir.clone()), 44100.0, 64, 1.0);
+        let latency = reverb.latency_samples();
+
+        let input: Vec<f32> = (0..2000).map(signal).collect();
+        let output: Vec<f32> = input
+            .iter()
+            .map(|&x| reverb.process_frame(x, x).0)
+            .collect();
+        for n in (latency..2000).step_by(37) {
+            let expected: f32 = (0..ir.len().min(n - latency + 1))
+                .map(|k| ir[k] * input[n - latency - k])
+                .sum();
+            assert!((output[n] - expected).abs() < 1e-3, "sample {n}");
+        }
+    }
+
+    #[test]
+    fn test_default_is_registered() {
+        let registry = crate::effects::EffectRegistry::with_builtin();
+        let metadata = registry.get_metadata("convolution").unwrap();
+        assert_eq!(metadata.latency_samples, DEFAULT_BLOCK_SIZE);
+    }
+}
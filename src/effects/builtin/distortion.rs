@@ -1,6 +1,7 @@
-//! Distortion effects (distortion, bitcrusher, krush)
+//! Distortion effects (distortion, bitcrusher, krush, amp, exciter)
 
 use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
+use super::super::util::dual;
 use fundsp::hacker32::*;
 use numeric_array::typenum::U1;
 use std::collections::HashMap;
@@ -15,15 +16,15 @@ impl EffectBuilder for DistortionBuilder {
 
         // Soft clipping distortion using tanh
         let drive = 1.0 + amount * 10.0;
-        let left = (pass() * drive) >> shape(Tanh(1.0));
-        let right = (pass() * drive) >> shape(Tanh(1.0));
+        let processor = (pass() * drive) >> shape(Tanh(1.0));
 
-        (Box::new(left | right), EffectControls::new())
+        (Box::new(dual(processor)), EffectControls::new())
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("distortion", "Distortion effect")
             .with_param("amount", 0.5, 0.0, 1.0)
+            .with_tag("distortion")
     }
 }
 
@@ -36,15 +37,15 @@ impl EffectBuilder for BitcrusherBuilder {
 
         // Reduce bit depth - use map with Frame
         let levels = 2.0_f32.powf(bits);
-        let left = pass() >> map(move |x: &Frame<f32, U1>| (x[0] * levels).round() / levels);
-        let right = pass() >> map(move |x: &Frame<f32, U1>| (x[0] * levels).round() / levels);
+        let processor = pass() >> map(move |x: &Frame<f32, U1>| (x[0] * levels).round() / levels);
 
-        (Box::new(left | right), EffectControls::new())
+        (Box::new(dual(processor)), EffectControls::new())
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("bitcrusher", "Bitcrusher (reduces bit depth)")
             .with_param("bits", 8.0, 1.0, 16.0)
+            .with_tag("lofi")
     }
 }
 
@@ -70,6 +71,7 @@ impl EffectBuilder for PanBuilder {
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("pan", "Pan (stereo positioning)")
             .with_param("pan", 0.0, -1.0, 1.0)
+            .with_tag("spatial")
     }
 }
 
@@ -112,6 +114,113 @@ impl EffectBuilder for KrushBuilder {
             .with_param("bits", 8.0, 1.0, 16.0)
             .with_param("sample_rate", 8000.0, 1000.0, 48000.0)
             .with_param("mix", 1.0, 0.0, 1.0)
+            .with_tag("lofi")
+    }
+}
+
+/// Linear gain from a dB value held in a `Shared`
+fn db_gain(gain_db: &Shared) -> An<impl AudioNode<Inputs = U0, Outputs = U1>> {
+    var(gain_db) >> map(|x: &Frame<f32, U1>| db_amp(x[0]))
+}
+
+/// Asymmetric tube-style transfer curve
+///
+/// Biasing the tanh makes positive peaks clip earlier than negative ones,
+/// adding the even harmonics of a single-ended stage. Silence maps to
+/// silence; the remaining DC offset is removed after the shaper.
+#[inline]
+fn tube(x: f32) -> f32 {
+    const BIAS: f32 = 0.3;
+    (x + BIAS).tanh() - BIAS.tanh()
+}
+
+/// Guitar amp and cabinet simulation
+///
+/// Signal order per channel is drive → tone → cab:
+/// - `drive` (0..1) sets the preamp gain into an asymmetric tube waveshaper
+/// - `bass` (100 Hz shelf), `mid` (800 Hz bell), `treble` (3 kHz shelf) and
+///   `presence` (5 kHz bell) form the tone stack, in dB
+/// - a fixed cabinet response rolls off below 80 Hz and above 5 kHz
+///
+/// All five controls are live.
+pub struct AmpSimBuilder;
+
+impl EffectBuilder for AmpSimBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let drive = shared(params.get("drive").copied().unwrap_or(0.5));
+        let bass = shared(params.get("bass").copied().unwrap_or(0.0));
+        let mid = shared(params.get("mid").copied().unwrap_or(0.0));
+        let treble = shared(params.get("treble").copied().unwrap_or(0.0));
+        let presence = shared(params.get("presence").copied().unwrap_or(0.0));
+
+        let mut controls = EffectControls::new();
+        controls.params.insert("drive".to_string(), drive.clone());
+        controls.params.insert("bass".to_string(), bass.clone());
+        controls.params.insert("mid".to_string(), mid.clone());
+        controls.params.insert("treble".to_string(), treble.clone());
+        controls
+            .params
+            .insert("presence".to_string(), presence.clone());
+
+        // Preamp: 1x to 21x gain into the tube curve
+        let preamp = (pass() * (var(&drive) >> map(|x: &Frame<f32, U1>| 1.0 + x[0] * 20.0)))
+            >> map(|x: &Frame<f32, U1>| tube(x[0]))
+            >> dcblock();
+
+        // Tone stack: filters take input, frequency, q, linear gain
+        let tone = (pass() | dc((100.0, 0.7)) | db_gain(&bass)) >> lowshelf();
+        let tone = (tone | dc((800.0, 0.7)) | db_gain(&mid)) >> bell();
+        let tone = (tone | dc((3000.0, 0.7)) | db_gain(&treble)) >> highshelf();
+        let tone = (tone | dc((5000.0, 1.0)) | db_gain(&presence)) >> bell();
+
+        // Cabinet: fixed speaker band-limiting
+        let cab = highpass_hz(80.0, 0.7) >> lowpass_hz(5000.0, 0.7);
+
+        (Box::new(dual(preamp >> tone >> cab)), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("amp", "Guitar amp and cabinet simulation")
+            .with_param("drive", 0.5, 0.0, 1.0)
+            .with_param("bass", 0.0, -12.0, 12.0)
+            .with_param("mid", 0.0, -12.0, 12.0)
+            .with_param("treble", 0.0, -12.0, 12.0)
+            .with_param("presence", 0.0, -12.0, 12.0)
+            .with_tag("distortion")
+    }
+}
+
+/// Harmonic exciter
+///
+/// The signal above `freq` is driven through the asymmetric tube curve,
+/// which synthesises even and odd harmonics of the high end, highpassed
+/// again to drop the DC and low intermodulation it creates, and added to
+/// the dry signal scaled by `amount`. `freq` and `amount` are live.
+pub struct ExciterBuilder;
+
+impl EffectBuilder for ExciterBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let freq = shared(params.get("freq").copied().unwrap_or(3000.0));
+        let amount = shared(params.get("amount").copied().unwrap_or(0.3));
+
+        let mut controls = EffectControls::new();
+        controls.params.insert("freq".to_string(), freq.clone());
+        controls.params.insert("amount".to_string(), amount.clone());
+
+        // Filters take input, cutoff, q
+        let band = (pass() | var(&freq) | dc(0.7)) >> highpass();
+        let shaped = band >> map(|x: &Frame<f32, U1>| tube(x[0] * 4.0) * 0.25);
+        let harmonics = (shaped | var(&freq) | dc(0.7)) >> highpass();
+        let exciter = pass() & (harmonics * var(&amount));
+
+        (Box::new(dual(exciter)), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("exciter", "Harmonic exciter (high-frequency enhancer)")
+            .with_param("freq", 3000.0, 1000.0, 12000.0)
+            .with_param("amount", 0.3, 0.0, 1.0)
+            .with_tag("distortion")
     }
 }
 
@@ -120,4 +229,83 @@ pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("distortion", Arc::new(DistortionBuilder));
     registry.register("bitcrusher", Arc::new(BitcrusherBuilder));
     registry.register("krush", Arc::new(KrushBuilder));
+    registry.register("amp", Arc::new(AmpSimBuilder));
+    registry.register("exciter", Arc::new(ExciterBuilder));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sum of squared output over 0.1 s of a 6 kHz sine
+    fn treble_energy(amp: &mut dyn AudioUnit) -> f32 {
+        amp.reset();
+        (0..4800)
+            .map(|i| {
+                let x = 0.05 * (i as f32 * std::f32::consts::TAU * 6000.0 / 48000.0).sin();
+                amp.filter_stereo(x, x).0.powi(2)
+            })
+            .sum()
+    }
+
+    /// Power of `signal` at `freq` Hz (Goertzel)
+    fn power_at(signal: &[f32], freq: f32) -> f32 {
+        let coeff = 2.0 * (std::f32::consts::TAU * freq / 48000.0).cos();
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        for &x in signal {
+            let s = x + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s;
+        }
+        s1 * s1 + s2 * s2 - coeff * s1 * s2
+    }
+
+    #[test]
+    fn test_exciter_adds_harmonics_above_input() {
+        let excite = |amount: f32| {
+            let params =
+                HashMap::from([("freq".to_string(), 2000.0), ("amount".to_string(), amount)]);
+            let (mut exciter, _) = ExciterBuilder.build(&params);
+            // 3 kHz sine, nothing above it; skip the filters' settling
+            let output: Vec<f32> = (0..9600)
+                .map(|i| {
+                    let x = 0.5 * (i as f32 * std::f32::consts::TAU * 3000.0 / 48000.0).sin();
+                    exciter.filter_stereo(x, x).0
+                })
+                .collect();
+            output[4800..].to_vec()
+        };
+
+        let dry = excite(0.0);
+        let wet = excite(1.0);
+        let fundamental = power_at(&dry, 3000.0);
+        assert!(power_at(&dry, 6000.0) < fundamental * 1e-8);
+        assert!(power_at(&dry, 9000.0) < fundamental * 1e-8);
+        assert!(power_at(&wet, 6000.0) > fundamental * 1e-3);
+        assert!(power_at(&wet, 9000.0) > fundamental * 1e-3);
+    }
+
+    #[test]
+    fn test_amp_output_is_bounded() {
+        let params = HashMap::from([("drive".to_string(), 1.0)]);
+        let (mut amp, _) = AmpSimBuilder.build(&params);
+        for i in 0..48000 {
+            let x = 4.0 * (i as f32 * 0.02).sin();
+            let (l, r) = amp.filter_stereo(x, x);
+            assert!(l.is_finite() && l.abs() < 4.0);
+            assert_eq!(l, r);
+        }
+    }
+
+    #[test]
+    fn test_amp_tone_controls_are_live() {
+        let (mut amp, controls) = AmpSimBuilder.build(&HashMap::new());
+        let flat = treble_energy(amp.as_mut());
+        controls.set("treble", 12.0);
+        let bright = treble_energy(amp.as_mut());
+        controls.set("treble", -12.0);
+        let dark = treble_energy(amp.as_mut());
+        assert!(bright > flat * 2.0);
+        assert!(dark < flat * 0.5);
+    }
 }
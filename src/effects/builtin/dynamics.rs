@@ -1,47 +1,1011 @@
-//! Dynamics effects (limiter, compressor, normaliser)
+//! Dynamics effects (limiter, compressor, transient shaper, gate, normaliser)
 
-use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
-use super::super::sidechain::{SidechainCompressor, SidechainGate};
+use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata, LATENCY_CONTROL};
+use super::super::sidechain::{
+    amplitude_to_db, db_to_amplitude, sidechain_peak, SidechainAwareEffect, SidechainCompressor,
+    SidechainGate,
+};
+use super::filters::Crossover;
 use fundsp::hacker32::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
+/// Longest limiter lookahead in milliseconds
+const MAX_LOOKAHEAD_MS: f32 = 20.0;
+/// Default limiter lookahead in milliseconds
+const DEFAULT_LOOKAHEAD_MS: f32 = 5.0;
+
+/// Brickwall lookahead limiter
+///
+/// The audio is delayed by the lookahead time while the detector watches the
+/// stereo-linked peak of everything still in the delay line, so gain
+/// reduction is in place before a transient reaches the output. The gain
+/// computer has an optional soft knee centred on the ceiling; the applied
+/// gain is additionally capped so no output sample exceeds the ceiling.
+/// `gain_reduction` (dB, positive) is written every sample for metering and
+/// `latency` holds the lookahead in samples.
+#[derive(Clone)]
+pub struct Limiter {
+    /// Output ceiling in dB
+    pub ceiling: Shared,
+    /// Knee width in dB below the ceiling (0.0 = hard knee)
+    pub knee: Shared,
+    /// Gain reduction attack time in seconds
+    pub attack: Shared,
+    /// Release time in seconds
+    pub release: Shared,
+    /// Current gain reduction in dB (positive values, read-only meter)
+    pub gain_reduction: Shared,
+    /// Lookahead delay in samples (read-only)
+    pub latency: Shared,
+    lookahead_ms: f32,
+    /// Delayed audio, `lookahead` samples long
+    delay: VecDeque<(f32, f32)>,
+    /// Sliding maximum of the detector: (sample index, peak), decreasing peaks
+    peaks: VecDeque<(u64, f32)>,
+    /// Index of the next input sample
+    index: u64,
+    gain: f32,
+    sample_rate: f32,
+}
+
+impl Limiter {
+    /// Create a new limiter
+    pub fn new(
+        ceiling_db: f32,
+        knee_db: f32,
+        lookahead_ms: f32,
+        attack_sec: f32,
+        release_sec: f32,
+    ) -> Self {
+        let mut limiter = Self {
+            ceiling: shared(ceiling_db),
+            knee: shared(knee_db),
+            attack: shared(attack_sec),
+            release: shared(release_sec),
+            gain_reduction: shared(0.0),
+            latency: shared(0.0),
+            lookahead_ms: lookahead_ms.clamp(0.0, MAX_LOOKAHEAD_MS),
+            delay: VecDeque::new(),
+            peaks: VecDeque::new(),
+            index: 0,
+            gain: 1.0,
+            sample_rate: 48000.0,
+        };
+        limiter.allocate();
+        limiter
+    }
+
+    /// Lookahead delay in samples
+    pub fn latency_samples(&self) -> usize {
+        self.delay.len()
+    }
+
+    /// Size the lookahead for the current sample rate and clear the state
+    fn allocate(&mut self) {
+        let length = (self.lookahead_ms * 0.001 * self.sample_rate).round() as usize;
+        self.delay = VecDeque::from(vec![(0.0, 0.0); length]);
+        self.peaks.clear();
+        self.index = 0;
+        self.gain = 1.0;
+        self.latency.set_value(length as f32);
+        self.gain_reduction.set_value(0.0);
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let lookahead = self.delay.len() as u64;
+
+        // Peak of the samples between input and output, newest included
+        let peak = sidechain_peak(left, right);
+        while self.peaks.back().is_some_and(|&(_, p)| p <= peak) {
+            self.peaks.pop_back();
+        }
+        self.peaks.push_back((self.index, peak));
+        while self
+            .peaks
+            .front()
+            .is_some_and(|&(i, _)| i + lookahead < self.index)
+        {
+            self.peaks.pop_front();
+        }
+        let window_peak = self.peaks.front().map_or(0.0, |&(_, p)| p);
+        self.index += 1;
+
+        let ceiling_db = self.ceiling.value().min(0.0);
+        let reduction_db = Compressor::gain_reduction_db(
+            amplitude_to_db(window_peak),
+            ceiling_db,
+            f32::INFINITY,
+            self.knee.value(),
+        );
+        let target = db_to_amplitude(-reduction_db);
+
+        let time = if target < self.gain {
+            self.attack.value()
+        } else {
+            self.release.value()
+        };
+        let coeff = (-1.0 / (time.max(1.0e-5) * self.sample_rate)).exp();
+        self.gain = target + coeff * (self.gain - target);
+
+        let (out_l, out_r) = match self.delay.pop_front() {
+            Some(delayed) => {
+                self.delay.push_back((left, right));
+                delayed
+            }
+            None => (left, right),
+        };
+
+        // Attack smoothing may lag the target; never let a sample through
+        // above the ceiling
+        let ceiling = db_to_amplitude(ceiling_db);
+        let out_peak = sidechain_peak(out_l, out_r);
+        let gain = if out_peak * self.gain > ceiling {
+            ceiling / out_peak
+        } else {
+            self.gain
+        };
+        self.gain_reduction.set_value(-amplitude_to_db(gain));
+
+        (out_l * gain, out_r * gain)
+    }
+}
+
+impl AudioUnit for Limiter {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.delay.iter_mut().for_each(|frame| *frame = (0.0, 0.0));
+        self.peaks.clear();
+        self.index = 0;
+        self.gain = 1.0;
+        self.gain_reduction.set_value(0.0);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+        self.allocate();
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"limiter";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>() + self.delay.capacity() * std::mem::size_of::<(f32, f32)>()
+    }
+}
+
 /// Limiter effect
 pub struct LimiterBuilder;
 
 impl EffectBuilder for LimiterBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let ceiling = params.get("ceiling").copied().unwrap_or(0.0);
+        let knee = params.get("knee").copied().unwrap_or(0.0);
+        let lookahead = params
+            .get("lookahead")
+            .copied()
+            .unwrap_or(DEFAULT_LOOKAHEAD_MS);
         let attack = params.get("attack").copied().unwrap_or(0.01);
         let release = params.get("release").copied().unwrap_or(0.1);
 
-        let limiter = limiter_stereo(attack, release);
-        (Box::new(limiter), EffectControls::new())
+        let limiter = Limiter::new(ceiling, knee, lookahead, attack, release);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("ceiling".to_string(), limiter.ceiling.clone());
+        controls
+            .params
+            .insert("knee".to_string(), limiter.knee.clone());
+        controls
+            .params
+            .insert("attack".to_string(), limiter.attack.clone());
+        controls
+            .params
+            .insert("release".to_string(), limiter.release.clone());
+        controls
+            .meters
+            .insert("gain_reduction".to_string(), limiter.gain_reduction.clone());
+        controls
+            .meters
+            .insert(LATENCY_CONTROL.to_string(), limiter.latency.clone());
+
+        (Box::new(limiter), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
-        EffectMetadata::new("limiter", "Limiter (prevents clipping)")
+        // Latency of the default lookahead at 48 kHz; instances report their own
+        let latency = (DEFAULT_LOOKAHEAD_MS * 48.0).round() as usize;
+        EffectMetadata::new("limiter", "Brickwall lookahead limiter")
+            .with_param("ceiling", 0.0, -24.0, 0.0)
+            .with_param("knee", 0.0, 0.0, 12.0)
+            .with_param("lookahead", DEFAULT_LOOKAHEAD_MS, 0.0, MAX_LOOKAHEAD_MS)
             .with_param("attack", 0.01, 0.001, 0.1)
             .with_param("release", 0.1, 0.01, 1.0)
+            .with_latency(latency)
+            .with_tag("dynamics")
+    }
+}
+
+/// Feed-forward stereo compressor with soft knee and makeup gain
+///
+/// Detection is stereo-linked (peak of both channels) and the gain computer
+/// works in the dB domain. All parameters are `Shared`, so they can be changed
+/// while audio is running. `gain_reduction` is written by the processor every
+/// sample and is meant to be read for metering.
+#[derive(Clone)]
+pub struct Compressor {
+    /// Threshold in dB
+    pub threshold: Shared,
+    /// Compression ratio (e.g., 4.0 = 4:1)
+    pub ratio: Shared,
+    /// Attack time in seconds
+    pub attack: Shared,
+    /// Release time in seconds
+    pub release: Shared,
+    /// Knee width in dB (0.0 = hard knee)
+    pub knee: Shared,
+    /// Makeup gain in dB
+    pub makeup: Shared,
+    /// Current gain reduction in dB (positive values, read-only meter)
+    pub gain_reduction: Shared,
+    envelope: f32,
+    sample_rate: f32,
+}
+
+impl Compressor {
+    /// Create a new compressor
+    pub fn new(
+        threshold_db: f32,
+        ratio: f32,
+        attack_sec: f32,
+        release_sec: f32,
+        knee_db: f32,
+        makeup_db: f32,
+    ) -> Self {
+        Self {
+            threshold: shared(threshold_db),
+            ratio: shared(ratio),
+            attack: shared(attack_sec),
+            release: shared(release_sec),
+            knee: shared(knee_db),
+            makeup: shared(makeup_db),
+            gain_reduction: shared(0.0),
+            envelope: 0.0,
+            sample_rate: 48000.0,
+        }
+    }
+
+    /// Static gain computer: amount of gain reduction in dB for a detector level in dB
+    #[inline]
+    pub(crate) fn gain_reduction_db(level_db: f32, threshold: f32, ratio: f32, knee: f32) -> f32 {
+        let slope = 1.0 - 1.0 / ratio.max(1.0);
+        let over = level_db - threshold;
+        if knee > 0.0 && 2.0 * over.abs() <= knee {
+            // Quadratic interpolation inside the knee
+            slope * (over + knee * 0.5).powi(2) / (2.0 * knee)
+        } else if over > 0.0 {
+            slope * over
+        } else {
+            0.0
+        }
+    }
+
+    /// Process one stereo frame
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let level = sidechain_peak(left, right);
+
+        let time = if level > self.envelope {
+            self.attack.value()
+        } else {
+            self.release.value()
+        };
+        let coeff = (-1.0 / (time.max(1.0e-5) * self.sample_rate)).exp();
+        self.envelope = level + coeff * (self.envelope - level);
+
+        let reduction_db = Self::gain_reduction_db(
+            amplitude_to_db(self.envelope),
+            self.threshold.value(),
+            self.ratio.value(),
+            self.knee.value(),
+        );
+        self.gain_reduction.set_value(reduction_db);
+
+        let gain = db_to_amplitude(self.makeup.value() - reduction_db);
+        (left * gain, right * gain)
     }
 }
 
-/// Compressor effect (using limiter as base)
+impl AudioUnit for Compressor {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.gain_reduction.set_value(0.0);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"compressor";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Compressor effect
 pub struct CompressorBuilder;
 
 impl EffectBuilder for CompressorBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let threshold = params.get("threshold").copied().unwrap_or(-20.0);
+        let ratio = params.get("ratio").copied().unwrap_or(4.0);
         let attack = params.get("attack").copied().unwrap_or(0.01);
         let release = params.get("release").copied().unwrap_or(0.1);
+        let knee = params.get("knee").copied().unwrap_or(0.0);
+        let makeup = params.get("makeup").copied().unwrap_or(0.0);
+
+        let comp = Compressor::new(threshold, ratio, attack, release, knee, makeup);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("threshold".to_string(), comp.threshold.clone());
+        controls
+            .params
+            .insert("ratio".to_string(), comp.ratio.clone());
+        controls
+            .params
+            .insert("attack".to_string(), comp.attack.clone());
+        controls
+            .params
+            .insert("release".to_string(), comp.release.clone());
+        controls
+            .params
+            .insert("knee".to_string(), comp.knee.clone());
+        controls
+            .params
+            .insert("makeup".to_string(), comp.makeup.clone());
+        controls
+            .meters
+            .insert("gain_reduction".to_string(), comp.gain_reduction.clone());
 
-        // Use limiter as compressor base
-        let comp = limiter_stereo(attack, release);
-        (Box::new(comp), EffectControls::new())
+        (Box::new(comp), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("compressor", "Compressor (reduces dynamic range)")
+            .with_param("threshold", -20.0, -60.0, 0.0)
+            .with_param("ratio", 4.0, 1.0, 20.0)
             .with_param("attack", 0.01, 0.001, 0.1)
             .with_param("release", 0.1, 0.01, 1.0)
+            .with_param("knee", 0.0, 0.0, 24.0)
+            .with_param("makeup", 0.0, 0.0, 24.0)
+            .with_tag("dynamics")
+    }
+}
+
+/// Names of the multiband compressor bands, used as parameter prefixes
+const BANDS: [&str; 3] = ["low", "mid", "high"];
+
+/// Three-band compressor
+///
+/// Each channel is split at `low_freq` and `high_freq` with Linkwitz-Riley
+/// [`Crossover`]s, and the low band is passed through the upper crossover's
+/// allpass so all three bands stay in phase. With nothing compressed the
+/// bands sum to a flat magnitude response. Each band then runs through its own stereo-linked
+/// [`Compressor`] with independent threshold and ratio; attack and release
+/// are shared by all bands. Read each band's `gain_reduction` for metering.
+#[derive(Clone)]
+pub struct MultibandCompressor {
+    /// Low/mid crossover frequency in Hz
+    pub low_freq: Shared,
+    /// Mid/high crossover frequency in Hz
+    pub high_freq: Shared,
+    /// Low, mid and high band compressors
+    pub bands: [Compressor; 3],
+    /// Crossovers per channel: low/rest split, then mid/high split
+    crossovers: [[Crossover; 2]; 2],
+}
+
+impl MultibandCompressor {
+    /// Create a multiband compressor with crossovers at `low_freq` and
+    /// `high_freq` Hz and all bands compressing alike
+    pub fn new(
+        low_freq: f32,
+        high_freq: f32,
+        threshold_db: f32,
+        ratio: f32,
+        attack_sec: f32,
+        release_sec: f32,
+    ) -> Self {
+        let band = Compressor::new(threshold_db, ratio, attack_sec, release_sec, 0.0, 0.0);
+        let mut bands = [band.clone(), band.clone(), band];
+        // Independent thresholds, ratios and meters; shared timing
+        for band in &mut bands[1..] {
+            band.threshold = shared(threshold_db);
+            band.ratio = shared(ratio);
+            band.knee = shared(0.0);
+            band.makeup = shared(0.0);
+            band.gain_reduction = shared(0.0);
+        }
+        let split = |freq| [Crossover::new(freq, 48000.0), Crossover::new(freq, 48000.0)];
+        Self {
+            low_freq: shared(low_freq),
+            high_freq: shared(high_freq),
+            bands,
+            crossovers: [split(low_freq), split(high_freq)],
+        }
+    }
+
+    /// Split a frame into `[band][channel]` samples
+    #[inline]
+    fn split_frame(&mut self, left: f32, right: f32) -> [[f32; 2]; 3] {
+        let low_freq = self.low_freq.value();
+        let high_freq = self.high_freq.value().max(low_freq);
+
+        let mut split = [[0.0; 2]; 3];
+        for (channel, x) in [left, right].into_iter().enumerate() {
+            let [low_split, high_split] = &mut self.crossovers;
+            low_split[channel].set_cutoff(low_freq);
+            high_split[channel].set_cutoff(high_freq);
+            let (low, rest) = low_split[channel].split(x);
+            let (mid, high) = high_split[channel].split(rest);
+            split[0][channel] = high_split[channel].allpass(low);
+            split[1][channel] = mid;
+            split[2][channel] = high;
+        }
+        split
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let split = self.split_frame(left, right);
+        let (mut out_l, mut out_r) = (0.0, 0.0);
+        for (band, [l, r]) in self.bands.iter_mut().zip(split) {
+            let (l, r) = band.process_frame(l, r);
+            out_l += l;
+            out_r += r;
+        }
+        (out_l, out_r)
+    }
+}
+
+impl AudioUnit for MultibandCompressor {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        for band in &mut self.bands {
+            AudioUnit::reset(band);
+        }
+        self.crossovers
+            .iter_mut()
+            .flatten()
+            .for_each(Crossover::reset);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        for band in &mut self.bands {
+            AudioUnit::set_sample_rate(band, sample_rate);
+        }
+        for crossover in self.crossovers.iter_mut().flatten() {
+            crossover.set_sample_rate(sample_rate as f32);
+        }
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"multiband_comp";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Multiband compressor effect
+pub struct MultibandCompressorBuilder;
+
+impl EffectBuilder for MultibandCompressorBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let param = |name: &str, default: f32| params.get(name).copied().unwrap_or(default);
+
+        let mut comp = MultibandCompressor::new(
+            param("low_freq", 200.0),
+            param("high_freq", 2000.0),
+            -20.0,
+            3.0,
+            param("attack", 0.01),
+            param("release", 0.1),
+        );
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("low_freq".to_string(), comp.low_freq.clone());
+        controls
+            .params
+            .insert("high_freq".to_string(), comp.high_freq.clone());
+        controls
+            .params
+            .insert("attack".to_string(), comp.bands[0].attack.clone());
+        controls
+            .params
+            .insert("release".to_string(), comp.bands[0].release.clone());
+        for (name, band) in BANDS.iter().zip(&mut comp.bands) {
+            band.threshold
+                .set_value(param(&format!("{}_threshold", name), -20.0));
+            band.ratio.set_value(param(&format!("{}_ratio", name), 3.0));
+            controls
+                .params
+                .insert(format!("{}_threshold", name), band.threshold.clone());
+            controls
+                .params
+                .insert(format!("{}_ratio", name), band.ratio.clone());
+            controls.meters.insert(
+                format!("{}_gain_reduction", name),
+                band.gain_reduction.clone(),
+            );
+        }
+
+        (Box::new(comp), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        let mut metadata =
+            EffectMetadata::new("multiband_comp", "Three-band compressor (low/mid/high)")
+                .with_param("low_freq", 200.0, 20.0, 1000.0)
+                .with_param("high_freq", 2000.0, 500.0, 16000.0)
+                .with_param("attack", 0.01, 0.001, 0.1)
+                .with_param("release", 0.1, 0.01, 1.0)
+                .with_tag("dynamics");
+        for name in BANDS {
+            metadata = metadata
+                .with_param(format!("{}_threshold", name), -20.0, -60.0, 0.0)
+                .with_param(format!("{}_ratio", name), 3.0, 1.0, 20.0);
+        }
+        metadata
+    }
+}
+
+/// Largest boost or cut a transient shaper applies, in dB
+const MAX_SHAPER_GAIN_DB: f32 = 24.0;
+
+/// Envelope follower with separate attack and release times
+#[derive(Clone, Copy)]
+struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    value: f32,
+}
+
+impl EnvelopeFollower {
+    fn new(attack_sec: f32, release_sec: f32) -> Self {
+        Self {
+            attack: attack_sec,
+            release: release_sec,
+            value: 0.0,
+        }
+    }
+
+    #[inline]
+    fn next(&mut self, level: f32, sample_rate: f32) -> f32 {
+        let time = if level > self.value {
+            self.attack
+        } else {
+            self.release
+        };
+        let coeff = (-1.0 / (time * sample_rate)).exp();
+        self.value = level + coeff * (self.value - level);
+        self.value
+    }
+}
+
+/// Transient shaper
+///
+/// Level-independent attack and sustain control. A fast follower is compared
+/// against one with a slow attack, which lags behind it only while the signal
+/// is rising, and against one with a slow release, which stays above it only
+/// while the signal is decaying. `attack` and `sustain` (-1..1) scale those
+/// differences into a boost or cut of up to 24 dB; at zero the signal passes
+/// unchanged. Detection is stereo-linked.
+#[derive(Clone)]
+pub struct TransientShaper {
+    /// Attack emphasis: positive sharpens, negative softens (-1..1)
+    pub attack: Shared,
+    /// Sustain emphasis: positive lengthens tails, negative shortens (-1..1)
+    pub sustain: Shared,
+    fast: EnvelopeFollower,
+    slow_attack: EnvelopeFollower,
+    slow_release: EnvelopeFollower,
+    sample_rate: f32,
+}
+
+impl TransientShaper {
+    /// Create a new transient shaper
+    pub fn new(attack: f32, sustain: f32) -> Self {
+        Self {
+            attack: shared(attack),
+            sustain: shared(sustain),
+            fast: EnvelopeFollower::new(0.0005, 0.05),
+            slow_attack: EnvelopeFollower::new(0.02, 0.05),
+            slow_release: EnvelopeFollower::new(0.0005, 0.5),
+            sample_rate: 48000.0,
+        }
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let level = sidechain_peak(left, right);
+        let fast = amplitude_to_db(self.fast.next(level, self.sample_rate));
+        let slow_attack = amplitude_to_db(self.slow_attack.next(level, self.sample_rate));
+        let slow_release = amplitude_to_db(self.slow_release.next(level, self.sample_rate));
+
+        let transient = (fast - slow_attack).clamp(0.0, MAX_SHAPER_GAIN_DB);
+        let tail = (slow_release - fast).clamp(0.0, MAX_SHAPER_GAIN_DB);
+        let gain_db = self.attack.value().clamp(-1.0, 1.0) * transient
+            + self.sustain.value().clamp(-1.0, 1.0) * tail;
+        let gain = db_to_amplitude(gain_db.clamp(-MAX_SHAPER_GAIN_DB, MAX_SHAPER_GAIN_DB));
+
+        (left * gain, right * gain)
+    }
+}
+
+impl AudioUnit for TransientShaper {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.fast.value = 0.0;
+        self.slow_attack.value = 0.0;
+        self.slow_release.value = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"transient_shaper";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Transient shaper effect
+pub struct TransientShaperBuilder;
+
+impl EffectBuilder for TransientShaperBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let attack = params.get("attack").copied().unwrap_or(0.0);
+        let sustain = params.get("sustain").copied().unwrap_or(0.0);
+
+        let shaper = TransientShaper::new(attack, sustain);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("attack".to_string(), shaper.attack.clone());
+        controls
+            .params
+            .insert("sustain".to_string(), shaper.sustain.clone());
+
+        (Box::new(shaper), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("transient", "Transient shaper (attack/sustain emphasis)")
+            .with_param("attack", 0.0, -1.0, 1.0)
+            .with_param("sustain", 0.0, -1.0, 1.0)
+            .with_tag("dynamics")
+    }
+}
+
+/// Release time of the gate's level detector in seconds
+const GATE_DETECTOR_RELEASE: f32 = 0.01;
+
+/// Self-keyed noise gate with hysteresis and hold
+///
+/// The gate opens when the detected level rises above `threshold` and only
+/// closes once it has stayed below `threshold - hysteresis` for the `hold`
+/// time, so material hovering around the threshold does not chatter. Gain
+/// moves towards open over `attack` and towards closed over `release`.
+/// `open` is written every sample (1.0 = open) for metering.
+#[derive(Clone)]
+pub struct Gate {
+    /// Opening threshold in dB
+    pub threshold: Shared,
+    /// Distance in dB below the threshold at which the gate may close
+    pub hysteresis: Shared,
+    /// Opening time in seconds
+    pub attack: Shared,
+    /// Time in seconds the level must stay below the closing threshold
+    pub hold: Shared,
+    /// Closing time in seconds
+    pub release: Shared,
+    /// Gate state (1.0 = open, 0.0 = closed, read-only meter)
+    pub open: Shared,
+    detector: EnvelopeFollower,
+    is_open: bool,
+    /// Samples left before the gate may close
+    hold_remaining: usize,
+    gain: f32,
+    sample_rate: f32,
+}
+
+impl Gate {
+    /// Create a new gate
+    pub fn new(
+        threshold_db: f32,
+        hysteresis_db: f32,
+        attack_sec: f32,
+        hold_sec: f32,
+        release_sec: f32,
+    ) -> Self {
+        Self {
+            threshold: shared(threshold_db),
+            hysteresis: shared(hysteresis_db),
+            attack: shared(attack_sec),
+            hold: shared(hold_sec),
+            release: shared(release_sec),
+            open: shared(0.0),
+            detector: EnvelopeFollower::new(0.0001, GATE_DETECTOR_RELEASE),
+            is_open: false,
+            hold_remaining: 0,
+            gain: 0.0,
+            sample_rate: 48000.0,
+        }
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let level = self
+            .detector
+            .next(sidechain_peak(left, right), self.sample_rate);
+        let level_db = amplitude_to_db(level);
+        let threshold = self.threshold.value();
+        let close_below = threshold - self.hysteresis.value().max(0.0);
+
+        if level_db > threshold || (self.is_open && level_db >= close_below) {
+            self.is_open = true;
+            self.hold_remaining = (self.hold.value().max(0.0) * self.sample_rate) as usize;
+        } else if self.is_open {
+            if self.hold_remaining > 0 {
+                self.hold_remaining -= 1;
+            } else {
+                self.is_open = false;
+            }
+        }
+        self.open.set_value(if self.is_open { 1.0 } else { 0.0 });
+
+        let (target, time) = if self.is_open {
+            (1.0, self.attack.value())
+        } else {
+            (0.0, self.release.value())
+        };
+        let coeff = (-1.0 / (time.max(1.0e-5) * self.sample_rate)).exp();
+        self.gain = target + coeff * (self.gain - target);
+
+        (left * self.gain, right * self.gain)
+    }
+}
+
+impl AudioUnit for Gate {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.detector.value = 0.0;
+        self.is_open = false;
+        self.hold_remaining = 0;
+        self.gain = 0.0;
+        self.open.set_value(0.0);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"gate";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Gate effect
+pub struct GateBuilder;
+
+impl EffectBuilder for GateBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let threshold = params.get("threshold").copied().unwrap_or(-40.0);
+        let hysteresis = params.get("hysteresis").copied().unwrap_or(6.0);
+        let attack = params.get("attack").copied().unwrap_or(0.001);
+        let hold = params.get("hold").copied().unwrap_or(0.05);
+        let release = params.get("release").copied().unwrap_or(0.1);
+
+        let gate = Gate::new(threshold, hysteresis, attack, hold, release);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("threshold".to_string(), gate.threshold.clone());
+        controls
+            .params
+            .insert("hysteresis".to_string(), gate.hysteresis.clone());
+        controls
+            .params
+            .insert("attack".to_string(), gate.attack.clone());
+        controls
+            .params
+            .insert("hold".to_string(), gate.hold.clone());
+        controls
+            .params
+            .insert("release".to_string(), gate.release.clone());
+        controls
+            .meters
+            .insert("open".to_string(), gate.open.clone());
+
+        (Box::new(gate), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("gate", "Noise gate (hysteresis and hold)")
+            .with_param("threshold", -40.0, -80.0, 0.0)
+            .with_param("hysteresis", 6.0, 0.0, 24.0)
+            .with_param("attack", 0.001, 0.0001, 0.1)
+            .with_param("hold", 0.05, 0.0, 1.0)
+            .with_param("release", 0.1, 0.001, 2.0)
+            .with_tag("dynamics")
     }
 }
 
@@ -57,6 +1021,7 @@ impl EffectBuilder for NormaliserBuilder {
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("normaliser", "Normaliser (automatic gain control)")
+            .with_tag("dynamics")
     }
 }
 
@@ -71,26 +1036,40 @@ impl EffectBuilder for SidechainCompressorBuilder {
         let ratio = params.get("ratio").copied().unwrap_or(4.0);
         let attack = params.get("attack").copied().unwrap_or(0.01);
         let release = params.get("release").copied().unwrap_or(0.1);
+        let knee = params.get("knee").copied().unwrap_or(0.0);
+        let makeup = params.get("makeup").copied().unwrap_or(0.0);
+        let key_highpass = params.get("key_highpass").copied().unwrap_or(0.0);
+        let key_lowpass = params.get("key_lowpass").copied().unwrap_or(0.0);
+        let hold = params.get("hold").copied().unwrap_or(0.0);
 
         // Create actual sidechain compressor
         // Note: Sample rate will be passed from EffectChain which gets it from AudioBackend
         let sample_rate = 48000.0; // Default, overridden when chain.set_sample_rate() is called
-        let compressor = SidechainCompressor::new(threshold, ratio, attack, release, sample_rate);
-
-        // Create controls using the Shared parameters
-        let controls = EffectControls::new();
-        // Note: Parameters are already shared inside SidechainCompressor
-        // They can be controlled by modifying compressor.threshold.set_value(), etc.
+        let compressor = SidechainCompressor::new(threshold, ratio, attack, release, sample_rate)
+            .with_knee(knee)
+            .with_makeup(makeup)
+            .with_key_filter(key_highpass, key_lowpass)
+            .with_hold(hold);
+        let controls = compressor.controls();
 
         (Box::new(compressor), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
-        EffectMetadata::new("sidechain_compressor", "Sidechain Compressor (compress based on external signal)")
-            .with_param("threshold", -20.0, -60.0, 0.0)
-            .with_param("ratio", 4.0, 1.0, 20.0)
-            .with_param("attack", 0.01, 0.001, 0.1)
-            .with_param("release", 0.1, 0.01, 1.0)
+        EffectMetadata::new(
+            "sidechain_compressor",
+            "Sidechain Compressor (compress based on external signal)",
+        )
+        .with_param("threshold", -20.0, -60.0, 0.0)
+        .with_param("ratio", 4.0, 1.0, 20.0)
+        .with_param("attack", 0.01, 0.001, 0.1)
+        .with_param("release", 0.1, 0.01, 1.0)
+        .with_param("knee", 0.0, 0.0, 24.0)
+        .with_param("makeup", 0.0, 0.0, 24.0)
+        .with_param("key_highpass", 0.0, 0.0, 5000.0)
+        .with_param("key_lowpass", 0.0, 0.0, 20000.0)
+        .with_param("hold", 0.0, 0.0, 0.5)
+        .with_tag("dynamics")
     }
 }
 
@@ -118,10 +1097,14 @@ impl EffectBuilder for SidechainGateBuilder {
     }
 
     fn metadata(&self) -> EffectMetadata {
-        EffectMetadata::new("sidechain_gate", "Sidechain Gate (gate based on external signal)")
-            .with_param("threshold", -40.0, -80.0, 0.0)
-            .with_param("attack", 0.001, 0.0001, 0.1)
-            .with_param("release", 0.05, 0.001, 1.0)
+        EffectMetadata::new(
+            "sidechain_gate",
+            "Sidechain Gate (gate based on external signal)",
+        )
+        .with_param("threshold", -40.0, -80.0, 0.0)
+        .with_param("attack", 0.001, 0.0001, 0.1)
+        .with_param("release", 0.05, 0.001, 1.0)
+        .with_tag("dynamics")
     }
 }
 
@@ -129,7 +1112,297 @@ impl EffectBuilder for SidechainGateBuilder {
 pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("limiter", Arc::new(LimiterBuilder));
     registry.register("compressor", Arc::new(CompressorBuilder));
+    registry.register("multiband_comp", Arc::new(MultibandCompressorBuilder));
+    registry.register("transient", Arc::new(TransientShaperBuilder));
+    registry.register("gate", Arc::new(GateBuilder));
     registry.register("normaliser", Arc::new(NormaliserBuilder));
     registry.register("sidechain_compressor", Arc::new(SidechainCompressorBuilder));
     registry.register("sidechain_gate", Arc::new(SidechainGateBuilder));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressor_steady_state_level() {
+        let sample_rate = 48000.0;
+        let (mut comp, controls) = CompressorBuilder.build(&HashMap::from([
+            ("threshold".to_string(), -20.0),
+            ("ratio".to_string(), 4.0),
+            ("attack".to_string(), 0.001),
+            ("release".to_string(), 0.1),
+        ]));
+        comp.set_sample_rate(sample_rate);
+
+        // -6 dB sine at 1 kHz for one second
+        let amplitude = db_to_amplitude(-6.0);
+        let total = sample_rate as usize;
+        let mut peak = 0.0f32;
+        for i in 0..total {
+            let x = amplitude * (std::f32::consts::TAU * 1000.0 * i as f32 / 48000.0).sin();
+            let (l, _) = comp.filter_stereo(x, x);
+            if i > total / 2 {
+                peak = peak.max(l.abs());
+            }
+        }
+
+        // 14 dB over threshold at 4:1 leaves 3.5 dB above threshold
+        let expected_db = -20.0 + 14.0 / 4.0;
+        let measured_db = amplitude_to_db(peak);
+        assert!(
+            (measured_db - expected_db).abs() < 1.0,
+            "expected ~{expected_db} dB, got {measured_db} dB"
+        );
+
+        let reduction = controls.meter("gain_reduction").unwrap();
+        assert!((reduction - 10.5).abs() < 1.0, "gain reduction {reduction}");
+    }
+
+    #[test]
+    fn test_compressor_below_threshold_is_transparent() {
+        let (mut comp, _) = CompressorBuilder.build(&HashMap::new());
+        comp.set_sample_rate(48000.0);
+        for _ in 0..1000 {
+            let (l, r) = comp.filter_stereo(0.05, -0.05);
+            assert!((l - 0.05).abs() < 1e-6);
+            assert!((r + 0.05).abs() < 1e-6);
+        }
+    }
+
+    /// Peak-to-RMS ratio of a decaying 200 Hz hit every quarter second,
+    /// measured after the first hit
+    fn shaped_crest_factor(attack: f32, sustain: f32) -> f32 {
+        let (mut shaper, _) = TransientShaperBuilder.build(&HashMap::from([
+            ("attack".to_string(), attack),
+            ("sustain".to_string(), sustain),
+        ]));
+        let mut peak = 0.0f32;
+        let mut sum = 0.0f32;
+        let mut count = 0;
+        for i in 0..48000 {
+            let t = (i % 12000) as f32 / 48000.0;
+            let x = (-t / 0.05).exp() * (std::f32::consts::TAU * 200.0 * t).sin();
+            let (l, _) = shaper.filter_stereo(x, x);
+            if i >= 12000 {
+                peak = peak.max(l.abs());
+                sum += l * l;
+                count += 1;
+            }
+        }
+        peak / (sum / count as f32).sqrt()
+    }
+
+    #[test]
+    fn test_transient_shaper_attack_raises_crest_factor() {
+        let neutral = shaped_crest_factor(0.0, 0.0);
+        assert!(shaped_crest_factor(1.0, 0.0) > neutral * 1.2);
+        assert!(shaped_crest_factor(-1.0, 0.0) < neutral);
+        assert!(shaped_crest_factor(0.0, 1.0) < neutral);
+    }
+
+    #[test]
+    fn test_transient_shaper_neutral_is_transparent() {
+        let (mut shaper, _) = TransientShaperBuilder.build(&HashMap::new());
+        for i in 0..4800 {
+            let x = (i as f32 * 0.03).sin() * if i % 1200 < 100 { 1.0 } else { 0.2 };
+            let (l, r) = shaper.filter_stereo(x, -x);
+            assert_eq!((l, r), (x, -x));
+        }
+    }
+
+    /// Number of gate state changes over one second of a 1 kHz tone whose
+    /// level swings ±2 dB around -40 dB at 20 Hz
+    fn gate_transitions(hysteresis: f32, hold: f32) -> usize {
+        let (mut gate, controls) = GateBuilder.build(&HashMap::from([
+            ("threshold".to_string(), -40.0),
+            ("hysteresis".to_string(), hysteresis),
+            ("hold".to_string(), hold),
+        ]));
+        let mut transitions = 0;
+        let mut open = 0.0;
+        for i in 0..48000 {
+            let t = i as f32 / 48000.0;
+            let level_db = -40.0 + 2.0 * (std::f32::consts::TAU * 20.0 * t).sin();
+            let x = db_to_amplitude(level_db) * (std::f32::consts::TAU * 1000.0 * t).sin();
+            gate.filter_stereo(x, x);
+            let state = controls.meter("open").unwrap();
+            if state != open {
+                transitions += 1;
+                open = state;
+            }
+        }
+        transitions
+    }
+
+    #[test]
+    fn test_gate_does_not_chatter() {
+        // Without hysteresis or hold the gate follows every swing
+        assert!(gate_transitions(0.0, 0.0) >= 20);
+        // Either one keeps it open after the first crossing
+        assert_eq!(gate_transitions(6.0, 0.0), 1);
+        assert_eq!(gate_transitions(0.0, 0.1), 1);
+    }
+
+    #[test]
+    fn test_gate_closes_after_hold() {
+        let (mut gate, controls) = GateBuilder.build(&HashMap::from([
+            ("hold".to_string(), 0.05),
+            ("release".to_string(), 0.001),
+        ]));
+        for i in 0..4800 {
+            gate.filter_stereo((i as f32 * 0.1).sin() * 0.5, 0.0);
+        }
+        assert_eq!(controls.meter("open"), Some(1.0));
+
+        // The detector takes about 2200 samples to fall 40 dB to the closing
+        // threshold; the gate stays open for the hold time after that
+        let mut closed_at = None;
+        for i in 0..9600 {
+            let (l, _) = gate.filter_stereo(1e-4, 1e-4);
+            if controls.meter("open") == Some(0.0) && closed_at.is_none() {
+                closed_at = Some(i);
+            }
+            if i > 7200 {
+                assert!(l.abs() < 1e-6);
+            }
+        }
+        let closed_at = closed_at.unwrap();
+        assert!((4400..4800).contains(&closed_at), "closed at {closed_at}");
+    }
+
+    #[test]
+    fn test_limiter_never_exceeds_ceiling() {
+        for knee in [0.0, 6.0] {
+            let (mut limiter, controls) = LimiterBuilder.build(&HashMap::from([
+                ("ceiling".to_string(), -1.0),
+                ("knee".to_string(), knee),
+                ("attack".to_string(), 0.05),
+            ]));
+            let ceiling = db_to_amplitude(-1.0);
+            let latency = controls.meter(LATENCY_CONTROL).unwrap() as usize;
+            assert_eq!(latency, 240);
+
+            let mut max_reduction = 0.0f32;
+            for i in 0..48000 {
+                // +6 dB bursts with sharp onsets over a quiet bed
+                let burst = if (i / 4800) % 2 == 1 { 2.0 } else { 0.1 };
+                let x = burst * (i as f32 * 0.05).sin();
+                let (l, r) = limiter.filter_stereo(x, -x);
+                assert!(
+                    l.abs() <= ceiling + 1e-6 && r.abs() <= ceiling + 1e-6,
+                    "sample {i}: {l}"
+                );
+                max_reduction = max_reduction.max(controls.meter("gain_reduction").unwrap());
+            }
+            assert!(max_reduction > 6.0);
+        }
+    }
+
+    #[test]
+    fn test_limiter_latency_is_compensated_in_chain() {
+        let mut chain = crate::effects::EffectChain::with_registry(
+            crate::effects::EffectRegistry::with_builtin(),
+        );
+        chain.add("limiter", &[("lookahead", 10.0)]).unwrap();
+        assert_eq!(chain.effect_latency(0), Some(480));
+    }
+
+    #[test]
+    fn test_multiband_bands_sum_flat() {
+        let params = HashMap::from([
+            ("low_ratio".to_string(), 1.0),
+            ("mid_ratio".to_string(), 1.0),
+            ("high_ratio".to_string(), 1.0),
+        ]);
+        let (mut comp, _) = MultibandCompressorBuilder.build(&params);
+        comp.set_sample_rate(48000.0);
+        for freq in [50.0, 200.0, 700.0, 2000.0, 8000.0] {
+            comp.reset();
+            let (mut in_sq, mut left_sq, mut right_sq) = (0.0f32, 0.0f32, 0.0f32);
+            for i in 0..9600 {
+                let x = (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin();
+                let (l, r) = comp.filter_stereo(x, -x);
+                if i >= 4800 {
+                    in_sq += x * x;
+                    left_sq += l * l;
+                    right_sq += r * r;
+                }
+            }
+            assert!((left_sq / in_sq - 1.0).abs() < 0.02, "{freq} Hz");
+            assert!((right_sq / in_sq - 1.0).abs() < 0.02, "{freq} Hz");
+        }
+    }
+
+    #[test]
+    fn test_multiband_bands_are_isolated() {
+        let mut comp = MultibandCompressor::new(200.0, 2000.0, -20.0, 3.0, 0.01, 0.1);
+        comp.set_sample_rate(48000.0);
+
+        // RMS of each band for a unit sine
+        let mut band_levels = |freq: f32| {
+            comp.reset();
+            let mut sums = [0.0f32; 3];
+            for i in 0..9600 {
+                let x = (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin();
+                let split = comp.split_frame(x, x);
+                if i >= 4800 {
+                    for (sum, [l, _]) in sums.iter_mut().zip(split) {
+                        *sum += l * l;
+                    }
+                }
+            }
+            sums.map(|sum| amplitude_to_db((sum / 4800.0).sqrt() * std::f32::consts::SQRT_2))
+        };
+
+        // A 50 Hz tone stays in the low band
+        let [low, mid, high] = band_levels(50.0);
+        assert!(low > -0.5, "low {low} dB");
+        assert!(mid < -24.0, "mid {mid} dB");
+        assert!(high < -24.0, "high {high} dB");
+
+        // An 8 kHz tone stays in the high band
+        let [low, mid, high] = band_levels(8000.0);
+        assert!(low < -24.0, "low {low} dB");
+        assert!(mid < -24.0, "mid {mid} dB");
+        assert!(high > -0.5, "high {high} dB");
+    }
+
+    #[test]
+    fn test_multiband_compresses_only_the_loud_band() {
+        let (mut comp, controls) = MultibandCompressorBuilder.build(&HashMap::from([
+            ("attack".to_string(), 0.001),
+            ("low_threshold".to_string(), -30.0),
+            ("high_threshold".to_string(), -30.0),
+        ]));
+        comp.set_sample_rate(48000.0);
+
+        // Loud 60 Hz tone: only the low band goes over its threshold
+        let amplitude = db_to_amplitude(-6.0);
+        for i in 0..48000 {
+            let x = amplitude * (std::f32::consts::TAU * 60.0 * i as f32 / 48000.0).sin();
+            comp.filter_stereo(x, x);
+        }
+        let reduction = |band: &str| controls.meter(&format!("{}_gain_reduction", band)).unwrap();
+        assert!(
+            reduction("low") > 10.0,
+            "low band reduction {}",
+            reduction("low")
+        );
+        assert!(
+            reduction("high") < 1.0,
+            "high band reduction {}",
+            reduction("high")
+        );
+    }
+
+    #[test]
+    fn test_soft_knee_is_continuous() {
+        let below = Compressor::gain_reduction_db(-26.0, -20.0, 4.0, 12.0);
+        let mid = Compressor::gain_reduction_db(-20.0, -20.0, 4.0, 12.0);
+        let above = Compressor::gain_reduction_db(-14.0, -20.0, 4.0, 12.0);
+        assert!(below.abs() < 1e-6);
+        assert!(mid > 0.0 && mid < above);
+        assert!((above - 6.0 * 0.75).abs() < 1e-4);
+    }
+}
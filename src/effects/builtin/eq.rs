@@ -1,11 +1,22 @@
 //! EQ effects (3-band EQ, tilt EQ, etc.)
 
 use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
+use super::super::util::dual;
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Linear gain for an EQ band from its dB gain, flat while the band is bypassed
+fn band_gain(gain_db: &Shared, bypass: &Shared) -> An<impl AudioNode<Inputs = U0, Outputs = U1>> {
+    (var(gain_db) | var(bypass))
+        >> map(|x: &Frame<f32, U2>| if x[1] >= 0.5 { 1.0 } else { db_amp(x[0]) })
+}
+
 /// 3-band EQ (low, mid, high)
+///
+/// Band gains can be changed live. Each band can be bypassed with
+/// `low_bypass`, `mid_bypass` and `high_bypass` (>= 0.5 = bypassed), which
+/// makes it flat while keeping its gain setting for re-enabling.
 pub struct EQ3BandBuilder;
 
 impl EffectBuilder for EQ3BandBuilder {
@@ -16,14 +27,12 @@ impl EffectBuilder for EQ3BandBuilder {
         let low_freq = params.get("low_freq").copied().unwrap_or(200.0);
         let high_freq = params.get("high_freq").copied().unwrap_or(3000.0);
 
-        // Convert dB to linear amplitude (fundsp filters expect linear gain)
-        let low_gain = db_amp(low_gain_db);
-        let mid_gain = db_amp(mid_gain_db);
-        let high_gain = db_amp(high_gain_db);
-
         let low_shared = shared(low_gain_db);
         let mid_shared = shared(mid_gain_db);
         let high_shared = shared(high_gain_db);
+        let low_bypass = shared(params.get("low_bypass").copied().unwrap_or(0.0));
+        let mid_bypass = shared(params.get("mid_bypass").copied().unwrap_or(0.0));
+        let high_bypass = shared(params.get("high_bypass").copied().unwrap_or(0.0));
 
         let mut controls = EffectControls::new();
         controls
@@ -35,22 +44,29 @@ impl EffectBuilder for EQ3BandBuilder {
         controls
             .params
             .insert("high".to_string(), high_shared.clone());
+        controls
+            .params
+            .insert("low_bypass".to_string(), low_bypass.clone());
+        controls
+            .params
+            .insert("mid_bypass".to_string(), mid_bypass.clone());
+        controls
+            .params
+            .insert("high_bypass".to_string(), high_bypass.clone());
 
         // Use shelf filters for low and high, bell for mid
         // Low shelf at low_freq, High shelf at high_freq, Bell at geometric mean
         let mid_freq = (low_freq * high_freq).sqrt();
 
-        let left = pass()
-            >> lowshelf_hz(low_freq, 0.7, low_gain)
-            >> bell_hz(mid_freq, 1.0, mid_gain)
-            >> highshelf_hz(high_freq, 0.7, high_gain);
+        // Filters take: input, frequency, q, linear gain
+        let channel = || {
+            let low =
+                (pass() | dc((low_freq, 0.7)) | band_gain(&low_shared, &low_bypass)) >> lowshelf();
+            let mid = (low | dc((mid_freq, 1.0)) | band_gain(&mid_shared, &mid_bypass)) >> bell();
+            (mid | dc((high_freq, 0.7)) | band_gain(&high_shared, &high_bypass)) >> highshelf()
+        };
 
-        let right = pass()
-            >> lowshelf_hz(low_freq, 0.7, low_gain)
-            >> bell_hz(mid_freq, 1.0, mid_gain)
-            >> highshelf_hz(high_freq, 0.7, high_gain);
-
-        (Box::new(left | right), controls)
+        (Box::new(channel() | channel()), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
@@ -60,6 +76,10 @@ impl EffectBuilder for EQ3BandBuilder {
             .with_param("high", 0.0, -12.0, 12.0)
             .with_param("low_freq", 200.0, 50.0, 500.0)
             .with_param("high_freq", 3000.0, 1000.0, 10000.0)
+            .with_param("low_bypass", 0.0, 0.0, 1.0)
+            .with_param("mid_bypass", 0.0, 0.0, 1.0)
+            .with_param("high_bypass", 0.0, 0.0, 1.0)
+            .with_tag("eq")
     }
 }
 
@@ -96,6 +116,7 @@ impl EffectBuilder for TiltEQBuilder {
         EffectMetadata::new("tilt_eq", "Tilt EQ (bass/treble balance)")
             .with_param("tilt", 0.0, -1.0, 1.0)
             .with_param("freq", 1000.0, 200.0, 5000.0)
+            .with_tag("eq")
     }
 }
 
@@ -122,10 +143,9 @@ impl EffectBuilder for LowShelfBuilder {
             .params
             .insert("gain".to_string(), gain_shared.clone());
 
-        let left = pass() >> lowshelf_hz(freq, q, gain);
-        let right = pass() >> lowshelf_hz(freq, q, gain);
+        let shelf = pass() >> lowshelf_hz(freq, q, gain);
 
-        (Box::new(left | right), controls)
+        (Box::new(dual(shelf)), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
@@ -133,6 +153,7 @@ impl EffectBuilder for LowShelfBuilder {
             .with_param("freq", 200.0, 20.0, 1000.0)
             .with_param("gain", 0.0, -12.0, 12.0)
             .with_param("q", 0.7, 0.1, 2.0)
+            .with_tag("eq")
     }
 }
 
@@ -159,10 +180,9 @@ impl EffectBuilder for HighShelfBuilder {
             .params
             .insert("gain".to_string(), gain_shared.clone());
 
-        let left = pass() >> highshelf_hz(freq, q, gain);
-        let right = pass() >> highshelf_hz(freq, q, gain);
+        let shelf = pass() >> highshelf_hz(freq, q, gain);
 
-        (Box::new(left | right), controls)
+        (Box::new(dual(shelf)), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
@@ -170,6 +190,7 @@ impl EffectBuilder for HighShelfBuilder {
             .with_param("freq", 3000.0, 500.0, 15000.0)
             .with_param("gain", 0.0, -12.0, 12.0)
             .with_param("q", 0.7, 0.1, 2.0)
+            .with_tag("eq")
     }
 }
 
@@ -184,3 +205,62 @@ pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("high_shelf", Arc::new(HighShelfBuilder));
     registry.register("highshelf", Arc::new(HighShelfBuilder)); // alias
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eq(params: &[(&str, f32)]) -> (Box<dyn AudioUnit>, EffectControls) {
+        let params: HashMap<String, f32> =
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        EQ3BandBuilder.build(&params)
+    }
+
+    /// Render a two-tone signal and return the left output
+    fn render(unit: &mut dyn AudioUnit) -> Vec<f32> {
+        (0..4800)
+            .map(|i| {
+                let t = i as f32 / 48000.0;
+                let x = (std::f32::consts::TAU * 80.0 * t).sin() * 0.3
+                    + (std::f32::consts::TAU * 775.0 * t).sin() * 0.3;
+                unit.filter_stereo(x, x).0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_eq3_mid_bypass_matches_two_band() {
+        let (mut two_band, _) = eq(&[("low", 6.0), ("high", -4.0)]);
+        let (mut bypassed, controls) = eq(&[
+            ("low", 6.0),
+            ("mid", 9.0),
+            ("high", -4.0),
+            ("mid_bypass", 1.0),
+        ]);
+
+        let reference = render(two_band.as_mut());
+        let output = render(bypassed.as_mut());
+        for (a, b) in reference.iter().zip(&output) {
+            assert!((a - b).abs() < 1e-5);
+        }
+
+        // The mid gain setting survives the bypass
+        assert_eq!(controls.get("mid"), Some(9.0));
+
+        // Re-enabling the band brings the boost back
+        controls.set("mid_bypass", 0.0);
+        let boosted = render(bypassed.as_mut());
+        let energy = |v: &[f32]| v[2400..].iter().map(|x| x * x).sum::<f32>();
+        assert!(energy(&boosted) > energy(&reference) * 1.5);
+    }
+
+    #[test]
+    fn test_eq3_band_gains_are_live() {
+        let (mut unit, controls) = eq(&[]);
+        let flat = render(unit.as_mut());
+        controls.set("low", 12.0);
+        let boosted = render(unit.as_mut());
+        let energy = |v: &[f32]| v[2400..].iter().map(|x| x * x).sum::<f32>();
+        assert!(energy(&boosted) > energy(&flat) * 1.5);
+    }
+}
@@ -1,6 +1,11 @@
-//! Filter effects (lowpass, highpass, bandpass, resonant variants)
+//! Filter effects (lowpass, highpass, bandpass, resonant variants, crossover)
+//!
+//! Resonant filters take `res` in 0.0-1.0, mapped to Q with
+//! [`res_to_q`](crate::params::res_to_q); pass `res_raw` to set Q directly.
 
 use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
+use super::super::util::dual;
+use crate::params::{q_mapping, resonance_param};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -11,7 +16,7 @@ pub struct LowpassBuilder;
 impl EffectBuilder for LowpassBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
         let initial_cutoff = params.get("cutoff").copied().unwrap_or(1000.0);
-        let initial_res = params.get("res").copied().unwrap_or(0.5);
+        let (initial_res, raw_res) = resonance_param(params, 0.0);
 
         let cutoff_shared = shared(initial_cutoff);
         let res_shared = shared(initial_res);
@@ -20,20 +25,22 @@ impl EffectBuilder for LowpassBuilder {
         controls
             .params
             .insert("cutoff".to_string(), cutoff_shared.clone());
+        let res_name = if raw_res { "res_raw" } else { "res" };
         controls
             .params
-            .insert("res".to_string(), res_shared.clone());
+            .insert(res_name.to_string(), res_shared.clone());
 
-        let left = (pass() | var(&cutoff_shared) | var(&res_shared)) >> lowpass();
-        let right = (pass() | var(&cutoff_shared) | var(&res_shared)) >> lowpass();
+        let q = q_mapping(raw_res);
+        let filter = (pass() | var(&cutoff_shared) | var_fn(&res_shared, q)) >> lowpass();
 
-        (Box::new(left | right), controls)
+        (Box::new(dual(filter)), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("lpf", "Lowpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 0.5, 0.0, 10.0)
+            .with_param("res", 0.0, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -43,7 +50,7 @@ pub struct HighpassBuilder;
 impl EffectBuilder for HighpassBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
         let initial_cutoff = params.get("cutoff").copied().unwrap_or(1000.0);
-        let initial_res = params.get("res").copied().unwrap_or(0.5);
+        let (initial_res, raw_res) = resonance_param(params, 0.0);
 
         let cutoff_shared = shared(initial_cutoff);
         let res_shared = shared(initial_res);
@@ -52,20 +59,22 @@ impl EffectBuilder for HighpassBuilder {
         controls
             .params
             .insert("cutoff".to_string(), cutoff_shared.clone());
+        let res_name = if raw_res { "res_raw" } else { "res" };
         controls
             .params
-            .insert("res".to_string(), res_shared.clone());
+            .insert(res_name.to_string(), res_shared.clone());
 
-        let left = (pass() | var(&cutoff_shared) | var(&res_shared)) >> highpass();
-        let right = (pass() | var(&cutoff_shared) | var(&res_shared)) >> highpass();
+        let q = q_mapping(raw_res);
+        let filter = (pass() | var(&cutoff_shared) | var_fn(&res_shared, q)) >> highpass();
 
-        (Box::new(left | right), controls)
+        (Box::new(dual(filter)), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("hpf", "Highpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 0.5, 0.0, 10.0)
+            .with_param("res", 0.0, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -75,7 +84,7 @@ pub struct BandpassBuilder;
 impl EffectBuilder for BandpassBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
         let initial_center = params.get("center").copied().unwrap_or(1000.0);
-        let initial_res = params.get("res").copied().unwrap_or(0.5);
+        let (initial_res, raw_res) = resonance_param(params, 0.0);
 
         let center_shared = shared(initial_center);
         let res_shared = shared(initial_res);
@@ -84,20 +93,22 @@ impl EffectBuilder for BandpassBuilder {
         controls
             .params
             .insert("center".to_string(), center_shared.clone());
+        let res_name = if raw_res { "res_raw" } else { "res" };
         controls
             .params
-            .insert("res".to_string(), res_shared.clone());
+            .insert(res_name.to_string(), res_shared.clone());
 
-        let left = (pass() | var(&center_shared) | var(&res_shared)) >> bandpass();
-        let right = (pass() | var(&center_shared) | var(&res_shared)) >> bandpass();
+        let q = q_mapping(raw_res);
+        let filter = (pass() | var(&center_shared) | var_fn(&res_shared, q)) >> bandpass();
 
-        (Box::new(left | right), controls)
+        (Box::new(dual(filter)), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("bpf", "Bandpass filter")
             .with_param("center", 1000.0, 20.0, 20000.0)
-            .with_param("res", 0.5, 0.0, 10.0)
+            .with_param("res", 0.0, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -112,7 +123,8 @@ impl EffectBuilder for NormalizedLowpassBuilder {
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("nlpf", "Normalized lowpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 0.5, 0.0, 10.0)
+            .with_param("res", 0.0, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -126,7 +138,8 @@ impl EffectBuilder for NormalizedHighpassBuilder {
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("nhpf", "Normalized highpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 0.5, 0.0, 10.0)
+            .with_param("res", 0.0, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -140,7 +153,8 @@ impl EffectBuilder for NormalizedBandpassBuilder {
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("nbpf", "Normalized bandpass filter")
             .with_param("center", 1000.0, 20.0, 20000.0)
-            .with_param("res", 0.5, 0.0, 10.0)
+            .with_param("res", 0.0, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -150,14 +164,15 @@ pub struct ResonantLowpassBuilder;
 impl EffectBuilder for ResonantLowpassBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
         let mut resonant_params = params.clone();
-        resonant_params.entry("res".to_string()).or_insert(5.0); // Higher default resonance
+        resonant_params.entry("res".to_string()).or_insert(0.6); // Higher default resonance
         LowpassBuilder.build(&resonant_params)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("rlpf", "Resonant lowpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 5.0, 0.0, 10.0)
+            .with_param("res", 0.6, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -171,7 +186,8 @@ impl EffectBuilder for NormalizedResonantLowpassBuilder {
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("nrlpf", "Normalized resonant lowpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 5.0, 0.0, 10.0)
+            .with_param("res", 0.6, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -180,14 +196,15 @@ pub struct ResonantHighpassBuilder;
 impl EffectBuilder for ResonantHighpassBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
         let mut resonant_params = params.clone();
-        resonant_params.entry("res".to_string()).or_insert(5.0);
+        resonant_params.entry("res".to_string()).or_insert(0.6);
         HighpassBuilder.build(&resonant_params)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("rhpf", "Resonant highpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 5.0, 0.0, 10.0)
+            .with_param("res", 0.6, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -201,7 +218,8 @@ impl EffectBuilder for NormalizedResonantHighpassBuilder {
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("nrhpf", "Normalized resonant highpass filter")
             .with_param("cutoff", 1000.0, 20.0, 20000.0)
-            .with_param("res", 5.0, 0.0, 10.0)
+            .with_param("res", 0.6, 0.0, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -229,10 +247,9 @@ impl EffectBuilder for ParametricEQBuilder {
 
         // Use FunDSP's bell filter (parametric EQ band)
         // bell takes: input, frequency, q, gain_db
-        let left = (pass() | var(&freq_shared) | var(&q_shared) | var(&gain_shared)) >> bell();
-        let right = (pass() | var(&freq_shared) | var(&q_shared) | var(&gain_shared)) >> bell();
+        let band = (pass() | var(&freq_shared) | var(&q_shared) | var(&gain_shared)) >> bell();
 
-        (Box::new(left | right), controls)
+        (Box::new(dual(band)), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
@@ -240,6 +257,7 @@ impl EffectBuilder for ParametricEQBuilder {
             .with_param("freq", 1000.0, 20.0, 20000.0)
             .with_param("q", 1.0, 0.1, 10.0)
             .with_param("gain", 0.0, -24.0, 24.0)
+            .with_tag("eq")
     }
 }
 
@@ -251,15 +269,15 @@ impl EffectBuilder for DCBlockerBuilder {
         let cutoff = params.get("cutoff").copied().unwrap_or(10.0); // Hz
 
         // DC blocker is a highpass filter at very low frequency
-        let left = dcblock_hz(cutoff);
-        let right = dcblock_hz(cutoff);
+        let filter = dcblock_hz(cutoff);
 
-        (Box::new(left | right), EffectControls::new())
+        (Box::new(dual(filter)), EffectControls::new())
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("dc_blocker", "DC Blocker (removes DC offset)")
             .with_param("cutoff", 10.0, 1.0, 50.0)
+            .with_tag("filter")
     }
 }
 
@@ -280,16 +298,405 @@ impl EffectBuilder for NotchBuilder {
             .insert("freq".to_string(), freq_shared.clone());
         controls.params.insert("q".to_string(), q_shared.clone());
 
-        let left = (pass() | var(&freq_shared) | var(&q_shared)) >> notch();
-        let right = (pass() | var(&freq_shared) | var(&q_shared)) >> notch();
+        let filter = (pass() | var(&freq_shared) | var(&q_shared)) >> notch();
 
-        (Box::new(left | right), controls)
+        (Box::new(dual(filter)), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("notch", "Notch filter (removes specific frequency)")
             .with_param("freq", 1000.0, 20.0, 20000.0)
             .with_param("q", 2.0, 0.1, 100.0)
+            .with_tag("filter")
+    }
+}
+
+/// Lowest allpass break frequency reached at `amount = 1.0` (Hz)
+const PHASE_ROTATE_MIN_FREQ: f32 = 150.0;
+
+/// Broadband phase rotator built from first-order allpass sections
+///
+/// Every section has unity magnitude at all frequencies; `amount` moves the
+/// shared allpass coefficient from 1.0 (identity, no rotation) towards the
+/// coefficient for a low break frequency, rotating phase across the spectrum.
+#[derive(Clone)]
+pub struct PhaseRotate {
+    /// Rotation amount (0.0 = none, 1.0 = maximum)
+    pub amount: Shared,
+    /// Allpass state per channel: (x1, y1) for each stage
+    stages: [Vec<(f32, f32)>; 2],
+    sample_rate: f32,
+}
+
+impl PhaseRotate {
+    /// Create a new phase rotator with `stages` allpass sections (clamped to 1..=8)
+    pub fn new(amount: f32, stages: usize) -> Self {
+        let stages = stages.clamp(1, 8);
+        Self {
+            amount: shared(amount),
+            stages: [vec![(0.0, 0.0); stages], vec![(0.0, 0.0); stages]],
+            sample_rate: 48000.0,
+        }
+    }
+
+    #[inline]
+    fn coefficient(&self) -> f32 {
+        let t = (std::f32::consts::PI * PHASE_ROTATE_MIN_FREQ / self.sample_rate).tan();
+        let min_coeff = (t - 1.0) / (t + 1.0);
+        let amount = self.amount.value().clamp(0.0, 1.0);
+        1.0 + (min_coeff - 1.0) * amount
+    }
+
+    #[inline]
+    fn process_channel(&mut self, channel: usize, input: f32, coeff: f32) -> f32 {
+        let mut x = input;
+        for (x1, y1) in self.stages[channel].iter_mut() {
+            let y = coeff * x + *x1 - coeff * *y1;
+            *x1 = x;
+            *y1 = y;
+            x = y;
+        }
+        x
+    }
+}
+
+impl AudioUnit for PhaseRotate {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        for channel in &mut self.stages {
+            channel.iter_mut().for_each(|s| *s = (0.0, 0.0));
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let coeff = self.coefficient();
+        output[0] = self.process_channel(0, input[0], coeff);
+        output[1] = self.process_channel(1, input[1], coeff);
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        let coeff = self.coefficient();
+        for i in 0..size {
+            let l = self.process_channel(0, input.at_f32(0, i), coeff);
+            let r = self.process_channel(1, input.at_f32(1, i), coeff);
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"phase_rotate";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Phase rotation - allpass cascade for phase alignment and crest factor reduction
+pub struct PhaseRotateBuilder;
+
+impl EffectBuilder for PhaseRotateBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let amount = params.get("amount").copied().unwrap_or(0.5);
+        let stages = params.get("stages").copied().unwrap_or(4.0).round() as usize;
+
+        let rotate = PhaseRotate::new(amount, stages);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("amount".to_string(), rotate.amount.clone());
+
+        (Box::new(rotate), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("phase_rotate", "Phase rotation (allpass phase alignment)")
+            .with_param("amount", 0.5, 0.0, 1.0)
+            .with_param("stages", 4.0, 1.0, 8.0)
+            .with_tag("filter")
+    }
+}
+
+/// Direct form I biquad section
+#[derive(Clone, Default)]
+struct Biquad {
+    /// Coefficients (b0, b1, b2, a1, a2), normalized by a0
+    coeffs: [f32; 5],
+    /// State (x1, x2, y1, y2)
+    state: [f32; 4],
+}
+
+impl Biquad {
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let [b0, b1, b2, a1, a2] = self.coeffs;
+        let [x1, x2, y1, y2] = self.state;
+        let y = b0 * x + b1 * x1 + b2 * x2 - a1 * y1 - a2 * y2;
+        self.state = [x, x1, y, y1];
+        y
+    }
+}
+
+/// Linkwitz-Riley (LR4) lowpass/highpass pair for splitting a signal into two bands
+///
+/// Each band is two cascaded 2nd-order Butterworth sections, giving 24 dB/oct
+/// slopes that are both 6 dB down at the cutoff. The bands sum to a flat
+/// magnitude response with the phase of a 2nd-order allpass; other bands can
+/// be given the same phase with [`allpass`](Self::allpass). Use one per channel.
+#[derive(Clone)]
+pub struct Crossover {
+    /// Crossover frequency in Hz
+    cutoff: f32,
+    /// Cascaded lowpass sections
+    lowpass: [Biquad; 2],
+    /// Cascaded highpass sections
+    highpass: [Biquad; 2],
+    /// Allpass matching the phase of the summed bands
+    allpass: Biquad,
+    sample_rate: f32,
+}
+
+impl Crossover {
+    /// Create a crossover at `cutoff` Hz
+    pub fn new(cutoff: f32, sample_rate: f32) -> Self {
+        let mut crossover = Self {
+            cutoff,
+            lowpass: Default::default(),
+            highpass: Default::default(),
+            allpass: Biquad::default(),
+            sample_rate,
+        };
+        crossover.update_coeffs();
+        crossover
+    }
+
+    /// Crossover frequency in Hz
+    pub fn cutoff(&self) -> f32 {
+        self.cutoff
+    }
+
+    /// Move the crossover frequency
+    pub fn set_cutoff(&mut self, cutoff: f32) {
+        if cutoff != self.cutoff {
+            self.cutoff = cutoff;
+            self.update_coeffs();
+        }
+    }
+
+    /// Set the sample rate in Hz
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_coeffs();
+        self.reset();
+    }
+
+    /// Clear the filter state
+    pub fn reset(&mut self) {
+        for section in self.sections() {
+            section.state = [0.0; 4];
+        }
+    }
+
+    fn sections(&mut self) -> impl Iterator<Item = &mut Biquad> {
+        self.lowpass
+            .iter_mut()
+            .chain(self.highpass.iter_mut())
+            .chain(std::iter::once(&mut self.allpass))
+    }
+
+    /// Recompute the Butterworth sections and allpass (RBJ cookbook)
+    fn update_coeffs(&mut self) {
+        let freq = self.cutoff.clamp(1.0, self.sample_rate * 0.45);
+        let omega = std::f32::consts::TAU * freq / self.sample_rate;
+        let alpha = omega.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = omega.cos();
+        let a0 = 1.0 + alpha;
+        let (a1, a2) = (-2.0 * cos / a0, (1.0 - alpha) / a0);
+
+        let low = (1.0 - cos) / a0;
+        let high = (1.0 + cos) / a0;
+        for section in &mut self.lowpass {
+            section.coeffs = [low * 0.5, low, low * 0.5, a1, a2];
+        }
+        for section in &mut self.highpass {
+            section.coeffs = [high * 0.5, -high, high * 0.5, a1, a2];
+        }
+        self.allpass.coeffs = [a2, a1, 1.0, a1, a2];
+    }
+
+    /// Split one sample into (low, high)
+    ///
+    /// `low + high` equals `x` run through [`allpass`](Self::allpass).
+    #[inline]
+    pub fn split(&mut self, x: f32) -> (f32, f32) {
+        (self.lowpass(x), self.highpass(x))
+    }
+
+    /// Run one sample through the low band only (24 dB/oct lowpass)
+    #[inline]
+    pub fn lowpass(&mut self, x: f32) -> f32 {
+        self.lowpass[1].process(self.lowpass[0].process(x))
+    }
+
+    /// Run one sample through the high band only (24 dB/oct highpass)
+    #[inline]
+    pub fn highpass(&mut self, x: f32) -> f32 {
+        self.highpass[1].process(self.highpass[0].process(x))
+    }
+
+    /// Run one sample through an allpass with the phase of the summed bands
+    ///
+    /// When a band split off earlier is recombined with the two bands of this
+    /// crossover, passing it through here keeps the three bands in phase so
+    /// they still sum flat. Uses its own state, separate from [`split`](Self::split).
+    #[inline]
+    pub fn allpass(&mut self, x: f32) -> f32 {
+        self.allpass.process(x)
+    }
+}
+
+/// Stereo crossover effect outputting the summed bands or a single band
+///
+/// `mode` 0 outputs low + high (flat in magnitude, for checking a crossover),
+/// 1 outputs the low band and 2 the high band.
+#[derive(Clone)]
+pub struct CrossoverPair {
+    /// Crossover frequency in Hz
+    pub freq: Shared,
+    /// Output (0 = sum, 1 = low band, 2 = high band)
+    pub mode: Shared,
+    bands: [Crossover; 2],
+}
+
+impl CrossoverPair {
+    /// Create a new crossover pair effect
+    pub fn new(freq: f32, mode: f32) -> Self {
+        Self {
+            freq: shared(freq),
+            mode: shared(mode),
+            bands: [Crossover::new(freq, 48000.0), Crossover::new(freq, 48000.0)],
+        }
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32, freq: f32, mode: i32) -> (f32, f32) {
+        let mut output = [left, right];
+        for (band, sample) in self.bands.iter_mut().zip(output.iter_mut()) {
+            band.set_cutoff(freq);
+            let (low, high) = band.split(*sample);
+            *sample = match mode {
+                1 => low,
+                2 => high,
+                _ => low + high,
+            };
+        }
+        (output[0], output[1])
+    }
+}
+
+impl AudioUnit for CrossoverPair {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.bands.iter_mut().for_each(Crossover::reset);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        for band in &mut self.bands {
+            band.set_sample_rate(sample_rate as f32);
+        }
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (freq, mode) = (self.freq.value(), self.mode.value().round() as i32);
+        let (left, right) = self.process_frame(input[0], input[1], freq, mode);
+        output[0] = left;
+        output[1] = right;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        let (freq, mode) = (self.freq.value(), self.mode.value().round() as i32);
+        for i in 0..size {
+            let (left, right) =
+                self.process_frame(input.at_f32(0, i), input.at_f32(1, i), freq, mode);
+            output.set_f32(0, i, left);
+            output.set_f32(1, i, right);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"crossover_pair";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Crossover pair - Linkwitz-Riley lowpass/highpass split
+pub struct CrossoverPairBuilder;
+
+impl EffectBuilder for CrossoverPairBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let freq = params.get("freq").copied().unwrap_or(1000.0);
+        let mode = params.get("mode").copied().unwrap_or(0.0); // 0 = sum, 1 = low, 2 = high
+
+        let crossover = CrossoverPair::new(freq, mode);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("freq".to_string(), crossover.freq.clone());
+        controls
+            .params
+            .insert("mode".to_string(), crossover.mode.clone());
+
+        (Box::new(crossover), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new(
+            "crossover_pair",
+            "Crossover (Linkwitz-Riley low/high split)",
+        )
+        .with_param("freq", 1000.0, 20.0, 20000.0)
+        .with_param("mode", 0.0, 0.0, 2.0)
+        .with_tag("filter")
     }
 }
 
@@ -319,4 +726,198 @@ pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("peq", Arc::new(ParametricEQBuilder)); // alias
     registry.register("dc_blocker", Arc::new(DCBlockerBuilder));
     registry.register("notch", Arc::new(NotchBuilder));
+    registry.register("phase_rotate", Arc::new(PhaseRotateBuilder));
+    registry.register("crossover_pair", Arc::new(CrossoverPairBuilder));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render a sine through the unit, returning (input RMS, output RMS, difference RMS)
+    fn render_sine(unit: &mut dyn AudioUnit, freq: f32) -> (f32, f32, f32) {
+        let n = 48000;
+        let skip = 4800;
+        let (mut in_sq, mut out_sq, mut diff_sq) = (0.0f32, 0.0f32, 0.0f32);
+        for i in 0..n {
+            let x = (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin();
+            let (y, _) = unit.filter_stereo(x, x);
+            if i >= skip {
+                in_sq += x * x;
+                out_sq += y * y;
+                diff_sq += (y - x) * (y - x);
+            }
+        }
+        let count = (n - skip) as f32;
+        (
+            (in_sq / count).sqrt(),
+            (out_sq / count).sqrt(),
+            (diff_sq / count).sqrt(),
+        )
+    }
+
+    #[test]
+    fn test_phase_rotate_flat_magnitude() {
+        let (mut unit, controls) = PhaseRotateBuilder.build(&HashMap::new());
+        unit.set_sample_rate(48000.0);
+
+        for amount in [0.0, 0.5, 1.0] {
+            controls.set("amount", amount);
+            for freq in [100.0, 1000.0, 5000.0] {
+                unit.reset();
+                let (input, output, _) = render_sine(unit.as_mut(), freq);
+                assert!(
+                    (output - input).abs() / input < 0.01,
+                    "amount {amount}, {freq} Hz: {input} -> {output}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    /// RMS of (low, high, low + high, allpass) for a unit sine through a crossover
+    fn crossover_levels(cutoff: f32, freq: f32) -> [f32; 4] {
+        let mut crossover = Crossover::new(cutoff, 48000.0);
+        let mut sums = [0.0f32; 4];
+        for i in 0..9600 {
+            let x = (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin();
+            let (low, high) = crossover.split(x);
+            let allpass = crossover.allpass(x);
+            if i >= 4800 {
+                for (sum, y) in sums.iter_mut().zip([low, high, low + high, allpass]) {
+                    *sum += y * y;
+                }
+            }
+        }
+        sums.map(|sum| (sum / 4800.0).sqrt() * std::f32::consts::SQRT_2)
+    }
+
+    #[test]
+    fn test_crossover_rejects_opposite_band() {
+        // A decade either side of the cutoff each band is at least 48 dB down
+        let floor = 10.0_f32.powf(-48.0 / 20.0);
+        let [low, high, ..] = crossover_levels(1000.0, 100.0);
+        assert!(high < floor, "100 Hz leaks into the high band: {high}");
+        assert!((low - 1.0).abs() < 0.01, "{low}");
+        let [low, high, ..] = crossover_levels(1000.0, 10000.0);
+        assert!(low < floor, "10 kHz leaks into the low band: {low}");
+        assert!((high - 1.0).abs() < 0.01, "{high}");
+
+        // Both bands are 6 dB down at the cutoff
+        let [low, high, ..] = crossover_levels(1000.0, 1000.0);
+        assert!((low - 0.5).abs() < 0.01 && (high - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_crossover_bands_sum_flat() {
+        for cutoff in [100.0, 1000.0, 8000.0] {
+            for freq in [30.0, 250.0, 1000.0, 4000.0, 15000.0] {
+                let [_, _, sum, allpass] = crossover_levels(cutoff, freq);
+                assert!((sum - 1.0).abs() < 0.01, "{cutoff}/{freq} Hz: {sum}");
+                assert!(
+                    (allpass - 1.0).abs() < 0.01,
+                    "{cutoff}/{freq} Hz: {allpass}"
+                );
+            }
+        }
+
+        // The summed bands are in phase with the allpass
+        let mut crossover = Crossover::new(1000.0, 48000.0);
+        let mut compensation = Crossover::new(1000.0, 48000.0);
+        for i in 0..4800 {
+            let x = (std::f32::consts::TAU * 700.0 * i as f32 / 48000.0).sin();
+            let (low, high) = crossover.split(x);
+            assert!((low + high - compensation.allpass(x)).abs() < 1e-4);
+        }
+
+        // The effect's bands split around the crossover frequency
+        let params = HashMap::from([("freq".to_string(), 1000.0), ("mode".to_string(), 1.0)]);
+        let (mut unit, controls) = CrossoverPairBuilder.build(&params);
+        unit.set_sample_rate(48000.0);
+        let (input, low, _) = render_sine(unit.as_mut(), 8000.0);
+        assert!(low < input * 0.01, "low band leaks {low}");
+
+        unit.reset();
+        controls.set("mode", 0.0);
+        let (input, sum, _) = render_sine(unit.as_mut(), 8000.0);
+        assert!(
+            (sum - input).abs() / input < 0.01,
+            "summed bands {sum} vs {input}"
+        );
+    }
+
+    #[test]
+    fn test_phase_rotate_amount_changes_phase() {
+        let (mut unit, controls) = PhaseRotateBuilder.build(&HashMap::new());
+        unit.set_sample_rate(48000.0);
+
+        controls.set("amount", 0.0);
+        let (_, _, diff_none) = render_sine(unit.as_mut(), 1000.0);
+        assert!(
+            diff_none < 1e-4,
+            "amount 0 should be transparent: {diff_none}"
+        );
+
+        unit.reset();
+        controls.set("amount", 1.0);
+        let (_, _, diff_full) = render_sine(unit.as_mut(), 1000.0);
+        assert!(diff_full > 0.1, "amount 1 should rotate phase: {diff_full}");
+    }
+
+    /// Amplitude of `freq` in a 48kHz signal (single DFT bin)
+    fn bin_amplitude(signal: &[f32], freq: f32) -> f32 {
+        let (mut sin_sum, mut cos_sum) = (0.0f32, 0.0f32);
+        for (i, x) in signal.iter().enumerate() {
+            let phase = std::f32::consts::TAU * freq * i as f32 / 48000.0;
+            sin_sum += x * phase.sin();
+            cos_sum += x * phase.cos();
+        }
+        2.0 * (sin_sum * sin_sum + cos_sum * cos_sum).sqrt() / signal.len() as f32
+    }
+
+    #[test]
+    fn test_res_comparable_across_filter_and_synth() {
+        use crate::synth::synths::TB303SynthBuilder;
+        use crate::synth::SynthBuilder;
+
+        let params = HashMap::from([("cutoff".to_string(), 1000.0), ("res".to_string(), 0.6)]);
+
+        // Filter effect: gain at cutoff relative to the passband
+        let (mut lpf, _) = LowpassBuilder.build(&params);
+        lpf.set_sample_rate(48000.0);
+        let (input, at_cutoff, _) = render_sine(lpf.as_mut(), 1000.0);
+        lpf.reset();
+        let (_, passband, _) = render_sine(lpf.as_mut(), 200.0);
+        let effect_peak = at_cutoff / passband;
+        assert!(at_cutoff / input > 3.0, "lpf barely resonates");
+
+        // Synth: a 100 Hz saw through the ladder, harmonic n has amplitude 1/n
+        let (mut synth, _) = TB303SynthBuilder.build(100.0, &params);
+        synth.set_sample_rate(48000.0);
+        let signal: Vec<f32> = (0..48000)
+            .map(|_| synth.get_stereo().0)
+            .skip(9600)
+            .collect();
+        let synth_peak =
+            bin_amplitude(&signal, 1000.0) * 10.0 / (bin_amplitude(&signal, 200.0) * 2.0);
+
+        let ratio = synth_peak / effect_peak;
+        assert!(
+            ratio > 0.5 && ratio < 2.0,
+            "lpf peak {effect_peak}, tb303 peak {synth_peak}"
+        );
+    }
+
+    #[test]
+    fn test_res_raw_sets_q_directly() {
+        let params = HashMap::from([("cutoff".to_string(), 1000.0), ("res_raw".to_string(), 0.5)]);
+        let (mut unit, controls) = LowpassBuilder.build(&params);
+        unit.set_sample_rate(48000.0);
+        assert!(controls.get("res").is_none());
+        assert_eq!(controls.get("res_raw"), Some(0.5));
+
+        // Q 0.5 is below Butterworth: no peak at cutoff
+        let (input, output, _) = render_sine(unit.as_mut(), 1000.0);
+        assert!(output / input < 0.6);
+    }
 }
@@ -37,6 +37,7 @@ impl EffectBuilder for TapeSaturationBuilder {
             .with_param("drive", 0.5, 0.0, 1.0)
             .with_param("warmth", 0.5, 0.0, 1.0)
             .with_param("mix", 1.0, 0.0, 1.0)
+            .with_tag("distortion")
     }
 }
 
@@ -77,6 +78,7 @@ impl EffectBuilder for LofiBuilder {
         EffectMetadata::new("lofi", "Lo-fi effect (retro degradation)")
             .with_param("amount", 0.5, 0.0, 1.0)
             .with_param("mix", 1.0, 0.0, 1.0)
+            .with_tag("lofi")
     }
 }
 
@@ -113,6 +115,7 @@ impl EffectBuilder for VinylBuilder {
             .with_param("crackle", 0.3, 0.0, 1.0)
             .with_param("hiss", 0.2, 0.0, 1.0)
             .with_param("warmth", 0.5, 0.0, 1.0)
+            .with_tag("lofi")
     }
 }
 
@@ -1,5 +1,7 @@
 //! Built-in audio effects
 
+pub mod analysis;
+pub mod convolution;
 pub mod distortion;
 pub mod dynamics;
 pub mod eq;
@@ -9,9 +11,12 @@ pub mod modulation;
 pub mod other;
 pub mod spatial;
 pub mod time;
+pub mod tuning;
 
 /// Register all built-in effects with the registry
 pub fn register_all(registry: &mut super::registry::EffectRegistry) {
+    analysis::register_all(registry);
+    convolution::register_all(registry);
     distortion::register_all(registry);
     dynamics::register_all(registry);
     eq::register_all(registry);
@@ -21,4 +26,5 @@ pub fn register_all(registry: &mut super::registry::EffectRegistry) {
     other::register_all(registry);
     spatial::register_all(registry);
     time::register_all(registry);
+    tuning::register_all(registry);
 }
@@ -25,6 +25,7 @@ impl EffectBuilder for ChorusBuilder {
             .with_param("separation", 0.02, 0.0, 0.1)
             .with_param("variation", 0.5, 0.0, 1.0)
             .with_param("mod_frequency", 0.5, 0.1, 10.0)
+            .with_tag("modulation")
     }
 }
 
@@ -54,6 +55,7 @@ impl EffectBuilder for FlangerBuilder {
         EffectMetadata::new("flanger", "Flanger effect")
             .with_param("depth", 0.005, 0.0, 0.02)
             .with_param("rate", 0.5, 0.1, 10.0)
+            .with_tag("modulation")
     }
 }
 
@@ -81,40 +83,176 @@ impl EffectBuilder for TremoloBuilder {
         EffectMetadata::new("tremolo", "Tremolo (amplitude modulation)")
             .with_param("rate", 4.0, 0.1, 20.0)
             .with_param("depth", 0.5, 0.0, 1.0)
+            .with_tag("modulation")
     }
 }
 
-/// Phaser effect
-pub struct PhaserBuilder;
+/// Lowest and highest allpass break frequencies swept by the phaser LFO (Hz)
+const PHASER_MIN_FREQ: f32 = 200.0;
+const PHASER_MAX_FREQ: f32 = 4000.0;
+
+/// Stereo phaser: a cascade of first-order allpass stages swept by an LFO
+///
+/// The wet path is fed back into the cascade input and mixed with the dry
+/// signal, which turns the phase shift into moving notches. The right channel
+/// LFO runs a quarter cycle ahead of the left for stereo movement.
+#[derive(Clone)]
+pub struct Phaser {
+    /// LFO rate in Hz
+    pub rate: Shared,
+    /// Wet/dry depth (0.0 = dry, 1.0 = deepest notches)
+    pub depth: Shared,
+    /// Feedback from the cascade output to its input
+    pub feedback: Shared,
+    /// Allpass state per channel: (x1, y1) for each stage
+    stages: [Vec<(f32, f32)>; 2],
+    last_wet: [f32; 2],
+    phase: f32,
+    sample_rate: f32,
+}
 
-impl EffectBuilder for PhaserBuilder {
-    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
-        let _rate = params.get("rate").copied().unwrap_or(0.5);
-        let depth = params.get("depth").copied().unwrap_or(0.5);
-        let _feedback = params.get("feedback").copied().unwrap_or(0.5);
-        let _stages = params.get("stages").copied().unwrap_or(4.0) as usize;
+impl Phaser {
+    /// Create a new phaser with `stages` allpass sections (clamped to 2..=12)
+    pub fn new(rate: f32, depth: f32, feedback: f32, stages: usize) -> Self {
+        let stages = stages.clamp(2, 12);
+        Self {
+            rate: shared(rate),
+            depth: shared(depth),
+            feedback: shared(feedback),
+            stages: [vec![(0.0, 0.0); stages], vec![(0.0, 0.0); stages]],
+            last_wet: [0.0; 2],
+            phase: 0.0,
+            sample_rate: 48000.0,
+        }
+    }
 
-        // Phaser uses cascaded allpass filters
-        // Simplified version: just apply allpass stages directly
-        // The allpass filters create phase shifts that produce the phasing effect
+    /// Number of allpass stages
+    pub fn num_stages(&self) -> usize {
+        self.stages[0].len()
+    }
 
-        let base_delay = 1.0 + (depth * 3.0);
+    /// First-order allpass coefficient for a break frequency
+    #[inline]
+    fn coefficient(&self, freq: f32) -> f32 {
+        let t = (std::f32::consts::PI * freq / self.sample_rate).tan();
+        (t - 1.0) / (t + 1.0)
+    }
 
-        // Build 4-stage allpass chain for left channel
-        let left = pass()
-            >> allpole_delay(base_delay)
-            >> allpole_delay(base_delay * 1.5)
-            >> allpole_delay(base_delay * 2.0)
-            >> allpole_delay(base_delay * 2.5);
+    /// LFO position (0..1) mapped exponentially onto the sweep range
+    #[inline]
+    fn sweep_freq(phase: f32) -> f32 {
+        let lfo = 0.5 - 0.5 * (std::f32::consts::TAU * phase).cos();
+        PHASER_MIN_FREQ * (PHASER_MAX_FREQ / PHASER_MIN_FREQ).powf(lfo)
+    }
 
-        // Build 4-stage allpass chain for right channel
-        let right = pass()
-            >> allpole_delay(base_delay)
-            >> allpole_delay(base_delay * 1.5)
-            >> allpole_delay(base_delay * 2.0)
-            >> allpole_delay(base_delay * 2.5);
+    #[inline]
+    fn process_channel(&mut self, channel: usize, input: f32, coeff: f32) -> f32 {
+        let feedback = self.feedback.value().clamp(-0.95, 0.95);
+        let mut x = input + feedback * self.last_wet[channel];
+        for (x1, y1) in self.stages[channel].iter_mut() {
+            let y = coeff * x + *x1 - coeff * *y1;
+            *x1 = x;
+            *y1 = y;
+            x = y;
+        }
+        self.last_wet[channel] = x;
+
+        let depth = self.depth.value().clamp(0.0, 1.0);
+        input * (1.0 - 0.5 * depth) + x * 0.5 * depth
+    }
 
-        (Box::new(left | right), EffectControls::new())
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let coeff_l = self.coefficient(Self::sweep_freq(self.phase));
+        let coeff_r = self.coefficient(Self::sweep_freq(self.phase + 0.25));
+
+        self.phase += self.rate.value().max(0.0) / self.sample_rate;
+        self.phase -= self.phase.floor();
+
+        (
+            self.process_channel(0, left, coeff_l),
+            self.process_channel(1, right, coeff_r),
+        )
+    }
+}
+
+impl AudioUnit for Phaser {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        for channel in &mut self.stages {
+            channel.iter_mut().for_each(|s| *s = (0.0, 0.0));
+        }
+        self.last_wet = [0.0; 2];
+        self.phase = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"phaser";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Phaser effect
+pub struct PhaserBuilder;
+
+impl EffectBuilder for PhaserBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let rate = params.get("rate").copied().unwrap_or(0.5);
+        let depth = params.get("depth").copied().unwrap_or(0.5);
+        let feedback = params.get("feedback").copied().unwrap_or(0.5);
+        // Stage count changes the graph, so it is fixed at build time
+        let stages = params.get("stages").copied().unwrap_or(4.0).round() as usize;
+
+        let phaser = Phaser::new(rate, depth, feedback, stages);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("rate".to_string(), phaser.rate.clone());
+        controls
+            .params
+            .insert("depth".to_string(), phaser.depth.clone());
+        controls
+            .params
+            .insert("feedback".to_string(), phaser.feedback.clone());
+
+        (Box::new(phaser), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
@@ -123,6 +261,7 @@ impl EffectBuilder for PhaserBuilder {
             .with_param("depth", 0.5, 0.0, 1.0)
             .with_param("feedback", 0.5, 0.0, 0.95)
             .with_param("stages", 4.0, 2.0, 12.0)
+            .with_tag("modulation")
     }
 }
 
@@ -166,6 +305,7 @@ impl EffectBuilder for VibratoBuilder {
             .with_param("rate", 5.0, 0.5, 20.0)
             .with_param("depth", 0.5, 0.0, 1.0)
             .with_latency(441) // ~10ms at 44.1kHz
+            .with_tag("modulation")
     }
 }
 
@@ -177,3 +317,46 @@ pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("phaser", Arc::new(PhaserBuilder));
     registry.register("vibrato", Arc::new(VibratoBuilder));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phaser_notch_moves() {
+        let params = HashMap::from([
+            ("rate".to_string(), 1.0),
+            ("depth".to_string(), 1.0),
+            ("feedback".to_string(), 0.0),
+            ("stages".to_string(), 4.0),
+        ]);
+        let (mut phaser, _) = PhaserBuilder.build(&params);
+        phaser.set_sample_rate(48000.0);
+
+        // Probe at 1 kHz over one full sweep, measuring RMS in 10 ms windows
+        let window = 480;
+        let mut levels = Vec::new();
+        let mut sum_sq = 0.0f32;
+        for i in 0..48000 {
+            let x = (std::f32::consts::TAU * 1000.0 * i as f32 / 48000.0).sin();
+            let (l, _) = phaser.filter_stereo(x, x);
+            sum_sq += l * l;
+            if (i + 1) % window == 0 {
+                levels.push((sum_sq / window as f32).sqrt());
+                sum_sq = 0.0;
+            }
+        }
+
+        let max = levels.iter().cloned().fold(0.0f32, f32::max);
+        let min = levels.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(max > 0.5, "max level {max}");
+        assert!(max > min * 3.0, "notch did not sweep: min {min}, max {max}");
+    }
+
+    #[test]
+    fn test_phaser_stage_count() {
+        assert_eq!(Phaser::new(0.5, 0.5, 0.0, 1).num_stages(), 2);
+        assert_eq!(Phaser::new(0.5, 0.5, 0.0, 8).num_stages(), 8);
+        assert_eq!(Phaser::new(0.5, 0.5, 0.0, 20).num_stages(), 12);
+    }
+}
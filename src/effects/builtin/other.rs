@@ -1,6 +1,8 @@
-//! Other/special effects (slicer, wobble, ring_mod, octaver)
+//! Other/special effects (slicer, wobble, autowah, ring_mod, octaver)
 
 use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
+use super::super::sidechain::sidechain_peak;
+use crate::params::{moog_mapping, q_mapping, resonance_param};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -48,6 +50,7 @@ impl EffectBuilder for SlicerBuilder {
             .with_param("rate", 8.0, 0.1, 100.0)
             .with_param("phase", 0.0, 0.0, 1.0)
             .with_param("width", 0.5, 0.0, 1.0)
+            .with_tag("modulation")
     }
 }
 
@@ -59,7 +62,8 @@ impl EffectBuilder for WobbleBuilder {
         let rate = params.get("rate").copied().unwrap_or(4.0); // wobble rate in Hz
         let min_cutoff = params.get("min_cutoff").copied().unwrap_or(200.0);
         let max_cutoff = params.get("max_cutoff").copied().unwrap_or(2000.0);
-        let res = params.get("res").copied().unwrap_or(0.3);
+        let (res, raw_res) = resonance_param(params, 0.3);
+        let res = moog_mapping(raw_res)(res);
 
         // Create LFO for cutoff modulation
         let lfo_left = sine_hz(rate).clone();
@@ -85,6 +89,217 @@ impl EffectBuilder for WobbleBuilder {
             .with_param("min_cutoff", 200.0, 50.0, 5000.0)
             .with_param("max_cutoff", 2000.0, 100.0, 10000.0)
             .with_param("res", 0.3, 0.0, 1.0)
+            .with_tag("filter")
+    }
+}
+
+/// Envelope-following resonant lowpass (auto-wah)
+///
+/// A stereo-linked peak follower scaled by `sensitivity` sweeps the cutoff
+/// exponentially from `min_cutoff` (silence) to `max_cutoff` (follower at
+/// `1 / sensitivity` or above). The filter is a state-variable lowpass per
+/// channel. `cutoff` is written every sample for display.
+#[derive(Clone)]
+pub struct AutoWah {
+    /// Cutoff with no input, in Hz
+    pub min_cutoff: Shared,
+    /// Cutoff at full envelope, in Hz
+    pub max_cutoff: Shared,
+    /// Envelope gain before mapping to cutoff
+    pub sensitivity: Shared,
+    /// Resonance (0.0-1.0, or Q with `res_raw`)
+    pub res: Shared,
+    /// Envelope attack time in seconds
+    pub attack: Shared,
+    /// Envelope release time in seconds
+    pub release: Shared,
+    /// Current cutoff in Hz (read-only)
+    pub cutoff: Shared,
+    q_map: fn(f32) -> f32,
+    envelope: f32,
+    /// Filter integrator state per channel
+    state: [[f32; 2]; 2],
+    sample_rate: f32,
+}
+
+impl AutoWah {
+    /// Create a new auto-wah; `raw_res` makes `res` a Q value
+    pub fn new(
+        min_cutoff: f32,
+        max_cutoff: f32,
+        sensitivity: f32,
+        res: f32,
+        raw_res: bool,
+        attack_sec: f32,
+        release_sec: f32,
+    ) -> Self {
+        Self {
+            min_cutoff: shared(min_cutoff),
+            max_cutoff: shared(max_cutoff),
+            sensitivity: shared(sensitivity),
+            res: shared(res),
+            attack: shared(attack_sec),
+            release: shared(release_sec),
+            cutoff: shared(min_cutoff),
+            q_map: q_mapping(raw_res),
+            envelope: 0.0,
+            state: [[0.0; 2]; 2],
+            sample_rate: 48000.0,
+        }
+    }
+
+    /// Follow the envelope and return the cutoff for this sample
+    #[inline]
+    fn next_cutoff(&mut self, level: f32) -> f32 {
+        let time = if level > self.envelope {
+            self.attack.value()
+        } else {
+            self.release.value()
+        };
+        let coeff = (-1.0 / (time.max(1.0e-5) * self.sample_rate)).exp();
+        self.envelope = level + coeff * (self.envelope - level);
+
+        let amount = (self.envelope * self.sensitivity.value()).clamp(0.0, 1.0);
+        let min = self.min_cutoff.value().max(1.0);
+        let max = self.max_cutoff.value().max(min);
+        let cutoff = (min * (max / min).powf(amount)).min(self.sample_rate * 0.45);
+        self.cutoff.set_value(cutoff);
+        cutoff
+    }
+
+    /// Process one stereo frame
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let cutoff = self.next_cutoff(sidechain_peak(left, right));
+
+        // Trapezoidal state-variable filter (Simper)
+        let g = (std::f32::consts::PI * cutoff / self.sample_rate).tan();
+        let k = 1.0 / (self.q_map)(self.res.value()).max(0.1);
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let mut out = [left, right];
+        for (x, [ic1, ic2]) in out.iter_mut().zip(self.state.iter_mut()) {
+            let v3 = *x - *ic2;
+            let v1 = a1 * *ic1 + a2 * v3;
+            let v2 = *ic2 + a2 * *ic1 + a3 * v3;
+            *ic1 = 2.0 * v1 - *ic1;
+            *ic2 = 2.0 * v2 - *ic2;
+            *x = v2;
+        }
+        (out[0], out[1])
+    }
+}
+
+impl AudioUnit for AutoWah {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.state = [[0.0; 2]; 2];
+        self.cutoff.set_value(self.min_cutoff.value());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"autowah";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Auto-wah - envelope-following filter sweep
+pub struct AutoWahBuilder;
+
+impl EffectBuilder for AutoWahBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let min_cutoff = params.get("min_cutoff").copied().unwrap_or(300.0);
+        let max_cutoff = params.get("max_cutoff").copied().unwrap_or(3000.0);
+        let sensitivity = params.get("sensitivity").copied().unwrap_or(2.0);
+        let (res, raw_res) = resonance_param(params, 0.5);
+        let attack = params.get("attack").copied().unwrap_or(0.005);
+        let release = params.get("release").copied().unwrap_or(0.1);
+
+        let wah = AutoWah::new(
+            min_cutoff,
+            max_cutoff,
+            sensitivity,
+            res,
+            raw_res,
+            attack,
+            release,
+        );
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("min_cutoff".to_string(), wah.min_cutoff.clone());
+        controls
+            .params
+            .insert("max_cutoff".to_string(), wah.max_cutoff.clone());
+        controls
+            .params
+            .insert("sensitivity".to_string(), wah.sensitivity.clone());
+        let res_name = if raw_res { "res_raw" } else { "res" };
+        controls
+            .params
+            .insert(res_name.to_string(), wah.res.clone());
+        controls
+            .params
+            .insert("attack".to_string(), wah.attack.clone());
+        controls
+            .params
+            .insert("release".to_string(), wah.release.clone());
+        controls
+            .meters
+            .insert("cutoff".to_string(), wah.cutoff.clone());
+
+        (Box::new(wah), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("autowah", "Envelope-following filter (auto-wah)")
+            .with_param("min_cutoff", 300.0, 50.0, 5000.0)
+            .with_param("max_cutoff", 3000.0, 100.0, 10000.0)
+            .with_param("sensitivity", 2.0, 0.1, 10.0)
+            .with_param("res", 0.5, 0.0, 1.0)
+            .with_param("attack", 0.005, 0.001, 0.1)
+            .with_param("release", 0.1, 0.01, 1.0)
+            .with_tag("filter")
     }
 }
 
@@ -114,6 +329,7 @@ impl EffectBuilder for RingModBuilder {
         EffectMetadata::new("ring_mod", "Ring modulator for metallic tones")
             .with_param("freq", 440.0, 20.0, 5000.0)
             .with_param("mix", 0.5, 0.0, 1.0)
+            .with_tag("modulation")
     }
 }
 
@@ -170,6 +386,7 @@ impl EffectBuilder for OctaverBuilder {
         EffectMetadata::new("octaver", "Adds octaves above or below")
             .with_param("octave", -1.0, -2.0, 2.0)
             .with_param("mix", 0.5, 0.0, 1.0)
+            .with_tag("pitch")
     }
 }
 
@@ -177,6 +394,34 @@ impl EffectBuilder for OctaverBuilder {
 pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("slicer", Arc::new(SlicerBuilder));
     registry.register("wobble", Arc::new(WobbleBuilder));
+    registry.register("autowah", Arc::new(AutoWahBuilder));
     registry.register("ring_mod", Arc::new(RingModBuilder));
     registry.register("octaver", Arc::new(OctaverBuilder));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cutoff reached after 0.1 s of a 220 Hz sine at `amplitude`
+    fn settled_cutoff(amplitude: f32) -> f32 {
+        let (mut wah, controls) = AutoWahBuilder.build(&HashMap::new());
+        for i in 0..4800 {
+            let x = amplitude * (i as f32 * std::f32::consts::TAU * 220.0 / 48000.0).sin();
+            let (l, r) = wah.filter_stereo(x, x);
+            assert!(l.is_finite() && r.is_finite());
+        }
+        controls.meter("cutoff").unwrap()
+    }
+
+    #[test]
+    fn test_autowah_louder_input_opens_filter() {
+        let silent = settled_cutoff(0.0);
+        let quiet = settled_cutoff(0.1);
+        let loud = settled_cutoff(0.4);
+        assert_eq!(silent, 300.0);
+        assert!(quiet > silent);
+        assert!(loud > quiet);
+        assert!(loud <= 3000.0);
+    }
+}
@@ -1,6 +1,6 @@
-//! Spatial effects (pan)
+//! Spatial effects (pan, stereo width)
 
-use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata};
+use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata, LATENCY_CONTROL};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -27,6 +27,134 @@ impl EffectBuilder for PanBuilder {
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("pan", "Pan (stereo positioning)")
             .with_param("pan", 0.0, -1.0, 1.0)
+            .with_tag("spatial")
+    }
+}
+
+/// Mid/side stereo width with optional bass mono-ing
+///
+/// L/R is encoded to mid/side, the side signal is scaled by `width` and
+/// decoded back. When `mono_below` is above 0 Hz, side content below that
+/// frequency is removed (a 12 dB/oct lowpass on the side channel is
+/// subtracted from it), so the low end stays mono.
+#[derive(Clone)]
+pub struct StereoWidth {
+    /// Side gain (0.0 = mono, 1.0 = unchanged, 2.0 = wide)
+    pub width: Shared,
+    /// Crossover in Hz below which the signal is summed to mono (0 = off)
+    pub mono_below: Shared,
+    /// Cutoff the filter coefficients were computed for
+    cutoff: f32,
+    /// Lowpass biquad coefficients (b0, b1, b2, a1, a2)
+    coeffs: [f32; 5],
+    /// Lowpass biquad state (x1, x2, y1, y2)
+    state: [f32; 4],
+    sample_rate: f32,
+}
+
+impl StereoWidth {
+    /// Create a new stereo width processor
+    pub fn new(width: f32, mono_below: f32) -> Self {
+        Self {
+            width: shared(width),
+            mono_below: shared(mono_below),
+            cutoff: 0.0,
+            coeffs: [0.0; 5],
+            state: [0.0; 4],
+            sample_rate: 48000.0,
+        }
+    }
+
+    /// Recompute the side-channel lowpass (Butterworth, RBJ cookbook)
+    fn update_coeffs(&mut self, cutoff: f32) {
+        self.cutoff = cutoff;
+        let freq = cutoff.min(self.sample_rate * 0.45);
+        let omega = std::f32::consts::TAU * freq / self.sample_rate;
+        let alpha = omega.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = omega.cos();
+        let a0 = 1.0 + alpha;
+        let b1 = (1.0 - cos) / a0;
+        self.coeffs = [b1 * 0.5, b1, b1 * 0.5, -2.0 * cos / a0, (1.0 - alpha) / a0];
+    }
+
+    /// Lowpass one side-channel sample
+    #[inline]
+    fn lowpass(&mut self, x: f32) -> f32 {
+        let [b0, b1, b2, a1, a2] = self.coeffs;
+        let [x1, x2, y1, y2] = self.state;
+        let y = b0 * x + b1 * x1 + b2 * x2 - a1 * y1 - a2 * y2;
+        self.state = [x, x1, y, y1];
+        y
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let mid = (left + right) * 0.5;
+        let mut side = (left - right) * 0.5;
+
+        let cutoff = self.mono_below.value();
+        if cutoff > 0.0 {
+            if cutoff != self.cutoff {
+                self.update_coeffs(cutoff);
+            }
+            side -= self.lowpass(side);
+        } else if self.cutoff != 0.0 {
+            self.cutoff = 0.0;
+            self.state = [0.0; 4];
+        }
+
+        let side = side * self.width.value().max(0.0);
+        (mid + side, mid - side)
+    }
+}
+
+impl AudioUnit for StereoWidth {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.state = [0.0; 4];
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+        // Force a coefficient update on the next frame
+        self.cutoff = -1.0;
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (left, right) = self.process_frame(input[0], input[1]);
+        output[0] = left;
+        output[1] = right;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (left, right) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, left);
+            output.set_f32(1, i, right);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"stereo_width";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
     }
 }
 
@@ -36,56 +164,207 @@ pub struct StereoWidenerBuilder;
 impl EffectBuilder for StereoWidenerBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
         let width = params.get("width").copied().unwrap_or(1.0); // 0.0 = mono, 1.0 = normal, 2.0 = wide
+        let mono_below = params.get("mono_below").copied().unwrap_or(0.0); // Hz, 0 = off
+
+        let widener = StereoWidth::new(width, mono_below);
 
-        let width_shared = shared(width);
         let mut controls = EffectControls::new();
         controls
             .params
-            .insert("width".to_string(), width_shared.clone());
+            .insert("width".to_string(), widener.width.clone());
+        controls
+            .params
+            .insert("mono_below".to_string(), widener.mono_below.clone());
+
+        (Box::new(widener), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("stereo_widener", "Stereo Widener (adjusts stereo width)")
+            .with_param("width", 1.0, 0.0, 2.0)
+            .with_param("mono_below", 0.0, 0.0, 500.0)
+            .with_tag("spatial")
+    }
+}
+
+/// Longest Haas delay in milliseconds
+const MAX_HAAS_DELAY_MS: f32 = 40.0;
+/// Longest Haas delay with `mono_safe` on, in milliseconds
+///
+/// Summed to mono, a delay of `t` puts comb notches at odd multiples of
+/// `1 / 2t`; at 1 ms the first one sits at 500 Hz, above most fundamentals,
+/// instead of in the low mids.
+const MONO_SAFE_MAX_DELAY_MS: f32 = 1.0;
+
+/// Haas (precedence effect) widener
+///
+/// One channel is delayed by `delay_ms · |balance|`: positive `balance`
+/// delays the left channel so the right leads, negative delays the right.
+/// With `mono_safe` on the delay is capped at 1 ms to keep comb filtering
+/// out of the low mids when the output is summed to mono.
+///
+/// Like the limiter's lookahead, the delay is fixed when the widener is
+/// built, so `latency` always matches what is heard. It reports the delay
+/// of the lagging channel on purpose: the leading channel is not delayed
+/// at all, but latency compensation lines the rest of the chain up with
+/// the later of the two arrivals.
+#[derive(Clone)]
+pub struct Haas {
+    /// Delay of the lagging channel in samples (read-only)
+    pub latency: Shared,
+    /// Delay of the lagging channel in milliseconds (0-40)
+    delay_ms: f32,
+    /// Leading side and fraction of the delay applied (-1 = left leads, 1 = right leads)
+    balance: f32,
+    /// Cap the delay for mono compatibility
+    mono_safe: bool,
+    /// Delay of the lagging channel in samples
+    delay: f32,
+    /// Input history, one (left, right) frame per sample
+    buffer: Vec<(f32, f32)>,
+    /// Write position in `buffer`
+    pos: usize,
+    sample_rate: f32,
+}
+
+impl Haas {
+    /// Create a new Haas widener
+    pub fn new(delay_ms: f32, balance: f32, mono_safe: bool) -> Self {
+        let mut haas = Self {
+            latency: shared(0.0),
+            delay_ms,
+            balance: balance.clamp(-1.0, 1.0),
+            mono_safe,
+            delay: 0.0,
+            buffer: Vec::new(),
+            pos: 0,
+            sample_rate: 48000.0,
+        };
+        haas.allocate();
+        haas
+    }
+
+    /// Delay applied to the lagging channel in milliseconds
+    pub fn effective_delay_ms(&self) -> f32 {
+        let max = if self.mono_safe {
+            MONO_SAFE_MAX_DELAY_MS
+        } else {
+            MAX_HAAS_DELAY_MS
+        };
+        self.delay_ms.clamp(0.0, max) * self.balance.abs()
+    }
+
+    /// Size the history for the current sample rate and clear it
+    fn allocate(&mut self) {
+        self.delay = self.effective_delay_ms() * 0.001 * self.sample_rate;
+        self.buffer = vec![(0.0, 0.0); self.delay.ceil() as usize + 2];
+        self.pos = 0;
+        self.latency.set_value(self.delay.round());
+    }
+
+    /// Frame written `delay` samples ago, linearly interpolated
+    #[inline]
+    fn read(&self, delay: f32) -> (f32, f32) {
+        let len = self.buffer.len();
+        let whole = delay.floor() as usize;
+        let frac = delay - whole as f32;
+        let a = self.buffer[(self.pos + len - whole) % len];
+        let b = self.buffer[(self.pos + len - whole - 1) % len];
+        (a.0 + (b.0 - a.0) * frac, a.1 + (b.1 - a.1) * frac)
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        self.buffer[self.pos] = (left, right);
+        let delayed = self.read(self.delay);
+        self.pos = (self.pos + 1) % self.buffer.len();
+
+        if self.balance > 0.0 {
+            (delayed.0, right)
+        } else {
+            (left, delayed.1)
+        }
+    }
+}
+
+impl AudioUnit for Haas {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
 
-        // Mid-Side (M/S) stereo widening technique:
-        // 1. Convert L/R to Mid/Side: Mid = (L+R)/2, Side = (L-R)/2
-        // 2. Scale Side by width factor
-        // 3. Convert back to L/R: L = Mid + Side, R = Mid - Side
-        //
-        // Width values:
-        // 0.0 = mono (no side signal)
-        // 1.0 = normal stereo (unchanged)
-        // 2.0 = extra wide (side doubled)
+    fn reset(&mut self) {
+        self.buffer.iter_mut().for_each(|frame| *frame = (0.0, 0.0));
+        self.pos = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+        self.allocate();
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (left, right) = self.process_frame(input[0], input[1]);
+        output[0] = left;
+        output[1] = right;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (left, right) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, left);
+            output.set_f32(1, i, right);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
 
-        use fundsp::hacker32::*;
-        use fundsp::signal::Routing;
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"haas";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
 
-        // Create the width processing graph
-        // Input is stereo (2 channels)
-        let effect = An(MultiPass::<U2>::new())
-            >> An(Map::new(
-                move |input: &Frame<f32, U2>| {
-                    let left = input[0];
-                    let right = input[1];
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>() + self.buffer.capacity() * std::mem::size_of::<(f32, f32)>()
+    }
+}
 
-                    // Convert to Mid/Side
-                    let mid = (left + right) * 0.5;
-                    let side = (left - right) * 0.5;
+/// Haas widener - short inter-channel delay
+pub struct HaasBuilder;
 
-                    // Scale side by width
-                    let scaled_side = side * width;
+impl EffectBuilder for HaasBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let delay_ms = params.get("delay_ms").copied().unwrap_or(12.0);
+        let balance = params.get("balance").copied().unwrap_or(-1.0); // -1 = left leads, 1 = right leads
+        let mono_safe = params.get("mono_safe").copied().unwrap_or(0.0) >= 0.5;
 
-                    // Convert back to L/R
-                    let new_left = mid + scaled_side;
-                    let new_right = mid - scaled_side;
+        let haas = Haas::new(delay_ms, balance, mono_safe);
 
-                    Frame::<f32, U2>::from([new_left, new_right])
-                },
-                Routing::Arbitrary(0.0),
-            ));
+        // All three parameters set the delay, so they are build-time only
+        let mut controls = EffectControls::new();
+        controls
+            .meters
+            .insert(LATENCY_CONTROL.to_string(), haas.latency.clone());
 
-        (Box::new(effect), controls)
+        (Box::new(haas), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
-        EffectMetadata::new("stereo_widener", "Stereo Widener (adjusts stereo width)")
-            .with_param("width", 1.0, 0.0, 2.0)
+        // Latency of the default delay at 48 kHz; instances report their own
+        EffectMetadata::new("haas", "Haas widener (inter-channel delay)")
+            .with_param("delay_ms", 12.0, 0.0, MAX_HAAS_DELAY_MS)
+            .with_param("balance", -1.0, -1.0, 1.0)
+            .with_param("mono_safe", 0.0, 0.0, 1.0)
+            .with_latency(576)
+            .with_tag("spatial")
     }
 }
 
@@ -99,4 +378,102 @@ pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("stereo_widener", Arc::new(StereoWidenerBuilder));
     registry.register("stereo_width", Arc::new(StereoWidenerBuilder)); // alias
     registry.register("width", Arc::new(StereoWidenerBuilder)); // alias
+    registry.register("haas", Arc::new(HaasBuilder));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widener(params: &[(&str, f32)]) -> (Box<dyn AudioUnit>, EffectControls) {
+        let params: HashMap<String, f32> =
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        StereoWidenerBuilder.build(&params)
+    }
+
+    #[test]
+    fn test_width_zero_is_mono() {
+        let (mut unit, controls) = widener(&[]);
+
+        // Unity width leaves a panned signal untouched
+        let (l, r) = unit.filter_stereo(0.8, 0.2);
+        assert!((l - 0.8).abs() < 1e-6 && (r - 0.2).abs() < 1e-6);
+
+        // Width is live
+        controls.set("width", 0.0);
+        for i in 0..256 {
+            let x = (i as f32 * 0.05).sin();
+            let (l, r) = unit.filter_stereo(x * 0.9, x * 0.1);
+            assert_eq!(l, r);
+        }
+    }
+
+    /// Sample index at which each output channel reproduces an impulse
+    fn impulse_arrivals(params: &[(&str, f32)]) -> (usize, usize, f32) {
+        let params: HashMap<String, f32> =
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        let (mut unit, controls) = HaasBuilder.build(&params);
+        let (mut left, mut right) = (None, None);
+        for i in 0..4800 {
+            let x = if i == 0 { 1.0 } else { 0.0 };
+            let (l, r) = unit.filter_stereo(x, x);
+            if l == 1.0 {
+                left.get_or_insert(i);
+            }
+            if r == 1.0 {
+                right.get_or_insert(i);
+            }
+        }
+        let latency = controls.meter(LATENCY_CONTROL).unwrap();
+        (left.unwrap(), right.unwrap(), latency)
+    }
+
+    #[test]
+    fn test_haas_delays_lagging_side() {
+        assert_eq!(impulse_arrivals(&[("delay_ms", 10.0)]), (0, 480, 480.0));
+        assert_eq!(
+            impulse_arrivals(&[("delay_ms", 10.0), ("balance", 1.0)]),
+            (480, 0, 480.0)
+        );
+        assert_eq!(
+            impulse_arrivals(&[("delay_ms", 10.0), ("balance", -0.5)]),
+            (0, 240, 240.0)
+        );
+    }
+
+    #[test]
+    fn test_haas_mono_safe_caps_delay() {
+        assert_eq!(
+            impulse_arrivals(&[("delay_ms", 10.0), ("mono_safe", 1.0)]),
+            (0, 48, 48.0)
+        );
+
+        let mut chain = crate::effects::EffectChain::with_registry(
+            crate::effects::EffectRegistry::with_builtin(),
+        );
+        chain.add("haas", &[("delay_ms", 5.0)]).unwrap();
+        assert_eq!(chain.effect_latency(0), Some(240));
+    }
+
+    #[test]
+    fn test_mono_below_removes_low_side() {
+        let (mut unit, _) = widener(&[("width", 1.5), ("mono_below", 150.0)]);
+
+        // Side-only energy (L = -R) after the filter has settled
+        let side_energy = |unit: &mut dyn AudioUnit, freq: f32| {
+            (0..9600)
+                .map(|i| {
+                    let x = (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin() * 0.5;
+                    let (l, r) = unit.filter_stereo(x, -x);
+                    (l - r) * (l - r)
+                })
+                .skip(4800)
+                .sum::<f32>()
+        };
+
+        let low = side_energy(unit.as_mut(), 30.0);
+        unit.reset();
+        let high = side_energy(unit.as_mut(), 3000.0);
+        assert!(low < high * 0.05);
+    }
 }
@@ -6,6 +6,9 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Reverb effect
+///
+/// Wraps FunDSP's `reverb4_stereo`, which has no feedback saturation; the
+/// `room`, `hall`, `plate` and `stereo_reverb` effects offer it.
 pub struct ReverbBuilder;
 
 impl EffectBuilder for ReverbBuilder {
@@ -21,24 +24,109 @@ impl EffectBuilder for ReverbBuilder {
         EffectMetadata::new("reverb", "Reverb effect")
             .with_param("room", 0.5, 0.0, 1.0)
             .with_param("time", 1.0, 0.1, 10.0)
+            .with_tag("reverb")
     }
 }
 
+/// Defaults that give a reverb preset its character
+///
+/// The presets run on [`StereoReverb`]; `mix`, the decay time, `damping`
+/// and `pre_delay` can be overridden at build time.
+struct ReverbPreset {
+    /// Name of the decay time parameter
+    time_param: &'static str,
+    room: f32,
+    time: f32,
+    damping: f32,
+    pre_delay: f32,
+    cross_feedback: f32,
+    mix: f32,
+}
+
+impl ReverbPreset {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let param = |name: &str, default: f32| params.get(name).copied().unwrap_or(default);
+        let reverb = StereoReverb::new(
+            self.room,
+            param(self.time_param, self.time),
+            param("damping", self.damping),
+            self.cross_feedback,
+            param("mix", self.mix),
+        )
+        .with_pre_delay(param("pre_delay", self.pre_delay))
+        .with_saturation(param("saturation", 0.0));
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert(self.time_param.to_string(), reverb.time.clone());
+        controls
+            .params
+            .insert("damping".to_string(), reverb.damping.clone());
+        controls
+            .params
+            .insert("mix".to_string(), reverb.mix.clone());
+        controls
+            .params
+            .insert("saturation".to_string(), reverb.saturation.clone());
+
+        (Box::new(reverb), controls)
+    }
+
+    fn metadata(&self, name: &str, description: &str, max_time: f32) -> EffectMetadata {
+        EffectMetadata::new(name, description)
+            .with_param("mix", self.mix, 0.0, 1.0)
+            .with_param(self.time_param, self.time, 0.1, max_time)
+            .with_param("damping", self.damping, 0.0, 1.0)
+            .with_param("pre_delay", self.pre_delay, 0.0, MAX_PRE_DELAY)
+            .with_param("saturation", 0.0, 0.0, 1.0)
+            .with_tag("reverb")
+    }
+}
+
+/// Small room: short, fairly dark decay right behind the source
+const ROOM: ReverbPreset = ReverbPreset {
+    time_param: "time",
+    room: 0.3,
+    time: 0.5,
+    damping: 0.6,
+    pre_delay: 0.005,
+    cross_feedback: 0.1,
+    mix: 0.3,
+};
+
+/// Large hall: long, wide decay that starts well after the source
+const HALL: ReverbPreset = ReverbPreset {
+    time_param: "time",
+    room: 0.8,
+    time: 3.0,
+    damping: 0.4,
+    pre_delay: 0.03,
+    cross_feedback: 0.3,
+    mix: 0.4,
+};
+
+/// Plate: dense, bright decay with no pre-delay
+const PLATE: ReverbPreset = ReverbPreset {
+    time_param: "decay",
+    room: 0.5,
+    time: 2.0,
+    damping: 0.1,
+    pre_delay: 0.0,
+    cross_feedback: 0.5,
+    mix: 0.35,
+};
+
 /// Room reverb - small room preset
 pub struct RoomReverbBuilder;
 
 impl EffectBuilder for RoomReverbBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
-        let mix = params.get("mix").copied().unwrap_or(0.3);
-        // Small room: short time, small size
-        // Use & operator to branch input to dry/wet paths and sum outputs
-        let effect = ((pass() | pass()) * (1.0 - mix)) & (reverb4_stereo(0.3, 0.5) * mix);
-        (Box::new(effect), EffectControls::new())
+        ROOM.build(params)
     }
 
     fn metadata(&self) -> EffectMetadata {
-        EffectMetadata::new("room", "Small room reverb")
-            .with_param("mix", 0.3, 0.0, 1.0)
+        ROOM.metadata("room", "Small room reverb", 2.0)
     }
 }
 
@@ -47,16 +135,11 @@ pub struct HallReverbBuilder;
 
 impl EffectBuilder for HallReverbBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
-        let mix = params.get("mix").copied().unwrap_or(0.4);
-        // Large hall: long time, large size
-        // Use & operator to branch input to dry/wet paths and sum outputs
-        let effect = ((pass() | pass()) * (1.0 - mix)) & (reverb4_stereo(0.8, 3.0) * mix);
-        (Box::new(effect), EffectControls::new())
+        HALL.build(params)
     }
 
     fn metadata(&self) -> EffectMetadata {
-        EffectMetadata::new("hall", "Large hall reverb")
-            .with_param("mix", 0.4, 0.0, 1.0)
+        HALL.metadata("hall", "Large hall reverb", 10.0)
     }
 }
 
@@ -65,18 +148,482 @@ pub struct PlateReverbBuilder;
 
 impl EffectBuilder for PlateReverbBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        PLATE.build(params)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        PLATE.metadata("plate", "Plate reverb (bright, metallic)", 5.0)
+    }
+}
+
+/// Comb filter delay lengths in samples at 44.1kHz (Freeverb tuning)
+const COMB_TUNING: [usize; 4] = [1116, 1188, 1277, 1356];
+/// Allpass delay lengths in samples at 44.1kHz
+const ALLPASS_TUNING: [usize; 2] = [556, 441];
+/// Extra delay on the right network to decorrelate the channels
+const STEREO_SPREAD: usize = 23;
+/// Input attenuation into the comb bank
+const REVERB_INPUT_GAIN: f32 = 0.03;
+/// Output gain of the wet signal
+const REVERB_WET_GAIN: f32 = 3.0;
+/// Longest pre-delay supported by [`StereoReverb`] in seconds
+const MAX_PRE_DELAY: f32 = 0.2;
+
+/// Feedback comb filter with a damping lowpass in the loop
+#[derive(Clone)]
+struct Comb {
+    buffer: Vec<f32>,
+    pos: usize,
+    filter: f32,
+    gain: f32,
+}
+
+impl Comb {
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.0; length.max(1)],
+            pos: 0,
+            filter: 0.0,
+            gain: 0.0,
+        }
+    }
+
+    /// Current output of the delay line
+    #[inline]
+    fn output(&self) -> f32 {
+        self.buffer[self.pos]
+    }
+
+    /// Damped version of `output`, fed back into the loop
+    #[inline]
+    fn damp(&mut self, output: f32, damping: f32) -> f32 {
+        self.filter = output * (1.0 - damping) + self.filter * damping;
+        self.filter
+    }
+
+    #[inline]
+    fn write(&mut self, value: f32) {
+        self.buffer[self.pos] = value;
+        self.pos = (self.pos + 1) % self.buffer.len();
+    }
+
+    /// Set the loop gain for a decay time (RT60) in seconds
+    fn set_decay(&mut self, time: f32, sample_rate: f32) {
+        let length = self.buffer.len() as f32;
+        self.gain = 10.0_f32.powf(-3.0 * length / (time.max(0.01) * sample_rate));
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.filter = 0.0;
+    }
+}
+
+/// Schroeder allpass diffuser
+#[derive(Clone)]
+struct Allpass {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Allpass {
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.0; length.max(1)],
+            pos: 0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        let buffered = self.buffer[self.pos];
+        self.buffer[self.pos] = input + buffered * 0.5;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        buffered - input
+    }
+}
+
+/// Stereo reverb with separate left/right networks and cross-channel feedback
+///
+/// Each channel runs its own bank of damped comb filters followed by
+/// allpass diffusers. `cross_feedback` routes part of each comb's feedback
+/// into the opposite channel's comb, so a sound on one side spreads into a
+/// tail that wanders across the stereo field. At 0.0 the channels are fully
+/// independent; at 0.5 each round trip is split evenly between them. An
+/// optional pre-delay holds the input back before it reaches the combs, and
+/// optional saturation soft-clips the comb feedback so loud tails compress
+/// instead of ringing at full level.
+#[derive(Clone)]
+pub struct StereoReverb {
+    /// Decay time (RT60) in seconds
+    pub time: Shared,
+    /// High frequency damping in the feedback loops (0.0 - 1.0)
+    pub damping: Shared,
+    /// Share of the feedback crossing to the other channel (0.0 - 1.0)
+    pub cross_feedback: Shared,
+    /// Dry/wet mix
+    pub mix: Shared,
+    /// Soft saturation in the comb feedback loops (0.0 = clean, 1.0 = full tanh)
+    pub saturation: Shared,
+    /// Room size (scales the delay lengths)
+    room_size: f32,
+    /// Delay before the reverb in seconds
+    pre_delay: f32,
+    /// Pre-delay line, empty when there is no pre-delay
+    pre_delay_buffer: Vec<(f32, f32)>,
+    pre_delay_pos: usize,
+    combs: [Vec<Comb>; 2],
+    allpasses: [Vec<Allpass>; 2],
+    /// Decay time the comb gains were computed for
+    decay_time: f32,
+    sample_rate: f32,
+}
+
+impl StereoReverb {
+    /// Create a new stereo reverb
+    pub fn new(room_size: f32, time: f32, damping: f32, cross_feedback: f32, mix: f32) -> Self {
+        let mut reverb = Self {
+            time: shared(time),
+            damping: shared(damping),
+            cross_feedback: shared(cross_feedback),
+            mix: shared(mix),
+            saturation: shared(0.0),
+            room_size: room_size.clamp(0.0, 1.0),
+            pre_delay: 0.0,
+            pre_delay_buffer: Vec::new(),
+            pre_delay_pos: 0,
+            combs: [Vec::new(), Vec::new()],
+            allpasses: [Vec::new(), Vec::new()],
+            decay_time: 0.0,
+            sample_rate: 48000.0,
+        };
+        reverb.allocate();
+        reverb
+    }
+
+    /// Builder pattern: delay the reverb by `seconds` (up to 0.2s)
+    pub fn with_pre_delay(mut self, seconds: f32) -> Self {
+        self.pre_delay = seconds.clamp(0.0, MAX_PRE_DELAY);
+        self.allocate();
+        self
+    }
+
+    /// Builder pattern: set the comb feedback saturation (0.0 - 1.0)
+    pub fn with_saturation(self, amount: f32) -> Self {
+        self.saturation.set_value(amount.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Delay before the reverb in seconds
+    pub fn pre_delay(&self) -> f32 {
+        self.pre_delay
+    }
+
+    /// Build the delay networks for the current sample rate and room size
+    fn allocate(&mut self) {
+        let pre_delay = (self.pre_delay * self.sample_rate).round() as usize;
+        self.pre_delay_buffer = vec![(0.0, 0.0); pre_delay];
+        self.pre_delay_pos = 0;
+
+        let scale = self.sample_rate / 44100.0;
+        let room = 0.5 + self.room_size;
+        for (channel, spread) in [0, STEREO_SPREAD].into_iter().enumerate() {
+            self.combs[channel] = COMB_TUNING
+                .iter()
+                .map(|&length| Comb::new(((length + spread) as f32 * scale * room) as usize))
+                .collect();
+            self.allpasses[channel] = ALLPASS_TUNING
+                .iter()
+                .map(|&length| Allpass::new(((length + spread) as f32 * scale) as usize))
+                .collect();
+        }
+        // Force the comb gains to be recomputed
+        self.decay_time = 0.0;
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let time = self.time.value();
+        if time != self.decay_time {
+            self.decay_time = time;
+            for comb in self.combs.iter_mut().flatten() {
+                comb.set_decay(time, self.sample_rate);
+            }
+        }
+
+        let cross = self.cross_feedback.value().clamp(0.0, 1.0);
+        let damping = self.damping.value().clamp(0.0, 0.99);
+        let saturation = self.saturation.value().clamp(0.0, 1.0);
+        let (in_l, in_r) = match self.pre_delay_buffer.get_mut(self.pre_delay_pos) {
+            Some(slot) => {
+                let delayed = std::mem::replace(slot, (left, right));
+                self.pre_delay_pos = (self.pre_delay_pos + 1) % self.pre_delay_buffer.len();
+                delayed
+            }
+            None => (left, right),
+        };
+        let (in_l, in_r) = (in_l * REVERB_INPUT_GAIN, in_r * REVERB_INPUT_GAIN);
+
+        let [combs_l, combs_r] = &mut self.combs;
+        let (mut wet_l, mut wet_r) = (0.0, 0.0);
+        for (comb_l, comb_r) in combs_l.iter_mut().zip(combs_r.iter_mut()) {
+            let (out_l, out_r) = (comb_l.output(), comb_r.output());
+            wet_l += out_l;
+            wet_r += out_r;
+
+            let fb_l = comb_l.damp(out_l, damping);
+            let fb_r = comb_r.damp(out_r, damping);
+            let (gain_l, gain_r) = (comb_l.gain, comb_r.gain);
+            let loop_l = gain_l * (fb_l + (fb_r - fb_l) * cross);
+            let loop_r = gain_r * (fb_r + (fb_l - fb_r) * cross);
+            comb_l.write(in_l + soft_saturate(loop_l, saturation));
+            comb_r.write(in_r + soft_saturate(loop_r, saturation));
+        }
+
+        for allpass in &mut self.allpasses[0] {
+            wet_l = allpass.process(wet_l);
+        }
+        for allpass in &mut self.allpasses[1] {
+            wet_r = allpass.process(wet_r);
+        }
+
+        let mix = self.mix.value();
+        let dry = 1.0 - mix;
+        (
+            left * dry + wet_l * REVERB_WET_GAIN * mix,
+            right * dry + wet_r * REVERB_WET_GAIN * mix,
+        )
+    }
+}
+
+impl AudioUnit for StereoReverb {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        for comb in self.combs.iter_mut().flatten() {
+            comb.reset();
+        }
+        for allpass in self.allpasses.iter_mut().flatten() {
+            allpass.buffer.fill(0.0);
+        }
+        self.pre_delay_buffer.fill((0.0, 0.0));
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+        self.allocate();
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (left, right) = self.process_frame(input[0], input[1]);
+        output[0] = left;
+        output[1] = right;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (left, right) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, left);
+            output.set_f32(1, i, right);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"stereo_reverb";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Stereo reverb with cross-channel feedback
+pub struct StereoReverbBuilder;
+
+impl EffectBuilder for StereoReverbBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let room_size = params.get("room").copied().unwrap_or(0.5);
+        let time = params.get("time").copied().unwrap_or(2.0);
+        let damping = params.get("damping").copied().unwrap_or(0.5);
+        let cross_feedback = params.get("cross_feedback").copied().unwrap_or(0.3);
         let mix = params.get("mix").copied().unwrap_or(0.35);
-        let decay = params.get("decay").copied().unwrap_or(2.0);
-        // Plate: medium size, longer decay, bright character
-        // Use & operator to branch input to dry/wet paths and sum outputs
-        let effect = ((pass() | pass()) * (1.0 - mix)) & (reverb4_stereo(0.5, decay) * mix);
-        (Box::new(effect), EffectControls::new())
+        let saturation = params.get("saturation").copied().unwrap_or(0.0);
+
+        let reverb = StereoReverb::new(room_size, time, damping, cross_feedback, mix)
+            .with_saturation(saturation);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("time".to_string(), reverb.time.clone());
+        controls
+            .params
+            .insert("damping".to_string(), reverb.damping.clone());
+        controls
+            .params
+            .insert("cross_feedback".to_string(), reverb.cross_feedback.clone());
+        controls
+            .params
+            .insert("mix".to_string(), reverb.mix.clone());
+        controls
+            .params
+            .insert("saturation".to_string(), reverb.saturation.clone());
+
+        (Box::new(reverb), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
-        EffectMetadata::new("plate", "Plate reverb (bright, metallic)")
+        EffectMetadata::new("stereo_reverb", "Stereo reverb with cross-channel feedback")
+            .with_param("room", 0.5, 0.0, 1.0)
+            .with_param("time", 2.0, 0.1, 10.0)
+            .with_param("damping", 0.5, 0.0, 1.0)
+            .with_param("cross_feedback", 0.3, 0.0, 1.0)
             .with_param("mix", 0.35, 0.0, 1.0)
-            .with_param("decay", 2.0, 0.5, 5.0)
+            .with_param("saturation", 0.0, 0.0, 1.0)
+            .with_tag("reverb")
+    }
+}
+
+/// Longest delay time supported by [`FeedbackDelay`] in seconds
+const MAX_DELAY_TIME: f32 = 2.0;
+
+/// Blend between a clean signal and its tanh soft-clip (`amount` 0.0 - 1.0)
+#[inline]
+fn soft_saturate(x: f32, amount: f32) -> f32 {
+    if amount <= 0.0 {
+        x
+    } else {
+        x + (x.tanh() - x) * amount
+    }
+}
+
+/// Stereo delay with feedback and optional saturation in the feedback loop
+///
+/// Saturation soft-clips each repeat before it is written back, which adds
+/// warmth to the tail and keeps it bounded even at unity feedback.
+#[derive(Clone)]
+pub struct FeedbackDelay {
+    /// Delay time in seconds (up to 2.0)
+    pub time: Shared,
+    /// Amount of each repeat fed back into the delay (0.0 - 1.0)
+    pub feedback: Shared,
+    /// Soft saturation in the feedback loop (0.0 = clean, 1.0 = full tanh)
+    pub saturation: Shared,
+    /// Dry/wet mix
+    pub mix: Shared,
+    buffers: [Vec<f32>; 2],
+    pos: usize,
+    sample_rate: f32,
+}
+
+impl FeedbackDelay {
+    /// Create a new feedback delay
+    pub fn new(time: f32, feedback: f32, saturation: f32, mix: f32) -> Self {
+        let mut delay = Self {
+            time: shared(time),
+            feedback: shared(feedback),
+            saturation: shared(saturation),
+            mix: shared(mix),
+            buffers: [Vec::new(), Vec::new()],
+            pos: 0,
+            sample_rate: 48000.0,
+        };
+        delay.allocate_buffers();
+        delay
+    }
+
+    fn allocate_buffers(&mut self) {
+        let len = (MAX_DELAY_TIME * self.sample_rate).ceil() as usize + 1;
+        self.buffers = [vec![0.0; len], vec![0.0; len]];
+        self.pos = 0;
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let len = self.buffers[0].len();
+        let delay = ((self.time.value() * self.sample_rate).round() as usize).clamp(1, len - 1);
+        let feedback = self.feedback.value().clamp(0.0, 1.0);
+        let saturation = self.saturation.value().clamp(0.0, 1.0);
+        let mix = self.mix.value().clamp(0.0, 1.0);
+        let read = (self.pos + len - delay) % len;
+
+        let mut output = [left, right];
+        for (buffer, sample) in self.buffers.iter_mut().zip(output.iter_mut()) {
+            let wet = buffer[read];
+            buffer[self.pos] = *sample + soft_saturate(wet * feedback, saturation);
+            *sample += (wet - *sample) * mix;
+        }
+
+        self.pos = (self.pos + 1) % len;
+        (output[0], output[1])
+    }
+}
+
+impl AudioUnit for FeedbackDelay {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        for buffer in &mut self.buffers {
+            buffer.fill(0.0);
+        }
+        self.pos = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate as f32 {
+            self.sample_rate = sample_rate as f32;
+            self.allocate_buffers();
+        }
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (l, r) = self.process_frame(input[0], input[1]);
+        output[0] = l;
+        output[1] = r;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (l, r) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, l);
+            output.set_f32(1, i, r);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"feedback_delay";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>() + self.buffers[0].len() * 2 * std::mem::size_of::<f32>()
     }
 }
 
@@ -87,21 +634,33 @@ impl EffectBuilder for DelayBuilder {
     fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
         let time = params.get("time").copied().unwrap_or(0.5);
         let mix = params.get("mix").copied().unwrap_or(0.5);
-
-        // Use & operator to branch input to dry/wet paths and sum outputs
-        let delay_left = pass() >> fundsp::prelude::delay(time as f64);
-        let delay_right = pass() >> fundsp::prelude::delay(time as f64);
-
-        let left = (pass() * (1.0 - mix)) & (delay_left * mix);
-        let right = (pass() * (1.0 - mix)) & (delay_right * mix);
-
-        (Box::new(left | right), EffectControls::new())
+        let feedback = params.get("feedback").copied().unwrap_or(0.0);
+        let saturation = params.get("saturation").copied().unwrap_or(0.0);
+
+        let delay = FeedbackDelay::new(time, feedback, saturation, mix);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("time".to_string(), delay.time.clone());
+        controls
+            .params
+            .insert("feedback".to_string(), delay.feedback.clone());
+        controls
+            .params
+            .insert("saturation".to_string(), delay.saturation.clone());
+        controls.params.insert("mix".to_string(), delay.mix.clone());
+
+        (Box::new(delay), controls)
     }
 
     fn metadata(&self) -> EffectMetadata {
         EffectMetadata::new("delay", "Delay effect")
             .with_param("time", 0.5, 0.0, 2.0)
             .with_param("mix", 0.5, 0.0, 1.0)
+            .with_param("feedback", 0.0, 0.0, 1.0)
+            .with_param("saturation", 0.0, 0.0, 1.0)
+            .with_tag("delay")
     }
 }
 
@@ -130,6 +689,7 @@ impl EffectBuilder for StereoDelayBuilder {
             .with_param("time_l", 0.25, 0.0, 2.0)
             .with_param("time_r", 0.375, 0.0, 2.0)
             .with_param("mix", 0.4, 0.0, 1.0)
+            .with_tag("delay")
     }
 }
 
@@ -156,6 +716,7 @@ impl EffectBuilder for PingPongDelayBuilder {
         EffectMetadata::new("ping_pong", "Ping-pong delay (bounces L-R)")
             .with_param("time", 0.25, 0.05, 1.0)
             .with_param("mix", 0.4, 0.0, 1.0)
+            .with_tag("delay")
     }
 }
 
@@ -181,6 +742,7 @@ impl EffectBuilder for SlapbackDelayBuilder {
         EffectMetadata::new("slapback", "Slapback delay (short, punchy)")
             .with_param("time", 0.08, 0.03, 0.15)
             .with_param("mix", 0.3, 0.0, 1.0)
+            .with_tag("delay")
     }
 }
 
@@ -196,6 +758,9 @@ impl EffectBuilder for EchoBuilder {
         EffectMetadata::new("echo", "Echo effect")
             .with_param("time", 0.5, 0.0, 2.0)
             .with_param("mix", 0.5, 0.0, 1.0)
+            .with_param("feedback", 0.0, 0.0, 1.0)
+            .with_param("saturation", 0.0, 0.0, 1.0)
+            .with_tag("delay")
     }
 }
 
@@ -209,6 +774,8 @@ pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("hall_reverb", Arc::new(HallReverbBuilder)); // alias
     registry.register("plate", Arc::new(PlateReverbBuilder));
     registry.register("plate_reverb", Arc::new(PlateReverbBuilder)); // alias
+    registry.register("stereo_reverb", Arc::new(StereoReverbBuilder));
+    registry.register("cross_reverb", Arc::new(StereoReverbBuilder)); // alias
 
     // Delays
     registry.register("delay", Arc::new(DelayBuilder));
@@ -218,3 +785,159 @@ pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
     registry.register("slapback", Arc::new(SlapbackDelayBuilder));
     registry.register("echo", Arc::new(EchoBuilder));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed a constant signal and return the peak output level
+    fn peak_with_dc(delay: &mut FeedbackDelay, samples: usize) -> f32 {
+        (0..samples)
+            .map(|_| delay.process_frame(0.5, 0.5).0.abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_delay_single_tap_without_feedback() {
+        let mut delay = FeedbackDelay::new(0.01, 0.0, 0.0, 1.0);
+        let taps: Vec<f32> = (0..2000)
+            .map(|i| delay.process_frame(if i == 0 { 1.0 } else { 0.0 }, 0.0).0)
+            .collect();
+        assert_eq!(taps[480], 1.0);
+        assert_eq!(taps.iter().filter(|x| x.abs() > 0.0).count(), 1);
+    }
+
+    #[test]
+    fn test_saturated_feedback_stays_bounded() {
+        // 10ms delay, 200 round trips at unity feedback
+        let samples = 480 * 200;
+
+        let mut clean = FeedbackDelay::new(0.01, 1.0, 0.0, 1.0);
+        assert!(peak_with_dc(&mut clean, samples) > 50.0);
+
+        let mut saturated = FeedbackDelay::new(0.01, 1.0, 1.0, 1.0);
+        let peak = peak_with_dc(&mut saturated, samples);
+        assert!(peak.is_finite());
+        assert!(peak < 2.0, "saturated feedback diverged to {peak}");
+    }
+
+    #[test]
+    fn test_delay_builder_exposes_saturation() {
+        let params: HashMap<String, f32> = [
+            ("feedback".to_string(), 0.9),
+            ("saturation".to_string(), 0.5),
+        ]
+        .into();
+        let (_, controls) = DelayBuilder.build(&params);
+        assert_eq!(controls.get("feedback"), Some(0.9));
+        assert_eq!(controls.get("saturation"), Some(0.5));
+    }
+
+    /// Energy of each channel over a window of the impulse response
+    fn window_energy(response: &[(f32, f32)], range: std::ops::Range<usize>) -> (f32, f32) {
+        response[range]
+            .iter()
+            .fold((0.0, 0.0), |(l, r), (a, b)| (l + a * a, r + b * b))
+    }
+
+    fn left_impulse_response(cross_feedback: f32) -> Vec<(f32, f32)> {
+        let mut reverb = StereoReverb::new(0.5, 3.0, 0.3, cross_feedback, 1.0);
+        (0..48000)
+            .map(|i| reverb.process_frame(if i == 0 { 1.0 } else { 0.0 }, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_cross_feedback_moves_tail_to_other_channel() {
+        let response = left_impulse_response(0.5);
+
+        // Early reflections stay on the left side
+        let (early_l, early_r) = window_energy(&response, 0..2400);
+        assert!(early_l > 0.0);
+        assert_eq!(early_r, 0.0);
+
+        // The late tail is spread across both channels
+        let (late_l, late_r) = window_energy(&response, 24000..48000);
+        assert!(late_l > 0.0);
+        assert!(late_r > late_l * 0.5 && late_r < late_l * 2.0);
+    }
+
+    /// Left output of a reverb preset's response to a unit impulse
+    fn preset_impulse_response(builder: &dyn EffectBuilder, params: &[(&str, f32)]) -> Vec<f32> {
+        let params = params.iter().map(|&(k, v)| (k.to_string(), v)).collect();
+        let (mut unit, _) = builder.build(&params);
+        unit.set_sample_rate(48000.0);
+        let mut output = [0.0; 2];
+        (0..48000)
+            .map(|i| {
+                let x = if i == 0 { 1.0 } else { 0.0 };
+                unit.tick(&[x, x], &mut output);
+                output[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_damping_darkens_reverb_tail() {
+        // High-frequency energy: energy of the first difference of the tail
+        let hf_energy = |damping: f32| {
+            let response =
+                preset_impulse_response(&HallReverbBuilder, &[("mix", 1.0), ("damping", damping)]);
+            response[12000..]
+                .windows(2)
+                .map(|w| (w[1] - w[0]).powi(2))
+                .sum::<f32>()
+        };
+        let bright = hf_energy(0.1);
+        let dark = hf_energy(0.8);
+        assert!(bright > 0.0);
+        assert!(dark < bright * 0.5, "dark {dark} vs bright {bright}");
+    }
+
+    #[test]
+    fn test_saturation_tames_loud_reverb_tail() {
+        let peak = |saturation: f32| {
+            let mut reverb =
+                StereoReverb::new(0.5, 10.0, 0.2, 0.3, 1.0).with_saturation(saturation);
+            (0..48000)
+                .map(|_| reverb.process_frame(4.0, 4.0).0.abs())
+                .fold(0.0, f32::max)
+        };
+        let clean = peak(0.0);
+        let saturated = peak(1.0);
+        assert!(saturated.is_finite());
+        assert!(
+            saturated < clean * 0.8,
+            "saturated {saturated} vs clean {clean}"
+        );
+    }
+
+    #[test]
+    fn test_pre_delay_shifts_reverb_onset() {
+        let onset = |pre_delay: f32| {
+            preset_impulse_response(
+                &RoomReverbBuilder,
+                &[("mix", 1.0), ("pre_delay", pre_delay)],
+            )
+            .iter()
+            .position(|x| x.abs() > 0.0)
+            .unwrap()
+        };
+        assert_eq!(onset(0.02), onset(0.0) + 960);
+
+        assert_eq!(
+            StereoReverb::new(0.5, 1.0, 0.5, 0.0, 1.0)
+                .with_pre_delay(1.0)
+                .pre_delay(),
+            MAX_PRE_DELAY
+        );
+    }
+
+    #[test]
+    fn test_no_cross_feedback_keeps_channels_separate() {
+        let response = left_impulse_response(0.0);
+        let (left, right) = window_energy(&response, 0..48000);
+        assert!(left > 0.0);
+        assert_eq!(right, 0.0);
+    }
+}
@@ -0,0 +1,341 @@
+//! Pitch correction effects (autotune)
+
+use super::super::pitch::PitchDetector;
+use super::super::registry::{EffectBuilder, EffectControls, EffectMetadata, LATENCY_CONTROL};
+use fundsp::hacker32::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Length of the pitch shifter's crossfade window in seconds
+const SHIFT_WINDOW: f32 = 0.025;
+/// Latency of half the shift window at 48 kHz; instances report their own
+const AUTOTUNE_LATENCY: usize = 600;
+
+/// Scale masks (bit n set = n semitones above the key is in the scale)
+const CHROMATIC: u16 = 0b1111_1111_1111;
+const MAJOR: u16 = 0b1010_1011_0101;
+const MINOR: u16 = 0b0101_1010_1101;
+
+/// Scale mask for the `scale` parameter (0 = chromatic, 1 = major, 2 = minor)
+fn scale_mask(scale: f32) -> u16 {
+    match scale.round() as i32 {
+        1 => MAJOR,
+        2 => MINOR,
+        _ => CHROMATIC,
+    }
+}
+
+/// Frequency of the scale note nearest to `freq`
+fn nearest_scale_freq(freq: f32, mask: u16, key: i32) -> f32 {
+    let note = 69.0 + 12.0 * (freq / 440.0).log2();
+    let center = note.round() as i32;
+    let in_scale = |n: i32| mask & (1 << (n - key).rem_euclid(12)) != 0;
+
+    let mut best = center;
+    let mut best_distance = f32::MAX;
+    for offset in -6..=6 {
+        let candidate = center + offset;
+        let distance = (candidate as f32 - note).abs();
+        if in_scale(candidate) && distance < best_distance {
+            best = candidate;
+            best_distance = distance;
+        }
+    }
+    440.0 * 2.0_f32.powf((best as f32 - 69.0) / 12.0)
+}
+
+/// Pitch correction: detects the input pitch and shifts it to the nearest
+/// note of a scale
+///
+/// Pitch is detected with [`PitchDetector`] on the mono sum and shifted with
+/// a two-tap crossfading delay line, which adds half the shift window of
+/// delay. `speed` is the retune time in milliseconds; 0 snaps instantly
+/// for the hard "robotic" sound. `latency` holds the delay in samples.
+#[derive(Clone)]
+pub struct AutoTune {
+    /// Retune time in milliseconds (0 = instant)
+    pub speed: Shared,
+    /// Scale (0 = chromatic, 1 = major, 2 = natural minor)
+    pub scale: Shared,
+    /// Key root in semitones above C (0 = C, 9 = A)
+    pub key: Shared,
+    /// Latency in samples at the current sample rate (read-only)
+    pub latency: Shared,
+    detector: PitchDetector,
+    /// Delay lines for left and right
+    buffers: [Vec<f32>; 2],
+    /// Write position in the delay lines
+    pos: usize,
+    /// Shift window length in samples
+    window: f32,
+    /// Position of the first tap within the window (0.0 to 1.0)
+    phase: f32,
+    /// Current (smoothed) pitch ratio
+    ratio: f32,
+    /// Target ratio and the (pitch, scale, key) it was computed for
+    target: (f32, Option<f32>, f32, f32),
+    sample_rate: f32,
+}
+
+impl AutoTune {
+    /// Create a new autotune effect
+    pub fn new(speed_ms: f32, scale: f32, key: f32) -> Self {
+        let mut autotune = Self {
+            speed: shared(speed_ms),
+            scale: shared(scale),
+            key: shared(key),
+            latency: shared(0.0),
+            detector: PitchDetector::new(48000.0),
+            buffers: [Vec::new(), Vec::new()],
+            pos: 0,
+            window: 0.0,
+            phase: 0.5,
+            ratio: 1.0,
+            target: (1.0, None, 0.0, 0.0),
+            sample_rate: 48000.0,
+        };
+        autotune.allocate();
+        autotune
+    }
+
+    /// Size the delay lines for the current sample rate
+    fn allocate(&mut self) {
+        self.window = (SHIFT_WINDOW * self.sample_rate).max(16.0);
+        self.latency.set_value((self.window * 0.5).round());
+        let len = self.window as usize + 4;
+        self.buffers = [vec![0.0; len], vec![0.0; len]];
+        self.pos = 0;
+        self.phase = 0.5;
+    }
+
+    /// Pitch ratio that moves the detected pitch onto the scale
+    fn target_ratio(&mut self) -> f32 {
+        let pitch = self.detector.pitch();
+        let scale = self.scale.value();
+        let key = self.key.value();
+        let (ratio, last_pitch, last_scale, last_key) = self.target;
+        if pitch == last_pitch && scale == last_scale && key == last_key {
+            return ratio;
+        }
+
+        let ratio = match pitch {
+            Some(freq) => {
+                let target = nearest_scale_freq(freq, scale_mask(scale), key.round() as i32);
+                (target / freq).clamp(0.5, 2.0)
+            }
+            None => 1.0,
+        };
+        self.target = (ratio, pitch, scale, key);
+        ratio
+    }
+
+    /// Read a delay line `delay` samples behind the write position
+    #[inline]
+    fn read(&self, channel: usize, delay: f32) -> f32 {
+        let buffer = &self.buffers[channel];
+        let len = buffer.len();
+        let whole = delay.floor();
+        let frac = delay - whole;
+        let a = buffer[(self.pos + len - whole as usize % len) % len];
+        let b = buffer[(self.pos + len - (whole as usize + 1) % len) % len];
+        a + (b - a) * frac
+    }
+
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        self.detector.push((left + right) * 0.5);
+
+        let target = self.target_ratio();
+        let speed = self.speed.value();
+        if speed <= 0.0 {
+            self.ratio = target;
+        } else {
+            let coeff = (-1.0 / (speed * 0.001 * self.sample_rate)).exp();
+            self.ratio = target + (self.ratio - target) * coeff;
+        }
+
+        self.buffers[0][self.pos] = left;
+        self.buffers[1][self.pos] = right;
+
+        // Shrinking the delay reads faster (pitch up), growing it reads slower
+        self.phase += (1.0 - self.ratio) / self.window;
+        self.phase -= self.phase.floor();
+        let phase_b = (self.phase + 0.5).fract();
+
+        // sin² windows on taps half a window apart sum to unity
+        let gain_a = (std::f32::consts::PI * self.phase).sin().powi(2);
+        let gain_b = 1.0 - gain_a;
+        let (delay_a, delay_b) = (self.phase * self.window, phase_b * self.window);
+
+        let out_left = self.read(0, delay_a) * gain_a + self.read(0, delay_b) * gain_b;
+        let out_right = self.read(1, delay_a) * gain_a + self.read(1, delay_b) * gain_b;
+
+        self.pos = (self.pos + 1) % self.buffers[0].len();
+        (out_left, out_right)
+    }
+}
+
+impl AudioUnit for AutoTune {
+    fn inputs(&self) -> usize {
+        2
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+        for buffer in &mut self.buffers {
+            buffer.fill(0.0);
+        }
+        self.pos = 0;
+        self.phase = 0.5;
+        self.ratio = 1.0;
+        self.target = (1.0, None, 0.0, 0.0);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+        self.detector.set_sample_rate(self.sample_rate);
+        self.allocate();
+    }
+
+    fn tick(&mut self, input: &[f32], output: &mut [f32]) {
+        let (left, right) = self.process_frame(input[0], input[1]);
+        output[0] = left;
+        output[1] = right;
+    }
+
+    fn process(&mut self, size: usize, input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let (left, right) = self.process_frame(input.at_f32(0, i), input.at_f32(1, i));
+            output.set_f32(0, i, left);
+            output.set_f32(1, i, right);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"autotune";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Autotune effect
+pub struct AutoTuneBuilder;
+
+impl EffectBuilder for AutoTuneBuilder {
+    fn build(&self, params: &HashMap<String, f32>) -> (Box<dyn AudioUnit>, EffectControls) {
+        let speed = params.get("speed").copied().unwrap_or(20.0); // ms, 0 = hard
+        let scale = params.get("scale").copied().unwrap_or(0.0); // 0 = chromatic
+        let key = params.get("key").copied().unwrap_or(0.0); // 0 = C
+
+        let autotune = AutoTune::new(speed, scale, key);
+
+        let mut controls = EffectControls::new();
+        controls
+            .params
+            .insert("speed".to_string(), autotune.speed.clone());
+        controls
+            .params
+            .insert("scale".to_string(), autotune.scale.clone());
+        controls
+            .params
+            .insert("key".to_string(), autotune.key.clone());
+        controls
+            .meters
+            .insert(LATENCY_CONTROL.to_string(), autotune.latency.clone());
+
+        (Box::new(autotune), controls)
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata::new("autotune", "Pitch correction to the nearest scale note")
+            .with_param("speed", 20.0, 0.0, 500.0)
+            .with_param("scale", 0.0, 0.0, 2.0)
+            .with_param("key", 0.0, 0.0, 11.0)
+            .with_latency(AUTOTUNE_LATENCY)
+            .with_tag("pitch")
+    }
+}
+
+/// Register all pitch correction effects
+pub fn register_all(registry: &mut super::super::registry::EffectRegistry) {
+    registry.register("autotune", Arc::new(AutoTuneBuilder));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_scale_freq() {
+        // Chromatic: 430 Hz is closest to A4
+        assert!((nearest_scale_freq(430.0, CHROMATIC, 0) - 440.0).abs() < 1e-2);
+        // C major has no A#, so 460 Hz (closer to A#4) snaps to A4
+        let snapped = nearest_scale_freq(460.0, MAJOR, 0);
+        assert!((snapped - 440.0).abs() < 1e-2);
+        // In A minor, G# is out of scale: 410 Hz (closer to G#4) snaps to G4
+        let snapped = nearest_scale_freq(410.0, MINOR, 9);
+        assert!((snapped - 392.0).abs() < 1e-1);
+    }
+
+    #[test]
+    fn test_flat_note_is_corrected() {
+        let mut autotune = AutoTune::new(0.0, 0.0, 0.0);
+        let mut output = [0.0; 2];
+        let mut analysis = PitchDetector::new(48000.0);
+
+        // A4 40 cents flat
+        let freq = 430.0;
+        for i in 0..96000 {
+            let x = (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin() * 0.5;
+            autotune.tick(&[x, x], &mut output);
+            if i >= 96000 - 8192 {
+                analysis.push(output[0]);
+            }
+        }
+
+        let corrected = analysis.pitch().unwrap();
+        assert!(
+            (corrected - 440.0).abs() < 3.0,
+            "corrected to {}",
+            corrected
+        );
+    }
+
+    #[test]
+    fn test_latency_follows_sample_rate() {
+        let (mut unit, controls) = AutoTuneBuilder.build(&HashMap::new());
+        assert_eq!(
+            controls.meter(LATENCY_CONTROL),
+            Some(AUTOTUNE_LATENCY as f32)
+        );
+        unit.set_sample_rate(96000.0);
+        assert_eq!(controls.meter(LATENCY_CONTROL), Some(1200.0));
+        unit.set_sample_rate(44100.0);
+        assert_eq!(controls.meter(LATENCY_CONTROL), Some(551.0));
+    }
+
+    #[test]
+    fn test_silence_passes_unshifted() {
+        let mut autotune = AutoTune::new(20.0, 0.0, 0.0);
+        let mut output = [0.0; 2];
+        for _ in 0..4800 {
+            autotune.tick(&[0.0, 0.0], &mut output);
+        }
+        assert_eq!(autotune.ratio, 1.0);
+        assert_eq!(output, [0.0, 0.0]);
+    }
+}
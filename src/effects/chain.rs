@@ -1,15 +1,21 @@
 //! Effect chain for processing audio through multiple effects
 //!
 //! Provides an ordered chain of effects that can be applied to audio streams.
-use super::registry::{EffectControls, EffectRegistry};
+use super::lfo::{AttachedLfo, EffectLFO};
+use super::pitch::PitchDetector;
 #[cfg(feature = "serde")]
-use super::serialize::{ChainState, EffectState};
-use super::sidechain::SidechainAwareEffect;
+use super::preset::EffectPreset;
+use super::registry::{EffectControls, EffectRegistry, LATENCY_CONTROL};
+#[cfg(feature = "serde")]
+use super::serialize::{ChainState, EffectState, LfoAttachment};
+use super::sidechain::{BusId, SidechainAwareEffect};
+use super::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
 use super::EffectId;
 use crate::metrics::CpuMeter;
+use crate::render::RenderBuffer;
 use crate::Result;
 use fundsp::hacker32::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 /// An effect instance with its audio processing unit and controls
@@ -24,22 +30,409 @@ pub struct Effect {
     pub processor: Box<dyn AudioUnit>,
     /// Optional sidechain-aware processor (if this effect supports sidechain)
     pub sidechain_processor: Option<Box<dyn SidechainAwareEffect>>,
+    /// Sidechain bus the effect is keyed from by `EffectChain::process_with_buses`
+    pub sidechain_bus: Option<BusId>,
     /// Latency introduced by this effect (in samples)
     pub latency_samples: usize,
     /// Whether this effect is bypassed (passes audio through unchanged)
     pub bypassed: bool,
     /// Whether this effect is muted (outputs silence)
     pub muted: bool,
+    /// Dry/wet balance applied by the chain (0.0 = dry, 1.0 = fully processed)
+    pub mix: f32,
+    /// Output gain trim in dB, applied after processing (0.0 = unity)
+    pub gain_db: f32,
+    /// Linear gain cached for `cached_gain_db`
+    gain_linear: f32,
+    /// The `gain_db` value `gain_linear` was computed from
+    cached_gain_db: f32,
     /// Latest input levels (RMS L, RMS R, Peak L, Peak R) for metering
     pub last_input_levels: (f32, f32, f32, f32),
     /// Latest output levels (RMS L, RMS R, Peak L, Peak R) for metering
     pub last_output_levels: (f32, f32, f32, f32),
-    /// Sample buffer for calculating input RMS (rolling average)
-    input_level_buffer: Vec<(f32, f32)>,
-    /// Sample buffer for calculating output RMS (rolling average)
-    output_level_buffer: Vec<(f32, f32)>,
+    /// Rolling level meter for input RMS/peak
+    input_meter: LevelMeter,
+    /// Rolling level meter for output RMS/peak
+    output_meter: LevelMeter,
+    /// Delay on the dry path matching this effect's latency
+    dry_delay: DelayLine,
     /// CPU meter for performance tracking
     pub cpu_meter: CpuMeter,
+    /// Parallel branches, if this effect is a parallel group node
+    pub group: Option<ParallelGroup>,
+    /// In-progress parameter ramps started by `set_param_smoothed`
+    ramps: HashMap<String, ParamRamp>,
+    /// Output gain automation started by `ride_gain`
+    ride: Option<GainRide>,
+    /// Smoothed `mix` as heard; `None` until the effect first processes audio
+    mix_current: Option<f32>,
+    /// Smoothing of the effect's own `mix` parameter, if it has one
+    mix_param: Option<MixParam>,
+    /// Per-sample coefficient of the mix smoothing (0.0 = instant)
+    mix_coeff: f32,
+    /// LFOs attached to parameters with `attach_lfo`
+    lfos: Vec<AttachedLfo>,
+}
+
+impl Effect {
+    /// Record an input sample for metering
+    #[inline]
+    fn meter_input(&mut self, left: f32, right: f32) {
+        if let Some(levels) = self.input_meter.push(left, right) {
+            self.last_input_levels = levels;
+        }
+    }
+
+    /// Record an output sample for metering
+    #[inline]
+    fn meter_output(&mut self, left: f32, right: f32) {
+        if let Some(levels) = self.output_meter.push(left, right) {
+            self.last_output_levels = levels;
+        }
+    }
+
+    /// Latency in samples (the longest branch for parallel groups)
+    fn latency(&self) -> usize {
+        self.latency_samples
+    }
+
+    /// CPU usage (the sum of all branches for parallel groups)
+    fn cpu_usage(&self) -> f64 {
+        match &self.group {
+            Some(group) => group.cpu_usage(),
+            None => self.cpu_meter.metrics().cpu_usage,
+        }
+    }
+
+    /// Blend the processed signal with the dry input according to `mix`
+    ///
+    /// With latency compensation the dry input is delayed by the effect's
+    /// latency so both paths stay sample-aligned.
+    #[inline]
+    fn blend_dry(&mut self, dry: (f32, f32), wet: (f32, f32), compensate: bool) -> (f32, f32) {
+        let dry = if compensate {
+            self.dry_delay.process(dry.0, dry.1)
+        } else {
+            dry
+        };
+        let target = self.mix.clamp(0.0, 1.0);
+        let mix = match self.mix_current {
+            Some(current) if (current - target).abs() > MIX_SETTLED => {
+                target + (current - target) * self.mix_coeff
+            }
+            _ => target,
+        };
+        self.mix_current = Some(mix);
+        if mix >= 1.0 {
+            return wet;
+        }
+        (dry.0 + (wet.0 - dry.0) * mix, dry.1 + (wet.1 - dry.1) * mix)
+    }
+
+    /// Advance the smoothing of the effect's own `mix` parameter
+    #[inline]
+    fn advance_mix_param(&mut self, samples: usize) {
+        if let Some(param) = &mut self.mix_param {
+            param.advance(self.mix_coeff.powi(samples as i32));
+        }
+    }
+
+    /// Advance all parameter ramps by `samples` samples
+    #[inline]
+    fn advance_ramps(&mut self, samples: usize) {
+        if !self.ramps.is_empty() {
+            self.ramps.retain(|_, ramp| ramp.advance(samples));
+        }
+    }
+
+    /// Advance all attached LFOs by `samples` samples
+    #[inline]
+    fn advance_lfos(&mut self, samples: usize, sample_rate: f64, bpm: f32) {
+        for lfo in &mut self.lfos {
+            lfo.advance(samples, sample_rate, bpm);
+        }
+    }
+
+    /// Advance the gain ride by `samples` samples
+    #[inline]
+    fn advance_ride(&mut self, samples: usize, sample_rate: f64) {
+        if let Some(ride) = &mut self.ride {
+            ride.advance(samples, sample_rate);
+        }
+    }
+
+    /// Linear gain of the ride at the start and end of the current span
+    #[inline]
+    fn ride_gains(&self) -> (f32, f32) {
+        self.ride.as_ref().map_or((1.0, 1.0), |ride| ride.gains)
+    }
+
+    /// Clear audio state (processor memory, latency delay, meters)
+    fn reset(&mut self) {
+        self.processor.reset();
+        if let Some(processor) = &mut self.sidechain_processor {
+            processor.reset();
+        }
+        if let Some(group) = &mut self.group {
+            for branch in &mut group.branches {
+                branch.reset();
+            }
+            for delay in &mut group.delays {
+                delay.clear();
+            }
+        }
+        self.dry_delay.clear();
+        self.input_meter = LevelMeter::new(LEVEL_WINDOW);
+        self.output_meter = LevelMeter::new(LEVEL_WINDOW);
+        self.last_input_levels = (0.0, 0.0, 0.0, 0.0);
+        self.last_output_levels = (0.0, 0.0, 0.0, 0.0);
+        self.mix_current = None;
+        if let Some(param) = &mut self.mix_param {
+            param.current = None;
+        }
+        for lfo in &mut self.lfos {
+            lfo.reset();
+        }
+    }
+
+    /// Linear output gain for the current `gain_db`
+    #[inline]
+    fn output_gain(&mut self) -> f32 {
+        if self.gain_db != self.cached_gain_db {
+            self.cached_gain_db = self.gain_db;
+            self.gain_linear = 10.0_f32.powf(self.gain_db / 20.0);
+        }
+        self.gain_linear
+    }
+}
+
+/// Default tempo for tempo-synced LFOs
+const DEFAULT_BPM: f32 = 120.0;
+
+/// Mix values closer than this to their target snap to it
+const MIX_SETTLED: f32 = 1e-5;
+
+/// Per-sample coefficient of an exponential ramp with time constant `ms`
+fn mix_smoothing_coeff(ms: f32, sample_rate: f64) -> f32 {
+    if ms <= 0.0 {
+        0.0
+    } else {
+        (-1000.0 / (ms * sample_rate as f32)).exp()
+    }
+}
+
+/// An effect's own `mix` parameter, smoothed by the chain
+///
+/// The effect's `mix` control is replaced by `target`; the chain ramps the
+/// value the processor reads (`output`) toward it.
+struct MixParam {
+    /// Value set through the effect's controls
+    target: Shared,
+    /// Value read by the processor
+    output: Shared,
+    /// Value last written to `output`; `None` until audio is processed
+    current: Option<f32>,
+}
+
+impl MixParam {
+    /// Move toward the target by a ramp step with coefficient `coeff`
+    #[inline]
+    fn advance(&mut self, coeff: f32) {
+        let target = self.target.value();
+        let value = match self.current {
+            Some(current) if (current - target).abs() > MIX_SETTLED => {
+                target + (current - target) * coeff
+            }
+            Some(current) if current == target => return,
+            _ => target,
+        };
+        self.current = Some(value);
+        self.output.set_value(value);
+    }
+}
+
+/// Effect name used for parallel group nodes
+pub const PARALLEL_GROUP_NAME: &str = "parallel";
+
+/// A set of effect chains that process the same input in parallel
+///
+/// The outputs of all branches are summed, each scaled by its branch gain.
+/// Branch gains are exposed on the group node as `gain_0`, `gain_1`, ...
+/// parameters, so they can be changed with [`EffectChain::set_param`].
+pub struct ParallelGroup {
+    /// Branch chains, each fed the group input
+    branches: Vec<EffectChain>,
+    /// Linear output gain per branch
+    gains: Vec<Shared>,
+    /// Per-branch delays aligning shorter branches with the longest one
+    delays: Vec<DelayLine>,
+    /// Whether branch latencies are compensated
+    compensate: bool,
+}
+
+impl ParallelGroup {
+    /// Get the branch chains
+    pub fn branches(&self) -> &[EffectChain] {
+        &self.branches
+    }
+
+    /// Get mutable access to the branch chains
+    pub fn branches_mut(&mut self) -> &mut [EffectChain] {
+        &mut self.branches
+    }
+
+    /// Get the linear output gain of a branch
+    pub fn branch_gain(&self, branch: usize) -> Option<f32> {
+        self.gains.get(branch).map(|g| g.value())
+    }
+
+    /// Latency of the group: the longest branch latency
+    pub fn latency(&self) -> usize {
+        // Branch totals add up the effects' cached latencies, so this
+        // doesn't recurse into nested groups
+        self.branches
+            .iter()
+            .map(|b| b.total_latency())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// CPU usage of the group: the sum of all branches
+    pub fn cpu_usage(&self) -> f64 {
+        self.branches.iter().map(|b| b.total_cpu_usage()).sum()
+    }
+
+    /// Resize the branch delays to match the current branch latencies
+    fn sync_delays(&mut self) {
+        let latency = self.latency();
+        for (branch, delay) in self.branches.iter().zip(&mut self.delays) {
+            let length = if self.compensate {
+                latency - branch.total_latency()
+            } else {
+                0
+            };
+            delay.set_length(length);
+        }
+    }
+
+    /// Process one stereo sample through all branches and sum the outputs
+    fn process(&mut self, left: f32, right: f32, sidechain: SidechainFrame) -> (f32, f32) {
+        let mut out_left = 0.0;
+        let mut out_right = 0.0;
+        for ((branch, gain), delay) in self
+            .branches
+            .iter_mut()
+            .zip(&self.gains)
+            .zip(&mut self.delays)
+        {
+            let (l, r) = branch.process_frame(left, right, sidechain);
+            let (l, r) = delay.process(l, r);
+            let gain = gain.value();
+            out_left += l * gain;
+            out_right += r * gain;
+        }
+        (out_left, out_right)
+    }
+
+    /// Process up to `MAX_BUFFER_SIZE` frames in place through all branches
+    fn process_chunk(&mut self, left: &mut [f32], right: &mut [f32], sidechain: SidechainBlock) {
+        let size = left.len();
+        let mut dry_l = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let mut dry_r = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let mut wet_l = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let mut wet_r = [0.0; fundsp::MAX_BUFFER_SIZE];
+        dry_l[..size].copy_from_slice(left);
+        dry_r[..size].copy_from_slice(right);
+        left.fill(0.0);
+        right.fill(0.0);
+
+        for ((branch, gain), delay) in self
+            .branches
+            .iter_mut()
+            .zip(&self.gains)
+            .zip(&mut self.delays)
+        {
+            branch.process_block_inner(
+                &dry_l[..size],
+                &dry_r[..size],
+                &mut wet_l[..size],
+                &mut wet_r[..size],
+                sidechain,
+            );
+            let gain = gain.value();
+            for i in 0..size {
+                let (l, r) = delay.process(wet_l[i], wet_r[i]);
+                left[i] += l * gain;
+                right[i] += r * gain;
+            }
+        }
+    }
+}
+
+/// Sidechain signal for one frame
+#[derive(Clone, Copy)]
+enum SidechainFrame<'a> {
+    /// The same signal, if any, for every sidechain-aware effect
+    Global(Option<(f32, f32)>),
+    /// Each effect reads the bus it is bound to
+    Buses(&'a HashMap<BusId, (f32, f32)>),
+}
+
+impl SidechainFrame<'_> {
+    /// Sidechain signal for an effect bound to `bus`
+    #[inline]
+    fn for_effect(self, bus: Option<BusId>) -> Option<(f32, f32)> {
+        match self {
+            Self::Global(signal) => signal,
+            Self::Buses(buses) => bus.and_then(|bus| buses.get(&bus).copied()),
+        }
+    }
+}
+
+/// Sidechain signal for a block
+#[derive(Clone, Copy)]
+enum SidechainBlock<'a> {
+    /// The same signal, if any, for every sidechain-aware effect
+    Global(Option<(&'a [f32], &'a [f32])>),
+    /// Each effect reads frames `start..end` of the bus it is bound to
+    Buses {
+        buses: &'a HashMap<BusId, (&'a [f32], &'a [f32])>,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl<'a> SidechainBlock<'a> {
+    /// The frames from `start` to `end` of this block
+    fn slice(self, start: usize, end: usize) -> Self {
+        match self {
+            Self::Global(signal) => {
+                Self::Global(signal.map(|(left, right)| (&left[start..end], &right[start..end])))
+            }
+            Self::Buses {
+                buses,
+                start: offset,
+                ..
+            } => Self::Buses {
+                buses,
+                start: offset + start,
+                end: offset + end,
+            },
+        }
+    }
+
+    /// Sidechain block for an effect bound to `bus`
+    ///
+    /// A bus whose slices are shorter than the block counts as missing.
+    fn for_effect(self, bus: Option<BusId>) -> Option<(&'a [f32], &'a [f32])> {
+        match self {
+            Self::Global(signal) => signal,
+            Self::Buses { buses, start, end } => bus
+                .and_then(|bus| buses.get(&bus))
+                .filter(|(left, right)| left.len() >= end && right.len() >= end)
+                .map(|(left, right)| (&left[start..end], &right[start..end])),
+        }
+    }
 }
 
 /// A chain of audio effects that are processed in order
@@ -52,6 +445,32 @@ pub struct EffectChain {
     registry: Option<Arc<EffectRegistry>>,
     /// Sample rate for effect processing
     sample_rate: f64,
+    /// Scratch input buffer for block processing
+    block_input: BufferVec,
+    /// Scratch output buffer for block processing
+    block_output: BufferVec,
+    /// Whether latent effects get a matching delay on their dry path
+    latency_compensation: bool,
+    /// Set when a parallel group was handed out for editing, so cached
+    /// latencies are refreshed before the next processing call
+    latency_dirty: bool,
+    /// Index of the soloed effect, if any
+    soloed: Option<usize>,
+    /// Pitch detector fed with the chain input, when enabled
+    pitch_detector: Option<PitchDetector>,
+    /// Ramp time in milliseconds for changes to effect `mix` values
+    mix_smoothing_ms: f32,
+    /// Tempo in BPM for tempo-synced LFOs
+    bpm: f32,
+    /// Meter on the chain output, when enabled
+    master: Option<StereoMeter>,
+    /// Clip and CPU overload notifications, when a callback is set
+    overload: Option<OverloadMonitor>,
+    /// Names of the registered sidechain buses, indexed by `BusId`
+    sidechain_buses: Vec<String>,
+    /// Preset morph in progress, if any
+    #[cfg(feature = "serde")]
+    morph: Option<ChainMorph>,
 }
 
 impl EffectChain {
@@ -62,6 +481,19 @@ impl EffectChain {
             bypassed: false,
             registry: None,
             sample_rate: 48000.0, // Default sample rate
+            block_input: BufferVec::new(2),
+            block_output: BufferVec::new(2),
+            latency_compensation: true,
+            latency_dirty: false,
+            soloed: None,
+            pitch_detector: None,
+            mix_smoothing_ms: DEFAULT_SMOOTHING_MS,
+            bpm: DEFAULT_BPM,
+            master: None,
+            overload: None,
+            sidechain_buses: Vec::new(),
+            #[cfg(feature = "serde")]
+            morph: None,
         }
     }
 
@@ -72,6 +504,19 @@ impl EffectChain {
             bypassed: false,
             registry: Some(Arc::new(registry)),
             sample_rate: 48000.0, // Default sample rate
+            block_input: BufferVec::new(2),
+            block_output: BufferVec::new(2),
+            latency_compensation: true,
+            latency_dirty: false,
+            soloed: None,
+            pitch_detector: None,
+            mix_smoothing_ms: DEFAULT_SMOOTHING_MS,
+            bpm: DEFAULT_BPM,
+            master: None,
+            overload: None,
+            sidechain_buses: Vec::new(),
+            #[cfg(feature = "serde")]
+            morph: None,
         }
     }
 
@@ -82,6 +527,19 @@ impl EffectChain {
             bypassed: false,
             registry: Some(registry),
             sample_rate: 48000.0, // Default sample rate
+            block_input: BufferVec::new(2),
+            block_output: BufferVec::new(2),
+            latency_compensation: true,
+            latency_dirty: false,
+            soloed: None,
+            pitch_detector: None,
+            mix_smoothing_ms: DEFAULT_SMOOTHING_MS,
+            bpm: DEFAULT_BPM,
+            master: None,
+            overload: None,
+            sidechain_buses: Vec::new(),
+            #[cfg(feature = "serde")]
+            morph: None,
         }
     }
 
@@ -100,42 +558,346 @@ impl EffectChain {
     /// Set the sample rate for this effect chain
     pub fn set_sample_rate(&mut self, sample_rate: f64) {
         self.sample_rate = sample_rate;
+        if let Some(detector) = &mut self.pitch_detector {
+            detector.set_sample_rate(sample_rate as f32);
+        }
+        let mix_coeff = mix_smoothing_coeff(self.mix_smoothing_ms, sample_rate);
+        for effect in &mut self.effects {
+            effect.mix_coeff = mix_coeff;
+            effect.processor.set_sample_rate(sample_rate);
+            // Lookahead latencies are sized in samples at the new rate
+            if let Some(latency) = effect.controls.meter(LATENCY_CONTROL) {
+                effect.latency_samples = latency.max(0.0) as usize;
+            }
+            if let Some(processor) = &mut effect.sidechain_processor {
+                processor.set_sample_rate(sample_rate);
+            }
+            for ramp in effect.ramps.values_mut() {
+                ramp.smoother
+                    .set_sample_rate(sample_rate as f32, ramp.smoothing_ms);
+            }
+            if let Some(group) = &mut effect.group {
+                for branch in &mut group.branches {
+                    branch.set_sample_rate(sample_rate);
+                }
+            }
+        }
+        self.update_latency();
     }
 
-    /// Add an effect to the end of the chain by name
-    pub fn add_effect(&mut self, name: &str, params: &HashMap<String, f32>) -> Result<usize> {
-        if let Some(registry) = &self.registry {
-            let (processor, controls) = registry.build(name, params)?;
-            let metadata = registry.get_metadata(name).ok_or_else(|| {
-                crate::Error::InvalidEffect(format!("Effect not found: {}", name))
+    /// Refresh cached effect latencies and size the compensation delays
+    ///
+    /// Parallel groups take the longest latency of their branches. Called
+    /// whenever latencies can change (a new sample rate, toggling
+    /// compensation, editing a group), never per sample.
+    fn update_latency(&mut self) {
+        self.latency_dirty = false;
+        for effect in &mut self.effects {
+            if let Some(group) = &mut effect.group {
+                for branch in &mut group.branches {
+                    branch.update_latency();
+                }
+                group.sync_delays();
+                effect.latency_samples = group.latency();
+            }
+            effect.dry_delay.set_length(effect.latency_samples);
+        }
+    }
+
+    /// Set the ramp time for `mix` changes in milliseconds
+    ///
+    /// Both the chain's dry/wet blend ([`set_effect_mix`](Self::set_effect_mix))
+    /// and effects' own `mix` parameters glide to new values instead of
+    /// jumping, so rapid mix automation doesn't zipper. `ms` is the time
+    /// constant of an exponential ramp (default `DEFAULT_SMOOTHING_MS`,
+    /// 10 ms); 0 makes mix changes instant. Effects that have not processed
+    /// audio yet start directly at their mix.
+    pub fn set_mix_smoothing(&mut self, ms: f32) {
+        self.mix_smoothing_ms = ms.max(0.0);
+        let mix_coeff = mix_smoothing_coeff(self.mix_smoothing_ms, self.sample_rate);
+        for effect in &mut self.effects {
+            effect.mix_coeff = mix_coeff;
+            if let Some(group) = &mut effect.group {
+                for branch in &mut group.branches {
+                    branch.set_mix_smoothing(ms);
+                }
+            }
+        }
+    }
+
+    /// Ramp time for `mix` changes in milliseconds
+    pub fn mix_smoothing(&self) -> f32 {
+        self.mix_smoothing_ms
+    }
+
+    /// Set the tempo used by tempo-synced LFOs (default 120 BPM)
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.bpm = bpm.max(1.0);
+        for effect in &mut self.effects {
+            if let Some(group) = &mut effect.group {
+                for branch in &mut group.branches {
+                    branch.set_bpm(bpm);
+                }
+            }
+        }
+    }
+
+    /// Tempo used by tempo-synced LFOs in BPM
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Enable or disable latency compensation (enabled by default)
+    ///
+    /// When enabled, the dry path of each effect's `mix` blend is delayed by
+    /// the effect's reported latency, and shorter branches of parallel groups
+    /// are delayed to match the longest branch, so all paths stay aligned.
+    pub fn set_latency_compensation(&mut self, enabled: bool) {
+        self.latency_compensation = enabled;
+        for effect in &mut self.effects {
+            if let Some(group) = &mut effect.group {
+                group.compensate = enabled;
+                for branch in &mut group.branches {
+                    branch.set_latency_compensation(enabled);
+                }
+            }
+        }
+        self.update_latency();
+    }
+
+    /// Check whether latency compensation is enabled
+    pub fn latency_compensation(&self) -> bool {
+        self.latency_compensation
+    }
+
+    /// Build an effect instance from the registry
+    fn build_effect(
+        &self,
+        id: Option<EffectId>,
+        name: &str,
+        params: &HashMap<String, f32>,
+    ) -> Result<Effect> {
+        let registry = self
+            .registry
+            .as_ref()
+            .ok_or_else(|| crate::Error::InvalidEffect("No registry available".to_string()))?;
+
+        let (mut processor, mut controls) = registry.build(name, params)?;
+        processor.set_sample_rate(self.sample_rate);
+        let mix_param = controls.params.get("mix").cloned().map(|output| {
+            let target = shared(output.value());
+            controls.params.insert("mix".to_string(), target.clone());
+            MixParam {
+                target,
+                output,
+                current: None,
+            }
+        });
+        let metadata = registry
+            .get_metadata(name)
+            .ok_or_else(|| crate::Error::InvalidEffect(format!("Effect not found: {}", name)))?;
+        let latency_samples = controls
+            .meter(LATENCY_CONTROL)
+            .map_or(metadata.latency_samples, |latency| {
+                latency.max(0.0) as usize
+            });
+
+        // Check if this is a sidechain effect and build sidechain processor
+        let sidechain_processor =
+            super::sidechain::build_sidechain_effect(name, params, self.sample_rate as f32);
+        if let Some(processor) = &sidechain_processor {
+            let sidechain_controls = processor.controls();
+            controls.params.extend(sidechain_controls.params);
+            controls.meters.extend(sidechain_controls.meters);
+        }
+
+        Ok(Effect {
+            id,
+            name: name.to_string(),
+            controls,
+            processor,
+            sidechain_processor,
+            sidechain_bus: None,
+            latency_samples,
+            bypassed: false,
+            muted: false,
+            mix: 1.0,
+            gain_db: 0.0,
+            gain_linear: 1.0,
+            cached_gain_db: 0.0,
+            last_input_levels: (0.0, 0.0, 0.0, 0.0),
+            last_output_levels: (0.0, 0.0, 0.0, 0.0),
+            input_meter: LevelMeter::new(LEVEL_WINDOW),
+            output_meter: LevelMeter::new(LEVEL_WINDOW),
+            dry_delay: DelayLine::new(latency_samples),
+            cpu_meter: CpuMeter::new(self.sample_rate),
+            group: None,
+            ramps: HashMap::new(),
+            ride: None,
+            mix_current: None,
+            mix_param,
+            mix_coeff: mix_smoothing_coeff(self.mix_smoothing_ms, self.sample_rate),
+            lfos: Vec::new(),
+        })
+    }
+
+    /// Build a parallel group node from branch chains
+    fn build_parallel(
+        &self,
+        id: Option<EffectId>,
+        mut branches: Vec<EffectChain>,
+        gains: &[f32],
+    ) -> Result<Effect> {
+        if branches.is_empty() {
+            return Err(crate::Error::ChainError(
+                "Parallel group needs at least one branch".to_string(),
+            ));
+        }
+        if gains.len() != branches.len() {
+            return Err(crate::Error::ChainError(format!(
+                "Parallel group has {} branches but {} gains",
+                branches.len(),
+                gains.len()
+            )));
+        }
+
+        let mut controls = EffectControls::new();
+        let mut shared_gains = Vec::with_capacity(gains.len());
+        for (i, &gain) in gains.iter().enumerate() {
+            let gain = shared(gain);
+            controls.params.insert(format!("gain_{}", i), gain.clone());
+            shared_gains.push(gain);
+        }
+        for branch in &mut branches {
+            branch.set_sample_rate(self.sample_rate);
+            branch.set_latency_compensation(self.latency_compensation);
+        }
+
+        let mut group = ParallelGroup {
+            delays: branches.iter().map(|_| DelayLine::new(0)).collect(),
+            branches,
+            gains: shared_gains,
+            compensate: self.latency_compensation,
+        };
+        group.sync_delays();
+        let latency = group.latency();
+
+        Ok(Effect {
+            id,
+            name: PARALLEL_GROUP_NAME.to_string(),
+            controls,
+            processor: Box::new(multipass::<U2>()),
+            sidechain_processor: None,
+            sidechain_bus: None,
+            latency_samples: latency,
+            bypassed: false,
+            muted: false,
+            mix: 1.0,
+            gain_db: 0.0,
+            gain_linear: 1.0,
+            cached_gain_db: 0.0,
+            last_input_levels: (0.0, 0.0, 0.0, 0.0),
+            last_output_levels: (0.0, 0.0, 0.0, 0.0),
+            input_meter: LevelMeter::new(LEVEL_WINDOW),
+            output_meter: LevelMeter::new(LEVEL_WINDOW),
+            dry_delay: DelayLine::new(latency),
+            cpu_meter: CpuMeter::new(self.sample_rate),
+            group: Some(group),
+            ramps: HashMap::new(),
+            ride: None,
+            mix_current: None,
+            mix_param: None,
+            mix_coeff: mix_smoothing_coeff(self.mix_smoothing_ms, self.sample_rate),
+            lfos: Vec::new(),
+        })
+    }
+
+    /// Build a chain of built-in effects from a textual description
+    ///
+    /// Effects are separated by `->` and written as the effect name followed
+    /// by `param=value` pairs:
+    ///
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let chain = EffectChain::from_dsl("lpf cutoff=2000 res=0.7 -> reverb mix=0.4")?;
+    /// assert_eq!(chain.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// `mix` and `gain_db` are available on every effect: unless the effect
+    /// declares a parameter of that name, they set its chain-level dry/wet
+    /// balance and output trim (see [`set_effect_mix`](Self::set_effect_mix)
+    /// and [`set_effect_gain`](Self::set_effect_gain)). Unknown effects,
+    /// other parameters not listed in the effect's metadata and malformed
+    /// `param=value` pairs are reported as errors.
+    pub fn from_dsl(dsl: &str) -> Result<Self> {
+        Self::from_dsl_with_registry(dsl, Arc::new(EffectRegistry::with_builtin()))
+    }
+
+    /// Build a chain from a textual description using the given registry
+    ///
+    /// See [`from_dsl`](Self::from_dsl) for the syntax.
+    pub fn from_dsl_with_registry(dsl: &str, registry: Arc<EffectRegistry>) -> Result<Self> {
+        let mut chain = Self::with_shared_registry(registry.clone());
+        if dsl.trim().is_empty() {
+            return Ok(chain);
+        }
+
+        for (position, segment) in dsl.split("->").enumerate() {
+            let mut tokens = segment.split_whitespace();
+            let name = tokens.next().ok_or_else(|| {
+                crate::Error::ChainError(format!("empty effect at position {}", position + 1))
             })?;
+            let metadata = registry
+                .get_metadata(name)
+                .ok_or_else(|| registry.unknown_effect(name))?;
 
-            // Check if this is a sidechain effect and build sidechain processor
-            let sidechain_processor =
-                super::sidechain::build_sidechain_effect(name, params, self.sample_rate as f32);
-
-            let effect = Effect {
-                id: None,
-                name: name.to_string(),
-                controls,
-                processor,
-                sidechain_processor,
-                latency_samples: metadata.latency_samples,
-                bypassed: false,
-                muted: false,
-                last_input_levels: (0.0, 0.0, 0.0, 0.0),
-                last_output_levels: (0.0, 0.0, 0.0, 0.0),
-                input_level_buffer: Vec::with_capacity(2048), // ~43ms at 48kHz
-                output_level_buffer: Vec::with_capacity(2048), // ~43ms at 48kHz
-                cpu_meter: CpuMeter::new(self.sample_rate),
-            };
-            self.effects.push(effect);
-            Ok(self.effects.len() - 1)
-        } else {
-            Err(crate::Error::InvalidEffect(
-                "No registry available".to_string(),
-            ))
+            let mut params = HashMap::new();
+            let mut chain_settings = Vec::new();
+            for token in tokens {
+                let (key, value) = token.split_once('=').ok_or_else(|| {
+                    crate::Error::ChainError(format!(
+                        "expected param=value after '{}', found '{}'",
+                        name, token
+                    ))
+                })?;
+                let declared = metadata.parameters.iter().any(|p| p.name == key);
+                let chain_level = !declared && (key == "mix" || key == "gain_db");
+                if !declared && !chain_level {
+                    return Err(crate::Error::InvalidParameter(format!("{}.{}", name, key)));
+                }
+                let value: f32 = value.parse().map_err(|_| {
+                    crate::Error::ChainError(format!(
+                        "invalid number '{}' for {}.{}",
+                        value, name, key
+                    ))
+                })?;
+                if chain_level {
+                    chain_settings.push((key, value));
+                } else {
+                    params.insert(key.to_string(), value);
+                }
+            }
+
+            let index = chain.add_effect(name, &params)?;
+            for (key, value) in chain_settings {
+                if key == "mix" {
+                    chain.set_effect_mix(index, value)?;
+                } else {
+                    chain.set_effect_gain(index, value)?;
+                }
+            }
         }
+        Ok(chain)
+    }
+
+    /// Add an effect to the end of the chain by name
+    pub fn add_effect(&mut self, name: &str, params: &HashMap<String, f32>) -> Result<usize> {
+        let effect = self.build_effect(None, name, params)?;
+        self.effects.push(effect);
+        Ok(self.effects.len() - 1)
     }
 
     /// Add an effect with parameters as key-value pairs (chainable, consumes self)
@@ -184,6 +946,33 @@ impl EffectChain {
         Ok(self)
     }
 
+    /// Add an effect with a specific ID (chainable, borrows self)
+    ///
+    /// Like [`add`](Self::add), but the effect can later be addressed by `id`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+    /// let id = EffectId::new_v4();
+    /// chain.add_with_id(id, "lpf", &[("cutoff", 2000.0)])?;
+    /// chain.set_effect_param_by_id(id, "cutoff", 800.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_with_id(
+        &mut self,
+        id: EffectId,
+        name: &str,
+        params: &[(&str, f32)],
+    ) -> Result<&mut Self> {
+        let params_map: HashMap<String, f32> =
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        self.add_effect_with_id(id, name, params_map)?;
+        Ok(self)
+    }
+
     /// Add an effect with a specific ID (for IR synchronization)
     pub fn add_effect_with_id(
         &mut self,
@@ -191,38 +980,94 @@ impl EffectChain {
         name: &str,
         params: HashMap<String, f32>,
     ) -> Result<usize> {
-        if let Some(registry) = &self.registry {
-            let (processor, controls) = registry.build(name, &params)?;
-            let metadata = registry.get_metadata(name).ok_or_else(|| {
-                crate::Error::InvalidEffect(format!("Effect not found: {}", name))
-            })?;
+        let effect = self.build_effect(Some(id), name, &params)?;
+        self.effects.push(effect);
+        Ok(self.effects.len() - 1)
+    }
 
-            // Check if this is a sidechain effect and build sidechain processor
-            let sidechain_processor =
-                super::sidechain::build_sidechain_effect(name, &params, self.sample_rate as f32);
-
-            let effect = Effect {
-                id: Some(id),
-                name: name.to_string(),
-                controls,
-                processor,
-                sidechain_processor,
-                latency_samples: metadata.latency_samples,
-                bypassed: false,
-                muted: false,
-                last_input_levels: (0.0, 0.0, 0.0, 0.0),
-                last_output_levels: (0.0, 0.0, 0.0, 0.0),
-                input_level_buffer: Vec::with_capacity(2048), // ~43ms at 48kHz
-                output_level_buffer: Vec::with_capacity(2048), // ~43ms at 48kHz
-                cpu_meter: CpuMeter::new(self.sample_rate),
-            };
-            self.effects.push(effect);
-            Ok(self.effects.len() - 1)
-        } else {
-            Err(crate::Error::InvalidEffect(
-                "No registry available".to_string(),
-            ))
+    /// Add a parallel group that splits the signal into several sub-chains
+    ///
+    /// Every branch receives the same input; their outputs are summed with the
+    /// given linear `gains` (one per branch). The group reports the longest
+    /// branch latency and the summed CPU usage of its branches. Branch gains
+    /// can be changed later via the `gain_<n>` parameters of the group node.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # use std::sync::Arc;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let registry = Arc::new(EffectRegistry::with_builtin());
+    ///
+    /// // Parallel drum compression: dry + heavily compressed
+    /// let dry = EffectChain::with_shared_registry(registry.clone());
+    /// let mut crushed = EffectChain::with_shared_registry(registry.clone());
+    /// crushed.add("compressor", &[("threshold", -30.0), ("ratio", 10.0)])?;
+    ///
+    /// let mut chain = EffectChain::with_shared_registry(registry);
+    /// chain.add_parallel(vec![dry, crushed], &[1.0, 0.5])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_parallel(&mut self, branches: Vec<EffectChain>, gains: &[f32]) -> Result<usize> {
+        let effect = self.build_parallel(None, branches, gains)?;
+        self.effects.push(effect);
+        Ok(self.effects.len() - 1)
+    }
+
+    /// Get the parallel group at an index, if that effect is a group
+    pub fn parallel_group(&self, index: usize) -> Option<&ParallelGroup> {
+        self.effects.get(index).and_then(|e| e.group.as_ref())
+    }
+
+    /// Get mutable access to the parallel group at an index
+    ///
+    /// Latencies changed by editing the branches are picked up at the start
+    /// of the next processing call.
+    pub fn parallel_group_mut(&mut self, index: usize) -> Option<&mut ParallelGroup> {
+        let group = self.effects.get_mut(index).and_then(|e| e.group.as_mut());
+        self.latency_dirty |= group.is_some();
+        group
+    }
+
+    /// Insert an effect at a specific position in the chain
+    ///
+    /// Effects at `index` and after are shifted one position later. An `index`
+    /// equal to the chain length appends the effect.
+    pub fn insert_effect(
+        &mut self,
+        index: usize,
+        name: &str,
+        params: &HashMap<String, f32>,
+    ) -> Result<()> {
+        if index > self.effects.len() {
+            return Err(crate::Error::InvalidEffect(format!(
+                "Effect index {} out of range (chain has {} effects)",
+                index,
+                self.effects.len()
+            )));
+        }
+        let effect = self.build_effect(None, name, params)?;
+        self.soloed = None;
+        self.effects.insert(index, effect);
+        Ok(())
+    }
+
+    /// Move an effect from one position to another
+    ///
+    /// The effect ends up at index `to`; effects in between shift to fill the gap.
+    pub fn move_effect(&mut self, from: usize, to: usize) -> Result<()> {
+        let len = self.effects.len();
+        if from >= len || to >= len {
+            return Err(crate::Error::InvalidEffect(format!(
+                "Cannot move effect {} to {} (chain has {} effects)",
+                from, to, len
+            )));
         }
+        self.soloed = None;
+        let effect = self.effects.remove(from);
+        self.effects.insert(to, effect);
+        Ok(())
     }
 
     /// Find effect index by ID
@@ -232,6 +1077,7 @@ impl EffectChain {
 
     /// Remove effect by ID
     pub fn remove_effect_by_id(&mut self, id: EffectId) -> Option<Effect> {
+        self.soloed = None;
         if let Some(index) = self.find_effect_index(id) {
             Some(self.effects.remove(index))
         } else {
@@ -250,6 +1096,7 @@ impl EffectChain {
 
     /// Reorder effect by ID to new position
     pub fn reorder_effect_by_id(&mut self, id: EffectId, new_index: usize) -> bool {
+        self.soloed = None;
         if let Some(old_index) = self.find_effect_index(id) {
             if new_index < self.effects.len() {
                 let effect = self.effects.remove(old_index);
@@ -264,21 +1111,90 @@ impl EffectChain {
     }
 
     /// Toggle effect bypass by ID
-    pub fn toggle_effect_bypass_by_id(&mut self, _id: EffectId) -> bool {
-        // For now, we don't have per-effect bypass, only chain bypass
-        // This would require adding a bypassed field to Effect struct
-        false
+    pub fn toggle_effect_bypass_by_id(&mut self, id: EffectId) -> bool {
+        if let Some(index) = self.find_effect_index(id) {
+            let effect = &mut self.effects[index];
+            effect.bypassed = !effect.bypassed;
+            true
+        } else {
+            false
+        }
     }
 
     /// Set effect bypass by ID
-    pub fn set_effect_bypass_by_id(&mut self, _id: EffectId, _bypass: bool) -> bool {
-        // For now, we don't have per-effect bypass, only chain bypass
-        // This would require adding a bypassed field to Effect struct
-        false
+    pub fn set_effect_bypass_by_id(&mut self, id: EffectId, bypass: bool) -> bool {
+        if let Some(index) = self.find_effect_index(id) {
+            self.effects[index].bypassed = bypass;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Set effect mute by ID
+    pub fn set_effect_mute_by_id(&mut self, id: EffectId, muted: bool) -> bool {
+        if let Some(index) = self.find_effect_index(id) {
+            self.effects[index].muted = muted;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Register a named sidechain bus, returning its id
+    ///
+    /// Registering a name again returns the existing id.
+    pub fn add_sidechain_bus(&mut self, name: &str) -> BusId {
+        self.sidechain_bus(name).unwrap_or_else(|| {
+            self.sidechain_buses.push(name.to_string());
+            self.sidechain_buses.len() - 1
+        })
+    }
+
+    /// Id of the sidechain bus registered as `name`
+    pub fn sidechain_bus(&self, name: &str) -> Option<BusId> {
+        self.sidechain_buses.iter().position(|bus| bus == name)
+    }
+
+    /// Names of the registered sidechain buses, indexed by id
+    pub fn sidechain_buses(&self) -> &[String] {
+        &self.sidechain_buses
+    }
+
+    /// Key a sidechain-aware effect from `bus` in `process_with_buses` and
+    /// `process_block_with_buses`
+    ///
+    /// Bindings are ignored by `process_with_sidechain` and
+    /// `process_block_with_sidechain`, which key every effect from one signal.
+    ///
+    /// Returns false if no effect has the ID, it is not sidechain-aware or
+    /// the bus is not registered.
+    pub fn bind_sidechain(&mut self, id: EffectId, bus: BusId) -> bool {
+        if bus >= self.sidechain_buses.len() {
+            return false;
+        }
+        match self.find_effect_index(id) {
+            Some(index) if self.effects[index].sidechain_processor.is_some() => {
+                self.effects[index].sidechain_bus = Some(bus);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove an effect's sidechain bus binding by ID
+    pub fn unbind_sidechain(&mut self, id: EffectId) -> bool {
+        if let Some(index) = self.find_effect_index(id) {
+            self.effects[index].sidechain_bus = None;
+            true
+        } else {
+            false
+        }
     }
 
     /// Remove an effect by index
     pub fn remove_effect(&mut self, index: usize) -> bool {
+        self.soloed = None;
         if index < self.effects.len() {
             self.effects.remove(index);
             true
@@ -288,6 +1204,9 @@ impl EffectChain {
     }
 
     /// Set a parameter on an effect in the chain
+    ///
+    /// The change is instant and cancels any ramp started by
+    /// [`set_param_smoothed`](Self::set_param_smoothed) on the same parameter.
     pub fn set_param(&self, effect_index: usize, param_name: &str, value: f32) -> bool {
         if let Some(effect) = self.effects.get(effect_index) {
             effect.controls.set(param_name, value);
@@ -297,6 +1216,101 @@ impl EffectChain {
         }
     }
 
+    /// Ramp a parameter toward `value` over roughly `ms` milliseconds
+    ///
+    /// The ramp is advanced once per processed sample, which avoids the
+    /// zipper noise of instant jumps when automating cutoff, gain and the
+    /// like. `ms` is the time constant of an exponential ramp (the value
+    /// covers ~63% of the distance in `ms`); `DEFAULT_SMOOTHING_MS` (10 ms)
+    /// is a good default. A non-positive `ms` sets the value instantly.
+    ///
+    /// Calling this again while a ramp is running continues from the
+    /// current value. Writing the parameter directly (`set_param` or the
+    /// effect's controls) cancels the ramp.
+    pub fn set_param_smoothed(
+        &mut self,
+        effect_index: usize,
+        param_name: &str,
+        value: f32,
+        ms: f32,
+    ) -> Result<()> {
+        let sample_rate = self.sample_rate as f32;
+        let effect = self.effects.get_mut(effect_index).ok_or_else(|| {
+            crate::Error::InvalidEffect(format!("Effect index {} not found", effect_index))
+        })?;
+        let shared = effect
+            .controls
+            .params
+            .get(param_name)
+            .cloned()
+            .ok_or_else(|| crate::Error::InvalidParameter(param_name.to_string()))?;
+
+        if ms <= 0.0 {
+            effect.ramps.remove(param_name);
+            shared.set_value(value);
+            return Ok(());
+        }
+
+        let current = shared.value();
+        let smoother = SmoothedParam::new(current, ms, sample_rate);
+        smoother.set_target(value);
+        effect.ramps.insert(
+            param_name.to_string(),
+            ParamRamp {
+                shared,
+                smoother,
+                smoothing_ms: ms,
+                target: value,
+                last: current,
+            },
+        );
+        Ok(())
+    }
+
+    /// Morph an effect's parameters between two snapshots
+    ///
+    /// `t = 0.0` applies `params_a`, `t = 1.0` applies `params_b`, and values in
+    /// between interpolate linearly. Parameters present in only one snapshot are
+    /// held at that value for the whole sweep.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # use std::collections::HashMap;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+    /// chain.add("lpf", &[])?;
+    ///
+    /// let closed = HashMap::from([("cutoff".to_string(), 300.0), ("res".to_string(), 0.8)]);
+    /// let open = HashMap::from([("cutoff".to_string(), 8000.0), ("res".to_string(), 0.1)]);
+    /// chain.morph_effect(0, &closed, &open, 0.25)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn morph_effect(
+        &self,
+        index: usize,
+        params_a: &HashMap<String, f32>,
+        params_b: &HashMap<String, f32>,
+        t: f32,
+    ) -> Result<()> {
+        let effect = self.effects.get(index).ok_or_else(|| {
+            crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+        })?;
+
+        let t = t.clamp(0.0, 1.0);
+        for (name, &a) in params_a {
+            let b = params_b.get(name).copied().unwrap_or(a);
+            effect.controls.set(name, a + (b - a) * t);
+        }
+        for (name, &b) in params_b {
+            if !params_a.contains_key(name) {
+                effect.controls.set(name, b);
+            }
+        }
+        Ok(())
+    }
+
     /// Process stereo audio through the entire effect chain
     #[inline]
     pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
@@ -316,41 +1330,141 @@ impl EffectChain {
         right: f32,
         sidechain: Option<(f32, f32)>,
     ) -> (f32, f32) {
+        self.process_frame(left, right, SidechainFrame::Global(sidechain))
+    }
+
+    /// Process stereo audio with a separate sidechain signal per bus
+    ///
+    /// Each sidechain-aware effect bound to a bus with
+    /// [`bind_sidechain`](Self::bind_sidechain) is keyed from that bus's
+    /// (left, right) signal in `buses`. Unbound effects, and effects whose bus
+    /// is missing from `buses`, process normally. Branches of parallel groups
+    /// read the same map with their own bindings.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # use std::collections::HashMap;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+    /// let (gate, comp) = (EffectId::new_v4(), EffectId::new_v4());
+    /// chain
+    ///     .add_with_id(gate, "sidechain_gate", &[])?
+    ///     .add_with_id(comp, "sidechain_compressor", &[])?;
+    /// let hats = chain.add_sidechain_bus("hats");
+    /// let kick = chain.add_sidechain_bus("kick");
+    /// chain.bind_sidechain(gate, hats);
+    /// chain.bind_sidechain(comp, kick);
+    ///
+    /// let buses = HashMap::from([(hats, (0.2, 0.2)), (kick, (0.9, 0.9))]);
+    /// let (left, right) = chain.process_with_buses(0.5, 0.5, &buses);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn process_with_buses(
+        &mut self,
+        left: f32,
+        right: f32,
+        buses: &HashMap<BusId, (f32, f32)>,
+    ) -> (f32, f32) {
+        self.process_frame(left, right, SidechainFrame::Buses(buses))
+    }
+
+    /// Process one frame and feed the master meter
+    #[inline]
+    fn process_frame(&mut self, left: f32, right: f32, sidechain: SidechainFrame) -> (f32, f32) {
+        let output = self.run_with_sidechain(left, right, sidechain);
+        if let Some(master) = &mut self.master {
+            master.push(output.0, output.1);
+        }
+        if let Some(monitor) = &mut self.overload {
+            monitor.observe(&[output.0], &[output.1], &self.effects);
+        }
+        output
+    }
+
+    /// Process one frame without feeding the master meter
+    #[inline]
+    fn run_with_sidechain(
+        &mut self,
+        left: f32,
+        right: f32,
+        sidechain: SidechainFrame,
+    ) -> (f32, f32) {
+        #[cfg(feature = "serde")]
+        if let Some(mut morph) = self.morph.take() {
+            morph.advance(1, &mut self.effects);
+            let (mut out_left, mut out_right) = self.run_with_sidechain(left, right, sidechain);
+            if let MorphKind::Crossfade(previous) = &mut morph.kind {
+                let (old_left, old_right) = previous.process_frame(left, right, sidechain);
+                let (fade_out, fade_in) = ChainMorph::crossfade_gains(morph.progress());
+                out_left = old_left * fade_out + out_left * fade_in;
+                out_right = old_right * fade_out + out_right * fade_in;
+            }
+            if !morph.is_finished() {
+                self.morph = Some(morph);
+            }
+            return (out_left, out_right);
+        }
+
+        if let Some(detector) = &mut self.pitch_detector {
+            detector.push((left + right) * 0.5);
+        }
+
         if self.bypassed || self.effects.is_empty() {
             return (left, right);
         }
+        if self.latency_dirty {
+            self.update_latency();
+        }
 
         let mut current_left = left;
         let mut current_right = right;
+        let compensate = self.latency_compensation;
+        let soloed = self.soloed;
+        let sample_rate = self.sample_rate;
+
+        let bpm = self.bpm;
+
+        for (index, effect) in self.effects.iter_mut().enumerate() {
+            effect.advance_ramps(1);
+            effect.advance_lfos(1, sample_rate, bpm);
+            effect.advance_mix_param(1);
+            effect.advance_ride(1, sample_rate);
+
+            let (muted, bypassed) = match soloed {
+                // Only the soloed effect runs, fed with the chain input
+                Some(solo) if solo != index => continue,
+                // Solo overrides the effect's own mute/bypass
+                Some(_) => (false, false),
+                None => (effect.muted, effect.bypassed),
+            };
 
-        for effect in &mut self.effects {
             // Capture input levels before processing
-            effect
-                .input_level_buffer
-                .push((current_left, current_right));
-            if effect.input_level_buffer.len() > 2048 {
-                effect.input_level_buffer.remove(0);
-            }
-            if effect.input_level_buffer.len() >= 2048 {
-                let (rms_l, rms_r, peak_l, peak_r) =
-                    calculate_buffer_levels(&effect.input_level_buffer);
-                effect.last_input_levels = (rms_l, rms_r, peak_l, peak_r);
-            }
+            effect.meter_input(current_left, current_right);
+            let dry = (current_left, current_right);
 
             // Handle mute: output silence
-            if effect.muted {
+            if muted {
                 current_left = 0.0;
                 current_right = 0.0;
             }
             // Handle bypass: skip processing
-            else if !effect.bypassed {
+            else if !bypassed {
                 // Start CPU timing
                 let start = effect.cpu_meter.start_timing();
 
+                if let Some(group) = &mut effect.group {
+                    // Parallel group: split into branches and sum
+                    (current_left, current_right) =
+                        group.process(current_left, current_right, sidechain);
+                }
                 // Check if this effect has sidechain processing and we have sidechain data
-                if let (Some(ref mut sc_processor), Some((sc_left, sc_right))) =
-                    (&mut effect.sidechain_processor, sidechain)
-                {
+                else if let (Some(ref mut sc_processor), Some((sc_left, sc_right))) = (
+                    &mut effect.sidechain_processor,
+                    sidechain.for_effect(effect.sidechain_bus),
+                ) {
                     // Use sidechain-aware processing
                     (current_left, current_right) = sc_processor.process_with_sidechain(
                         current_left,
@@ -366,24 +1480,376 @@ impl EffectChain {
 
                 // Stop CPU timing
                 effect.cpu_meter.stop_timing(start, 1);
+
+                // Blend with the (latency-aligned) dry signal
+                (current_left, current_right) =
+                    effect.blend_dry(dry, (current_left, current_right), compensate);
+
+                // Apply output gain trim and ride
+                let gain = effect.output_gain() * effect.ride_gains().1;
+                current_left *= gain;
+                current_right *= gain;
             }
             // If bypassed, audio passes through unchanged
 
             // Capture output levels after processing
-            effect
-                .output_level_buffer
-                .push((current_left, current_right));
-            if effect.output_level_buffer.len() > 2048 {
-                effect.output_level_buffer.remove(0);
+            effect.meter_output(current_left, current_right);
+        }
+
+        (current_left, current_right)
+    }
+
+    /// Process a block of stereo audio through the effect chain
+    ///
+    /// Each effect runs over the block with `AudioUnit::process`, which avoids
+    /// per-sample dispatch. Blocks of any length are accepted and processed in
+    /// chunks of up to `MAX_BUFFER_SIZE` frames. Only the first
+    /// `min(in_l.len(), in_r.len(), out_l.len(), out_r.len())` frames are processed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+    /// chain.add("lpf", &[("cutoff", 2000.0)])?;
+    ///
+    /// let input = vec![0.5; 512];
+    /// let (mut out_l, mut out_r) = (vec![0.0; 512], vec![0.0; 512]);
+    /// chain.process_block(&input, &input, &mut out_l, &mut out_r);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn process_block(
+        &mut self,
+        in_l: &[f32],
+        in_r: &[f32],
+        out_l: &mut [f32],
+        out_r: &mut [f32],
+    ) {
+        self.process_block_with_sidechain(in_l, in_r, out_l, out_r, None);
+    }
+
+    /// Process a block of stereo audio with an optional sidechain block
+    ///
+    /// The sidechain slices must be at least as long as the processed block.
+    /// Sidechain-aware effects receive the sidechain through
+    /// `SidechainAwareEffect::process_block_with_sidechain`; all other effects
+    /// use regular block processing.
+    pub fn process_block_with_sidechain(
+        &mut self,
+        in_l: &[f32],
+        in_r: &[f32],
+        out_l: &mut [f32],
+        out_r: &mut [f32],
+        sidechain: Option<(&[f32], &[f32])>,
+    ) {
+        self.process_block_inner(in_l, in_r, out_l, out_r, SidechainBlock::Global(sidechain));
+    }
+
+    /// Process a block of stereo audio with a separate sidechain block per bus
+    ///
+    /// The block counterpart of [`process_with_buses`](Self::process_with_buses):
+    /// each effect bound to a bus with [`bind_sidechain`](Self::bind_sidechain)
+    /// is keyed from that bus's (left, right) slices in `buses`. Unbound
+    /// effects, and effects whose bus is missing from `buses` or shorter than
+    /// the processed block, use regular block processing.
+    pub fn process_block_with_buses(
+        &mut self,
+        in_l: &[f32],
+        in_r: &[f32],
+        out_l: &mut [f32],
+        out_r: &mut [f32],
+        buses: &HashMap<BusId, (&[f32], &[f32])>,
+    ) {
+        let len = in_l.len().min(in_r.len()).min(out_l.len()).min(out_r.len());
+        let sidechain = SidechainBlock::Buses {
+            buses,
+            start: 0,
+            end: len,
+        };
+        self.process_block_inner(in_l, in_r, out_l, out_r, sidechain);
+    }
+
+    /// Process a block and feed the master meter
+    fn process_block_inner(
+        &mut self,
+        in_l: &[f32],
+        in_r: &[f32],
+        out_l: &mut [f32],
+        out_r: &mut [f32],
+        sidechain: SidechainBlock,
+    ) {
+        let len = in_l.len().min(in_r.len()).min(out_l.len()).min(out_r.len());
+        self.run_block_with_sidechain(in_l, in_r, out_l, out_r, sidechain);
+        if let Some(master) = &mut self.master {
+            master.push_block(&out_l[..len], &out_r[..len]);
+        }
+        if let Some(monitor) = &mut self.overload {
+            monitor.observe(&out_l[..len], &out_r[..len], &self.effects);
+        }
+    }
+
+    /// Process a block without feeding the master meter
+    fn run_block_with_sidechain(
+        &mut self,
+        in_l: &[f32],
+        in_r: &[f32],
+        out_l: &mut [f32],
+        out_r: &mut [f32],
+        sidechain: SidechainBlock,
+    ) {
+        let len = in_l.len().min(in_r.len()).min(out_l.len()).min(out_r.len());
+
+        #[cfg(feature = "serde")]
+        if let Some(morph) = self.morph.take() {
+            self.process_block_morphing(morph, in_l, in_r, out_l, out_r, sidechain, len);
+            return;
+        }
+
+        if let Some(detector) = &mut self.pitch_detector {
+            for (&l, &r) in in_l[..len].iter().zip(&in_r[..len]) {
+                detector.push((l + r) * 0.5);
             }
-            if effect.output_level_buffer.len() >= 2048 {
-                let (rms_l, rms_r, peak_l, peak_r) =
-                    calculate_buffer_levels(&effect.output_level_buffer);
-                effect.last_output_levels = (rms_l, rms_r, peak_l, peak_r);
+        }
+
+        out_l[..len].copy_from_slice(&in_l[..len]);
+        out_r[..len].copy_from_slice(&in_r[..len]);
+
+        if self.bypassed || self.effects.is_empty() {
+            return;
+        }
+        if self.latency_dirty {
+            self.update_latency();
+        }
+
+        let mut offset = 0;
+        while offset < len {
+            let end = (offset + fundsp::MAX_BUFFER_SIZE).min(len);
+            self.process_chunk(
+                &mut out_l[offset..end],
+                &mut out_r[offset..end],
+                sidechain.slice(offset, end),
+            );
+            offset = end;
+        }
+    }
+
+    /// Render input audio offline into new planar (left, right) buffers
+    ///
+    /// Runs the block path over `min(left.len(), right.len())` frames at the
+    /// current sample rate.
+    pub fn render(&mut self, left: &[f32], right: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let frames = left.len().min(right.len());
+        let mut out_l = vec![0.0; frames];
+        let mut out_r = vec![0.0; frames];
+        self.process_block(left, right, &mut out_l, &mut out_r);
+        (out_l, out_r)
+    }
+
+    /// Render input audio offline at `sample_rate`
+    ///
+    /// Switches the chain to `sample_rate` first if it differs.
+    pub fn render_to_buffer(
+        &mut self,
+        left: &[f32],
+        right: &[f32],
+        sample_rate: f64,
+    ) -> RenderBuffer {
+        if self.sample_rate != sample_rate {
+            self.set_sample_rate(sample_rate);
+        }
+        let (out_l, out_r) = self.render(left, right);
+        RenderBuffer::new(out_l, out_r, sample_rate)
+    }
+
+    /// Block processing while a preset morph is running
+    ///
+    /// The block is processed in chunks so the morph advances smoothly; once
+    /// it finishes the rest of the block is processed normally.
+    #[cfg(feature = "serde")]
+    #[allow(clippy::too_many_arguments)]
+    fn process_block_morphing(
+        &mut self,
+        mut morph: ChainMorph,
+        in_l: &[f32],
+        in_r: &[f32],
+        out_l: &mut [f32],
+        out_r: &mut [f32],
+        sidechain: SidechainBlock,
+        len: usize,
+    ) {
+        let mut offset = 0;
+        while offset < len && !morph.is_finished() {
+            let end = (offset + fundsp::MAX_BUFFER_SIZE).min(len);
+            let size = end - offset;
+            let elapsed = morph.elapsed;
+            morph.advance(size, &mut self.effects);
+
+            let sidechain_chunk = sidechain.slice(offset, end);
+            self.run_block_with_sidechain(
+                &in_l[offset..end],
+                &in_r[offset..end],
+                &mut out_l[offset..end],
+                &mut out_r[offset..end],
+                sidechain_chunk,
+            );
+
+            if let MorphKind::Crossfade(previous) = &mut morph.kind {
+                let mut old_l = [0.0; fundsp::MAX_BUFFER_SIZE];
+                let mut old_r = [0.0; fundsp::MAX_BUFFER_SIZE];
+                previous.process_block_inner(
+                    &in_l[offset..end],
+                    &in_r[offset..end],
+                    &mut old_l[..size],
+                    &mut old_r[..size],
+                    sidechain_chunk,
+                );
+                for i in 0..size {
+                    let progress = ((elapsed + i + 1) as f32 / morph.length as f32).min(1.0);
+                    let (fade_out, fade_in) = ChainMorph::crossfade_gains(progress);
+                    out_l[offset + i] = old_l[i] * fade_out + out_l[offset + i] * fade_in;
+                    out_r[offset + i] = old_r[i] * fade_out + out_r[offset + i] * fade_in;
+                }
             }
+            offset = end;
         }
 
-        (current_left, current_right)
+        if !morph.is_finished() {
+            self.morph = Some(morph);
+        }
+        if offset < len {
+            self.run_block_with_sidechain(
+                &in_l[offset..len],
+                &in_r[offset..len],
+                &mut out_l[offset..len],
+                &mut out_r[offset..len],
+                sidechain.slice(offset, len),
+            );
+        }
+    }
+
+    /// Process up to `MAX_BUFFER_SIZE` frames in place
+    fn process_chunk(&mut self, left: &mut [f32], right: &mut [f32], sidechain: SidechainBlock) {
+        let size = left.len();
+        let input = &mut self.block_input;
+        let output = &mut self.block_output;
+        let compensate = self.latency_compensation;
+        let mut dry_l = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let mut dry_r = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let soloed = self.soloed;
+        let sample_rate = self.sample_rate;
+
+        let bpm = self.bpm;
+
+        for (index, effect) in self.effects.iter_mut().enumerate() {
+            effect.advance_ramps(size);
+            effect.advance_lfos(size, sample_rate, bpm);
+            effect.advance_mix_param(size);
+            effect.advance_ride(size, sample_rate);
+
+            let (muted, bypassed) = match soloed {
+                Some(solo) if solo != index => continue,
+                Some(_) => (false, false),
+                None => (effect.muted, effect.bypassed),
+            };
+
+            // Capture input levels before processing
+            for i in 0..size {
+                effect.meter_input(left[i], right[i]);
+            }
+            dry_l[..size].copy_from_slice(left);
+            dry_r[..size].copy_from_slice(right);
+
+            if muted {
+                left.fill(0.0);
+                right.fill(0.0);
+            } else if !bypassed {
+                let start = effect.cpu_meter.start_timing();
+
+                if let Some(group) = &mut effect.group {
+                    group.process_chunk(left, right, sidechain);
+                } else if let (Some(sc_processor), Some((sc_left, sc_right))) = (
+                    &mut effect.sidechain_processor,
+                    sidechain.for_effect(effect.sidechain_bus),
+                ) {
+                    let mut in_l = [0.0; fundsp::MAX_BUFFER_SIZE];
+                    let mut in_r = [0.0; fundsp::MAX_BUFFER_SIZE];
+                    in_l[..size].copy_from_slice(left);
+                    in_r[..size].copy_from_slice(right);
+                    sc_processor.process_block_with_sidechain(
+                        &in_l[..size],
+                        &in_r[..size],
+                        sc_left,
+                        sc_right,
+                        left,
+                        right,
+                    );
+                } else {
+                    for i in 0..size {
+                        input.set_f32(0, i, left[i]);
+                        input.set_f32(1, i, right[i]);
+                    }
+                    effect
+                        .processor
+                        .process(size, &input.buffer_ref(), &mut output.buffer_mut());
+                    for i in 0..size {
+                        left[i] = output.at_f32(0, i);
+                        right[i] = output.at_f32(1, i);
+                    }
+                }
+
+                effect.cpu_meter.stop_timing(start, size);
+
+                for i in 0..size {
+                    (left[i], right[i]) =
+                        effect.blend_dry((dry_l[i], dry_r[i]), (left[i], right[i]), compensate);
+                }
+
+                let gain = effect.output_gain();
+                let (ride_start, ride_end) = effect.ride_gains();
+                if ride_start != ride_end {
+                    // Ramp across the chunk so the ride has no steps
+                    let step = (ride_end - ride_start) / size as f32;
+                    for i in 0..size {
+                        let gain = gain * (ride_start + step * (i + 1) as f32);
+                        left[i] *= gain;
+                        right[i] *= gain;
+                    }
+                } else if gain * ride_end != 1.0 {
+                    let gain = gain * ride_end;
+                    left.iter_mut().for_each(|x| *x *= gain);
+                    right.iter_mut().for_each(|x| *x *= gain);
+                }
+            }
+
+            // Capture output levels after processing
+            for i in 0..size {
+                effect.meter_output(left[i], right[i]);
+            }
+        }
+    }
+
+    /// Start detecting the fundamental pitch of the chain input
+    ///
+    /// Analysis only: the audio is not affected. The input is mixed to mono
+    /// and analysed with YIN every 1024 samples, see [`PitchDetector`].
+    pub fn enable_pitch_detection(&mut self) {
+        if self.pitch_detector.is_none() {
+            self.pitch_detector = Some(PitchDetector::new(self.sample_rate as f32));
+        }
+    }
+
+    /// Stop pitch detection
+    pub fn disable_pitch_detection(&mut self) {
+        self.pitch_detector = None;
+    }
+
+    /// Latest detected input pitch in Hz
+    ///
+    /// Returns `None` when detection is disabled, for silence, and for
+    /// input without a clear pitch.
+    pub fn detected_pitch(&self) -> Option<f32> {
+        self.pitch_detector.as_ref().and_then(|d| d.pitch())
     }
 
     /// Set bypass state
@@ -396,66 +1862,778 @@ impl EffectChain {
         self.bypassed
     }
 
+    /// Metering key for an effect: its ID if set, otherwise `"{name}_{index}"`
+    fn level_key(index: usize, effect: &Effect) -> String {
+        if let Some(id) = effect.id {
+            format!("{}", id)
+        } else {
+            format!("{}_{}", effect.name, index)
+        }
+    }
+
     /// Get effect levels for metering (effect_id or name -> output levels)
-    /// Returns output levels only; for full metering use worker's EffectMeterData
+    /// Returns output levels only; see [`get_effect_io_levels`](Self::get_effect_io_levels)
+    /// for input and output levels
     pub fn get_effect_levels(&self) -> HashMap<String, (f32, f32, f32, f32)> {
         let mut levels = HashMap::new();
         for (idx, effect) in self.effects.iter().enumerate() {
-            // Use effect ID if available, otherwise use index-based key
-            let key = if let Some(id) = effect.id {
-                format!("{}", id)
-            } else {
-                format!("{}_{}", effect.name, idx)
-            };
-            levels.insert(key, effect.last_output_levels);
+            levels.insert(Self::level_key(idx, effect), effect.last_output_levels);
         }
         levels
     }
-}
 
-impl Default for EffectChain {
-    fn default() -> Self {
-        Self::new()
+    /// Get input and output levels of every effect for metering
+    ///
+    /// Keys are the effect's ID (hyphenated UUID) when it has one, otherwise
+    /// `"{name}_{index}"`, e.g. `"lpf_0"`; the same scheme as
+    /// [`get_effect_levels`](Self::get_effect_levels). Index-based keys
+    /// change when effects are inserted, removed or moved, so give effects
+    /// IDs if meters need to follow them.
+    pub fn get_effect_io_levels(&self) -> HashMap<String, (EffectMeter, EffectMeter)> {
+        self.effects
+            .iter()
+            .enumerate()
+            .map(|(idx, effect)| {
+                (
+                    Self::level_key(idx, effect),
+                    (
+                        effect.last_input_levels.into(),
+                        effect.last_output_levels.into(),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// Start metering the chain output with a [`StereoMeter`]
+    ///
+    /// Metering is off by default so chains that don't display levels don't
+    /// pay for it. Once enabled, the output is metered whatever the chain
+    /// contains, including when it is empty or bypassed.
+    pub fn enable_master_meter(&mut self) {
+        if self.master.is_none() {
+            self.master = Some(StereoMeter::default());
+        }
     }
-}
 
-/// Calculate RMS and peak levels from a buffer of stereo samples
-fn calculate_buffer_levels(buffer: &[(f32, f32)]) -> (f32, f32, f32, f32) {
-    if buffer.is_empty() {
-        return (0.0, 0.0, 0.0, 0.0);
+    /// Stop metering the chain output
+    pub fn disable_master_meter(&mut self) {
+        self.master = None;
     }
 
-    let mut sum_sq_l = 0.0;
-    let mut sum_sq_r = 0.0;
-    let mut peak_l = 0.0;
-    let mut peak_r = 0.0;
+    /// Get the RMS and sample peak levels of the chain output
+    ///
+    /// Returns `None` when the master meter is disabled.
+    pub fn master_meter(&self) -> Option<EffectMeter> {
+        self.master.as_ref().map(|master| master.levels())
+    }
 
-    for &(left, right) in buffer {
-        let abs_l = left.abs();
-        let abs_r = right.abs();
+    /// Check whether the chain output has clipped since the last clip reset
+    ///
+    /// Always false when the master meter is disabled.
+    pub fn master_clipped(&self) -> bool {
+        self.master
+            .as_ref()
+            .is_some_and(|master| master.is_clipped())
+    }
 
-        sum_sq_l += left * left;
-        sum_sq_r += right * right;
+    /// Clear the master clip indicator
+    pub fn reset_master_clip(&mut self) {
+        if let Some(master) = &mut self.master {
+            master.reset_clip();
+        }
+    }
 
-        peak_l = peak_l.max(abs_l);
-        peak_r = peak_r.max(abs_r);
+    /// Get notified when the output clips or an effect overloads the CPU
+    ///
+    /// The chain checks every 1024 samples whether the output peak went over
+    /// 1.0 and whether any effect used more than 80% CPU (see
+    /// [`has_overload`](Self::has_overload)). After an event fires, the same
+    /// kind of event stays quiet for the next 16 checks, so sustained
+    /// clipping reports a few times a second rather than every sample.
+    ///
+    /// The callback runs on the audio thread, inside `process`: it must be
+    /// quick and must not allocate, block or lock. Hand events to another
+    /// thread through a lock-free channel or atomics.
+    pub fn set_overload_callback(&mut self, callback: OverloadCallback) {
+        self.overload = Some(OverloadMonitor::new(callback));
     }
 
-    let count = buffer.len() as f32;
-    let rms_l = (sum_sq_l / count).sqrt();
-    let rms_r = (sum_sq_r / count).sqrt();
+    /// Remove the overload callback
+    pub fn clear_overload_callback(&mut self) {
+        self.overload = None;
+    }
 
-    (rms_l, rms_r, peak_l, peak_r)
+    /// Suggest level trims for effects whose metered levels are off
+    ///
+    /// Uses the levels captured while processing (see
+    /// [`get_effect_io_levels`](Self::get_effect_io_levels)), so run some
+    /// representative audio through the chain first. A stage is too hot when
+    /// its peak goes over 0 dBFS, where nonlinear effects clip and distort,
+    /// and too quiet when its peak stays below -40 dBFS. Each suggestion's
+    /// trim brings the peak to -6 dBFS. Silent, muted and bypassed effects
+    /// are skipped, and an output is only checked if its input was fine.
+    ///
+    /// Each joint between two effects is reported once, against the output
+    /// of the effect feeding it, since that is where the level is set. Input
+    /// issues are only reported for the first effect, which sees the chain
+    /// input.
+    pub fn gain_staging_report(&self) -> Vec<GainSuggestion> {
+        let mut suggestions = Vec::new();
+        let mut upstream = false;
+        for (index, effect) in self.effects.iter().enumerate() {
+            if effect.muted || effect.bypassed {
+                continue;
+            }
+            let input = EffectMeter::from(effect.last_input_levels).peak_db();
+            let output = EffectMeter::from(effect.last_output_levels).peak_db();
+            let issue = match GainIssue::classify(input) {
+                Some(issue) if !upstream => Some((issue, input)),
+                // Already reported as the previous effect's output
+                Some(_) => None,
+                None if input > STAGING_SILENCE_DB => {
+                    GainIssue::classify(output).map(|issue| (issue.at_output(), output))
+                }
+                None => None,
+            };
+            upstream = true;
+            if let Some((issue, peak_db)) = issue {
+                suggestions.push(GainSuggestion {
+                    index,
+                    name: effect.name.clone(),
+                    issue,
+                    peak_db,
+                    trim_db: STAGING_TARGET_DB - peak_db,
+                });
+            }
+        }
+        suggestions
+    }
 }
 
-// =============================================================================
-// New DAW-focused APIs
-// =============================================================================
+/// Peaks above this level (dBFS) are too hot
+const STAGING_HOT_DB: f32 = 0.0;
+/// Peaks below this level (dBFS) are too quiet
+const STAGING_QUIET_DB: f32 = -40.0;
+/// Peaks below this level (dBFS) count as silence and are not flagged
+const STAGING_SILENCE_DB: f32 = -90.0;
+/// Peak level (dBFS) suggested trims aim for
+const STAGING_TARGET_DB: f32 = -6.0;
 
-impl EffectChain {
-    /// Bypass a specific effect by index (passes audio through unchanged)
-    pub fn bypass_effect(&mut self, index: usize, bypassed: bool) -> Result<()> {
-        self.effects
+/// Level problem found by [`EffectChain::gain_staging_report`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GainIssue {
+    /// The chain input drives the first effect too hard; trim it down
+    InputTooHot,
+    /// The chain input gives the first effect too little signal; trim it up
+    InputTooQuiet,
+    /// The effect's output is too hot; trim its output down
+    OutputTooHot,
+    /// The effect's output is too quiet; trim its output up
+    OutputTooQuiet,
+}
+
+impl GainIssue {
+    /// Input issue for a peak level, if any
+    fn classify(peak_db: f32) -> Option<Self> {
+        if peak_db > STAGING_HOT_DB {
+            Some(Self::InputTooHot)
+        } else if peak_db < STAGING_QUIET_DB && peak_db > STAGING_SILENCE_DB {
+            Some(Self::InputTooQuiet)
+        } else {
+            None
+        }
+    }
+
+    /// The same issue on the output side
+    fn at_output(self) -> Self {
+        match self {
+            Self::InputTooHot => Self::OutputTooHot,
+            Self::InputTooQuiet => Self::OutputTooQuiet,
+            other => other,
+        }
+    }
+}
+
+/// A suggested level trim for one effect in a chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct GainSuggestion {
+    /// Index of the effect in the chain
+    pub index: usize,
+    /// Name of the effect
+    pub name: String,
+    /// What is wrong, and whether to trim the input or output
+    pub issue: GainIssue,
+    /// Measured peak level in dBFS
+    pub peak_db: f32,
+    /// Suggested trim in dB (negative = reduce)
+    pub trim_db: f32,
+}
+
+/// CPU usage of one effect in a [`ChainCpuReport`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectCpuEntry {
+    /// Name of the effect
+    pub name: String,
+    /// ID of the effect, if it has one
+    pub id: Option<EffectId>,
+    /// Category from the effect's metadata tags, if any
+    pub category: Option<String>,
+    /// CPU usage as a percentage of real time (0-100%)
+    pub percent: f64,
+    /// Whether the effect uses more than 80% CPU
+    pub overloaded: bool,
+    /// Latency introduced by the effect (in samples)
+    pub latency_samples: usize,
+}
+
+/// CPU usage of a whole chain, from [`EffectChain::cpu_report_detailed`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChainCpuReport {
+    /// Total CPU usage of the non-bypassed effects (0-100%)
+    pub total_percent: f64,
+    /// Whether any effect uses more than 80% CPU
+    pub overloaded: bool,
+    /// Per-effect usage in chain order
+    pub effects: Vec<EffectCpuEntry>,
+}
+
+impl ChainCpuReport {
+    /// Category name used for effects without one
+    pub const UNCATEGORIZED: &'static str = "uncategorized";
+
+    /// Total CPU percentage per effect category
+    ///
+    /// Effects without a category are grouped under
+    /// [`UNCATEGORIZED`](Self::UNCATEGORIZED).
+    pub fn by_category(&self) -> BTreeMap<String, f64> {
+        let mut totals = BTreeMap::new();
+        for entry in &self.effects {
+            let category = entry.category.as_deref().unwrap_or(Self::UNCATEGORIZED);
+            *totals.entry(category.to_string()).or_insert(0.0) += entry.percent;
+        }
+        totals
+    }
+}
+
+impl Default for EffectChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Callback for [`EffectChain::set_overload_callback`]
+pub type OverloadCallback = Box<dyn FnMut(OverloadEvent<'_>) + Send>;
+
+/// An overload reported to the chain's overload callback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverloadEvent<'a> {
+    /// An effect used more than 80% of the available CPU time
+    Cpu {
+        /// Index of the effect in the chain
+        index: usize,
+        /// Name of the effect
+        name: &'a str,
+        /// Measured CPU usage (1.0 = 100%)
+        usage: f64,
+    },
+    /// The chain output went over full scale
+    Clip {
+        /// Highest absolute output sample since the previous check
+        peak: f32,
+    },
+}
+
+/// Samples between overload checks
+const OVERLOAD_CHECK_INTERVAL: usize = 1024;
+/// Checks an event kind stays quiet after firing
+const OVERLOAD_HOLDOFF_CHECKS: u32 = 16;
+/// CPU usage above which an effect is overloaded
+const CPU_OVERLOAD: f64 = 0.8;
+
+/// Debounced clip and CPU overload detection for the overload callback
+struct OverloadMonitor {
+    callback: OverloadCallback,
+    /// Samples until the next check
+    countdown: usize,
+    /// Highest absolute output sample since the last check
+    peak: f32,
+    /// Checks left before clip events may fire again
+    clip_holdoff: u32,
+    /// Checks left before CPU events may fire again
+    cpu_holdoff: u32,
+}
+
+impl OverloadMonitor {
+    fn new(callback: OverloadCallback) -> Self {
+        Self {
+            callback,
+            countdown: OVERLOAD_CHECK_INTERVAL,
+            peak: 0.0,
+            clip_holdoff: 0,
+            cpu_holdoff: 0,
+        }
+    }
+
+    /// Track output samples, checking for overloads every check interval
+    #[inline]
+    fn observe(&mut self, left: &[f32], right: &[f32], effects: &[Effect]) {
+        for (&l, &r) in left.iter().zip(right) {
+            self.peak = self.peak.max(l.abs()).max(r.abs());
+            self.countdown -= 1;
+            if self.countdown == 0 {
+                self.check(effects);
+            }
+        }
+    }
+
+    fn check(&mut self, effects: &[Effect]) {
+        self.countdown = OVERLOAD_CHECK_INTERVAL;
+
+        if self.clip_holdoff > 0 {
+            self.clip_holdoff -= 1;
+        } else if self.peak > 1.0 {
+            (self.callback)(OverloadEvent::Clip { peak: self.peak });
+            self.clip_holdoff = OVERLOAD_HOLDOFF_CHECKS;
+        }
+        self.peak = 0.0;
+
+        if self.cpu_holdoff > 0 {
+            self.cpu_holdoff -= 1;
+            return;
+        }
+        for (index, effect) in effects.iter().enumerate() {
+            let usage = effect.cpu_usage();
+            if usage > CPU_OVERLOAD {
+                (self.callback)(OverloadEvent::Cpu {
+                    index,
+                    name: &effect.name,
+                    usage,
+                });
+                self.cpu_holdoff = OVERLOAD_HOLDOFF_CHECKS;
+            }
+        }
+    }
+}
+
+/// Stereo delay used for latency compensation
+///
+/// The ring buffer only grows; shortening the delay, or lengthening it
+/// within the buffer, just moves the read position.
+struct DelayLine {
+    /// Ring buffer, as long as the longest delay set so far
+    buffer: Vec<(f32, f32)>,
+    /// Delay in samples
+    length: usize,
+    /// Next write position in `buffer`
+    pos: usize,
+}
+
+impl DelayLine {
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![(0.0, 0.0); length],
+            length,
+            pos: 0,
+        }
+    }
+
+    /// Change the delay length
+    ///
+    /// Growing past the buffer reallocates and clears it, which happens
+    /// only when latencies are refreshed, not while processing.
+    fn set_length(&mut self, length: usize) {
+        if length > self.buffer.len() {
+            self.buffer = vec![(0.0, 0.0); length];
+            self.pos = 0;
+        }
+        self.length = length;
+    }
+
+    /// Fill the delay with silence
+    fn clear(&mut self) {
+        self.buffer.fill((0.0, 0.0));
+        self.pos = 0;
+    }
+
+    /// Push a sample and return the one from `length` samples ago
+    #[inline]
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.length == 0 {
+            return (left, right);
+        }
+        let size = self.buffer.len();
+        let read = (self.pos + size - self.length) % size;
+        let out = self.buffer[read];
+        self.buffer[self.pos] = (left, right);
+        self.pos += 1;
+        if self.pos == size {
+            self.pos = 0;
+        }
+        out
+    }
+}
+
+/// An in-progress `morph_to` transition
+#[cfg(feature = "serde")]
+struct ChainMorph {
+    /// Total length in samples
+    length: usize,
+    /// Samples processed so far
+    elapsed: usize,
+    kind: MorphKind,
+}
+
+#[cfg(feature = "serde")]
+enum MorphKind {
+    /// Same structure: parameters and per-effect gains as (start, end)
+    Params {
+        params: Vec<(Shared, f32, f32)>,
+        gains: Vec<(f32, f32)>,
+    },
+    /// Different structure: the previous chain, faded out as the new one fades in
+    Crossfade(Box<EffectChain>),
+}
+
+#[cfg(feature = "serde")]
+impl ChainMorph {
+    /// Progress from 0.0 to 1.0
+    fn progress(&self) -> f32 {
+        if self.length == 0 {
+            1.0
+        } else {
+            self.elapsed as f32 / self.length as f32
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.length
+    }
+
+    /// Advance by `samples` and apply interpolated parameters to `effects`
+    fn advance(&mut self, samples: usize, effects: &mut [Effect]) {
+        self.elapsed = (self.elapsed + samples).min(self.length);
+        if let MorphKind::Params { params, gains } = &self.kind {
+            let t = self.progress();
+            for (shared, start, end) in params {
+                shared.set_value(start + (end - start) * t);
+            }
+            for (effect, &(start, end)) in effects.iter_mut().zip(gains) {
+                effect.gain_db = start + (end - start) * t;
+            }
+        }
+    }
+
+    /// Equal-power (old, new) gains at `progress`
+    #[inline]
+    fn crossfade_gains(progress: f32) -> (f32, f32) {
+        let angle = progress * std::f32::consts::FRAC_PI_2;
+        (angle.cos(), angle.sin())
+    }
+}
+
+/// A parameter ramping toward a target value
+struct ParamRamp {
+    /// The parameter being ramped
+    shared: Shared,
+    /// Smoothing state
+    smoother: SmoothedParam,
+    /// Smoothing time in milliseconds (kept for sample rate changes)
+    smoothing_ms: f32,
+    /// Value the ramp ends at
+    target: f32,
+    /// Last value written, used to detect external writes
+    last: f32,
+}
+
+impl ParamRamp {
+    /// Advance the ramp, returning false once it has finished or was overridden
+    #[inline]
+    fn advance(&mut self, samples: usize) -> bool {
+        // Someone else wrote the parameter: stop ramping
+        if self.shared.value() != self.last {
+            return false;
+        }
+
+        let mut value = self.smoother.current();
+        for _ in 0..samples {
+            value = self.smoother.next();
+        }
+
+        if self
+            .smoother
+            .is_settled((self.target.abs() * 1e-4).max(1e-6))
+        {
+            self.shared.set_value(self.target);
+            return false;
+        }
+        self.shared.set_value(value);
+        self.last = value;
+        true
+    }
+}
+
+/// Ride levels at or below this are silent
+const RIDE_FLOOR_DB: f32 = -96.0;
+
+/// Output gain automation started by `ride_gain`
+struct GainRide {
+    /// Breakpoints as (seconds, dB), sorted by time
+    points: Vec<(f32, f32)>,
+    /// Time since the ride started in seconds
+    elapsed: f64,
+    /// Linear gain at the start and end of the last advanced span
+    gains: (f32, f32),
+}
+
+impl GainRide {
+    fn new(points: Vec<(f32, f32)>) -> Self {
+        let mut ride = Self {
+            points,
+            elapsed: 0.0,
+            gains: (1.0, 1.0),
+        };
+        let gain = ride.gain_at(0.0);
+        ride.gains = (gain, gain);
+        ride
+    }
+
+    /// Linear gain `time` seconds into the ride
+    fn gain_at(&self, time: f32) -> f32 {
+        let db = match self.points.iter().position(|&(t, _)| t > time) {
+            Some(0) => self.points[0].1,
+            Some(i) => {
+                let (t0, db0) = self.points[i - 1];
+                let (t1, db1) = self.points[i];
+                let (db0, db1) = (db0.max(RIDE_FLOOR_DB), db1.max(RIDE_FLOOR_DB));
+                db0 + (db1 - db0) * (time - t0) / (t1 - t0)
+            }
+            None => self.points[self.points.len() - 1].1,
+        };
+        if db <= RIDE_FLOOR_DB {
+            0.0
+        } else {
+            10.0_f32.powf(db / 20.0)
+        }
+    }
+
+    /// Advance the ride by `samples` samples
+    #[inline]
+    fn advance(&mut self, samples: usize, sample_rate: f64) {
+        self.elapsed += samples as f64 / sample_rate;
+        self.gains = (self.gains.1, self.gain_at(self.elapsed as f32));
+    }
+}
+
+/// RMS and peak levels of a stereo signal
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EffectMeter {
+    /// Left channel RMS level
+    pub rms_l: f32,
+    /// Right channel RMS level
+    pub rms_r: f32,
+    /// Left channel sample peak level
+    pub peak_l: f32,
+    /// Right channel sample peak level
+    pub peak_r: f32,
+}
+
+impl EffectMeter {
+    /// Louder channel's peak in dBFS
+    pub fn peak_db(&self) -> f32 {
+        20.0 * self.peak_l.max(self.peak_r).max(1e-10).log10()
+    }
+}
+
+impl From<(f32, f32, f32, f32)> for EffectMeter {
+    fn from((rms_l, rms_r, peak_l, peak_r): (f32, f32, f32, f32)) -> Self {
+        Self {
+            rms_l,
+            rms_r,
+            peak_l,
+            peak_r,
+        }
+    }
+}
+
+/// Number of samples in the rolling metering window (~43ms at 48kHz)
+const LEVEL_WINDOW: usize = 2048;
+
+/// Stereo RMS and peak-hold meter with a latching clip indicator
+///
+/// Feed it samples with [`push`](Self::push) or [`push_block`](Self::push_block)
+/// and read [`levels`](Self::levels). RMS is taken over a rolling window and
+/// peaks are held for one window length before decaying; levels read zero
+/// until the first window has been filled. The clip indicator latches when a
+/// sample reaches full scale (|x| >= 1.0) and stays on until
+/// [`reset_clip`](Self::reset_clip) or [`reset`](Self::reset).
+///
+/// Peaks are sample peaks: the samples are not oversampled, so inter-sample
+/// peaks of a signal near full scale can read up to a few dB low.
+///
+/// # Example
+/// ```
+/// # use fundsp_rack::prelude::*;
+/// let mut meter = StereoMeter::new(256);
+/// for _ in 0..256 {
+///     meter.push(0.5, -0.5);
+/// }
+/// assert!((meter.levels().rms_l - 0.5).abs() < 1e-6);
+/// assert!(!meter.is_clipped());
+/// ```
+#[derive(Clone)]
+pub struct StereoMeter {
+    /// Rolling RMS and peak measurement
+    meter: LevelMeter,
+    /// Levels at the last completed measurement
+    levels: EffectMeter,
+    /// Whether a sample has reached full scale since the last clip reset
+    clipped: bool,
+}
+
+impl StereoMeter {
+    /// Create a meter with an RMS window and peak hold of `window` samples
+    pub fn new(window: usize) -> Self {
+        Self {
+            meter: LevelMeter::new(window),
+            levels: EffectMeter::default(),
+            clipped: false,
+        }
+    }
+
+    /// Feed one stereo sample
+    #[inline]
+    pub fn push(&mut self, left: f32, right: f32) {
+        if left.abs() >= 1.0 || right.abs() >= 1.0 {
+            self.clipped = true;
+        }
+        if let Some(levels) = self.meter.push(left, right) {
+            self.levels = levels.into();
+        }
+    }
+
+    /// Feed a block of stereo samples
+    pub fn push_block(&mut self, left: &[f32], right: &[f32]) {
+        for (&l, &r) in left.iter().zip(right) {
+            self.push(l, r);
+        }
+    }
+
+    /// Current RMS and held peak levels
+    pub fn levels(&self) -> EffectMeter {
+        self.levels
+    }
+
+    /// Check whether a sample has reached full scale since the last clip reset
+    pub fn is_clipped(&self) -> bool {
+        self.clipped
+    }
+
+    /// Clear the clip indicator
+    pub fn reset_clip(&mut self) {
+        self.clipped = false;
+    }
+
+    /// Clear levels and the clip indicator
+    pub fn reset(&mut self) {
+        self.meter = LevelMeter::new(self.meter.samples.len());
+        self.levels = EffectMeter::default();
+        self.clipped = false;
+    }
+}
+
+impl Default for StereoMeter {
+    fn default() -> Self {
+        Self::new(LEVEL_WINDOW)
+    }
+}
+
+/// Per-sample decay of the peak hold once the hold time has elapsed
+/// (about -60 dB over 150ms at 48kHz)
+const PEAK_DECAY: f32 = 0.999;
+
+/// Rolling stereo RMS and peak meter
+///
+/// RMS is computed over a fixed window using a ring buffer and a running
+/// sum of squares, so each sample costs O(1). Peaks are held for one window
+/// length and then decay exponentially.
+#[derive(Clone)]
+struct LevelMeter {
+    /// Ring buffer of the last `window` samples
+    samples: Vec<(f32, f32)>,
+    /// Next write position in `samples`
+    pos: usize,
+    /// Whether the window has been filled at least once
+    primed: bool,
+    /// Running sum of squares over the window (left, right)
+    sum_sq: (f64, f64),
+    /// Held peak level (left, right)
+    peak: (f32, f32),
+    /// Remaining hold samples before each peak starts decaying (left, right)
+    hold: (usize, usize),
+}
+
+impl LevelMeter {
+    fn new(window: usize) -> Self {
+        Self {
+            samples: vec![(0.0, 0.0); window.max(1)],
+            pos: 0,
+            primed: false,
+            sum_sq: (0.0, 0.0),
+            peak: (0.0, 0.0),
+            hold: (0, 0),
+        }
+    }
+
+    /// Push a stereo sample and return (RMS L, RMS R, Peak L, Peak R)
+    /// once the window has been filled
+    #[inline]
+    fn push(&mut self, left: f32, right: f32) -> Option<(f32, f32, f32, f32)> {
+        let window = self.samples.len();
+        let (old_l, old_r) = std::mem::replace(&mut self.samples[self.pos], (left, right));
+        self.sum_sq.0 += (left as f64) * (left as f64) - (old_l as f64) * (old_l as f64);
+        self.sum_sq.1 += (right as f64) * (right as f64) - (old_r as f64) * (old_r as f64);
+
+        Self::update_peak(&mut self.peak.0, &mut self.hold.0, left.abs(), window);
+        Self::update_peak(&mut self.peak.1, &mut self.hold.1, right.abs(), window);
+
+        self.pos += 1;
+        if self.pos == window {
+            self.pos = 0;
+            self.primed = true;
+        }
+
+        if !self.primed {
+            return None;
+        }
+
+        let count = window as f64;
+        let rms_l = (self.sum_sq.0.max(0.0) / count).sqrt() as f32;
+        let rms_r = (self.sum_sq.1.max(0.0) / count).sqrt() as f32;
+        Some((rms_l, rms_r, self.peak.0, self.peak.1))
+    }
+
+    #[inline]
+    fn update_peak(peak: &mut f32, hold: &mut usize, level: f32, window: usize) {
+        if level >= *peak {
+            *peak = level;
+            *hold = window;
+        } else if *hold > 0 {
+            *hold -= 1;
+        } else {
+            *peak = (*peak * PEAK_DECAY).max(level);
+        }
+    }
+}
+
+// =============================================================================
+// New DAW-focused APIs
+// =============================================================================
+
+impl EffectChain {
+    /// Bypass a specific effect by index (passes audio through unchanged)
+    pub fn bypass_effect(&mut self, index: usize, bypassed: bool) -> Result<()> {
+        self.effects
             .get_mut(index)
             .ok_or_else(|| {
                 crate::Error::InvalidEffect(format!("Effect index {} not found", index))
@@ -464,207 +2642,2345 @@ impl EffectChain {
         Ok(())
     }
 
-    /// Mute a specific effect by index (outputs silence)
-    pub fn mute_effect(&mut self, index: usize, muted: bool) -> Result<()> {
-        self.effects
-            .get_mut(index)
-            .ok_or_else(|| {
-                crate::Error::InvalidEffect(format!("Effect index {} not found", index))
-            })?
-            .muted = muted;
-        Ok(())
+    /// Mute a specific effect by index (outputs silence)
+    pub fn mute_effect(&mut self, index: usize, muted: bool) -> Result<()> {
+        self.effects
+            .get_mut(index)
+            .ok_or_else(|| {
+                crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+            })?
+            .muted = muted;
+        Ok(())
+    }
+
+    /// Set the output gain trim of an effect in dB (0.0 = unity)
+    ///
+    /// The gain is applied after the effect processes and is reflected in its
+    /// output levels. Bypassed effects pass audio through without the trim.
+    pub fn set_effect_gain(&mut self, index: usize, gain_db: f32) -> Result<()> {
+        self.effects
+            .get_mut(index)
+            .ok_or_else(|| {
+                crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+            })?
+            .gain_db = gain_db;
+        Ok(())
+    }
+
+    /// Get the output gain trim of an effect in dB
+    pub fn effect_gain(&self, index: usize) -> Option<f32> {
+        self.effects.get(index).map(|e| e.gain_db)
+    }
+
+    /// Automate the output gain of an effect with a curve of `(seconds, dB)` points
+    ///
+    /// Times are relative to now and must be ascending. The gain is
+    /// interpolated linearly in dB between points, holds the first value
+    /// before the first point and the last value after the last one. Levels
+    /// at or below -96 dB (including `f32::NEG_INFINITY`) are silent, so
+    /// `&[(0.0, f32::NEG_INFINITY), (2.0, 0.0)]` is a two second fade-in.
+    ///
+    /// The ride multiplies the [`set_effect_gain`](Self::set_effect_gain)
+    /// trim and replaces any previous ride. It keeps running while the effect
+    /// is bypassed or muted, but like the trim is only heard when the effect
+    /// is processed.
+    pub fn ride_gain(&mut self, index: usize, curve: &[(f32, f32)]) -> Result<()> {
+        if curve.is_empty() {
+            return Err(crate::Error::InvalidParameter(
+                "gain ride curve is empty".to_string(),
+            ));
+        }
+        let mut previous = 0.0;
+        for &(time, db) in curve {
+            if !time.is_finite() || time < previous {
+                return Err(crate::Error::InvalidValue {
+                    param: "time".to_string(),
+                    value: time,
+                    reason: "ride times must be finite, non-negative and ascending".to_string(),
+                });
+            }
+            if db.is_nan() {
+                return Err(crate::Error::InvalidValue {
+                    param: "gain_db".to_string(),
+                    value: db,
+                    reason: "ride levels must not be NaN".to_string(),
+                });
+            }
+            previous = time;
+        }
+
+        self.effects
+            .get_mut(index)
+            .ok_or_else(|| {
+                crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+            })?
+            .ride = Some(GainRide::new(curve.to_vec()));
+        Ok(())
+    }
+
+    /// Stop the gain ride of an effect, returning it to its static trim
+    pub fn clear_gain_ride(&mut self, index: usize) -> Result<()> {
+        self.effects
+            .get_mut(index)
+            .ok_or_else(|| {
+                crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+            })?
+            .ride = None;
+        Ok(())
+    }
+
+    /// Modulate a parameter of an effect with an LFO
+    ///
+    /// The LFO swings the parameter around its current value and is advanced
+    /// as the chain processes audio (once per sample, or once per block in
+    /// block processing). Pass an [`LFOConfig`](crate::synth::LFOConfig) for
+    /// bipolar modulation or an [`EffectLFO`] for unipolar; configs with
+    /// `sync` set follow the chain tempo (see [`set_bpm`](Self::set_bpm)).
+    ///
+    /// The swing is scaled to the range declared in the effect's metadata;
+    /// parameters without one swing by up to their value (at least 1.0)
+    /// either way. Setting the parameter while the LFO runs moves the value
+    /// it swings around. Attaching a second LFO to the same parameter
+    /// replaces the first.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+    /// chain.add("lpf", &[("cutoff", 1000.0)])?;
+    /// chain.attach_lfo(0, "cutoff", LFOConfig::new(0.5, 0.3, LFOWaveform::Sine))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn attach_lfo(
+        &mut self,
+        index: usize,
+        param: &str,
+        lfo: impl Into<EffectLFO>,
+    ) -> Result<()> {
+        let metadata = self.effects.get(index).and_then(|effect| {
+            self.registry
+                .as_ref()
+                .and_then(|registry| registry.get_metadata(&effect.name))
+        });
+        let effect = self.effects.get_mut(index).ok_or_else(|| {
+            crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+        })?;
+        let shared = effect
+            .controls
+            .params
+            .get(param)
+            .cloned()
+            .ok_or_else(|| crate::Error::InvalidParameter(param.to_string()))?;
+
+        if let Some(position) = effect.lfos.iter().position(|lfo| lfo.param == param) {
+            effect.lfos.remove(position).detach();
+        }
+        let range = metadata
+            .and_then(|metadata| {
+                metadata
+                    .parameters
+                    .iter()
+                    .find(|def| def.name == param)
+                    .map(|def| (def.min, def.max))
+            })
+            .unwrap_or_else(|| {
+                let center = shared.value();
+                let reach = center.abs().max(1.0);
+                (center - reach, center + reach)
+            });
+        effect
+            .lfos
+            .push(AttachedLfo::new(param, lfo.into(), shared, range));
+        Ok(())
+    }
+
+    /// Remove the LFO from a parameter, returning it to its unmodulated value
+    pub fn detach_lfo(&mut self, index: usize, param: &str) -> Result<()> {
+        let effect = self.effects.get_mut(index).ok_or_else(|| {
+            crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+        })?;
+        if let Some(position) = effect.lfos.iter().position(|lfo| lfo.param == param) {
+            effect.lfos.remove(position).detach();
+        }
+        Ok(())
+    }
+
+    /// Get the LFO attached to a parameter of an effect, if any
+    pub fn effect_lfo(&self, index: usize, param: &str) -> Option<EffectLFO> {
+        self.effects
+            .get(index)?
+            .lfos
+            .iter()
+            .find(|lfo| lfo.param == param)
+            .map(|lfo| lfo.lfo)
+    }
+
+    /// Solo an effect for auditioning
+    ///
+    /// While an effect is soloed, the chain input is routed straight into it
+    /// and only its output is heard; all other effects are skipped. The soloed
+    /// effect is processed even if it is muted or bypassed. Solo is cleared by
+    /// [`clear_solo`](Self::clear_solo) and by any change that reorders or
+    /// removes effects.
+    pub fn solo_effect(&mut self, index: usize) -> Result<()> {
+        if index >= self.effects.len() {
+            return Err(crate::Error::InvalidEffect(format!(
+                "Effect index {} not found",
+                index
+            )));
+        }
+        self.soloed = Some(index);
+        Ok(())
+    }
+
+    /// Clear the solo so the whole chain is heard again
+    pub fn clear_solo(&mut self) {
+        self.soloed = None;
+    }
+
+    /// Get the index of the soloed effect, if any
+    pub fn soloed_effect(&self) -> Option<usize> {
+        self.soloed
+    }
+
+    /// Set the dry/wet balance of an effect (0.0 = dry, 1.0 = fully processed)
+    ///
+    /// The blend happens in the chain, so it works for every effect. With
+    /// latency compensation enabled the dry path is delayed to stay aligned
+    /// with the processed signal. Changes glide over the chain's
+    /// [`mix_smoothing`](Self::mix_smoothing) time.
+    pub fn set_effect_mix(&mut self, index: usize, mix: f32) -> Result<()> {
+        self.effects
+            .get_mut(index)
+            .ok_or_else(|| {
+                crate::Error::InvalidEffect(format!("Effect index {} not found", index))
+            })?
+            .mix = mix.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    /// Get the dry/wet balance of an effect
+    pub fn effect_mix(&self, index: usize) -> Option<f32> {
+        self.effects.get(index).map(|e| e.mix)
+    }
+
+    /// Check if an effect is bypassed
+    pub fn is_effect_bypassed(&self, index: usize) -> Option<bool> {
+        self.effects.get(index).map(|e| e.bypassed)
+    }
+
+    /// Check if an effect is muted
+    pub fn is_effect_muted(&self, index: usize) -> Option<bool> {
+        self.effects.get(index).map(|e| e.muted)
+    }
+
+    /// Get total latency of the chain in samples
+    pub fn total_latency(&self) -> usize {
+        self.effects
+            .iter()
+            .filter(|e| !e.bypassed)
+            .map(|e| e.latency())
+            .sum()
+    }
+
+    /// Get latency of a specific effect
+    pub fn effect_latency(&self, index: usize) -> Option<usize> {
+        self.effects.get(index).map(|e| e.latency())
+    }
+
+    /// Read a meter of an effect, such as a compressor's `gain_reduction`
+    pub fn effect_meter(&self, index: usize, name: &str) -> Option<f32> {
+        self.effects
+            .get(index)
+            .and_then(|effect| effect.controls.meter(name))
+    }
+
+    /// Get the memory footprint of the chain's processors in bytes
+    ///
+    /// Sums [`AudioUnit::footprint`] over every effect, including bypassed
+    /// ones and the branches of parallel groups.
+    pub fn total_footprint(&self) -> usize {
+        self.effects
+            .iter()
+            .map(|e| match &e.group {
+                Some(group) => group.branches.iter().map(|b| b.total_footprint()).sum(),
+                None => e.processor.footprint(),
+            })
+            .sum()
+    }
+
+    /// Serialize the chain to JSON
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # let chain = EffectChain::new();
+    /// let json = chain.to_json().unwrap();
+    /// std::fs::write("my_chain.json", json).unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String> {
+        let state = self.to_state();
+        state
+            .to_json()
+            .map_err(|e| crate::Error::SerializationError(e.to_string()))
+    }
+
+    /// Deserialize and load a chain from JSON
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+    /// let json = std::fs::read_to_string("my_chain.json").unwrap();
+    /// chain.from_json(&json).unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_json(&mut self, json: &str) -> Result<()> {
+        let state = ChainState::from_json(json)
+            .map_err(|e| crate::Error::SerializationError(e.to_string()))?;
+        self.from_state(&state)
+    }
+
+    /// Convert to serializable state
+    #[cfg(feature = "serde")]
+    pub fn to_state(&self) -> ChainState {
+        let mut state = ChainState::new(self.sample_rate);
+        state.bypassed = self.bypassed;
+        state.sidechain_buses = self.sidechain_buses.clone();
+
+        for effect in &self.effects {
+            let mut effect_state = EffectState::new(effect.name.clone());
+            effect_state.id = effect.id;
+            effect_state.sidechain_bus = effect
+                .sidechain_bus
+                .and_then(|bus| self.sidechain_buses.get(bus).cloned());
+            effect_state.bypassed = effect.bypassed;
+            effect_state.muted = effect.muted;
+            effect_state.mix = (effect.mix < 1.0).then_some(effect.mix);
+            effect_state.gain_db = effect.gain_db;
+
+            // Extract parameters from controls
+            for (key, shared) in &effect.controls.params {
+                effect_state.set_param(key, shared.value());
+            }
+
+            // Modulated parameters are saved at their unmodulated value
+            for lfo in &effect.lfos {
+                effect_state.set_param(&lfo.param, lfo.center);
+                effect_state.lfos.push(LfoAttachment {
+                    param: lfo.param.clone(),
+                    lfo: lfo.lfo,
+                });
+            }
+
+            // Nested branch chains for parallel groups
+            if let Some(group) = &effect.group {
+                effect_state.branches = group.branches.iter().map(|b| b.to_state()).collect();
+            }
+
+            state.add_effect(effect_state);
+        }
+
+        state
+    }
+
+    /// Load from serializable state
+    ///
+    /// The state's sidechain buses are added to the chain's own, so bus ids
+    /// handed out earlier stay valid, and saved bindings are restored by name.
+    #[cfg(feature = "serde")]
+    pub fn from_state(&mut self, state: &ChainState) -> Result<()> {
+        // Clear existing effects
+        self.effects.clear();
+        self.soloed = None;
+        self.morph = None;
+        self.bypassed = state.bypassed;
+        self.set_sample_rate(state.sample_rate);
+        for bus in &state.sidechain_buses {
+            self.add_sidechain_bus(bus);
+        }
+
+        // Rebuild effects from state
+        for effect_state in &state.effects {
+            let index = if !effect_state.branches.is_empty() {
+                let effect = self.parallel_from_state(effect_state)?;
+                self.effects.push(effect);
+                self.effects.len() - 1
+            } else if let Some(id) = effect_state.id {
+                self.add_effect_with_id(id, &effect_state.name, effect_state.parameters.clone())?
+            } else {
+                self.add_effect(&effect_state.name, &effect_state.parameters)?
+            };
+
+            // Restore bypass/mute/mix/gain state and the sidechain binding
+            let bus = effect_state
+                .sidechain_bus
+                .as_deref()
+                .map(|name| self.add_sidechain_bus(name));
+            if let Some(effect) = self.effects.get_mut(index) {
+                effect.bypassed = effect_state.bypassed;
+                effect.muted = effect_state.muted;
+                effect.mix = effect_state.mix.map_or(1.0, |mix| mix.clamp(0.0, 1.0));
+                effect.gain_db = effect_state.gain_db;
+                if effect.sidechain_processor.is_some() {
+                    effect.sidechain_bus = bus;
+                }
+            }
+            for attachment in &effect_state.lfos {
+                self.attach_lfo(index, &attachment.param, attachment.lfo)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the chain's effects with those of a preset
+    ///
+    /// The chain is rebuilt as by [`from_state`](Self::from_state), so
+    /// chain-level bypass is cleared. It switches to the preset's sample
+    /// rate if the preset has one and keeps its own otherwise.
+    ///
+    /// The preset is [validated](EffectPreset::validate) first; if it has a
+    /// problem, the first one is returned and the chain is left untouched.
+    #[cfg(feature = "serde")]
+    pub fn load_preset(&mut self, preset: &EffectPreset) -> Result<()> {
+        let registry = self
+            .registry
+            .as_ref()
+            .ok_or_else(|| crate::Error::InvalidEffect("No registry available".to_string()))?;
+        let mut preset = preset.clone();
+        preset.migrate();
+        if let Err(mut errors) = preset.validate(registry) {
+            return Err(errors.swap_remove(0).error);
+        }
+
+        let mut state = ChainState::new(preset.sample_rate.unwrap_or(self.sample_rate));
+        state.version = preset.version;
+        state.effects = preset.effects;
+        self.from_state(&state)
+    }
+
+    /// Capture the chain as a preset named `name`
+    ///
+    /// Effects are stored as by [`to_state`](Self::to_state). The preset
+    /// has no sample rate, so loading it leaves the target chain's rate alone.
+    #[cfg(feature = "serde")]
+    pub fn save_as_preset(&self, name: impl Into<String>) -> EffectPreset {
+        let mut preset = EffectPreset::new(name);
+        preset.effects = self.to_state().effects;
+        preset
+    }
+
+    /// Rebuild a parallel group node from its serialized state
+    #[cfg(feature = "serde")]
+    fn parallel_from_state(&self, effect_state: &EffectState) -> Result<Effect> {
+        let mut branches = Vec::with_capacity(effect_state.branches.len());
+        let mut gains = Vec::with_capacity(effect_state.branches.len());
+        for (i, branch_state) in effect_state.branches.iter().enumerate() {
+            let mut branch = match &self.registry {
+                Some(registry) => EffectChain::with_shared_registry(registry.clone()),
+                None => EffectChain::new(),
+            };
+            branch.from_state(branch_state)?;
+            branches.push(branch);
+            gains.push(
+                effect_state
+                    .parameters
+                    .get(&format!("gain_{}", i))
+                    .copied()
+                    .unwrap_or(1.0),
+            );
+        }
+        self.build_parallel(effect_state.id, branches, &gains)
+    }
+
+    /// Morph the chain toward a saved state over `duration_ms` milliseconds
+    ///
+    /// When `state` has the same effects in the same order (parallel groups
+    /// excluded), every numeric parameter and gain trim is interpolated
+    /// linearly from its current value to the target; bypass and mute switch
+    /// immediately. Otherwise the new chain is built from `state` and the old
+    /// and new chains run side by side with an equal-power crossfade for the
+    /// duration.
+    ///
+    /// Starting a new morph replaces one in progress. A zero duration applies
+    /// the target state immediately.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fundsp_rack::prelude::*;
+    /// # fn main() -> fundsp_rack::Result<()> {
+    /// let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
+    /// chain.add("lpf", &[("cutoff", 500.0)])?;
+    ///
+    /// let mut bright = chain.to_state();
+    /// bright.effects[0].set_param("cutoff", 8000.0);
+    /// chain.morph_to(&bright, 2000.0)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn morph_to(&mut self, state: &ChainState, duration_ms: f32) -> Result<()> {
+        let length = (duration_ms.max(0.0) * 0.001 * self.sample_rate as f32) as usize;
+        let same_structure = state.effects.len() == self.effects.len()
+            && self
+                .effects
+                .iter()
+                .zip(&state.effects)
+                .all(|(effect, target)| {
+                    effect.name == target.name
+                        && effect.group.is_none()
+                        && target.branches.is_empty()
+                });
+
+        let kind = if same_structure {
+            for bus in &state.sidechain_buses {
+                self.add_sidechain_bus(bus);
+            }
+            let buses: Vec<_> = state
+                .effects
+                .iter()
+                .map(|target| {
+                    target
+                        .sidechain_bus
+                        .as_deref()
+                        .map(|name| self.add_sidechain_bus(name))
+                })
+                .collect();
+            let mut params = Vec::new();
+            let mut gains = Vec::with_capacity(self.effects.len());
+            for ((effect, target), bus) in self.effects.iter_mut().zip(&state.effects).zip(buses) {
+                // Effects the state leaves unbound keep their binding
+                if bus.is_some() && effect.sidechain_processor.is_some() {
+                    effect.sidechain_bus = bus;
+                }
+                for (name, &end) in &target.parameters {
+                    if let Some(shared) = effect.controls.params.get(name) {
+                        params.push((shared.clone(), shared.value(), end));
+                    }
+                }
+                gains.push((effect.gain_db, target.gain_db));
+                effect.id = target.id.or(effect.id);
+                effect.bypassed = target.bypassed;
+                effect.muted = target.muted;
+                effect.mix = target.mix.map_or(1.0, |mix| mix.clamp(0.0, 1.0));
+            }
+            self.bypassed = state.bypassed;
+            MorphKind::Params { params, gains }
+        } else {
+            let mut next = match &self.registry {
+                Some(registry) => EffectChain::with_shared_registry(registry.clone()),
+                None => EffectChain::new(),
+            };
+            next.sidechain_buses = self.sidechain_buses.clone();
+            next.from_state(state)?;
+            next.set_sample_rate(self.sample_rate);
+            next.set_latency_compensation(self.latency_compensation);
+            next.set_mix_smoothing(self.mix_smoothing_ms);
+            next.set_bpm(self.bpm);
+
+            // Effects the state leaves unbound keep their current binding
+            for effect in &mut next.effects {
+                let bus = self
+                    .effects
+                    .iter()
+                    .find(|old| old.id.is_some() && old.id == effect.id)
+                    .and_then(|old| old.sidechain_bus);
+                if effect.sidechain_processor.is_some() && effect.sidechain_bus.is_none() {
+                    effect.sidechain_bus = bus;
+                }
+            }
+
+            // Only the effects and the state tied to them trade places: the
+            // old effects play out the crossfade on their own, while the
+            // chain keeps its meters, callbacks and settings
+            std::mem::swap(&mut self.effects, &mut next.effects);
+            std::mem::swap(&mut self.bypassed, &mut next.bypassed);
+            std::mem::swap(&mut self.latency_dirty, &mut next.latency_dirty);
+            self.sidechain_buses.clone_from(&next.sidechain_buses);
+            let mut previous = next;
+            previous.soloed = self.soloed.take();
+            previous.morph = self.morph.take();
+            MorphKind::Crossfade(Box::new(previous))
+        };
+
+        let mut morph = ChainMorph {
+            length,
+            elapsed: 0,
+            kind,
+        };
+        morph.advance(0, &mut self.effects);
+        self.morph = if morph.is_finished() {
+            None
+        } else {
+            Some(morph)
+        };
+        Ok(())
+    }
+
+    /// Progress of the running `morph_to` (0.0 to 1.0, 1.0 when idle)
+    #[cfg(feature = "serde")]
+    pub fn morph_progress(&self) -> f32 {
+        self.morph.as_ref().map_or(1.0, |morph| morph.progress())
+    }
+
+    /// Get current sample rate
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Get number of effects in chain
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Check if chain is empty
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Get effect name by index
+    pub fn effect_name(&self, index: usize) -> Option<&str> {
+        self.effects.get(index).map(|e| e.name.as_str())
+    }
+
+    /// Get effect ID by index
+    pub fn effect_id(&self, index: usize) -> Option<EffectId> {
+        self.effects.get(index).and_then(|e| e.id)
+    }
+
+    /// Clear the audio state of every effect, keeping effects and parameters
+    ///
+    /// Delay lines, reverb tails, filter memory and level meters are
+    /// silenced, as after building the chain.
+    pub fn reset(&mut self) {
+        for effect in &mut self.effects {
+            effect.reset();
+        }
+        if let Some(detector) = &mut self.pitch_detector {
+            detector.reset();
+        }
+        if let Some(master) = &mut self.master {
+            master.reset();
+        }
+    }
+
+    /// Check that the chain's impulse response dies away instead of building up
+    ///
+    /// Feeds a unit impulse followed by silence for `test_blocks` blocks of
+    /// `MAX_BUFFER_SIZE` frames (at least 4) and splits the response into
+    /// quarters. The chain is stable if the output stays finite and the last
+    /// quarter is at least 3 dB quieter than the loudest one, which catches
+    /// feedback at or above unity. Use a test period several times longer than
+    /// the longest delay time in the chain.
+    ///
+    /// The impulse runs through the chain itself, so its state is
+    /// [`reset`](Self::reset) before and after the test. Call this before
+    /// playback starts.
+    pub fn is_stable(&mut self, test_blocks: usize) -> bool {
+        let frames = test_blocks.max(4) * fundsp::MAX_BUFFER_SIZE;
+        let quarter = frames / 4;
+        let mut input = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let mut out_l = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let mut out_r = [0.0; fundsp::MAX_BUFFER_SIZE];
+        let mut energy = [0.0f64; 4];
+        let mut finite = true;
+
+        self.reset();
+        input[0] = 1.0;
+        for block in 0..frames / fundsp::MAX_BUFFER_SIZE {
+            self.process_block(&input, &input, &mut out_l, &mut out_r);
+            input[0] = 0.0;
+            for (i, (&l, &r)) in out_l.iter().zip(&out_r).enumerate() {
+                if !l.is_finite() || !r.is_finite() {
+                    finite = false;
+                }
+                let frame = block * fundsp::MAX_BUFFER_SIZE + i;
+                energy[(frame / quarter).min(3)] += (l * l + r * r) as f64;
+            }
+            if !finite {
+                break;
+            }
+        }
+        self.reset();
+
+        let loudest = energy.iter().copied().fold(0.0, f64::max);
+        finite && (loudest == 0.0 || energy[3] < loudest * 0.5)
+    }
+
+    /// Clear all effects from the chain
+    pub fn clear(&mut self) {
+        self.soloed = None;
+        #[cfg(feature = "serde")]
+        {
+            self.morph = None;
+        }
+        self.effects.clear();
+    }
+
+    /// Get CPU usage for a specific effect
+    pub fn effect_cpu_usage(&self, index: usize) -> Option<f64> {
+        self.effects.get(index).map(|e| e.cpu_usage())
+    }
+
+    /// Get CPU percentage for a specific effect (0-100%)
+    pub fn effect_cpu_percent(&self, index: usize) -> Option<f64> {
+        self.effects.get(index).map(|e| e.cpu_usage() * 100.0)
+    }
+
+    /// Get total CPU usage across all effects
+    pub fn total_cpu_usage(&self) -> f64 {
+        self.effects
+            .iter()
+            .filter(|e| !e.bypassed)
+            .map(|e| e.cpu_usage())
+            .sum()
+    }
+
+    /// Get total CPU percentage across all effects (0-100%)
+    pub fn total_cpu_percent(&self) -> f64 {
+        self.total_cpu_usage() * 100.0
+    }
+
+    /// Check if any effect is overloaded (>80% CPU)
+    pub fn has_overload(&self) -> bool {
+        self.effects.iter().any(|e| e.cpu_usage() > 0.8)
+    }
+
+    /// Reset CPU meters for all effects
+    pub fn reset_cpu_meters(&mut self) {
+        for effect in &mut self.effects {
+            effect.cpu_meter.reset();
+            if let Some(group) = &mut effect.group {
+                for branch in &mut group.branches {
+                    branch.reset_cpu_meters();
+                }
+            }
+        }
+    }
+
+    /// Get a structured CPU report for the chain
+    ///
+    /// Effect categories come from the registry metadata (see
+    /// [`EffectMetadata::category`](super::registry::EffectMetadata::category)).
+    pub fn cpu_report_detailed(&self) -> ChainCpuReport {
+        let effects = self
+            .effects
+            .iter()
+            .map(|e| {
+                let usage = e.cpu_usage();
+                let category = self
+                    .registry
+                    .as_ref()
+                    .and_then(|r| r.get_metadata(&e.name))
+                    .and_then(|m| m.category().map(str::to_string));
+                EffectCpuEntry {
+                    name: e.name.clone(),
+                    id: e.id,
+                    category,
+                    percent: usage * 100.0,
+                    overloaded: usage > 0.8,
+                    latency_samples: e.latency(),
+                }
+            })
+            .collect();
+        ChainCpuReport {
+            total_percent: self.total_cpu_percent(),
+            overloaded: self.has_overload(),
+            effects,
+        }
+    }
+
+    /// Get detailed CPU metrics for all effects as (name, percent, overloaded)
+    ///
+    /// See [`cpu_report_detailed`](Self::cpu_report_detailed) for a structured
+    /// report.
+    pub fn cpu_report(&self) -> Vec<(String, f64, bool)> {
+        self.effects
+            .iter()
+            .map(|e| {
+                let usage = e.cpu_usage();
+                (e.name.clone(), usage * 100.0, usage > 0.8)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synth::{LFOConfig, LFOWaveform, NoteDivision};
+
+    fn builtin_chain() -> EffectChain {
+        EffectChain::with_registry(EffectRegistry::with_builtin())
+    }
+
+    #[test]
+    fn test_morph_effect() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[]).unwrap();
+
+        let a = HashMap::from([("cutoff".to_string(), 200.0), ("res".to_string(), 4.0)]);
+        let b = HashMap::from([("cutoff".to_string(), 8200.0), ("res".to_string(), 0.0)]);
+        let controls = &chain.effects[0].controls;
+
+        chain.morph_effect(0, &a, &b, 0.0).unwrap();
+        assert_eq!(controls.get("cutoff"), Some(200.0));
+        assert_eq!(controls.get("res"), Some(4.0));
+
+        chain.morph_effect(0, &a, &b, 1.0).unwrap();
+        assert_eq!(controls.get("cutoff"), Some(8200.0));
+        assert_eq!(controls.get("res"), Some(0.0));
+
+        chain.morph_effect(0, &a, &b, 0.5).unwrap();
+        assert_eq!(controls.get("cutoff"), Some(4200.0));
+        assert_eq!(controls.get("res"), Some(2.0));
+
+        assert!(chain.morph_effect(1, &a, &b, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_effect_gain_trim() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 20000.0)]).unwrap();
+        assert_eq!(chain.effect_gain(0), Some(0.0));
+
+        chain.set_effect_gain(0, -6.0).unwrap();
+        assert_eq!(chain.effect_gain(0), Some(-6.0));
+        assert!(chain.set_effect_gain(1, 0.0).is_err());
+
+        let mut reference = builtin_chain();
+        reference.add("lpf", &[("cutoff", 20000.0)]).unwrap();
+
+        let expected = 10.0_f32.powf(-6.0 / 20.0);
+        for i in 0..4096 {
+            let x = (i as f32 * 0.01).sin() * 0.5;
+            let (trimmed, _) = chain.process(x, x);
+            let (plain, _) = reference.process(x, x);
+            assert!((trimmed - plain * expected).abs() < 1e-5);
+        }
+
+        // Output meter reflects the post-gain level
+        let (_, _, peak_trimmed, _) = chain.effects[0].last_output_levels;
+        let (_, _, peak_plain, _) = reference.effects[0].last_output_levels;
+        assert!((peak_trimmed - peak_plain * expected).abs() < 1e-4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_effect_gain_persisted() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[]).unwrap();
+        chain.set_effect_gain(0, 3.0).unwrap();
+
+        let mut loaded = builtin_chain();
+        loaded.from_state(&chain.to_state()).unwrap();
+        assert_eq!(loaded.effect_gain(0), Some(3.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_preset_round_trip() {
+        use crate::effects::preset::MixingPresets;
+
+        let preset = MixingPresets::vocal_clean();
+        let mut chain = builtin_chain();
+        chain.add("reverb", &[]).unwrap();
+        chain.load_preset(&preset).unwrap();
+        assert_eq!(chain.len(), preset.effects.len());
+        assert_eq!(chain.sample_rate(), 48000.0);
+
+        let saved = chain.save_as_preset("Copy");
+        assert_eq!(saved.name, "Copy");
+        assert_eq!(saved.sample_rate, None);
+        for (original, copy) in preset.effects.iter().zip(&saved.effects) {
+            assert_eq!(original.name, copy.name);
+            for (param, value) in &original.parameters {
+                assert_eq!(
+                    copy.get_param(param),
+                    Some(*value),
+                    "{}.{}",
+                    original.name,
+                    param
+                );
+            }
+        }
+
+        // Loading the copy reproduces the chain
+        let mut reloaded = builtin_chain();
+        reloaded.load_preset(&saved).unwrap();
+        assert_eq!(reloaded.to_state(), chain.to_state());
+
+        // A preset's sample rate is honoured
+        reloaded
+            .load_preset(&saved.clone().with_sample_rate(44100.0))
+            .unwrap();
+        assert_eq!(reloaded.sample_rate(), 44100.0);
+
+        // A bad preset is rejected before the chain is touched
+        let before = chain.to_state();
+        let bad = EffectPreset::new("Bad")
+            .with_simple_effect("lpf")
+            .with_simple_effect("frobnicator");
+        assert!(matches!(
+            chain.load_preset(&bad),
+            Err(crate::Error::UnknownEffect { .. })
+        ));
+        assert_eq!(chain.to_state(), before);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_effect_mix_gain_and_lfo_persisted() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 800.0)]).unwrap();
+        chain.add("reverb", &[]).unwrap();
+        chain.set_effect_mix(1, 0.35).unwrap();
+        chain.set_effect_gain(1, -4.5).unwrap();
+        let lfo = EffectLFO::new(LFOConfig::new(0.5, 0.4, LFOWaveform::Sine));
+        chain.attach_lfo(0, "cutoff", lfo).unwrap();
+
+        let mut loaded = builtin_chain();
+        loaded.from_json(&chain.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.effect_mix(0), Some(1.0));
+        assert_eq!(loaded.effect_mix(1), Some(0.35));
+        assert_eq!(loaded.effect_gain(1), Some(-4.5));
+        assert_eq!(loaded.effect_lfo(0, "cutoff"), Some(lfo));
+        assert_eq!(loaded.to_state(), chain.to_state());
+
+        // An effect at full mix is not written, so older readers see no change
+        assert_eq!(loaded.to_state().effects[0].mix, None);
+    }
+
+    #[test]
+    fn test_stereo_meter_clip_latches() {
+        let mut meter = StereoMeter::new(64);
+        meter.push_block(&[0.25; 64], &[0.5; 64]);
+        let levels = meter.levels();
+        assert!((levels.rms_l - 0.25).abs() < 1e-6);
+        assert!((levels.peak_r - 0.5).abs() < 1e-6);
+        assert!(!meter.is_clipped());
+
+        // One full-scale sample latches the indicator
+        meter.push(0.0, -1.0);
+        meter.push_block(&[0.1; 256], &[0.1; 256]);
+        assert!(meter.is_clipped());
+
+        meter.reset_clip();
+        assert!(!meter.is_clipped());
+        meter.reset();
+        assert_eq!(meter.levels(), EffectMeter::default());
+    }
+
+    #[test]
+    fn test_master_meter() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 20000.0)]).unwrap();
+        chain.set_effect_gain(0, -6.0).unwrap();
+        assert_eq!(chain.master_meter(), None);
+        chain.enable_master_meter();
+
+        let input = vec![0.8; 4096];
+        let (mut out_l, mut out_r) = (vec![0.0; 4096], vec![0.0; 4096]);
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+        let levels = chain.master_meter().unwrap();
+        assert!((levels.peak_l - out_l[4095].abs()).abs() < 0.05);
+        assert!(levels.rms_l > 0.3 && levels.rms_l < 0.5);
+        assert!(!chain.master_clipped());
+
+        // Bypassed chains are metered too
+        chain.set_bypass(true);
+        for _ in 0..16 {
+            chain.process(1.2, 0.0);
+        }
+        assert!(chain.master_clipped());
+        chain.reset_master_clip();
+        assert!(!chain.master_clipped());
+
+        chain.disable_master_meter();
+        chain.process(1.2, 0.0);
+        assert!(!chain.master_clipped());
+    }
+
+    #[test]
+    fn test_overload_callback() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 20000.0)]).unwrap();
+        chain.set_effect_gain(0, 12.0).unwrap();
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        chain.set_overload_callback(Box::new(move |event| {
+            let event = match event {
+                OverloadEvent::Cpu { index, .. } => format!("cpu {}", index),
+                OverloadEvent::Clip { peak } => format!("clip over {}", peak > 1.0),
+            };
+            sink.lock().unwrap().push(event);
+        }));
+
+        // Sustained clipping reports once, not every check
+        let input = vec![0.5; 4096];
+        let (mut out_l, mut out_r) = (vec![0.0; 4096], vec![0.0; 4096]);
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+        assert_eq!(*events.lock().unwrap(), vec!["clip over true".to_string()]);
+
+        // An overloaded effect is reported with its index
+        events.lock().unwrap().clear();
+        chain.set_effect_gain(0, 0.0).unwrap();
+        chain.effects[0]
+            .cpu_meter
+            .record(std::time::Duration::from_millis(10), 1);
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+        assert_eq!(*events.lock().unwrap(), vec!["cpu 0".to_string()]);
+
+        // Without a callback nothing is reported
+        chain.clear_overload_callback();
+        events.lock().unwrap().clear();
+        chain.set_effect_gain(0, 12.0).unwrap();
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_render_to_buffer() {
+        let build = || {
+            let mut chain = builtin_chain();
+            chain.add("lpf", &[("cutoff", 1000.0)]).unwrap();
+            chain
+        };
+        let input: Vec<f32> = (0..500).map(|i| (i as f32 * 0.3).sin()).collect();
+
+        let mut reference = build();
+        reference.set_sample_rate(44100.0);
+        let (mut out_l, mut out_r) = (vec![0.0; 500], vec![0.0; 500]);
+        reference.process_block(&input, &input, &mut out_l, &mut out_r);
+
+        let mut chain = build();
+        let buffer = chain.render_to_buffer(&input, &input[..400], 44100.0);
+        assert_eq!(chain.sample_rate(), 44100.0);
+        assert_eq!(buffer.len(), 400);
+        assert_eq!(buffer.left, out_l[..400]);
+    }
+
+    #[test]
+    fn test_render_to_buffer_scales_effect_timing() {
+        let input = vec![1.0; 20000];
+        let attack_samples = |sample_rate: f64| {
+            let mut chain = builtin_chain();
+            chain.add("compressor", &[]).unwrap();
+            let buffer = chain.render_to_buffer(&input, &input, sample_rate);
+            assert_eq!(buffer.sample_rate, sample_rate);
+            buffer.left.iter().position(|&x| x < 0.5).unwrap()
+        };
+        let at_44k = attack_samples(44100.0);
+        let at_96k = attack_samples(96000.0);
+        let ratio = at_96k as f64 / at_44k as f64;
+        assert!(
+            (ratio - 96000.0 / 44100.0).abs() < 0.05,
+            "{at_44k} vs {at_96k}"
+        );
+    }
+
+    #[test]
+    fn test_process_block_matches_per_sample() {
+        let build = || {
+            let mut chain = builtin_chain();
+            chain
+                .add("lpf", &[("cutoff", 1500.0)])
+                .unwrap()
+                .add("compressor", &[("threshold", -12.0)])
+                .unwrap()
+                .add("phaser", &[])
+                .unwrap();
+            chain.set_effect_gain(1, -3.0).unwrap();
+            chain
+        };
+        let mut block_chain = build();
+        let mut sample_chain = build();
+
+        // Odd length exercises chunking across MAX_BUFFER_SIZE boundaries
+        let len = 1000;
+        let in_l: Vec<f32> = (0..len).map(|i| (i as f32 * 0.05).sin() * 0.8).collect();
+        let in_r: Vec<f32> = (0..len).map(|i| (i as f32 * 0.03).cos() * 0.8).collect();
+        let mut out_l = vec![0.0; len];
+        let mut out_r = vec![0.0; len];
+        block_chain.process_block(&in_l, &in_r, &mut out_l, &mut out_r);
+
+        for i in 0..len {
+            let (l, r) = sample_chain.process(in_l[i], in_r[i]);
+            assert!((l - out_l[i]).abs() < 1e-5, "left mismatch at {i}");
+            assert!((r - out_r[i]).abs() < 1e-5, "right mismatch at {i}");
+        }
+    }
+
+    #[test]
+    fn test_process_block_metering() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 20000.0)]).unwrap();
+
+        let input = vec![0.5; 4096];
+        let (mut out_l, mut out_r) = (vec![0.0; 4096], vec![0.0; 4096]);
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+
+        let (rms_l, _, peak_l, _) = chain.effects[0].last_input_levels;
+        assert!((rms_l - 0.5).abs() < 1e-4);
+        assert!((peak_l - 0.5).abs() < 1e-4);
+        assert!(chain.effects[0].last_output_levels.0 > 0.0);
+    }
+
+    #[test]
+    fn test_process_block_with_sidechain_matches_per_sample() {
+        let build = || {
+            let mut chain = builtin_chain();
+            chain
+                .add(
+                    "sidechain_compressor",
+                    &[("threshold", -30.0), ("ratio", 8.0)],
+                )
+                .unwrap();
+            chain
+        };
+        let mut block_chain = build();
+        let mut sample_chain = build();
+
+        let len = 300;
+        let input: Vec<f32> = (0..len).map(|i| (i as f32 * 0.1).sin() * 0.5).collect();
+        let sidechain: Vec<f32> = (0..len).map(|i| if i < 150 { 0.8 } else { 0.0 }).collect();
+        let mut out_l = vec![0.0; len];
+        let mut out_r = vec![0.0; len];
+        block_chain.process_block_with_sidechain(
+            &input,
+            &input,
+            &mut out_l,
+            &mut out_r,
+            Some((&sidechain, &sidechain)),
+        );
+
+        for i in 0..len {
+            let (l, r) = sample_chain.process_with_sidechain(
+                input[i],
+                input[i],
+                Some((sidechain[i], sidechain[i])),
+            );
+            assert!((l - out_l[i]).abs() < 1e-6, "left mismatch at {i}");
+            assert!((r - out_r[i]).abs() < 1e-6, "right mismatch at {i}");
+        }
+        // The sidechain must actually have ducked the signal
+        assert!(out_l[100].abs() < input[100].abs());
+    }
+
+    #[test]
+    fn test_set_sample_rate_reaches_effect_processors() {
+        // Samples until a full-scale input pulls the compressor's gain below half
+        let attack_samples = |sample_rate: f64, add_first: bool| {
+            let mut chain = builtin_chain();
+            if add_first {
+                chain.add("compressor", &[]).unwrap();
+                chain.set_sample_rate(sample_rate);
+            } else {
+                chain.set_sample_rate(sample_rate);
+                chain.add("compressor", &[]).unwrap();
+            }
+            (0..100_000)
+                .position(|_| chain.process(1.0, 1.0).0 < 0.5)
+                .unwrap()
+        };
+        let at_44k = attack_samples(44100.0, true);
+        let at_96k = attack_samples(96000.0, true);
+        let ratio = at_96k as f64 / at_44k as f64;
+        assert!(
+            (ratio - 96000.0 / 44100.0).abs() < 0.05,
+            "{at_44k} vs {at_96k}"
+        );
+        // Effects added after the change start at the chain rate
+        assert_eq!(attack_samples(96000.0, false), at_96k);
+
+        // Lookahead latency follows the rate
+        let mut chain = builtin_chain();
+        chain.add("limiter", &[("lookahead", 5.0)]).unwrap();
+        chain.set_sample_rate(96000.0);
+        assert_eq!(chain.effect_latency(0), Some(480));
+    }
+
+    #[test]
+    fn test_set_sample_rate_reaches_sidechain_effects() {
+        let attack_samples = |sample_rate: f64| {
+            let mut chain = builtin_chain();
+            chain.add("sidechain_compressor", &[]).unwrap();
+            chain.set_sample_rate(sample_rate);
+            (0..100_000)
+                .position(|_| chain.process_with_sidechain(1.0, 1.0, Some((1.0, 1.0))).0 < 0.5)
+                .unwrap()
+        };
+        let at_44k = attack_samples(44100.0);
+        let at_96k = attack_samples(96000.0);
+        let ratio = at_96k as f64 / at_44k as f64;
+        assert!(
+            (ratio - 96000.0 / 44100.0).abs() < 0.05,
+            "{at_44k} vs {at_96k}"
+        );
+    }
+
+    #[test]
+    fn test_sidechain_compressor_makeup_param() {
+        let mut chain = builtin_chain();
+        chain.add("sidechain_compressor", &[]).unwrap();
+        let level = |chain: &mut EffectChain| {
+            let mut out = 0.0;
+            for _ in 0..4800 {
+                out = chain.process_with_sidechain(0.5, 0.5, Some((1.0, 1.0))).0;
+            }
+            out
+        };
+
+        let ducked = level(&mut chain);
+        assert!(chain.set_param(0, "makeup", 12.0));
+        let restored = level(&mut chain);
+        let gain_db = 20.0 * (restored / ducked).log10();
+        assert!((gain_db - 12.0).abs() < 0.1, "{gain_db}");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_meters_are_not_parameters() {
+        let mut chain = builtin_chain();
+        chain.add("compressor", &[("threshold", -30.0)]).unwrap();
+        for _ in 0..4800 {
+            chain.process(0.5, 0.5);
+        }
+        let reduction = chain.effect_meter(0, "gain_reduction").unwrap();
+        assert!(reduction > 10.0, "{reduction}");
+
+        assert_eq!(
+            chain.to_state().effects[0].get_param("gain_reduction"),
+            None
+        );
+        chain.set_param(0, "gain_reduction", 0.0);
+        assert_eq!(chain.effect_meter(0, "gain_reduction"), Some(reduction));
+        assert_eq!(chain.effect_meter(0, "threshold"), None);
+    }
+
+    #[test]
+    fn test_insert_effect() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[]).unwrap().add("reverb", &[]).unwrap();
+
+        chain.insert_effect(1, "chorus", &HashMap::new()).unwrap();
+        chain.insert_effect(0, "hpf", &HashMap::new()).unwrap();
+        chain.insert_effect(4, "delay", &HashMap::new()).unwrap();
+
+        let names: Vec<_> = (0..chain.len())
+            .filter_map(|i| chain.effect_name(i))
+            .collect();
+        assert_eq!(names, ["hpf", "lpf", "chorus", "reverb", "delay"]);
+
+        assert!(chain.insert_effect(6, "lpf", &HashMap::new()).is_err());
+        assert!(chain
+            .insert_effect(0, "does_not_exist", &HashMap::new())
+            .is_err());
+        assert_eq!(chain.len(), 5);
+    }
+
+    #[test]
+    fn test_move_effect() {
+        let mut chain = builtin_chain();
+        chain
+            .add("lpf", &[])
+            .unwrap()
+            .add("chorus", &[])
+            .unwrap()
+            .add("reverb", &[])
+            .unwrap();
+
+        chain.move_effect(0, 2).unwrap();
+        let names: Vec<_> = (0..chain.len())
+            .filter_map(|i| chain.effect_name(i))
+            .collect();
+        assert_eq!(names, ["chorus", "reverb", "lpf"]);
+
+        chain.move_effect(2, 0).unwrap();
+        let names: Vec<_> = (0..chain.len())
+            .filter_map(|i| chain.effect_name(i))
+            .collect();
+        assert_eq!(names, ["lpf", "chorus", "reverb"]);
+
+        assert!(matches!(
+            chain.move_effect(3, 0),
+            Err(crate::Error::InvalidEffect(_))
+        ));
+        assert!(matches!(
+            chain.move_effect(0, 3),
+            Err(crate::Error::InvalidEffect(_))
+        ));
+    }
+
+    /// Naive RMS and peak over a window of stereo samples
+    fn naive_levels(buffer: &[(f32, f32)]) -> (f32, f32, f32, f32) {
+        let count = buffer.len() as f32;
+        let sum_l: f32 = buffer.iter().map(|(l, _)| l * l).sum();
+        let sum_r: f32 = buffer.iter().map(|(_, r)| r * r).sum();
+        let peak_l = buffer.iter().fold(0.0_f32, |p, (l, _)| p.max(l.abs()));
+        let peak_r = buffer.iter().fold(0.0_f32, |p, (_, r)| p.max(r.abs()));
+        (
+            (sum_l / count).sqrt(),
+            (sum_r / count).sqrt(),
+            peak_l,
+            peak_r,
+        )
+    }
+
+    #[test]
+    fn test_level_meter_rms_matches_naive() {
+        let window = 256;
+        let mut meter = LevelMeter::new(window);
+        let mut history = Vec::new();
+
+        // Varying amplitude so the window contents change substantially
+        for i in 0..window * 20 {
+            let env = 0.2 + 0.8 * ((i as f32) * 0.0007).sin().abs();
+            let sample = (
+                (i as f32 * 0.05).sin() * env,
+                (i as f32 * 0.031).cos() * env * 0.5,
+            );
+            history.push(sample);
+            let levels = meter.push(sample.0, sample.1);
+
+            if history.len() < window {
+                assert!(levels.is_none());
+                continue;
+            }
+            let (rms_l, rms_r, peak_l, peak_r) = levels.unwrap();
+            let (naive_rms_l, naive_rms_r, _, _) = naive_levels(&history[history.len() - window..]);
+            assert!((rms_l - naive_rms_l).abs() < 1e-4, "left RMS drift at {i}");
+            assert!((rms_r - naive_rms_r).abs() < 1e-4, "right RMS drift at {i}");
+            // Peak hold covers the current sample and never overshoots the input
+            assert!(peak_l >= sample.0.abs() && peak_r >= sample.1.abs());
+            assert!(peak_l <= 1.0 && peak_r <= 0.5);
+        }
+    }
+
+    #[test]
+    fn test_level_meter_peak_decays() {
+        let window = 64;
+        let mut meter = LevelMeter::new(window);
+        meter.push(1.0, 1.0);
+        for _ in 0..window - 1 {
+            meter.push(0.0, 0.0);
+        }
+        let (_, _, held, _) = meter.push(0.0, 0.0).unwrap();
+        assert_eq!(held, 1.0);
+
+        let mut last = held;
+        for _ in 0..window * 4 {
+            let (rms, _, peak, _) = meter.push(0.0, 0.0).unwrap();
+            assert!(peak < last);
+            assert!(rms.abs() < 1e-6);
+            last = peak;
+        }
+    }
+
+    fn lpf_branch(registry: &Arc<EffectRegistry>) -> EffectChain {
+        let mut branch = EffectChain::with_shared_registry(registry.clone());
+        branch.add("lpf", &[("cutoff", 800.0)]).unwrap();
+        branch
+    }
+
+    #[test]
+    fn test_parallel_group_sums_branches() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let mut chain = EffectChain::with_shared_registry(registry.clone());
+        let dry = EffectChain::with_shared_registry(registry.clone());
+        let index = chain
+            .add_parallel(vec![dry, lpf_branch(&registry)], &[0.5, 0.25])
+            .unwrap();
+        assert_eq!(chain.effect_name(index), Some(PARALLEL_GROUP_NAME));
+
+        let mut reference = lpf_branch(&registry);
+        for i in 0..512 {
+            let x = (i as f32 * 0.2).sin() * 0.5;
+            let (l, r) = chain.process(x, x);
+            let (filtered, _) = reference.process(x, x);
+            let expected = 0.5 * x + 0.25 * filtered;
+            assert!((l - expected).abs() < 1e-5 && (r - expected).abs() < 1e-5);
+        }
+
+        // Branch gains are regular parameters on the group node
+        assert!(chain.set_param(index, "gain_1", 0.0));
+        let (l, _) = chain.process(0.4, 0.4);
+        assert!((l - 0.2).abs() < 1e-6);
     }
 
-    /// Check if an effect is bypassed
-    pub fn is_effect_bypassed(&self, index: usize) -> Option<bool> {
-        self.effects.get(index).map(|e| e.bypassed)
+    #[test]
+    fn test_parallel_group_validation() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let mut chain = EffectChain::with_shared_registry(registry.clone());
+        assert!(chain.add_parallel(Vec::new(), &[]).is_err());
+        assert!(chain
+            .add_parallel(vec![lpf_branch(&registry)], &[1.0, 1.0])
+            .is_err());
+        assert!(chain.is_empty());
     }
 
-    /// Check if an effect is muted
-    pub fn is_effect_muted(&self, index: usize) -> Option<bool> {
-        self.effects.get(index).map(|e| e.muted)
+    #[test]
+    fn test_parallel_group_latency_and_cpu() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let mut slow = EffectChain::with_shared_registry(registry.clone());
+        slow.add("vibrato", &[]).unwrap();
+        let slow_latency = slow.total_latency();
+        assert!(slow_latency > 0);
+
+        let mut chain = EffectChain::with_shared_registry(registry.clone());
+        chain.add("lpf", &[]).unwrap();
+        let group = chain
+            .add_parallel(vec![lpf_branch(&registry), slow], &[1.0, 1.0])
+            .unwrap();
+        assert_eq!(chain.effect_latency(group), Some(slow_latency));
+        assert_eq!(chain.total_latency(), slow_latency);
+
+        for _ in 0..1024 {
+            chain.process(0.1, 0.1);
+        }
+        let group_node = chain.parallel_group(group).unwrap();
+        let branch_sum: f64 = group_node
+            .branches()
+            .iter()
+            .map(|b| b.total_cpu_usage())
+            .sum();
+        assert!(branch_sum > 0.0);
+        assert_eq!(chain.effect_cpu_usage(group), Some(branch_sum));
     }
 
-    /// Get total latency of the chain in samples
-    pub fn total_latency(&self) -> usize {
-        self.effects
+    #[test]
+    fn test_cpu_report_by_category() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let mut chain = EffectChain::with_shared_registry(registry.clone());
+        chain.add("lpf", &[]).unwrap();
+        chain.add("hpf", &[]).unwrap();
+        chain.add("reverb", &[]).unwrap();
+        chain.add("compressor", &[]).unwrap();
+        // Parallel groups are not registry effects and stay uncategorized
+        chain
+            .add_parallel(vec![lpf_branch(&registry)], &[1.0])
+            .unwrap();
+        for _ in 0..1024 {
+            chain.process(0.1, 0.1);
+        }
+
+        let report = chain.cpu_report_detailed();
+        let categories: Vec<_> = report
+            .effects
             .iter()
-            .filter(|e| !e.bypassed)
-            .map(|e| e.latency_samples)
-            .sum()
+            .map(|e| e.category.as_deref())
+            .collect();
+        assert_eq!(
+            categories,
+            [
+                Some("filter"),
+                Some("filter"),
+                Some("reverb"),
+                Some("dynamics"),
+                None
+            ]
+        );
+        assert!((report.total_percent - chain.total_cpu_percent()).abs() < 1e-9);
+
+        let by_category = report.by_category();
+        let filters = report.effects[0].percent + report.effects[1].percent;
+        assert_eq!(by_category.len(), 4);
+        assert!((by_category["filter"] - filters).abs() < 1e-9);
+        assert_eq!(by_category["reverb"], report.effects[2].percent);
+        assert_eq!(by_category["dynamics"], report.effects[3].percent);
+        assert_eq!(
+            by_category[ChainCpuReport::UNCATEGORIZED],
+            report.effects[4].percent
+        );
+
+        // The tuple report carries the same numbers
+        for (entry, (name, percent, overloaded)) in report.effects.iter().zip(chain.cpu_report()) {
+            assert_eq!(entry.name, name);
+            assert_eq!(entry.percent, percent);
+            assert_eq!(entry.overloaded, overloaded);
+        }
     }
 
-    /// Get latency of a specific effect
-    pub fn effect_latency(&self, index: usize) -> Option<usize> {
-        self.effects.get(index).map(|e| e.latency_samples)
+    #[test]
+    fn test_parallel_group_block_matches_per_sample() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let build = || {
+            let mut chain = EffectChain::with_shared_registry(registry.clone());
+            let dry = EffectChain::with_shared_registry(registry.clone());
+            chain
+                .add_parallel(vec![dry, lpf_branch(&registry)], &[0.7, 0.3])
+                .unwrap();
+            chain.add("hpf", &[("cutoff", 100.0)]).unwrap();
+            chain
+        };
+        let mut block_chain = build();
+        let mut sample_chain = build();
+
+        let len = 200;
+        let input: Vec<f32> = (0..len).map(|i| (i as f32 * 0.13).sin()).collect();
+        let mut out_l = vec![0.0; len];
+        let mut out_r = vec![0.0; len];
+        block_chain.process_block(&input, &input, &mut out_l, &mut out_r);
+
+        for i in 0..len {
+            let (l, r) = sample_chain.process(input[i], input[i]);
+            assert!((l - out_l[i]).abs() < 1e-5 && (r - out_r[i]).abs() < 1e-5);
+        }
     }
 
-    /// Serialize the chain to JSON
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use fundsp_rack::prelude::*;
-    /// # let chain = EffectChain::new();
-    /// let json = chain.to_json().unwrap();
-    /// std::fs::write("my_chain.json", json).unwrap();
-    /// ```
     #[cfg(feature = "serde")]
-    pub fn to_json(&self) -> Result<String> {
-        let state = self.to_state();
-        state
-            .to_json()
-            .map_err(|e| crate::Error::SerializationError(e.to_string()))
+    #[test]
+    fn test_parallel_group_serialization() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let mut chain = EffectChain::with_shared_registry(registry.clone());
+        let dry = EffectChain::with_shared_registry(registry.clone());
+        chain
+            .add_parallel(vec![dry, lpf_branch(&registry)], &[1.0, 0.5])
+            .unwrap();
+        chain.add("reverb", &[]).unwrap();
+
+        let state = chain.to_state();
+        assert_eq!(state.effects[0].name, PARALLEL_GROUP_NAME);
+        assert_eq!(state.effects[0].branches.len(), 2);
+        assert_eq!(state.effects[0].branches[1].effects[0].name, "lpf");
+
+        let json = state.to_json().unwrap();
+        let mut restored = EffectChain::with_shared_registry(registry);
+        restored.from_json(&json).unwrap();
+
+        let group = restored.parallel_group(0).unwrap();
+        assert_eq!(group.branches().len(), 2);
+        assert!(group.branches()[0].is_empty());
+        assert_eq!(group.branches()[1].effect_name(0), Some("lpf"));
+        assert_eq!(group.branch_gain(1), Some(0.5));
+        assert_eq!(restored.effect_name(1), Some("reverb"));
+        assert_eq!(restored.to_state(), state);
     }
 
-    /// Deserialize and load a chain from JSON
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use fundsp_rack::prelude::*;
-    /// # let mut chain = EffectChain::with_registry(EffectRegistry::with_builtin());
-    /// let json = std::fs::read_to_string("my_chain.json").unwrap();
-    /// chain.from_json(&json).unwrap();
-    /// ```
-    #[cfg(feature = "serde")]
-    pub fn from_json(&mut self, json: &str) -> Result<()> {
-        let state = ChainState::from_json(json)
-            .map_err(|e| crate::Error::SerializationError(e.to_string()))?;
-        self.from_state(&state)
+    /// Feed an impulse and return the left output
+    fn impulse_response(chain: &mut EffectChain, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let x = if i == 0 { 1.0 } else { 0.0 };
+                chain.process(x, x).0
+            })
+            .collect()
     }
 
-    /// Convert to serializable state
-    #[cfg(feature = "serde")]
-    pub fn to_state(&self) -> ChainState {
-        let mut state = ChainState::new(self.sample_rate);
-        state.bypassed = self.bypassed;
+    /// Vibrato with zero depth is a pure delay equal to its reported latency
+    fn latent_chain(registry: &Arc<EffectRegistry>) -> EffectChain {
+        let mut chain = EffectChain::with_shared_registry(registry.clone());
+        chain.add("vibrato", &[("depth", 0.0)]).unwrap();
+        chain
+    }
 
-        for effect in &self.effects {
-            let mut effect_state = EffectState::new(effect.name.clone());
-            effect_state.id = effect.id;
-            effect_state.bypassed = effect.bypassed;
-            effect_state.muted = effect.muted;
+    #[test]
+    fn test_latency_compensation_aligns_dry_path() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let mut chain = latent_chain(&registry);
+        let latency = chain.effect_latency(0).unwrap();
+        assert!(latency > 0);
+        assert!(chain.latency_compensation());
+        chain.set_effect_mix(0, 0.5).unwrap();
+        assert_eq!(chain.effect_mix(0), Some(0.5));
 
-            // Extract parameters from controls
-            for (key, shared) in &effect.controls.params {
-                effect_state.set_param(key, shared.value());
+        // Dry and wet both arrive at the reported latency
+        let response = impulse_response(&mut chain, latency * 2);
+        assert!((response[latency] - 1.0).abs() < 1e-3);
+        for (i, &y) in response.iter().enumerate() {
+            if i != latency {
+                assert!(y.abs() < 1e-3, "unexpected output {y} at {i}");
             }
+        }
 
-            state.add_effect(effect_state);
+        // Without compensation the dry impulse leads the wet one
+        let mut chain = latent_chain(&registry);
+        chain.set_latency_compensation(false);
+        chain.set_effect_mix(0, 0.5).unwrap();
+        let response = impulse_response(&mut chain, latency * 2);
+        assert!((response[0] - 0.5).abs() < 1e-3);
+        assert!((response[latency] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_latency_compensation_aligns_parallel_branches() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let build = |compensate: bool| {
+            let mut chain = EffectChain::with_shared_registry(registry.clone());
+            chain.set_latency_compensation(compensate);
+            let dry = EffectChain::with_shared_registry(registry.clone());
+            chain
+                .add_parallel(vec![dry, latent_chain(&registry)], &[0.5, 0.5])
+                .unwrap();
+            chain
+        };
+
+        let mut aligned = build(true);
+        let latency = aligned.total_latency();
+        let response = impulse_response(&mut aligned, latency * 2);
+        assert!((response[latency] - 1.0).abs() < 1e-3);
+        assert!(response[0].abs() < 1e-3);
+
+        let mut unaligned = build(false);
+        let response = impulse_response(&mut unaligned, latency * 2);
+        assert!((response[0] - 0.5).abs() < 1e-3);
+        assert!((response[latency] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_editing_parallel_group_updates_latency() {
+        let registry = Arc::new(EffectRegistry::with_builtin());
+        let dry = || EffectChain::with_shared_registry(registry.clone());
+        let mut chain = dry();
+        chain.add_parallel(vec![dry(), dry()], &[0.5, 0.5]).unwrap();
+        assert_eq!(chain.total_latency(), 0);
+
+        let latency = latent_chain(&registry).total_latency();
+        chain.parallel_group_mut(0).unwrap().branches_mut()[1]
+            .add("vibrato", &[("depth", 0.0)])
+            .unwrap();
+        // The group is measured again before the next sample is processed
+        chain.process(0.0, 0.0);
+        assert_eq!(chain.total_latency(), latency);
+        let response = impulse_response(&mut chain, latency * 2);
+        assert!((response[latency] - 1.0).abs() < 1e-3);
+        assert!(response[0].abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_solo_effect() {
+        let mut chain = builtin_chain();
+        chain
+            .add("lpf", &[("cutoff", 500.0)])
+            .unwrap()
+            .add("delay", &[("mix", 0.0)])
+            .unwrap()
+            .add("hpf", &[("cutoff", 2000.0)])
+            .unwrap();
+        chain.set_effect_gain(1, -6.0).unwrap();
+        chain.mute_effect(0, true).unwrap();
+        chain.bypass_effect(1, true).unwrap();
+        assert_eq!(chain.soloed_effect(), None);
+
+        chain.solo_effect(1).unwrap();
+        assert_eq!(chain.soloed_effect(), Some(1));
+
+        // Only the soloed effect runs, despite its bypass and the mute before it
+        let mut reference = builtin_chain();
+        reference.add("delay", &[("mix", 0.0)]).unwrap();
+        reference.set_effect_gain(0, -6.0).unwrap();
+        for i in 0..256 {
+            let x = (i as f32 * 0.3).sin() * 0.5;
+            let (l, r) = chain.process(x, x);
+            let (expected, _) = reference.process(x, x);
+            assert!((l - expected).abs() < 1e-6 && (r - expected).abs() < 1e-6);
         }
 
-        state
+        // Clearing the solo restores the mute on the first effect
+        chain.clear_solo();
+        assert_eq!(chain.soloed_effect(), None);
+        assert_eq!(chain.process(0.5, 0.5), (0.0, 0.0));
+
+        assert!(chain.solo_effect(3).is_err());
+        chain.solo_effect(2).unwrap();
+        chain.move_effect(2, 0).unwrap();
+        assert_eq!(chain.soloed_effect(), None);
+    }
+
+    #[test]
+    fn test_set_param_smoothed_ramps() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 1000.0)]).unwrap();
+        let controls = chain.effects[0].controls.clone();
+
+        chain.set_param_smoothed(0, "cutoff", 2000.0, 10.0).unwrap();
+        // Nothing moves until audio is processed
+        assert_eq!(controls.get("cutoff"), Some(1000.0));
+
+        let mut previous = 1000.0;
+        for _ in 0..480 {
+            chain.process(0.0, 0.0);
+            let cutoff = controls.get("cutoff").unwrap();
+            assert!(cutoff > previous && cutoff < 2000.0);
+            previous = cutoff;
+        }
+        // One time constant covers ~63% of the distance
+        assert!((previous - 1632.0).abs() < 5.0);
+
+        // Block processing keeps ramping and lands exactly on the target
+        let silence = vec![0.0; 48000];
+        let (mut out_l, mut out_r) = (vec![0.0; 48000], vec![0.0; 48000]);
+        chain.process_block(&silence, &silence, &mut out_l, &mut out_r);
+        assert_eq!(controls.get("cutoff"), Some(2000.0));
+        assert!(chain.effects[0].ramps.is_empty());
+
+        assert!(chain.set_param_smoothed(0, "missing", 1.0, 10.0).is_err());
+        assert!(chain.set_param_smoothed(1, "cutoff", 1.0, 10.0).is_err());
+    }
+
+    #[test]
+    fn test_set_param_cancels_ramp() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 1000.0)]).unwrap();
+        let controls = chain.effects[0].controls.clone();
+
+        chain.set_param_smoothed(0, "cutoff", 5000.0, 50.0).unwrap();
+        chain.process(0.0, 0.0);
+        assert!(chain.set_param(0, "cutoff", 300.0));
+        for _ in 0..64 {
+            chain.process(0.0, 0.0);
+        }
+        assert_eq!(controls.get("cutoff"), Some(300.0));
+        assert!(chain.effects[0].ramps.is_empty());
+
+        // A zero smoothing time is instant
+        chain.set_param_smoothed(0, "cutoff", 700.0, 0.0).unwrap();
+        assert_eq!(controls.get("cutoff"), Some(700.0));
     }
 
-    /// Load from serializable state
     #[cfg(feature = "serde")]
-    pub fn from_state(&mut self, state: &ChainState) -> Result<()> {
-        // Clear existing effects
-        self.effects.clear();
-        self.bypassed = state.bypassed;
-        self.sample_rate = state.sample_rate;
+    #[test]
+    fn test_morph_to_interpolates_params() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 1000.0)]).unwrap();
+        let controls = chain.effects[0].controls.clone();
 
-        // Rebuild effects from state
-        for effect_state in &state.effects {
-            let index = if let Some(id) = effect_state.id {
-                self.add_effect_with_id(id, &effect_state.name, effect_state.parameters.clone())?
-            } else {
-                self.add_effect(&effect_state.name, &effect_state.parameters)?
-            };
+        let mut target = chain.to_state();
+        target.effects[0].set_param("cutoff", 3000.0);
+        target.effects[0].gain_db = -6.0;
 
-            // Restore bypass/mute state
-            if let Some(effect) = self.effects.get_mut(index) {
-                effect.bypassed = effect_state.bypassed;
-                effect.muted = effect_state.muted;
+        // 10ms at 48kHz = 480 samples
+        chain.morph_to(&target, 10.0).unwrap();
+        assert_eq!(chain.morph_progress(), 0.0);
+        for _ in 0..240 {
+            chain.process(0.0, 0.0);
+        }
+        assert!((chain.morph_progress() - 0.5).abs() < 1e-6);
+        assert!((controls.get("cutoff").unwrap() - 2000.0).abs() < 1e-2);
+        assert!((chain.effect_gain(0).unwrap() + 3.0).abs() < 1e-4);
+
+        let silence = vec![0.0; 1024];
+        let (mut out_l, mut out_r) = (vec![0.0; 1024], vec![0.0; 1024]);
+        chain.process_block(&silence, &silence, &mut out_l, &mut out_r);
+        assert_eq!(chain.morph_progress(), 1.0);
+        assert_eq!(controls.get("cutoff"), Some(3000.0));
+        assert_eq!(chain.effect_gain(0), Some(-6.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_morph_to_crossfades_different_structure() {
+        let build = || {
+            let mut chain = builtin_chain();
+            chain.add("delay", &[("mix", 0.0)]).unwrap();
+            chain
+        };
+        let mut target = builtin_chain();
+        target.add("lpf", &[]).unwrap();
+        target.mute_effect(0, true).unwrap();
+        let target = target.to_state();
+
+        // Fading from an identity chain to a muted one leaves only the fade-out gain
+        let mut chain = build();
+        chain.morph_to(&target, 10.0).unwrap();
+        assert_eq!(chain.effect_name(0), Some("lpf"));
+        let per_sample: Vec<f32> = (0..1000).map(|_| chain.process(1.0, 1.0).0).collect();
+        let expected = (0.5 * std::f32::consts::FRAC_PI_2).cos();
+        assert!((per_sample[239] - expected).abs() < 1e-5);
+        assert!(per_sample[..480].windows(2).all(|w| w[1] <= w[0]));
+        assert!(per_sample[480..].iter().all(|&x| x == 0.0));
+        assert_eq!(chain.morph_progress(), 1.0);
+
+        // Block processing follows the same curve
+        let mut chain = build();
+        chain.morph_to(&target, 10.0).unwrap();
+        let input = vec![1.0; 1000];
+        let (mut out_l, mut out_r) = (vec![0.0; 1000], vec![0.0; 1000]);
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+        for (a, b) in per_sample.iter().zip(&out_l) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_morph_to_different_structure_keeps_chain_settings() {
+        let mut chain = builtin_chain();
+        let comp = EffectId::new_v4();
+        chain
+            .add_with_id(comp, "sidechain_compressor", &[])
+            .unwrap();
+        let kick = chain.add_sidechain_bus("kick");
+        assert!(chain.bind_sidechain(comp, kick));
+        chain.set_bpm(140.0);
+        chain.enable_master_meter();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        chain.set_overload_callback(Box::new(move |event| {
+            if let OverloadEvent::Clip { peak } = event {
+                sink.lock().unwrap().push(peak);
             }
+        }));
+
+        let mut target = builtin_chain();
+        target
+            .add_with_id(comp, "sidechain_compressor", &[])
+            .unwrap()
+            .add("lpf", &[("cutoff", 20000.0)])
+            .unwrap();
+        chain.morph_to(&target.to_state(), 10.0).unwrap();
+        assert_eq!(chain.effect_name(1), Some("lpf"));
+        assert_eq!(chain.bpm(), 140.0);
+        assert_eq!(chain.sidechain_bus("kick"), Some(kick));
+        assert!(chain.master_meter().is_some());
+
+        // The kick bus still ducks the compressor once the crossfade is over
+        let buses = HashMap::from([(kick, (1.0, 1.0))]);
+        let mut out = 0.0;
+        for _ in 0..4800 {
+            out = chain.process_with_buses(0.5, 0.5, &buses).0;
         }
+        assert_eq!(chain.morph_progress(), 1.0);
+        assert!(out < 0.25, "{out}");
 
-        Ok(())
+        // And clipping is still reported
+        chain.set_effect_gain(1, 24.0).unwrap();
+        let input = vec![1.0; 4096];
+        let (mut out_l, mut out_r) = (vec![0.0; 4096], vec![0.0; 4096]);
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+        assert!(!events.lock().unwrap().is_empty());
     }
 
-    /// Get current sample rate
-    pub fn sample_rate(&self) -> f64 {
-        self.sample_rate
+    #[test]
+    fn test_detected_pitch() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 5000.0)]).unwrap();
+        assert_eq!(chain.detected_pitch(), None);
+
+        chain.enable_pitch_detection();
+        for i in 0..8192 {
+            let x = (std::f32::consts::TAU * 440.0 * i as f32 / 48000.0).sin() * 0.5;
+            chain.process(x, x);
+        }
+        let pitch = chain.detected_pitch().unwrap();
+        assert!((pitch - 440.0).abs() < 2.0);
+
+        // Silence clears the estimate
+        let silence = vec![0.0; 4096];
+        let (mut out_l, mut out_r) = (vec![0.0; 4096], vec![0.0; 4096]);
+        chain.process_block(&silence, &silence, &mut out_l, &mut out_r);
+        assert_eq!(chain.detected_pitch(), None);
+
+        chain.disable_pitch_detection();
+        assert_eq!(chain.detected_pitch(), None);
     }
 
-    /// Get number of effects in chain
-    pub fn len(&self) -> usize {
-        self.effects.len()
+    #[test]
+    fn test_add_with_id() {
+        let mut chain = builtin_chain();
+        let (lpf, reverb) = (EffectId::new_v4(), EffectId::new_v4());
+        chain
+            .add_with_id(lpf, "lpf", &[("cutoff", 900.0)])
+            .unwrap()
+            .add_with_id(reverb, "reverb", &[])
+            .unwrap();
+
+        assert_eq!(chain.find_effect_index(lpf), Some(0));
+        assert_eq!(chain.find_effect_index(reverb), Some(1));
+        assert!(chain.set_effect_param_by_id(lpf, "cutoff", 400.0));
+        assert_eq!(chain.effects[0].controls.get("cutoff"), Some(400.0));
+        assert!(chain
+            .add_with_id(EffectId::new_v4(), "missing", &[])
+            .is_err());
     }
 
-    /// Check if chain is empty
-    pub fn is_empty(&self) -> bool {
-        self.effects.is_empty()
+    #[test]
+    fn test_process_with_buses() {
+        let mut chain = builtin_chain();
+        let (gate, comp) = (EffectId::new_v4(), EffectId::new_v4());
+        let lpf = EffectId::new_v4();
+        chain
+            .add_with_id(gate, "sidechain_gate", &[])
+            .unwrap()
+            .add_with_id(comp, "sidechain_compressor", &[])
+            .unwrap()
+            .add_with_id(lpf, "lpf", &[])
+            .unwrap();
+        let hats = chain.add_sidechain_bus("hats");
+        let kick = chain.add_sidechain_bus("kick");
+        assert_eq!(chain.add_sidechain_bus("hats"), hats);
+        assert_eq!(chain.sidechain_bus("kick"), Some(kick));
+        assert_eq!(chain.sidechain_buses(), ["hats", "kick"]);
+
+        assert!(chain.bind_sidechain(gate, hats));
+        assert!(chain.bind_sidechain(comp, kick));
+        assert!(!chain.bind_sidechain(lpf, kick));
+        assert!(!chain.bind_sidechain(gate, 2));
+        assert!(!chain.bind_sidechain(EffectId::new_v4(), hats));
+
+        // Hats open the gate; the kick ducks only through the compressor
+        let mut run = |buses: &HashMap<BusId, (f32, f32)>| {
+            chain.reset();
+            let mut out = 0.0;
+            for _ in 0..4800 {
+                out = chain.process_with_buses(0.5, 0.5, buses).0;
+            }
+            out
+        };
+        let open = run(&HashMap::from([(hats, (1.0, 1.0))]));
+        let ducked = run(&HashMap::from([(hats, (1.0, 1.0)), (kick, (1.0, 1.0))]));
+        let closed = run(&HashMap::from([(hats, (0.0, 0.0)), (kick, (1.0, 1.0))]));
+        assert!(open > 0.4, "{open}");
+        assert!(ducked < open * 0.5, "{ducked}");
+        assert!(closed.abs() < 1e-3, "{closed}");
+
+        assert!(chain.unbind_sidechain(comp));
+        assert_eq!(chain.effects[1].sidechain_bus, None);
     }
 
-    /// Get effect name by index
-    pub fn effect_name(&self, index: usize) -> Option<&str> {
-        self.effects.get(index).map(|e| e.name.as_str())
+    #[test]
+    fn test_process_block_with_buses_matches_per_sample() {
+        let build = || {
+            let mut chain = builtin_chain();
+            let (gate, comp) = (EffectId::new_v4(), EffectId::new_v4());
+            chain
+                .add_with_id(gate, "sidechain_gate", &[])
+                .unwrap()
+                .add_with_id(comp, "sidechain_compressor", &[])
+                .unwrap();
+            let hats = chain.add_sidechain_bus("hats");
+            let kick = chain.add_sidechain_bus("kick");
+            chain.bind_sidechain(gate, hats);
+            chain.bind_sidechain(comp, kick);
+            (chain, hats, kick)
+        };
+
+        // Longer than one chunk, with the buses pulsing at different rates
+        let len = 1500;
+        let input: Vec<f32> = (0..len).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let pulse = |period: usize| -> Vec<f32> {
+            (0..len)
+                .map(|i| if (i / period) % 2 == 0 { 1.0 } else { 0.0 })
+                .collect()
+        };
+        let (hat_sc, kick_sc) = (pulse(200), pulse(300));
+
+        let (mut block_chain, hats, kick) = build();
+        let buses = HashMap::from([
+            (hats, (&hat_sc[..], &hat_sc[..])),
+            (kick, (&kick_sc[..], &kick_sc[..])),
+        ]);
+        let (mut out_l, mut out_r) = (vec![0.0; len], vec![0.0; len]);
+        block_chain.process_block_with_buses(&input, &input, &mut out_l, &mut out_r, &buses);
+
+        let (mut sample_chain, ..) = build();
+        for i in 0..len {
+            let frame = HashMap::from([
+                (hats, (hat_sc[i], hat_sc[i])),
+                (kick, (kick_sc[i], kick_sc[i])),
+            ]);
+            let (l, r) = sample_chain.process_with_buses(input[i], input[i], &frame);
+            assert!((l - out_l[i]).abs() < 1e-6, "left mismatch at {i}");
+            assert!((r - out_r[i]).abs() < 1e-6, "right mismatch at {i}");
+        }
+
+        // Gate open, kick ducking: quieter than the input
+        assert!(out_l[150].abs() < input[150].abs() * 0.5);
     }
 
-    /// Get effect ID by index
-    pub fn effect_id(&self, index: usize) -> Option<EffectId> {
-        self.effects.get(index).and_then(|e| e.id)
+    #[test]
+    fn test_short_bus_slice_counts_as_missing() {
+        let build = || {
+            let mut chain = builtin_chain();
+            let comp = EffectId::new_v4();
+            chain
+                .add_with_id(comp, "sidechain_compressor", &[])
+                .unwrap();
+            let kick = chain.add_sidechain_bus("kick");
+            chain.bind_sidechain(comp, kick);
+            (chain, kick)
+        };
+        let input = vec![0.5; 256];
+        let short = vec![1.0; 100];
+
+        let (mut chain, kick) = build();
+        let buses = HashMap::from([(kick, (&short[..], &short[..]))]);
+        let (mut out_l, mut out_r) = (vec![0.0; 256], vec![0.0; 256]);
+        chain.process_block_with_buses(&input, &input, &mut out_l, &mut out_r, &buses);
+
+        let (mut unbound, _) = build();
+        let (mut ref_l, mut ref_r) = (vec![0.0; 256], vec![0.0; 256]);
+        unbound.process_block_with_buses(&input, &input, &mut ref_l, &mut ref_r, &HashMap::new());
+        assert_eq!(out_l, ref_l);
+        assert_eq!(out_r, ref_r);
     }
 
-    /// Clear all effects from the chain
-    pub fn clear(&mut self) {
-        self.effects.clear();
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_sidechain_bindings_round_trip_through_state() {
+        let mut chain = builtin_chain();
+        let comp = EffectId::new_v4();
+        chain
+            .add_with_id(comp, "sidechain_compressor", &[])
+            .unwrap();
+        chain.add_sidechain_bus("hats");
+        let kick = chain.add_sidechain_bus("kick");
+        chain.bind_sidechain(comp, kick);
+
+        let state = chain.to_state();
+        assert_eq!(state.sidechain_buses, ["hats", "kick"]);
+        assert_eq!(state.effects[0].sidechain_bus.as_deref(), Some("kick"));
+
+        let mut restored = builtin_chain();
+        restored.from_state(&state).unwrap();
+        assert_eq!(restored.sidechain_buses(), ["hats", "kick"]);
+        assert_eq!(restored.effects[0].sidechain_bus, Some(kick));
+
+        // The restored binding still ducks on the kick bus
+        let mut out = 0.0;
+        let buses = HashMap::from([(kick, (1.0, 1.0))]);
+        for _ in 0..4800 {
+            out = restored.process_with_buses(0.5, 0.5, &buses).0;
+        }
+        assert!(out < 0.25, "{out}");
     }
 
-    /// Get CPU usage for a specific effect
-    pub fn effect_cpu_usage(&self, index: usize) -> Option<f64> {
-        self.effects
-            .get(index)
-            .map(|e| e.cpu_meter.metrics().cpu_usage)
+    #[test]
+    fn test_bypass_and_mute_by_id() {
+        let mut chain = builtin_chain();
+        let id = EffectId::new_v4();
+        chain.add_with_id(id, "lpf", &[]).unwrap();
+
+        assert!(chain.set_effect_bypass_by_id(id, true));
+        assert_eq!(chain.is_effect_bypassed(0), Some(true));
+        assert!(chain.toggle_effect_bypass_by_id(id));
+        assert_eq!(chain.is_effect_bypassed(0), Some(false));
+        assert!(chain.toggle_effect_bypass_by_id(id));
+        assert_eq!(chain.is_effect_bypassed(0), Some(true));
+
+        assert!(chain.set_effect_mute_by_id(id, true));
+        assert_eq!(chain.is_effect_muted(0), Some(true));
+
+        // Unknown IDs report failure
+        let unknown = EffectId::new_v4();
+        assert!(!chain.toggle_effect_bypass_by_id(unknown));
+        assert!(!chain.set_effect_bypass_by_id(unknown, false));
+        assert!(!chain.set_effect_mute_by_id(unknown, false));
     }
 
-    /// Get CPU percentage for a specific effect (0-100%)
-    pub fn effect_cpu_percent(&self, index: usize) -> Option<f64> {
-        self.effects
-            .get(index)
-            .map(|e| e.cpu_meter.metrics().cpu_percent())
+    #[test]
+    fn test_effect_io_levels() {
+        let mut chain = builtin_chain();
+        let id = EffectId::new_v4();
+        chain
+            .add("lpf", &[("cutoff", 20000.0)])
+            .unwrap()
+            .add_with_id(id, "lpf", &[("cutoff", 20000.0)])
+            .unwrap();
+        chain.set_effect_gain(0, -6.0).unwrap();
+
+        for i in 0..LEVEL_WINDOW * 2 {
+            let x = (i as f32 * 0.05).sin() * 0.5;
+            chain.process(x, x);
+        }
+
+        let levels = chain.get_effect_io_levels();
+        assert_eq!(levels.len(), 2);
+        let (input, output) = levels["lpf_0"];
+        assert!((input.peak_l - 0.5).abs() < 1e-3);
+        assert!(output.rms_l < input.rms_l * 0.6);
+
+        // The second effect is keyed by its ID and sees the trimmed signal
+        let (input, _) = levels[&id.to_string()];
+        assert!((input.peak_l - output.peak_l).abs() < 1e-6);
+
+        // The output-only view agrees
+        let legacy = chain.get_effect_levels();
+        let (rms_l, rms_r, peak_l, peak_r) = legacy["lpf_0"];
+        assert_eq!(
+            output,
+            EffectMeter {
+                rms_l,
+                rms_r,
+                peak_l,
+                peak_r
+            }
+        );
     }
 
-    /// Get total CPU usage across all effects
-    pub fn total_cpu_usage(&self) -> f64 {
-        self.effects
-            .iter()
-            .filter(|e| !e.bypassed)
-            .map(|e| e.cpu_meter.metrics().cpu_usage)
-            .sum()
+    #[test]
+    fn test_gain_staging_report() {
+        let mut chain = builtin_chain();
+        chain
+            .add("lpf", &[("cutoff", 20000.0)])
+            .unwrap()
+            .add("distortion", &[])
+            .unwrap();
+        // Drive the distortion 20 dB over full scale
+        chain.set_effect_gain(0, 20.0).unwrap();
+
+        for i in 0..LEVEL_WINDOW * 2 {
+            let x = (i as f32 * 0.05).sin() * 0.5;
+            chain.process(x, x);
+        }
+
+        // The hot joint is reported once, on the output gain that sets it
+        let report = chain.gain_staging_report();
+        assert_eq!(report.len(), 1, "{report:?}");
+        let hot = &report[0];
+        assert_eq!((hot.index, hot.issue), (0, GainIssue::OutputTooHot));
+        assert!((hot.peak_db - 14.0).abs() < 0.1, "{}", hot.peak_db);
+        assert!((hot.trim_db + 20.0).abs() < 0.1, "{}", hot.trim_db);
+
+        // A healthy level produces no suggestions
+        chain.set_effect_gain(0, 0.0).unwrap();
+        chain.set_effect_gain(1, -6.0).unwrap();
+        chain.reset();
+        for i in 0..LEVEL_WINDOW * 2 {
+            let x = (i as f32 * 0.05).sin() * 0.5;
+            chain.process(x, x);
+        }
+        assert!(chain.gain_staging_report().is_empty());
     }
 
-    /// Get total CPU percentage across all effects (0-100%)
-    pub fn total_cpu_percent(&self) -> f64 {
-        self.total_cpu_usage() * 100.0
+    #[test]
+    fn test_ride_gain_fades_in() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 20000.0)]).unwrap();
+        chain
+            .ride_gain(0, &[(0.0, f32::NEG_INFINITY), (0.1, 0.0)])
+            .unwrap();
+
+        let input = vec![0.5; 9600];
+        let (mut out_l, mut out_r) = (vec![0.0; 9600], vec![0.0; 9600]);
+        chain.process_block(&input, &input, &mut out_l, &mut out_r);
+
+        // Silent at the start, full level once the 100ms ride is over
+        assert!(out_l[0].abs() < 1e-3);
+        assert!((out_l[4800] - 0.5).abs() < 1e-3);
+        assert!((out_l[9599] - 0.5).abs() < 1e-3);
+
+        // A smooth, monotonic rise with no steps
+        for pair in out_l[16..4800].windows(2) {
+            assert!(pair[1] >= pair[0] - 1e-5);
+            assert!(pair[1] - pair[0] < 0.005);
+        }
+
+        // Out-of-order points are rejected
+        assert!(chain.ride_gain(0, &[(1.0, 0.0), (0.5, -6.0)]).is_err());
+        assert!(chain.ride_gain(1, &[(0.0, 0.0)]).is_err());
     }
 
-    /// Check if any effect is overloaded (>80% CPU)
-    pub fn has_overload(&self) -> bool {
-        self.effects
-            .iter()
-            .any(|e| e.cpu_meter.metrics().is_overloaded())
+    #[test]
+    fn test_is_stable() {
+        let mut decaying = builtin_chain();
+        decaying
+            .add("delay", &[("time", 0.05), ("feedback", 0.5), ("mix", 0.5)])
+            .unwrap();
+        assert!(decaying.is_stable(750));
+
+        let mut runaway = builtin_chain();
+        runaway
+            .add("delay", &[("time", 0.05), ("feedback", 1.1), ("mix", 0.5)])
+            .unwrap();
+        assert!(!runaway.is_stable(750));
+
+        // The test leaves no tail behind
+        let (l, r) = runaway.process(0.0, 0.0);
+        assert_eq!((l, r), (0.0, 0.0));
+
+        let mut filter = builtin_chain();
+        filter.add("lpf", &[("cutoff", 2000.0)]).unwrap();
+        assert!(filter.is_stable(16));
     }
 
-    /// Reset CPU meters for all effects
-    pub fn reset_cpu_meters(&mut self) {
-        for effect in &mut self.effects {
-            effect.cpu_meter.reset();
+    #[test]
+    fn test_mix_changes_are_smoothed() {
+        // A Nyquist-rate input is removed by the lowpass, so the output level
+        // is the dry share: 1 - mix
+        let nyquist_levels = |chain: &mut EffectChain, samples: usize| -> Vec<f32> {
+            (0..samples)
+                .map(|i| {
+                    let x = if i % 2 == 0 { 1.0 } else { -1.0 };
+                    chain.process(x, x).0.abs()
+                })
+                .collect()
+        };
+
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 100.0)]).unwrap();
+        chain.set_effect_mix(0, 0.0).unwrap();
+        let levels = nyquist_levels(&mut chain, 4800);
+        assert!((levels[4799] - 1.0).abs() < 1e-3);
+
+        // Stepping the mix to 1 glides instead of jumping
+        chain.set_effect_mix(0, 1.0).unwrap();
+        let levels = nyquist_levels(&mut chain, 4800);
+        assert!(levels[0] > 0.99, "mix jumped: {}", levels[0]);
+        assert!(levels[4799] < 1e-3);
+        for pair in levels.windows(2) {
+            assert!((pair[0] - pair[1]).abs() < 0.01, "zipper step {pair:?}");
         }
+
+        // With smoothing off the change is instant
+        chain.set_mix_smoothing(0.0);
+        chain.set_effect_mix(0, 0.0).unwrap();
+        assert!(nyquist_levels(&mut chain, 1)[0] > 0.99);
+
+        // An effect's own mix parameter is smoothed the same way
+        let mut chain = builtin_chain();
+        chain.add("delay", &[("mix", 0.0)]).unwrap();
+        chain.process(0.0, 0.0);
+        chain.set_param(0, "mix", 1.0);
+        assert_eq!(chain.effects[0].controls.get("mix"), Some(1.0));
+        chain.process(0.0, 0.0);
+        let heard =
+            |chain: &EffectChain| chain.effects[0].mix_param.as_ref().unwrap().output.value();
+        assert!(heard(&chain) < 0.01);
+        nyquist_levels(&mut chain, 4800);
+        assert!((heard(&chain) - 1.0).abs() < 1e-3);
     }
 
-    /// Get detailed CPU metrics for all effects
-    pub fn cpu_report(&self) -> Vec<(String, f64, bool)> {
-        self.effects
-            .iter()
-            .map(|e| {
-                let metrics = e.cpu_meter.metrics();
-                (
-                    e.name.clone(),
-                    metrics.cpu_percent(),
-                    metrics.is_overloaded(),
-                )
-            })
-            .collect()
+    #[test]
+    fn test_effect_lfo_modulates_param() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 1000.0)]).unwrap();
+        let controls = chain.effects[0].controls.clone();
+        chain
+            .attach_lfo(0, "cutoff", LFOConfig::new(10.0, 0.05, LFOWaveform::Sine))
+            .unwrap();
+
+        // Depth 0.05 of the 20-20000 Hz range swings ~500 Hz either way
+        let (mut low, mut high) = (f32::MAX, f32::MIN);
+        for _ in 0..4800 {
+            chain.process(0.0, 0.0);
+            let cutoff = controls.get("cutoff").unwrap();
+            low = low.min(cutoff);
+            high = high.max(cutoff);
+        }
+        assert!((high - 1499.5).abs() < 1.0, "{high}");
+        assert!((low - 500.5).abs() < 1.0, "{low}");
+
+        // Setting the parameter moves the centre of the swing
+        chain.set_param(0, "cutoff", 2000.0);
+        let (mut low, mut high) = (f32::MAX, f32::MIN);
+        for _ in 0..4800 {
+            chain.process(0.0, 0.0);
+            let cutoff = controls.get("cutoff").unwrap();
+            low = low.min(cutoff);
+            high = high.max(cutoff);
+        }
+        assert!((high - 2499.5).abs() < 1.0, "{high}");
+        assert!((low - 1500.5).abs() < 1.0, "{low}");
+
+        chain.detach_lfo(0, "cutoff").unwrap();
+        assert_eq!(controls.get("cutoff"), Some(2000.0));
+        assert!(chain.effect_lfo(0, "cutoff").is_none());
+
+        assert!(chain.attach_lfo(0, "nope", LFOConfig::default()).is_err());
+        assert!(chain.attach_lfo(1, "cutoff", LFOConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_effect_lfo_unipolar_tempo_sync() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 1000.0)]).unwrap();
+        let controls = chain.effects[0].controls.clone();
+        let config = LFOConfig {
+            sync: Some(NoteDivision::Quarter),
+            ..LFOConfig::new(0.1, 0.1, LFOWaveform::Square)
+        };
+        let lfo = EffectLFO::new(config).unipolar();
+        chain.attach_lfo(0, "cutoff", lfo).unwrap();
+        assert_eq!(chain.effect_lfo(0, "cutoff"), Some(lfo));
+
+        // Quarter notes at 120 BPM are 2 Hz: high for the first 0.25s
+        let silence = vec![0.0; 9600];
+        let (mut out_l, mut out_r) = (vec![0.0; 9600], vec![0.0; 9600]);
+        chain.process_block(&silence, &silence, &mut out_l, &mut out_r);
+        assert!((controls.get("cutoff").unwrap() - 2998.0).abs() < 0.5);
+
+        // ...then back at the set value, never below it
+        chain.process_block(
+            &silence[..4800],
+            &silence[..4800],
+            &mut out_l[..4800],
+            &mut out_r[..4800],
+        );
+        assert_eq!(controls.get("cutoff"), Some(1000.0));
+
+        // Halving the tempo halves the rate
+        chain.set_bpm(60.0);
+        chain.reset();
+        chain.process_block(&silence, &silence, &mut out_l, &mut out_r);
+        chain.process_block(
+            &silence[..4800],
+            &silence[..4800],
+            &mut out_l[..4800],
+            &mut out_r[..4800],
+        );
+        assert!((controls.get("cutoff").unwrap() - 2998.0).abs() < 0.5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_effect_lfo_state_round_trip() {
+        let mut chain = builtin_chain();
+        chain.add("lpf", &[("cutoff", 1000.0)]).unwrap();
+        let lfo = EffectLFO::new(LFOConfig::new(3.0, 0.2, LFOWaveform::Triangle)).unipolar();
+        chain.attach_lfo(0, "cutoff", lfo).unwrap();
+        for _ in 0..1000 {
+            chain.process(0.0, 0.0);
+        }
+
+        // The unmodulated value is saved, not wherever the LFO happens to be
+        let state = chain.to_state();
+        assert_eq!(state.effects[0].get_param("cutoff"), Some(1000.0));
+        assert_eq!(state.effects[0].lfos.len(), 1);
+
+        let json = chain.to_json().unwrap();
+        let mut loaded = builtin_chain();
+        loaded.from_json(&json).unwrap();
+        assert_eq!(loaded.effect_lfo(0, "cutoff"), Some(lfo));
+        assert_eq!(loaded.effects[0].controls.get("cutoff"), Some(1000.0));
+    }
+
+    #[test]
+    fn test_from_dsl() {
+        let chain = EffectChain::from_dsl("lpf cutoff=2000 res=0.7 -> reverb mix=0.4").unwrap();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain.effect_name(0), Some("lpf"));
+        assert_eq!(chain.effect_name(1), Some("reverb"));
+        assert_eq!(chain.effects[0].controls.get("cutoff"), Some(2000.0));
+        assert_eq!(chain.effects[0].controls.get("res"), Some(0.7));
+        // The reverb has no mix parameter, so the chain blends it
+        assert_eq!(chain.effect_mix(1), Some(0.4));
+
+        let chain = EffectChain::from_dsl("delay mix=0.2 gain_db=-3").unwrap();
+        assert_eq!(chain.effects[0].controls.get("mix"), Some(0.2));
+        assert_eq!(chain.effect_mix(0), Some(1.0));
+        assert_eq!(chain.effect_gain(0), Some(-3.0));
+
+        // Registry is kept, so more effects can be added
+        let mut chain = EffectChain::from_dsl("  ").unwrap();
+        assert!(chain.is_empty());
+        assert!(chain.add("hpf", &[]).is_ok());
+
+        assert!(matches!(
+            EffectChain::from_dsl("lpf -> nope"),
+            Err(crate::Error::UnknownEffect { name, .. }) if name == "nope"
+        ));
+        assert!(matches!(
+            EffectChain::from_dsl("lpf cutof=200"),
+            Err(crate::Error::InvalidParameter(name)) if name == "lpf.cutof"
+        ));
+        assert!(EffectChain::from_dsl("lpf cutoff=abc").is_err());
+        assert!(EffectChain::from_dsl("lpf cutoff").is_err());
+        assert!(EffectChain::from_dsl("lpf -> -> reverb").is_err());
     }
 }
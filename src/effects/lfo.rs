@@ -0,0 +1,142 @@
+//! LFO modulation of effect parameters
+//!
+//! An [`EffectLFO`] attached to a parameter of an effect in an
+//! [`EffectChain`](super::EffectChain) sweeps that parameter around the value
+//! it was set to. The chain advances attached LFOs as it processes audio and
+//! writes the modulated value into the parameter's `Shared`.
+
+use crate::synth::lfo::{LFOConfig, LFOGenerator};
+use fundsp::shared::Shared;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Where an LFO's swing lies relative to the parameter value
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LFOPolarity {
+    /// Swing above and below the value
+    Bipolar,
+    /// Swing upward from the value only
+    Unipolar,
+}
+
+/// An LFO routed to an effect parameter
+///
+/// `config.depth` is the fraction of the parameter's range covered: a
+/// bipolar LFO at depth 1.0 swings half the range either way, a unipolar one
+/// the whole range upward. The result is clamped to the range. A config with
+/// `sync` set follows the chain tempo.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectLFO {
+    /// Rate, depth, waveform and start phase
+    pub config: LFOConfig,
+    /// Where the swing lies relative to the parameter value
+    pub polarity: LFOPolarity,
+}
+
+impl EffectLFO {
+    /// Create a free-running bipolar LFO
+    pub fn new(config: LFOConfig) -> Self {
+        Self {
+            config,
+            polarity: LFOPolarity::Bipolar,
+        }
+    }
+
+    /// Builder pattern: swing above and below the value
+    pub fn bipolar(mut self) -> Self {
+        self.polarity = LFOPolarity::Bipolar;
+        self
+    }
+
+    /// Builder pattern: swing upward from the value only
+    pub fn unipolar(mut self) -> Self {
+        self.polarity = LFOPolarity::Unipolar;
+        self
+    }
+}
+
+impl From<LFOConfig> for EffectLFO {
+    fn from(config: LFOConfig) -> Self {
+        Self::new(config)
+    }
+}
+
+/// An LFO attached to a parameter, with its running state
+pub(crate) struct AttachedLfo {
+    /// Name of the modulated parameter
+    pub(crate) param: String,
+    /// LFO settings
+    pub(crate) lfo: EffectLFO,
+    /// The modulated parameter
+    shared: Shared,
+    /// Value the LFO swings around
+    pub(crate) center: f32,
+    /// Lowest value written
+    min: f32,
+    /// Highest value written
+    max: f32,
+    /// Waveform generator
+    generator: LFOGenerator,
+    /// Last value written, used to detect external writes
+    last: f32,
+}
+
+impl AttachedLfo {
+    /// Attach `lfo` to `shared`, swinging around its current value within `range`
+    pub(crate) fn new(param: &str, lfo: EffectLFO, shared: Shared, range: (f32, f32)) -> Self {
+        let center = shared.value();
+        Self {
+            param: param.to_string(),
+            lfo,
+            shared,
+            center,
+            min: range.0.min(range.1),
+            max: range.0.max(range.1),
+            generator: LFOGenerator::new(lfo.config),
+            last: center,
+        }
+    }
+
+    /// Write the value for the current phase, then advance by `samples` samples
+    #[inline]
+    pub(crate) fn advance(&mut self, samples: usize, sample_rate: f64, bpm: f32) {
+        // Someone else wrote the parameter: swing around the new value
+        let value = self.shared.value();
+        if value != self.last {
+            self.center = value;
+        }
+
+        let value = self.value();
+        self.shared.set_value(value);
+        self.last = value;
+
+        self.generator.advance(samples, sample_rate as f32, bpm);
+    }
+
+    /// Restart from the configured phase
+    pub(crate) fn reset(&mut self) {
+        self.generator.reset();
+    }
+
+    /// Stop modulating, leaving the parameter at its unmodulated value
+    pub(crate) fn detach(self) {
+        if self.shared.value() == self.last {
+            self.shared.set_value(self.center);
+        }
+    }
+
+    /// Modulated parameter value at the current phase
+    fn value(&self) -> f32 {
+        let depth = self.lfo.config.depth;
+        let span = self.max - self.min;
+        let shape = self.generator.value();
+        let offset = match self.lfo.polarity {
+            LFOPolarity::Bipolar => shape * depth * span * 0.5,
+            LFOPolarity::Unipolar => (shape + 1.0) * 0.5 * depth * span,
+        };
+        (self.center + offset).clamp(self.min, self.max)
+    }
+}
@@ -11,6 +11,8 @@
 pub mod builder;
 pub mod builtin;
 pub mod chain;
+pub mod lfo;
+pub mod pitch;
 #[cfg(feature = "serde")]
 pub mod preset;
 pub mod registry;
@@ -18,21 +20,27 @@ pub mod registry;
 pub mod serialize;
 pub mod sidechain;
 pub mod smoothing;
+pub mod util;
 
 pub use builder::{Effect, EffectBuilder as FluentEffectBuilder, EffectRegistryExt};
-pub use chain::EffectChain;
+pub use chain::{
+    ChainCpuReport, EffectChain, EffectCpuEntry, EffectMeter, GainIssue, GainSuggestion,
+    OverloadCallback, OverloadEvent, ParallelGroup, StereoMeter,
+};
+pub use lfo::{EffectLFO, LFOPolarity};
+pub use pitch::PitchDetector;
 #[cfg(feature = "serde")]
 pub use preset::{
-    mastering_bank, mixing_bank, EffectPreset, EffectPresetBank, MasteringPresets,
-    MixingPresets, PresetBankMasteringExt, PresetBankMixingExt,
+    all_builtin_presets, mastering_bank, mixing_bank, EffectPreset, EffectPresetBank,
+    MasteringPresets, MixingPresets, PresetBankMasteringExt, PresetBankMixingExt, ValidationError,
 };
 pub use registry::{
     EffectBuilder, EffectControls, EffectMetadata, EffectRegistry, ParameterRange,
 };
 #[cfg(feature = "serde")]
-pub use serialize::{ChainState, EffectState};
-pub use sidechain::SidechainAwareEffect;
-pub use smoothing::{SmoothedParam, SmoothedParamBuilder};
+pub use serialize::{ChainState, EffectState, LfoAttachment};
+pub use sidechain::{BusId, SidechainAwareEffect};
+pub use smoothing::{SmoothedParam, SmoothedParamBuilder, DEFAULT_SMOOTHING_MS};
 
 // Re-export UUID for effect IDs
 pub use uuid::Uuid;
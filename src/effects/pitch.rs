@@ -0,0 +1,218 @@
+//! Pitch detection for tuners and pitch-tracking effects
+//!
+//! Uses the YIN algorithm (de Cheveigné & Kawahara, 2002) on a sliding
+//! window of mono input.
+
+/// Analysis window length in samples
+const WINDOW_SIZE: usize = 2048;
+/// Samples between analyses
+const HOP_SIZE: usize = 1024;
+/// YIN threshold on the cumulative mean normalized difference
+const YIN_THRESHOLD: f32 = 0.15;
+/// Windows quieter than this RMS are treated as silence
+const SILENCE_RMS: f32 = 1e-4;
+/// Lowest detectable frequency in Hz
+const MIN_FREQUENCY: f32 = 50.0;
+/// Highest detectable frequency in Hz
+const MAX_FREQUENCY: f32 = 2000.0;
+
+/// Fundamental frequency detector
+///
+/// Feed samples with [`push`](Self::push); a new estimate is made every
+/// 1024 samples over the last 2048. [`pitch`](Self::pitch) returns `None`
+/// for silence and for signals without a clear periodicity.
+#[derive(Clone)]
+pub struct PitchDetector {
+    /// Ring buffer of recent input
+    buffer: Vec<f32>,
+    /// Write position in `buffer`
+    pos: usize,
+    /// Samples pushed since the last analysis
+    since_analysis: usize,
+    /// Samples pushed in total (saturating), to wait for a full window
+    filled: usize,
+    /// Linearized window for analysis
+    frame: Vec<f32>,
+    /// Difference function / cumulative mean normalized difference
+    diff: Vec<f32>,
+    /// Latest estimate in Hz
+    pitch: Option<f32>,
+    /// Confidence of the latest estimate (0.0 to 1.0)
+    confidence: f32,
+    sample_rate: f32,
+}
+
+impl PitchDetector {
+    /// Create a new pitch detector
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            buffer: vec![0.0; WINDOW_SIZE],
+            pos: 0,
+            since_analysis: 0,
+            filled: 0,
+            frame: vec![0.0; WINDOW_SIZE],
+            diff: vec![0.0; WINDOW_SIZE / 2 + 1],
+            pitch: None,
+            confidence: 0.0,
+            sample_rate,
+        }
+    }
+
+    /// Set the sample rate in Hz
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.reset();
+    }
+
+    /// Clear the input history and the current estimate
+    pub fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.pos = 0;
+        self.since_analysis = 0;
+        self.filled = 0;
+        self.pitch = None;
+        self.confidence = 0.0;
+    }
+
+    /// Push one mono input sample
+    #[inline]
+    pub fn push(&mut self, sample: f32) {
+        self.buffer[self.pos] = sample;
+        self.pos = (self.pos + 1) % WINDOW_SIZE;
+        self.filled = (self.filled + 1).min(WINDOW_SIZE);
+        self.since_analysis += 1;
+        if self.since_analysis >= HOP_SIZE && self.filled == WINDOW_SIZE {
+            self.since_analysis = 0;
+            self.analyze();
+        }
+    }
+
+    /// Latest detected fundamental in Hz
+    pub fn pitch(&self) -> Option<f32> {
+        self.pitch
+    }
+
+    /// Confidence of the latest estimate (0.0 = none, 1.0 = perfectly periodic)
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Run YIN over the current window
+    fn analyze(&mut self) {
+        // Oldest sample first
+        let (newer, older) = self.buffer.split_at(self.pos);
+        self.frame[..older.len()].copy_from_slice(older);
+        self.frame[older.len()..].copy_from_slice(newer);
+
+        let rms = (self.frame.iter().map(|x| x * x).sum::<f32>() / WINDOW_SIZE as f32).sqrt();
+        if rms < SILENCE_RMS {
+            self.pitch = None;
+            self.confidence = 0.0;
+            return;
+        }
+
+        let half = WINDOW_SIZE / 2;
+        let min_lag = ((self.sample_rate / MAX_FREQUENCY) as usize).max(2);
+        let max_lag = ((self.sample_rate / MIN_FREQUENCY) as usize).min(half - 1);
+        if min_lag >= max_lag {
+            self.pitch = None;
+            self.confidence = 0.0;
+            return;
+        }
+
+        // Difference function
+        self.diff[0] = 0.0;
+        for lag in 1..=max_lag {
+            let mut sum = 0.0;
+            for j in 0..half {
+                let delta = self.frame[j] - self.frame[j + lag];
+                sum += delta * delta;
+            }
+            self.diff[lag] = sum;
+        }
+
+        // Cumulative mean normalized difference
+        self.diff[0] = 1.0;
+        let mut running_sum = 0.0;
+        for lag in 1..=max_lag {
+            running_sum += self.diff[lag];
+            self.diff[lag] = if running_sum > 0.0 {
+                self.diff[lag] * lag as f32 / running_sum
+            } else {
+                1.0
+            };
+        }
+
+        // First dip below the threshold, followed down to its local minimum
+        let mut lag = min_lag;
+        let found = loop {
+            if lag > max_lag {
+                break None;
+            }
+            if self.diff[lag] < YIN_THRESHOLD {
+                while lag < max_lag && self.diff[lag + 1] < self.diff[lag] {
+                    lag += 1;
+                }
+                break Some(lag);
+            }
+            lag += 1;
+        };
+
+        let Some(lag) = found else {
+            self.pitch = None;
+            self.confidence = 0.0;
+            return;
+        };
+
+        // Parabolic interpolation for sub-sample accuracy
+        let refined = if lag > 1 && lag < max_lag {
+            let (a, b, c) = (self.diff[lag - 1], self.diff[lag], self.diff[lag + 1]);
+            let denom = a - 2.0 * b + c;
+            if denom.abs() > f32::EPSILON {
+                lag as f32 + 0.5 * (a - c) / denom
+            } else {
+                lag as f32
+            }
+        } else {
+            lag as f32
+        };
+
+        self.pitch = Some(self.sample_rate / refined);
+        self.confidence = (1.0 - self.diff[lag]).clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(freq: f32, amplitude: f32) -> Option<f32> {
+        let mut detector = PitchDetector::new(48000.0);
+        for i in 0..8192 {
+            let t = i as f32 / 48000.0;
+            detector.push((std::f32::consts::TAU * freq * t).sin() * amplitude);
+        }
+        detector.pitch()
+    }
+
+    #[test]
+    fn test_detects_sine_frequencies() {
+        for freq in [82.4, 220.0, 440.0, 1000.0] {
+            let pitch = detect(freq, 0.5).unwrap();
+            assert!((pitch - freq).abs() < freq * 0.005, "{} -> {}", freq, pitch);
+        }
+    }
+
+    #[test]
+    fn test_silence_and_noise_have_no_pitch() {
+        assert_eq!(detect(440.0, 0.0), None);
+
+        let mut detector = PitchDetector::new(48000.0);
+        let mut seed = 12345u32;
+        for _ in 0..8192 {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            detector.push((seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5);
+        }
+        assert_eq!(detector.pitch(), None);
+    }
+}
@@ -91,7 +91,7 @@ impl MasteringPresets {
             .with_effect(
                 EffectState::new("hpf")
                     .with_param("cutoff", 80.0)
-                    .with_param("res", 0.5),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("eq_3band")
@@ -122,7 +122,7 @@ impl MasteringPresets {
             .with_effect(
                 EffectState::new("lpf")
                     .with_param("cutoff", 8000.0)
-                    .with_param("res", 0.3),
+                    .with_param("res", 0.0),
             )
             .with_effect(EffectState::new("bitcrush").with_param("bits", 12.0))
             .with_effect(EffectState::new("tape").with_param("saturation", 0.5))
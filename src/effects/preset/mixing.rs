@@ -17,7 +17,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("hpf")
                     .with_param("cutoff", 100.0)
-                    .with_param("res", 0.5),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("eq_3band")
@@ -41,7 +41,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("hpf")
                     .with_param("cutoff", 80.0)
-                    .with_param("res", 0.5),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("eq_3band")
@@ -71,12 +71,12 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("hpf")
                     .with_param("cutoff", 500.0)
-                    .with_param("res", 0.7),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("lpf")
                     .with_param("cutoff", 3000.0)
-                    .with_param("res", 0.7),
+                    .with_param("res", 0.0),
             )
             .with_effect(EffectState::new("bitcrush").with_param("bits", 10.0))
             .with_effect(EffectState::new("soft_clip").with_param("amount", 0.4))
@@ -125,7 +125,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("lpf")
                     .with_param("cutoff", 6000.0)
-                    .with_param("res", 0.3),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("delay")
@@ -151,7 +151,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("lpf")
                     .with_param("cutoff", 5000.0)
-                    .with_param("res", 0.4),
+                    .with_param("res", 0.0),
             )
             .with_effect(EffectState::new("gate").with_param("threshold", -40.0))
             .with_description("High-gain distorted guitar with gate")
@@ -226,7 +226,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("lpf")
                     .with_param("cutoff", 8000.0)
-                    .with_param("res", 0.2),
+                    .with_param("res", 0.0),
             )
             .with_description("Wide, spacious synth pad processing")
             .with_tag("synth")
@@ -267,7 +267,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("hpf")
                     .with_param("cutoff", 40.0)
-                    .with_param("res", 0.5),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("eq_3band")
@@ -291,7 +291,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("hpf")
                     .with_param("cutoff", 50.0)
-                    .with_param("res", 0.5),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("eq_3band")
@@ -303,7 +303,7 @@ impl MixingPresets {
             .with_effect(
                 EffectState::new("lpf")
                     .with_param("cutoff", 4000.0)
-                    .with_param("res", 0.4),
+                    .with_param("res", 0.0),
             )
             .with_effect(
                 EffectState::new("compressor")
@@ -12,9 +12,12 @@
 pub mod mastering;
 pub mod mixing;
 
+use crate::effects::registry::EffectRegistry;
 use crate::effects::serialize::EffectState;
+use crate::params::FORMAT_VERSION;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use uuid::Uuid;
 
 pub use mastering::{mastering_bank, MasteringPresets, PresetBankMasteringExt};
@@ -23,6 +26,9 @@ pub use mixing::{mixing_bank, MixingPresets, PresetBankMixingExt};
 /// A complete effect chain preset
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EffectPreset {
+    /// Format version (see [`FORMAT_VERSION`]); 0 when absent
+    #[serde(default)]
+    pub version: u32,
     /// Unique ID for this preset
     pub id: Uuid,
     /// Preset name
@@ -43,6 +49,7 @@ impl EffectPreset {
     /// Create a new preset
     pub fn new(name: impl Into<String>) -> Self {
         Self {
+            version: FORMAT_VERSION,
             id: Uuid::new_v4(),
             name: name.into(),
             effects: Vec::new(),
@@ -111,9 +118,38 @@ impl EffectPreset {
         serde_json::to_string_pretty(self)
     }
 
-    /// Deserialize preset from JSON string
+    /// Deserialize preset from JSON string, upgrading older formats
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+        let mut preset: Self = serde_json::from_str(json)?;
+        preset.migrate();
+        Ok(preset)
+    }
+
+    /// Check the preset against the effects available in `registry`
+    ///
+    /// Every effect must be registered and every parameter must be one the
+    /// effect takes (`res_raw` is accepted wherever `res` is) and within its
+    /// range. Effects inside parallel groups are checked too. All problems are
+    /// returned at once, in effect order.
+    pub fn validate(&self, registry: &EffectRegistry) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        validate_effects(&self.effects, registry, None, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Upgrade a preset saved by an older version to [`FORMAT_VERSION`]
+    ///
+    /// [`from_json`](Self::from_json) and [`load`](Self::load) do this
+    /// already. Current presets are left unchanged.
+    pub fn migrate(&mut self) {
+        for effect in &mut self.effects {
+            effect.migrate(self.version);
+        }
+        self.version = self.version.max(FORMAT_VERSION);
     }
 
     /// Save preset to file
@@ -136,6 +172,85 @@ impl EffectPreset {
     }
 }
 
+/// A problem found by [`EffectPreset::validate`]
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    /// Position in [`EffectPreset::effects`] of the effect with the problem
+    /// (the group, for effects inside a parallel group's branches)
+    pub index: usize,
+    /// [`Error::UnknownEffect`](crate::Error::UnknownEffect),
+    /// [`Error::InvalidParameter`](crate::Error::InvalidParameter) (as
+    /// `effect.param`) or
+    /// [`Error::ParameterOutOfRange`](crate::Error::ParameterOutOfRange)
+    pub error: crate::Error,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "effect {}: {}", self.index, self.error)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Check `effects` against `registry`, reporting problems at `index`, or at
+/// each effect's own position when `index` is `None`
+fn validate_effects(
+    effects: &[EffectState],
+    registry: &EffectRegistry,
+    index: Option<usize>,
+    errors: &mut Vec<ValidationError>,
+) {
+    for (position, effect) in effects.iter().enumerate() {
+        let index = index.unwrap_or(position);
+        if !effect.branches.is_empty() {
+            for branch in &effect.branches {
+                validate_effects(&branch.effects, registry, Some(index), errors);
+            }
+            continue;
+        }
+
+        let mut report = |error| errors.push(ValidationError { index, error });
+
+        let Some(defs) = registry.parameters(&effect.name) else {
+            report(registry.unknown_effect(&effect.name));
+            continue;
+        };
+        // States saved by earlier versions carry meters as parameters
+        let controls = registry
+            .create(&effect.name, &HashMap::new())
+            .map(|(_, controls)| controls)
+            .unwrap_or_default();
+
+        let mut names: Vec<&String> = effect.parameters.keys().collect();
+        names.sort();
+        for name in names {
+            let value = effect.parameters[name];
+            match defs.iter().find(|def| def.name == *name) {
+                Some(def) if !def.contains(value) => report(crate::Error::ParameterOutOfRange {
+                    name: format!("{}.{}", effect.name, name),
+                    value,
+                    min: def.min,
+                    max: def.max,
+                }),
+                Some(_) => {}
+                None => {
+                    let raw_res = name == "res_raw" && defs.iter().any(|def| def.name == "res");
+                    if !raw_res
+                        && !controls.params.contains_key(name)
+                        && !controls.meters.contains_key(name)
+                    {
+                        report(crate::Error::InvalidParameter(format!(
+                            "{}.{}",
+                            effect.name, name
+                        )));
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// A collection of effect presets (preset bank)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EffectPresetBank {
@@ -185,14 +300,69 @@ impl EffectPresetBank {
             .collect()
     }
 
+    /// Search presets by name, description, and tags
+    ///
+    /// The query is split on whitespace and matched case-insensitively. Each
+    /// term scores 3 for an exact tag match, 2 for a name match, and 1 for a
+    /// description or partial tag match. Results are sorted by total score
+    /// (highest first), then by name; presets that match nothing are omitted.
+    pub fn search(&self, query: &str) -> Vec<&EffectPreset> {
+        let terms: Vec<String> = query.split_whitespace().map(|t| t.to_lowercase()).collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(u32, &EffectPreset)> = self
+            .presets
+            .iter()
+            .filter_map(|preset| {
+                let name = preset.name.to_lowercase();
+                let description = preset
+                    .description
+                    .as_deref()
+                    .unwrap_or_default()
+                    .to_lowercase();
+                let tags: Vec<String> = preset.tags.iter().map(|t| t.to_lowercase()).collect();
+
+                let score: u32 = terms
+                    .iter()
+                    .map(|term| {
+                        let mut score = 0;
+                        if tags.iter().any(|t| t == term) {
+                            score += 3;
+                        } else if tags.iter().any(|t| t.contains(term.as_str())) {
+                            score += 1;
+                        }
+                        if name.contains(term.as_str()) {
+                            score += 2;
+                        }
+                        if description.contains(term.as_str()) {
+                            score += 1;
+                        }
+                        score
+                    })
+                    .sum();
+
+                (score > 0).then_some((score, preset))
+            })
+            .collect();
+
+        scored.sort_by(|(a_score, a), (b_score, b)| {
+            b_score.cmp(a_score).then_with(|| a.name.cmp(&b.name))
+        });
+        scored.into_iter().map(|(_, preset)| preset).collect()
+    }
+
     /// Serialize bank to JSON
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
     }
 
-    /// Deserialize bank from JSON
+    /// Deserialize bank from JSON, upgrading presets in older formats
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+        let mut bank: Self = serde_json::from_str(json)?;
+        bank.presets.iter_mut().for_each(EffectPreset::migrate);
+        Ok(bank)
     }
 
     /// Save bank to file
@@ -213,6 +383,99 @@ impl EffectPresetBank {
         let json = fs::read_to_string(path)?;
         Self::from_json(&json).map_err(std::io::Error::other)
     }
+
+    /// Load every `.json` preset file in a directory into a bank
+    ///
+    /// The bank is named after the directory. Files are read in name order;
+    /// ones that cannot be read or parsed as an [`EffectPreset`] are skipped
+    /// with a warning. Only failing to list the directory is an error.
+    pub fn load_dir(path: &std::path::Path) -> std::io::Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut bank = Self::new(name);
+
+        let mut files: Vec<_> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| {
+                file.is_file()
+                    && file
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+            })
+            .collect();
+        files.sort();
+
+        for file in files {
+            match EffectPreset::load(&file) {
+                Ok(preset) => bank.add_preset(preset),
+                Err(error) => {
+                    tracing::warn!("skipping preset file {}: {}", file.display(), error);
+                }
+            }
+        }
+        Ok(bank)
+    }
+
+    /// Save each preset to its own file in a directory
+    ///
+    /// The directory is created if needed. Files are named after the slugified
+    /// preset name (`"Warm Vocal"` becomes `warm-vocal.json`), with a numeric
+    /// suffix when two presets would share a name. Existing files with the
+    /// same names are overwritten.
+    pub fn save_dir(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(path)?;
+
+        let mut used = std::collections::HashSet::new();
+        for preset in &self.presets {
+            let slug = slugify(&preset.name);
+            let mut stem = slug.clone();
+            let mut n = 2;
+            while !used.insert(stem.clone()) {
+                stem = format!("{}-{}", slug, n);
+                n += 1;
+            }
+            preset.save(&path.join(format!("{}.json", stem)))?;
+        }
+        Ok(())
+    }
+
+    /// Add the presets of `other` whose IDs are not already in this bank
+    pub fn merge(&mut self, other: EffectPresetBank) {
+        for preset in other.presets {
+            if self.get_by_id(&preset.id).is_none() {
+                self.presets.push(preset);
+            }
+        }
+    }
+}
+
+/// File-name friendly form of a preset name: lowercase ASCII letters and
+/// digits, with every other run of characters replaced by a single `-`
+fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "preset".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Create a preset bank containing every built-in preset (mastering and mixing)
+pub fn all_builtin_presets() -> EffectPresetBank {
+    let mut bank = EffectPresetBank::new("Built-in");
+    bank.add_mastering_presets();
+    bank.add_mixing_presets();
+    bank
 }
 
 #[cfg(test)]
@@ -245,6 +508,80 @@ mod tests {
         assert_eq!(preset.effects.len(), deserialized.effects.len());
     }
 
+    #[test]
+    fn test_unversioned_preset_upgrades() {
+        let legacy = r#"{
+            "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "name": "Old Filter",
+            "effects": [{"name": "rlpf", "parameters": {"cutoff": 900.0, "res": 5.0}}],
+            "author": null,
+            "description": null,
+            "tags": [],
+            "sample_rate": null
+        }"#;
+        let preset = EffectPreset::from_json(legacy).unwrap();
+        assert_eq!(preset.version, FORMAT_VERSION);
+        assert_eq!(preset.effects[0].get_param("res_raw"), Some(5.0));
+        assert_eq!(preset.effects[0].get_param("res"), None);
+
+        // Upgrading is idempotent and new presets need nothing
+        let mut upgraded = preset.clone();
+        upgraded.migrate();
+        assert_eq!(upgraded, preset);
+        let current =
+            EffectPreset::new("New").with_effect(EffectState::new("lpf").with_param("res", 0.5));
+        assert_eq!(
+            EffectPreset::from_json(&current.to_json().unwrap()).unwrap(),
+            current
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let registry = EffectRegistry::with_builtin();
+        let preset = EffectPreset::new("Broken")
+            .with_effect(EffectState::new("lpf").with_param("cutoff", 800.0))
+            .with_effect(EffectState::new("frobnicator").with_param("amount", 1.0))
+            .with_effect(
+                EffectState::new("compressor")
+                    .with_param("ratio", 50.0)
+                    .with_param("wobble", 1.0),
+            );
+
+        let errors = preset.validate(&registry).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            &errors[0],
+            ValidationError { index: 1, error: crate::Error::UnknownEffect { name, .. } }
+                if name == "frobnicator"
+        ));
+        assert!(matches!(
+            &errors[1],
+            ValidationError { index: 2, error: crate::Error::ParameterOutOfRange { name, value, .. } }
+                if name == "compressor.ratio" && *value == 50.0
+        ));
+        assert!(matches!(
+            &errors[2],
+            ValidationError { index: 2, error: crate::Error::InvalidParameter(name) }
+                if name == "compressor.wobble"
+        ));
+        assert!(errors[0]
+            .to_string()
+            .starts_with("effect 1: unknown effect 'frobnicator'"));
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_and_saved_presets() {
+        let registry = EffectRegistry::with_builtin();
+        assert!(MasteringPresets::transparent().validate(&registry).is_ok());
+
+        // States saved by earlier versions carry meters; raw resonance is allowed
+        let saved = EffectPreset::new("Saved")
+            .with_effect(EffectState::new("compressor").with_param("gain_reduction", 3.0))
+            .with_effect(EffectState::new("lpf").with_param("res_raw", 4.0));
+        assert!(saved.validate(&registry).is_ok());
+    }
+
     #[test]
     fn test_preset_bank() {
         let mut bank = EffectPresetBank::new("My Effects");
@@ -260,4 +597,86 @@ mod tests {
         assert!(bank.get_by_name("Nonexistent").is_none());
         assert_eq!(bank.get_by_tag("mastering").len(), 1);
     }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Warm Vocal"), "warm-vocal");
+        assert_eq!(slugify("  Drum Bus (Parallel) #2 "), "drum-bus-parallel-2");
+        assert_eq!(slugify("???"), "preset");
+    }
+
+    #[test]
+    fn test_load_save_dir_and_merge() {
+        let dir = std::env::temp_dir().join(format!("fundsp_rack_presets_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let vocal = EffectPreset::new("Warm Vocal")
+            .with_effect(EffectState::new("compressor").with_param("ratio", 3.0));
+        let drums = EffectPreset::new("Drum Bus").with_simple_effect("limiter");
+        let mut bank = EffectPresetBank::new("Mine");
+        bank.add_preset(vocal.clone());
+        bank.add_preset(drums.clone());
+        bank.save_dir(&dir).unwrap();
+        assert!(dir.join("warm-vocal.json").is_file());
+        assert!(dir.join("drum-bus.json").is_file());
+
+        // A malformed preset and a non-JSON file are skipped
+        std::fs::write(dir.join("broken.json"), "{ not json").unwrap();
+        std::fs::write(dir.join("notes.txt"), "hello").unwrap();
+
+        let loaded = EffectPresetBank::load_dir(&dir).unwrap();
+        assert_eq!(loaded.presets.len(), 2);
+        assert_eq!(loaded.get_by_id(&vocal.id), Some(&vocal));
+        assert_eq!(loaded.get_by_id(&drums.id), Some(&drums));
+
+        // Merging dedupes by ID
+        let mut merged = EffectPresetBank::new("Merged");
+        merged.add_preset(vocal.clone());
+        merged.merge(loaded);
+        assert_eq!(merged.presets.len(), 2);
+        merged.merge(mastering_bank());
+        assert_eq!(merged.presets.len(), 2 + mastering_bank().presets.len());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(EffectPresetBank::load_dir(&dir).is_err());
+    }
+
+    #[test]
+    fn test_all_builtin_presets() {
+        let bank = all_builtin_presets();
+        assert_eq!(
+            bank.presets.len(),
+            mastering_bank().presets.len() + mixing_bank().presets.len()
+        );
+    }
+
+    #[test]
+    fn test_search_vocal() {
+        let bank = all_builtin_presets();
+        let results = bank.search("vocal");
+
+        let mixing = mixing_bank();
+        let vocal_names: Vec<&str> = mixing
+            .get_by_tag("vocal")
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(vocal_names.len(), 3);
+
+        // The tagged vocal presets rank first
+        let top: Vec<&str> = results.iter().take(3).map(|p| p.name.as_str()).collect();
+        for name in vocal_names {
+            assert!(top.contains(&name), "{} missing from {:?}", name, top);
+        }
+    }
+
+    #[test]
+    fn test_search_ranking_and_misses() {
+        let bank = all_builtin_presets();
+        assert!(bank.search("").is_empty());
+        assert!(bank.search("frobnicator").is_empty());
+
+        let results = bank.search("warm vocal");
+        assert_eq!(results[0].name, "Warm Vocal");
+    }
 }
@@ -4,12 +4,13 @@
 //! Each effect implements the EffectBuilder trait, allowing for easy registration
 //! and extensibility without modifying core backend code.
 
-use crate::error::Error;
+use crate::error::{self, Error};
+use crate::params::{check_ranges, ClampWarnings};
 pub use crate::params::ParameterDef;
 use crate::Result;
 use fundsp::hacker32::*;
 use fundsp::shared::Shared;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 
 /// Trait for building custom effects
@@ -22,18 +23,31 @@ pub trait EffectBuilder: Send + Sync {
     fn metadata(&self) -> EffectMetadata;
 }
 
+/// Name of the meter through which an effect reports its latency in
+/// samples when it depends on build parameters or the sample rate
+///
+/// Takes precedence over [`EffectMetadata::latency_samples`] when an effect
+/// is added to a chain.
+pub const LATENCY_CONTROL: &str = "latency_samples";
+
 /// Controllable parameters for an effect instance
 /// Uses Shared variables for real-time control
 #[derive(Clone)]
 pub struct EffectControls {
     /// Effect-specific parameters stored as Shared for real-time control
     pub params: HashMap<String, Shared>,
+    /// Read-only values written by the processor, such as gain reduction
+    ///
+    /// Meters are not parameters: they are not saved with chain states,
+    /// morphed or settable through [`set`](Self::set).
+    pub meters: HashMap<String, Shared>,
 }
 
 impl EffectControls {
     pub fn new() -> Self {
         Self {
             params: HashMap::new(),
+            meters: HashMap::new(),
         }
     }
 
@@ -48,6 +62,11 @@ impl EffectControls {
     pub fn get(&self, name: &str) -> Option<f32> {
         self.params.get(name).map(|s| s.value())
     }
+
+    /// Get a meter reading
+    pub fn meter(&self, name: &str) -> Option<f32> {
+        self.meters.get(name).map(|s| s.value())
+    }
 }
 
 impl Default for EffectControls {
@@ -112,6 +131,15 @@ impl EffectMetadata {
     pub fn has_tag(&self, tag: &str) -> bool {
         self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
     }
+
+    /// Category of the effect: its first tag that isn't namespaced like
+    /// `source:builtin`
+    pub fn category(&self) -> Option<&str> {
+        self.tags
+            .iter()
+            .map(String::as_str)
+            .find(|t| !t.contains(':'))
+    }
 }
 
 /// Parameter range
@@ -126,6 +154,12 @@ pub struct ParameterRange {
 #[derive(Clone)]
 pub struct EffectRegistry {
     builders: HashMap<String, Arc<dyn EffectBuilder>>,
+    /// Parameter schema of each effect, captured at registration
+    parameters: HashMap<String, Vec<ParameterDef>>,
+    /// Reject out-of-range parameters instead of clamping them
+    strict_params: bool,
+    /// Parameters that already warned about being clamped
+    clamp_warnings: Arc<ClampWarnings>,
 }
 
 impl EffectRegistry {
@@ -133,9 +167,26 @@ impl EffectRegistry {
     pub fn new() -> Self {
         Self {
             builders: HashMap::new(),
+            parameters: HashMap::new(),
+            strict_params: false,
+            clamp_warnings: Arc::default(),
         }
     }
 
+    /// Reject out-of-range parameters in [`build`](Self::build)
+    ///
+    /// By default a parameter outside its [`ParameterDef`] range is clamped,
+    /// with a warning the first time each parameter is clamped. In strict
+    /// mode it fails with [`Error::ParameterOutOfRange`] instead.
+    pub fn set_strict_params(&mut self, strict: bool) {
+        self.strict_params = strict;
+    }
+
+    /// Whether out-of-range parameters are rejected
+    pub fn strict_params(&self) -> bool {
+        self.strict_params
+    }
+
     /// Create a new registry with all built-in effects registered
     pub fn with_builtin() -> Self {
         let mut registry = Self::new();
@@ -150,7 +201,10 @@ impl EffectRegistry {
 
     /// Register an effect builder
     pub fn register(&mut self, name: impl Into<String>, builder: Arc<dyn EffectBuilder>) {
-        self.builders.insert(name.into(), builder);
+        let name = name.into();
+        self.parameters
+            .insert(name.clone(), builder.metadata().parameters);
+        self.builders.insert(name, builder);
     }
 
     /// Get an effect builder by name
@@ -158,7 +212,7 @@ impl EffectRegistry {
         self.builders.get(name).cloned()
     }
 
-    /// Create an effect instance
+    /// Create an effect instance, passing parameters through unchecked
     pub fn create(
         &self,
         name: &str,
@@ -168,13 +222,21 @@ impl EffectRegistry {
     }
 
     /// Build an effect with error handling
+    ///
+    /// Parameters are checked against the effect's [`ParameterDef`] ranges,
+    /// see [`set_strict_params`](Self::set_strict_params).
     pub fn build(
         &self,
         name: &str,
         params: &HashMap<String, f32>,
     ) -> Result<(Box<dyn AudioUnit>, EffectControls)> {
-        self.create(name, params)
-            .ok_or_else(|| Error::InvalidEffect(name.to_string()))
+        let (builder, defs) = self
+            .builders
+            .get(name)
+            .zip(self.parameters.get(name))
+            .ok_or_else(|| self.unknown_effect(name))?;
+        let params = check_ranges(name, defs, params, self.strict_params, &self.clamp_warnings)?;
+        Ok(builder.build(&params))
     }
 
     /// Get metadata for an effect
@@ -182,6 +244,26 @@ impl EffectRegistry {
         self.get(name).map(|builder| builder.metadata())
     }
 
+    /// Registered names close to `name`, closest first
+    ///
+    /// Used to suggest a correction for a misspelled effect name.
+    pub fn suggest(&self, name: &str) -> Vec<String> {
+        error::suggest(name, self.builders.keys().map(String::as_str))
+    }
+
+    /// Error for an unregistered effect name, with the best suggestion
+    pub(crate) fn unknown_effect(&self, name: &str) -> Error {
+        Error::UnknownEffect {
+            name: name.to_string(),
+            suggestion: self.suggest(name).into_iter().next(),
+        }
+    }
+
+    /// Get the parameter schema of an effect without building it
+    pub fn parameters(&self, name: &str) -> Option<&[ParameterDef]> {
+        self.parameters.get(name).map(Vec::as_slice)
+    }
+
     /// Check if an effect exists in the registry
     pub fn contains(&self, name: &str) -> bool {
         self.builders.contains_key(name)
@@ -192,6 +274,18 @@ impl EffectRegistry {
         self.builders.keys().cloned().collect()
     }
 
+    /// Registered effect names, aliases included, in no particular order
+    ///
+    /// Borrows the names without building any metadata.
+    pub fn names(&self) -> Vec<&str> {
+        self.builders.keys().map(String::as_str).collect()
+    }
+
+    /// Number of registered effect names, aliases included
+    pub fn count(&self) -> usize {
+        self.builders.len()
+    }
+
     /// List all effect metadata
     pub fn list_effects(&self) -> Vec<EffectMetadata> {
         self.builders
@@ -199,6 +293,23 @@ impl EffectRegistry {
             .map(|builder| builder.metadata())
             .collect()
     }
+
+    /// List each effect once, with every name it is registered under
+    ///
+    /// Aliases such as `lpf` and `lowpass` share metadata, so entries are
+    /// grouped by the metadata name. Sorted by that name.
+    pub fn list_unique(&self) -> Vec<(EffectMetadata, BTreeSet<String>)> {
+        let mut unique: BTreeMap<String, (EffectMetadata, BTreeSet<String>)> = BTreeMap::new();
+        for (key, builder) in &self.builders {
+            let metadata = builder.metadata();
+            unique
+                .entry(metadata.name.clone())
+                .or_insert_with(|| (metadata, BTreeSet::new()))
+                .1
+                .insert(key.clone());
+        }
+        unique.into_values().collect()
+    }
 }
 
 impl Default for EffectRegistry {
@@ -206,3 +317,101 @@ impl Default for EffectRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_checks_param_ranges() {
+        let mut registry = EffectRegistry::with_builtin();
+        let params = HashMap::from([("cutoff".to_string(), 50000.0)]);
+
+        let (_, controls) = registry.build("lpf", &params).unwrap();
+        assert_eq!(controls.get("cutoff"), Some(20000.0));
+
+        registry.set_strict_params(true);
+        match registry.build("lpf", &params) {
+            Err(Error::ParameterOutOfRange {
+                name,
+                value,
+                min,
+                max,
+            }) => {
+                assert_eq!(name, "cutoff");
+                assert_eq!(value, 50000.0);
+                assert_eq!((min, max), (20.0, 20000.0));
+            }
+            _ => panic!("expected ParameterOutOfRange"),
+        }
+
+        // In-range values are accepted in strict mode
+        let params = HashMap::from([("cutoff".to_string(), 500.0)]);
+        let (_, controls) = registry.build("lpf", &params).unwrap();
+        assert_eq!(controls.get("cutoff"), Some(500.0));
+    }
+
+    #[test]
+    fn test_parameters() {
+        let registry = EffectRegistry::with_builtin();
+        let params = registry.parameters("lpf").unwrap();
+        let cutoff = params.iter().find(|p| p.name == "cutoff").unwrap();
+        assert_eq!(
+            (cutoff.default, cutoff.min, cutoff.max),
+            (1000.0, 20.0, 20000.0)
+        );
+        assert_eq!(params, registry.get_metadata("lpf").unwrap().parameters);
+        assert!(registry.parameters("no_such_effect").is_none());
+    }
+
+    #[test]
+    fn test_unknown_effect_suggestion() {
+        let registry = EffectRegistry::with_builtin();
+        assert_eq!(
+            registry.suggest("lpff").first().map(String::as_str),
+            Some("lpf")
+        );
+
+        match registry.build("revreb", &HashMap::new()) {
+            Err(Error::UnknownEffect { name, suggestion }) => {
+                assert_eq!(name, "revreb");
+                assert_eq!(suggestion.as_deref(), Some("reverb"));
+            }
+            _ => panic!("expected UnknownEffect"),
+        }
+        assert!(matches!(
+            registry.build("zzzzzzzz", &HashMap::new()),
+            Err(Error::UnknownEffect {
+                suggestion: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_names_and_count() {
+        let registry = EffectRegistry::with_builtin();
+        let names = registry.names();
+        assert_eq!(names.len(), registry.count());
+        assert!(names.contains(&"lpf") && names.contains(&"lowpass"));
+        assert_eq!(EffectRegistry::new().count(), 0);
+    }
+
+    #[test]
+    fn test_list_unique_groups_aliases() {
+        let registry = EffectRegistry::with_builtin();
+        let unique = registry.list_unique();
+
+        let lowpass: Vec<_> = unique
+            .iter()
+            .filter(|(_, aliases)| aliases.contains("lpf") || aliases.contains("lowpass"))
+            .collect();
+        assert_eq!(lowpass.len(), 1);
+        let (metadata, aliases) = lowpass[0];
+        assert_eq!(metadata.name, "lpf");
+        assert_eq!(aliases.iter().collect::<Vec<_>>(), ["lowpass", "lpf"]);
+
+        let total: usize = unique.iter().map(|(_, aliases)| aliases.len()).sum();
+        assert_eq!(total, registry.count());
+    }
+}
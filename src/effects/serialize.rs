@@ -2,6 +2,8 @@
 //!
 //! This module provides serialization for effect parameters and chain state.
 //! Useful for presets, project files, or any application state persistence.
+use super::lfo::EffectLFO;
+use crate::params::{migrate_effect_params, FORMAT_VERSION};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -26,13 +28,42 @@ pub struct EffectState {
     /// Whether this effect is muted
     #[serde(default)]
     pub muted: bool,
+
+    /// Chain-level dry/wet balance; `None` is fully processed (1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mix: Option<f32>,
+
+    /// Output gain trim in dB
+    #[serde(default)]
+    pub gain_db: f32,
+
+    /// Branch chains when this node is a parallel group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<ChainState>,
+
+    /// LFOs attached to parameters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lfos: Vec<LfoAttachment>,
+
+    /// Name of the sidechain bus the effect is keyed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sidechain_bus: Option<String>,
+}
+
+/// Serializable LFO attached to an effect parameter
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LfoAttachment {
+    /// Name of the modulated parameter
+    pub param: String,
+    /// LFO settings
+    pub lfo: EffectLFO,
 }
 
 /// Serializable representation of an effect chain
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ChainState {
-    /// Format version for future compatibility
-    #[serde(default = "default_version")]
+    /// Format version (see [`FORMAT_VERSION`]); 0 when absent
+    #[serde(default)]
     pub version: u32,
 
     /// Sample rate when saved
@@ -44,20 +75,21 @@ pub struct ChainState {
 
     /// Effects in order
     pub effects: Vec<EffectState>,
-}
 
-fn default_version() -> u32 {
-    1
+    /// Names of the chain's sidechain buses, indexed by bus id
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sidechain_buses: Vec<String>,
 }
 
 impl ChainState {
     /// Create a new empty chain state
     pub fn new(sample_rate: f64) -> Self {
         Self {
-            version: default_version(),
+            version: FORMAT_VERSION,
             sample_rate,
             bypassed: false,
             effects: Vec::new(),
+            sidechain_buses: Vec::new(),
         }
     }
 
@@ -76,14 +108,29 @@ impl ChainState {
         serde_json::to_vec_pretty(self)
     }
 
-    /// Deserialize from JSON string
+    /// Deserialize from JSON string, upgrading older formats
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+        let mut state: Self = serde_json::from_str(json)?;
+        state.migrate();
+        Ok(state)
     }
 
-    /// Deserialize from JSON bytes
+    /// Deserialize from JSON bytes, upgrading older formats
     pub fn from_json_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
-        serde_json::from_slice(bytes)
+        let mut state: Self = serde_json::from_slice(bytes)?;
+        state.migrate();
+        Ok(state)
+    }
+
+    /// Upgrade a state saved by an older version to [`FORMAT_VERSION`]
+    ///
+    /// [`from_json`](Self::from_json) does this already; call it on states
+    /// deserialized some other way. Current states are left unchanged.
+    pub fn migrate(&mut self) {
+        for effect in &mut self.effects {
+            effect.migrate(self.version);
+        }
+        self.version = self.version.max(FORMAT_VERSION);
     }
 }
 
@@ -96,6 +143,11 @@ impl EffectState {
             parameters: HashMap::new(),
             bypassed: false,
             muted: false,
+            mix: None,
+            gain_db: 0.0,
+            branches: Vec::new(),
+            lfos: Vec::new(),
+            sidechain_bus: None,
         }
     }
 
@@ -107,6 +159,11 @@ impl EffectState {
             parameters: HashMap::new(),
             bypassed: false,
             muted: false,
+            mix: None,
+            gain_db: 0.0,
+            branches: Vec::new(),
+            lfos: Vec::new(),
+            sidechain_bus: None,
         }
     }
 
@@ -128,6 +185,18 @@ impl EffectState {
         self
     }
 
+    /// Builder pattern: set chain-level dry/wet balance (0.0 = dry, 1.0 = wet)
+    pub fn with_mix(mut self, mix: f32) -> Self {
+        self.mix = Some(mix);
+        self
+    }
+
+    /// Builder pattern: set output gain trim (dB)
+    pub fn with_gain(mut self, gain_db: f32) -> Self {
+        self.gain_db = gain_db;
+        self
+    }
+
     /// Set a parameter value
     pub fn set_param(&mut self, name: impl Into<String>, value: f32) {
         self.parameters.insert(name.into(), value);
@@ -137,6 +206,14 @@ impl EffectState {
     pub fn get_param(&self, name: &str) -> Option<f32> {
         self.parameters.get(name).copied()
     }
+
+    /// Upgrade parameters saved at format version `from`
+    pub(crate) fn migrate(&mut self, from: u32) {
+        migrate_effect_params(&self.name, &mut self.parameters, from);
+        for branch in &mut self.branches {
+            branch.migrate();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -197,4 +274,53 @@ mod tests {
         assert_eq!(loaded.effects[0].name, effect.name);
         assert_eq!(loaded.effects[0].bypassed, effect.bypassed);
     }
+
+    #[test]
+    fn test_gain_roundtrip_and_default() {
+        let mut chain = ChainState::new(48000.0);
+        chain.add_effect(EffectState::new("lpf").with_gain(-6.0));
+        let loaded = ChainState::from_json(&chain.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.effects[0].gain_db, -6.0);
+
+        // Older files without gain_db load at unity gain
+        let legacy = r#"{"sample_rate":48000.0,"effects":[{"name":"lpf","parameters":{}}]}"#;
+        let loaded = ChainState::from_json(legacy).unwrap();
+        assert_eq!(loaded.effects[0].gain_db, 0.0);
+        assert_eq!(loaded.effects[0].mix, None);
+    }
+
+    #[test]
+    fn test_legacy_state_migrates_raw_resonance() {
+        // Unversioned: res was a Q value then
+        let legacy = r#"{"sample_rate":48000.0,"effects":[
+            {"name":"lpf","parameters":{"cutoff":800.0,"res":4.0}},
+            {"name":"delay","parameters":{"res":0.5}}
+        ]}"#;
+        let loaded = ChainState::from_json(legacy).unwrap();
+        assert_eq!(loaded.version, FORMAT_VERSION);
+        assert_eq!(loaded.effects[0].get_param("res"), None);
+        assert_eq!(loaded.effects[0].get_param("res_raw"), Some(4.0));
+        assert_eq!(loaded.effects[0].get_param("cutoff"), Some(800.0));
+        // Effects whose res never changed meaning are left alone
+        assert_eq!(loaded.effects[1].get_param("res"), Some(0.5));
+
+        // Current states round-trip untouched
+        let mut chain = ChainState::new(48000.0);
+        chain.add_effect(EffectState::new("lpf").with_param("res", 0.4));
+        let loaded = ChainState::from_json(&chain.to_json().unwrap()).unwrap();
+        assert_eq!(loaded, chain);
+    }
+
+    #[test]
+    fn test_mix_roundtrip_and_omitted_when_unset() {
+        let mut chain = ChainState::new(48000.0);
+        chain.add_effect(EffectState::new("reverb").with_mix(0.3));
+        chain.add_effect(EffectState::new("lpf"));
+        let json = chain.to_json().unwrap();
+        assert_eq!(json.matches("\"mix\"").count(), 1);
+
+        let loaded = ChainState::from_json(&json).unwrap();
+        assert_eq!(loaded.effects[0].mix, Some(0.3));
+        assert_eq!(loaded.effects[1].mix, None);
+    }
 }
@@ -2,6 +2,9 @@
 //!
 //! Effects that can be controlled by external audio signals (sidechain compression, gating, etc.)
 
+use super::builtin::dynamics::Compressor;
+use super::builtin::filters::Crossover;
+use super::registry::EffectControls;
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 
@@ -27,8 +30,55 @@ pub trait SidechainAwareEffect: AudioUnit {
         sidechain_left: f32,
         sidechain_right: f32,
     ) -> (f32, f32);
+
+    /// Process a block of stereo audio with sidechain input
+    ///
+    /// The default implementation calls `process_with_sidechain` for each frame,
+    /// so the per-sample calls are statically dispatched and only the block call
+    /// goes through the trait object. Only the first `min` length of all slices
+    /// is processed.
+    fn process_block_with_sidechain(
+        &mut self,
+        input_left: &[f32],
+        input_right: &[f32],
+        sidechain_left: &[f32],
+        sidechain_right: &[f32],
+        output_left: &mut [f32],
+        output_right: &mut [f32],
+    ) {
+        let len = input_left
+            .len()
+            .min(input_right.len())
+            .min(sidechain_left.len())
+            .min(sidechain_right.len())
+            .min(output_left.len())
+            .min(output_right.len());
+        for i in 0..len {
+            (output_left[i], output_right[i]) = self.process_with_sidechain(
+                input_left[i],
+                input_right[i],
+                sidechain_left[i],
+                sidechain_right[i],
+            );
+        }
+    }
+
+    /// Controls of this processor
+    ///
+    /// [`EffectChain`](super::EffectChain) runs a separate processor for
+    /// sidechained audio and adds these to the effect's controls, so
+    /// parameters and meters reach the processor actually in use.
+    fn controls(&self) -> EffectControls {
+        EffectControls::new()
+    }
 }
 
+/// Identifier of a sidechain bus registered on an
+/// [`EffectChain`](super::EffectChain)
+///
+/// Ids count up from 0 in the order buses are registered.
+pub type BusId = usize;
+
 /// Helper function to detect peak level from stereo sidechain signal
 #[inline]
 pub fn sidechain_peak(left: f32, right: f32) -> f32 {
@@ -53,19 +103,58 @@ pub fn db_to_amplitude(db: f32) -> f32 {
     10.0_f32.powf(db / 20.0)
 }
 
+/// One-pole smoothing coefficient for a time constant of `seconds`
+#[inline]
+fn time_coeff(seconds: f32, sample_rate: f32) -> f32 {
+    (-1.0 / (seconds * sample_rate)).exp()
+}
+
 /// Sidechain Compressor - compresses audio based on external sidechain signal
+///
+/// `attack` and `release` are in seconds and can be changed while running;
+/// their smoothing coefficients are recomputed when they or the sample rate
+/// change. `gain_reduction_db` (dB, positive) is written every sample and
+/// exposed as the `gain_reduction` meter, as on the other compressors;
+/// `makeup` gain is applied after the reduction.
+///
+/// Level detection runs on the sidechain after the key filter, so a kick can
+/// duck a mix by its click rather than its sub. `key_highpass` and
+/// `key_lowpass` are in Hz, 0 leaving that side unfiltered, with 24 dB/oct
+/// slopes; the audio itself is never filtered. `hold` (seconds) delays the
+/// release after the sidechain last pushed the envelope up.
 #[derive(Clone)]
 pub struct SidechainCompressor {
     /// Threshold in dB (when sidechain exceeds this, compression happens)
     pub threshold: Shared,
     /// Compression ratio (e.g., 4.0 = 4:1)
     pub ratio: Shared,
-    /// Attack time coefficient
-    pub attack_coeff: Shared,
-    /// Release time coefficient
-    pub release_coeff: Shared,
+    /// Attack time in seconds
+    pub attack: Shared,
+    /// Release time in seconds
+    pub release: Shared,
+    /// Soft knee width in dB centred on the threshold (0 = hard knee)
+    pub knee: Shared,
+    /// Makeup gain in dB
+    pub makeup: Shared,
+    /// Current gain reduction in dB (read-only meter)
+    pub gain_reduction_db: Shared,
+    /// Key filter highpass cutoff in Hz (0 = off)
+    pub key_highpass: Shared,
+    /// Key filter lowpass cutoff in Hz (0 = off)
+    pub key_lowpass: Shared,
+    /// Hold time in seconds before the release starts
+    pub hold: Shared,
     /// Current envelope level (for smooth attack/release)
     envelope: Shared,
+    /// Key filters per sidechain channel: (highpass, lowpass)
+    key_filters: [(Crossover, Crossover); 2],
+    /// Samples left before the release starts
+    hold_remaining: f32,
+    /// Attack and release times the coefficients were computed for
+    coeff_times: (f32, f32),
+    attack_coeff: f32,
+    release_coeff: f32,
+    sample_rate: f32,
 }
 
 impl SidechainCompressor {
@@ -77,65 +166,120 @@ impl SidechainCompressor {
         release_sec: f32,
         sample_rate: f32,
     ) -> Self {
-        // Calculate attack/release coefficients
-        let attack_coeff = (-1.0 / (attack_sec * sample_rate)).exp();
-        let release_coeff = (-1.0 / (release_sec * sample_rate)).exp();
-
-        Self {
+        let key_filter = || {
+            (
+                Crossover::new(20.0, sample_rate),
+                Crossover::new(20000.0, sample_rate),
+            )
+        };
+        let mut compressor = Self {
             threshold: shared(threshold_db),
             ratio: shared(ratio),
-            attack_coeff: shared(attack_coeff),
-            release_coeff: shared(release_coeff),
+            attack: shared(attack_sec),
+            release: shared(release_sec),
+            knee: shared(0.0),
+            makeup: shared(0.0),
+            gain_reduction_db: shared(0.0),
+            key_highpass: shared(0.0),
+            key_lowpass: shared(0.0),
+            hold: shared(0.0),
             envelope: shared(0.0),
+            key_filters: [key_filter(), key_filter()],
+            hold_remaining: 0.0,
+            coeff_times: (attack_sec, release_sec),
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            sample_rate,
+        };
+        compressor.set_coeffs(attack_sec, release_sec);
+        compressor
+    }
+
+    /// Compute the smoothing coefficients for attack and release times in seconds
+    fn set_coeffs(&mut self, attack: f32, release: f32) {
+        self.coeff_times = (attack, release);
+        self.attack_coeff = time_coeff(attack.max(1.0e-5), self.sample_rate);
+        self.release_coeff = time_coeff(release.max(1.0e-5), self.sample_rate);
+    }
+
+    /// Builder pattern: set the soft knee width in dB
+    pub fn with_knee(self, knee_db: f32) -> Self {
+        self.knee.set_value(knee_db.max(0.0));
+        self
+    }
+
+    /// Builder pattern: set the makeup gain in dB
+    pub fn with_makeup(self, makeup_db: f32) -> Self {
+        self.makeup.set_value(makeup_db);
+        self
+    }
+
+    /// Builder pattern: filter the detection signal (Hz, 0 = off)
+    pub fn with_key_filter(self, highpass: f32, lowpass: f32) -> Self {
+        self.key_highpass.set_value(highpass.max(0.0));
+        self.key_lowpass.set_value(lowpass.max(0.0));
+        self
+    }
+
+    /// Builder pattern: set the hold time in seconds
+    pub fn with_hold(self, hold_sec: f32) -> Self {
+        self.hold.set_value(hold_sec.max(0.0));
+        self
+    }
+
+    /// Peak level of the sidechain after the key filter
+    #[inline]
+    fn key_level(&mut self, sidechain_left: f32, sidechain_right: f32) -> f32 {
+        let highpass = self.key_highpass.value();
+        let lowpass = self.key_lowpass.value();
+        let mut key = [sidechain_left, sidechain_right];
+        for ((high, low), sample) in self.key_filters.iter_mut().zip(key.iter_mut()) {
+            if highpass > 0.0 {
+                high.set_cutoff(highpass);
+                *sample = high.highpass(*sample);
+            }
+            if lowpass > 0.0 {
+                low.set_cutoff(lowpass);
+                *sample = low.lowpass(*sample);
+            }
         }
+        sidechain_peak(key[0], key[1])
     }
 
-    /// Calculate gain reduction based on sidechain level
+    /// Calculate the output gain, makeup included, from the sidechain
     #[inline]
-    fn calculate_gain_reduction(&mut self, sidechain_level: f32) -> f32 {
+    fn calculate_gain_reduction(&mut self, sidechain_left: f32, sidechain_right: f32) -> f32 {
+        let sidechain_level = self.key_level(sidechain_left, sidechain_right);
         let threshold = self.threshold.value();
         let ratio = self.ratio.value();
-        let attack_coeff = self.attack_coeff.value();
-        let release_coeff = self.release_coeff.value();
+        let times = (self.attack.value(), self.release.value());
+        if times != self.coeff_times {
+            self.set_coeffs(times.0, times.1);
+        }
         let mut envelope = self.envelope.value();
 
-        // Convert sidechain level to dB
-        let sidechain_db = amplitude_to_db(sidechain_level);
-
-        // Calculate target envelope level
-        let target_envelope = if sidechain_db > threshold {
-            // Above threshold: apply compression
-            sidechain_level
+        // Smooth envelope follower (attack/hold/release)
+        let coeff = if sidechain_level > envelope {
+            self.hold_remaining = self.hold.value().max(0.0) * self.sample_rate;
+            self.attack_coeff
+        } else if self.hold_remaining > 0.0 {
+            self.hold_remaining -= 1.0;
+            1.0
         } else {
-            0.0
+            self.release_coeff
         };
 
-        // Smooth envelope follower (attack/release)
-        let coeff = if target_envelope > envelope {
-            attack_coeff
-        } else {
-            release_coeff
-        };
-
-        envelope = target_envelope + coeff * (envelope - target_envelope);
+        envelope = sidechain_level + coeff * (envelope - sidechain_level);
         self.envelope.set_value(envelope);
 
-        // Calculate gain reduction
-        if envelope > 0.0 {
-            let envelope_db = amplitude_to_db(envelope);
-            if envelope_db > threshold {
-                // Amount over threshold
-                let over_db = envelope_db - threshold;
-                // Apply ratio
-                let gain_reduction_db = over_db * (1.0 - 1.0 / ratio);
-                // Convert to linear gain
-                db_to_amplitude(-gain_reduction_db)
-            } else {
-                1.0 // No reduction
-            }
-        } else {
-            1.0 // No reduction
-        }
+        let reduction_db = Compressor::gain_reduction_db(
+            amplitude_to_db(envelope),
+            threshold,
+            ratio,
+            self.knee.value(),
+        );
+        self.gain_reduction_db.set_value(reduction_db);
+        db_to_amplitude(self.makeup.value() - reduction_db)
     }
 }
 
@@ -149,6 +293,22 @@ impl AudioUnit for SidechainCompressor {
 
     fn reset(&mut self) {
         self.envelope.set_value(0.0);
+        self.gain_reduction_db.set_value(0.0);
+        self.hold_remaining = 0.0;
+        for (high, low) in &mut self.key_filters {
+            high.reset();
+            low.reset();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        let sample_rate = sample_rate as f32;
+        self.sample_rate = sample_rate;
+        self.set_coeffs(self.attack.value(), self.release.value());
+        for (high, low) in &mut self.key_filters {
+            high.set_sample_rate(sample_rate);
+            low.set_sample_rate(sample_rate);
+        }
     }
 
     fn tick(&mut self, input: &[f32], output: &mut [f32]) {
@@ -191,18 +351,39 @@ impl SidechainAwareEffect for SidechainCompressor {
         sidechain_left: f32,
         sidechain_right: f32,
     ) -> (f32, f32) {
-        // Detect sidechain level (using peak)
-        let sidechain_level = sidechain_peak(sidechain_left, sidechain_right);
-
-        // Calculate gain reduction
-        let gain = self.calculate_gain_reduction(sidechain_level);
+        // Calculate gain reduction from the key-filtered sidechain peak
+        let gain = self.calculate_gain_reduction(sidechain_left, sidechain_right);
 
         // Apply gain reduction to input
         (input_left * gain, input_right * gain)
     }
+
+    fn controls(&self) -> EffectControls {
+        let mut controls = EffectControls::new();
+        for (name, shared) in [
+            ("threshold", &self.threshold),
+            ("ratio", &self.ratio),
+            ("attack", &self.attack),
+            ("release", &self.release),
+            ("knee", &self.knee),
+            ("makeup", &self.makeup),
+            ("key_highpass", &self.key_highpass),
+            ("key_lowpass", &self.key_lowpass),
+            ("hold", &self.hold),
+        ] {
+            controls.params.insert(name.to_string(), shared.clone());
+        }
+        controls
+            .meters
+            .insert("gain_reduction".to_string(), self.gain_reduction_db.clone());
+        controls
+    }
 }
 
 /// Sidechain Gate - mutes audio when sidechain signal is below threshold
+///
+/// The attack and release coefficients are recomputed from their times
+/// when the sample rate changes.
 #[derive(Clone)]
 pub struct SidechainGate {
     /// Threshold in dB (when sidechain is below this, gate closes)
@@ -213,20 +394,22 @@ pub struct SidechainGate {
     pub release_coeff: Shared,
     /// Current gate state (0.0 = closed, 1.0 = open)
     gate_state: Shared,
+    /// Attack time in seconds
+    attack: f32,
+    /// Release time in seconds
+    release: f32,
 }
 
 impl SidechainGate {
     /// Create a new sidechain gate
     pub fn new(threshold_db: f32, attack_sec: f32, release_sec: f32, sample_rate: f32) -> Self {
-        // Calculate attack/release coefficients
-        let attack_coeff = (-1.0 / (attack_sec * sample_rate)).exp();
-        let release_coeff = (-1.0 / (release_sec * sample_rate)).exp();
-
         Self {
             threshold: shared(threshold_db),
-            attack_coeff: shared(attack_coeff),
-            release_coeff: shared(release_coeff),
+            attack_coeff: shared(time_coeff(attack_sec, sample_rate)),
+            release_coeff: shared(time_coeff(release_sec, sample_rate)),
             gate_state: shared(0.0),
+            attack: attack_sec,
+            release: release_sec,
         }
     }
 
@@ -274,6 +457,14 @@ impl AudioUnit for SidechainGate {
         self.gate_state.set_value(0.0);
     }
 
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        let sample_rate = sample_rate as f32;
+        self.attack_coeff
+            .set_value(time_coeff(self.attack, sample_rate));
+        self.release_coeff
+            .set_value(time_coeff(self.release, sample_rate));
+    }
+
     fn tick(&mut self, _input: &[f32], output: &mut [f32]) {
         // Without sidechain, gate is closed (muted)
         output[0] = 0.0;
@@ -338,13 +529,18 @@ pub fn build_sidechain_effect(
             let ratio = params.get("ratio").copied().unwrap_or(4.0);
             let attack = params.get("attack").copied().unwrap_or(0.01);
             let release = params.get("release").copied().unwrap_or(0.1);
-            Some(Box::new(SidechainCompressor::new(
-                threshold,
-                ratio,
-                attack,
-                release,
-                sample_rate,
-            )))
+            let knee = params.get("knee").copied().unwrap_or(0.0);
+            let makeup = params.get("makeup").copied().unwrap_or(0.0);
+            let key_highpass = params.get("key_highpass").copied().unwrap_or(0.0);
+            let key_lowpass = params.get("key_lowpass").copied().unwrap_or(0.0);
+            let hold = params.get("hold").copied().unwrap_or(0.0);
+            Some(Box::new(
+                SidechainCompressor::new(threshold, ratio, attack, release, sample_rate)
+                    .with_knee(knee)
+                    .with_makeup(makeup)
+                    .with_key_filter(key_highpass, key_lowpass)
+                    .with_hold(hold),
+            ))
         }
         "sidechain_gate" => {
             let threshold = params.get("threshold").copied().unwrap_or(-40.0);
@@ -360,3 +556,176 @@ pub fn build_sidechain_effect(
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_signals(len: usize) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+        let input: Vec<f32> = (0..len).map(|i| (i as f32 * 0.07).sin() * 0.6).collect();
+        // Sidechain pulses on and off so both attack and release are exercised
+        let sidechain: Vec<f32> = (0..len)
+            .map(|i| if (i / 100) % 2 == 0 { 0.9 } else { 0.0 })
+            .collect();
+        let input_right: Vec<f32> = input.iter().map(|x| -x).collect();
+        (input, input_right, sidechain)
+    }
+
+    fn assert_block_matches_per_sample(name: &str) {
+        let params = HashMap::new();
+        let mut block = build_sidechain_effect(name, &params, 48000.0).unwrap();
+        let mut per_sample = build_sidechain_effect(name, &params, 48000.0).unwrap();
+
+        let len = 1000;
+        let (in_l, in_r, sc) = test_signals(len);
+        let mut out_l = vec![0.0; len];
+        let mut out_r = vec![0.0; len];
+        block.process_block_with_sidechain(&in_l, &in_r, &sc, &sc, &mut out_l, &mut out_r);
+
+        for i in 0..len {
+            let (l, r) = per_sample.process_with_sidechain(in_l[i], in_r[i], sc[i], sc[i]);
+            assert_eq!(l, out_l[i], "{name}: left mismatch at {i}");
+            assert_eq!(r, out_r[i], "{name}: right mismatch at {i}");
+        }
+    }
+
+    #[test]
+    fn test_compressor_block_matches_per_sample() {
+        assert_block_matches_per_sample("sidechain_compressor");
+    }
+
+    #[test]
+    fn test_gate_block_matches_per_sample() {
+        assert_block_matches_per_sample("sidechain_gate");
+    }
+
+    /// Samples until a full-scale sidechain pulls the compressor's gain below half
+    fn attack_samples(effect: &mut dyn SidechainAwareEffect) -> usize {
+        (0..100_000)
+            .position(|_| effect.process_with_sidechain(1.0, 1.0, 1.0, 1.0).0 < 0.5)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_compressor_makeup_restores_level() {
+        let params = HashMap::from([("release".to_string(), 0.05)]);
+        let mut compressor =
+            build_sidechain_effect("sidechain_compressor", &params, 48000.0).unwrap();
+        let controls = compressor.controls();
+
+        // A loud sidechain passage, then its average reduction
+        let (input, _, _) = test_signals(48000);
+        let mut reduction = 0.0;
+        for (i, &x) in input.iter().enumerate() {
+            compressor.process_with_sidechain(x, x, 0.9, 0.9);
+            if i >= 24000 {
+                reduction += controls.meter("gain_reduction").unwrap();
+            }
+        }
+        let reduction = reduction / 24000.0;
+        assert!(reduction > 10.0, "reduction {reduction}");
+
+        controls.set("makeup", reduction);
+        let rms = |samples: &[f32]| {
+            (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+        };
+        let output: Vec<f32> = input
+            .iter()
+            .map(|&x| compressor.process_with_sidechain(x, x, 0.9, 0.9).0)
+            .collect();
+        let level_db = amplitude_to_db(rms(&output) / rms(&input));
+        assert!(level_db.abs() < 0.5, "level {level_db} dB");
+    }
+
+    #[test]
+    fn test_compressor_soft_knee() {
+        // 3 dB below the threshold: untouched by a hard knee, reduced inside a 12 dB one
+        let level = db_to_amplitude(-23.0);
+        let settle = |knee: f32| {
+            let mut compressor =
+                SidechainCompressor::new(-20.0, 4.0, 0.001, 0.1, 48000.0).with_knee(knee);
+            for _ in 0..4800 {
+                compressor.process_with_sidechain(1.0, 1.0, level, level);
+            }
+            compressor.gain_reduction_db.value()
+        };
+        assert_eq!(settle(0.0), 0.0);
+        assert!(settle(12.0) > 0.1);
+    }
+
+    #[test]
+    fn test_key_filter_ignores_sub() {
+        // A 50 Hz sub with a quiet 3 kHz click on top
+        let sidechain = |i: usize| {
+            let t = i as f32 / 48000.0;
+            0.9 * (std::f32::consts::TAU * 50.0 * t).sin()
+                + 0.05 * (std::f32::consts::TAU * 3000.0 * t).sin()
+        };
+        let peak_reduction = |key_highpass: f32| {
+            let mut compressor = SidechainCompressor::new(-20.0, 4.0, 0.001, 0.1, 48000.0)
+                .with_key_filter(key_highpass, 0.0);
+            (0..24000).fold(0.0_f32, |peak, i| {
+                let key = sidechain(i);
+                compressor.process_with_sidechain(0.5, 0.5, key, key);
+                peak.max(compressor.gain_reduction_db.value())
+            })
+        };
+        let unfiltered = peak_reduction(0.0);
+        let filtered = peak_reduction(1000.0);
+        assert!(unfiltered > 10.0, "unfiltered {unfiltered}");
+        assert!(filtered < unfiltered * 0.25, "filtered {filtered}");
+    }
+
+    #[test]
+    fn test_key_highpass_rejects_sub_only_key() {
+        // A loud 50 Hz sub with no click: nothing left above the key highpass
+        let mut compressor =
+            SidechainCompressor::new(-20.0, 4.0, 0.001, 0.1, 48000.0).with_key_filter(1000.0, 0.0);
+        let mut peak = 0.0_f32;
+        for i in 0..24000 {
+            let key = 0.9 * (std::f32::consts::TAU * 50.0 * i as f32 / 48000.0).sin();
+            compressor.process_with_sidechain(0.5, 0.5, key, key);
+            peak = peak.max(compressor.gain_reduction_db.value());
+        }
+        assert!(peak < 0.01, "{peak}");
+    }
+
+    #[test]
+    fn test_hold_delays_release() {
+        let reduction_after_hit = |hold: f32| {
+            let mut compressor =
+                SidechainCompressor::new(-20.0, 4.0, 0.001, 0.01, 48000.0).with_hold(hold);
+            for _ in 0..480 {
+                compressor.process_with_sidechain(0.5, 0.5, 1.0, 1.0);
+            }
+            let peak = compressor.gain_reduction_db.value();
+            for _ in 0..1920 {
+                compressor.process_with_sidechain(0.5, 0.5, 0.0, 0.0);
+            }
+            (peak, compressor.gain_reduction_db.value())
+        };
+        // 50 ms hold outlasts the 40 ms gap
+        let (peak, held) = reduction_after_hit(0.05);
+        assert!(peak > 10.0);
+        assert_eq!(held, peak);
+        let (_, released) = reduction_after_hit(0.0);
+        assert_eq!(released, 0.0);
+    }
+
+    #[test]
+    fn test_set_sample_rate_rescales_attack() {
+        let params = HashMap::new();
+        let mut effect = build_sidechain_effect("sidechain_compressor", &params, 48000.0).unwrap();
+        let at_48k = attack_samples(effect.as_mut());
+
+        effect.reset();
+        effect.set_sample_rate(96000.0);
+        let at_96k = attack_samples(effect.as_mut());
+        let ratio = at_96k as f32 / at_48k as f32;
+        assert!((ratio - 2.0).abs() < 0.05, "{at_48k} vs {at_96k}");
+
+        // Same as building at the new rate
+        let mut fresh = build_sidechain_effect("sidechain_compressor", &params, 96000.0).unwrap();
+        assert_eq!(attack_samples(fresh.as_mut()), at_96k);
+    }
+}
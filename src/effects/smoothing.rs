@@ -6,6 +6,9 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// Default smoothing time in milliseconds
+pub const DEFAULT_SMOOTHING_MS: f32 = 10.0;
+
 /// A smoothed parameter that gradually transitions to target values
 pub struct SmoothedParam {
     /// Current smoothed value
@@ -99,7 +102,7 @@ impl SmoothedParamBuilder {
     pub fn new() -> Self {
         Self {
             initial_value: 0.0,
-            smoothing_ms: 10.0, // 10ms default smoothing
+            smoothing_ms: DEFAULT_SMOOTHING_MS,
             sample_rate: 48000.0,
         }
     }
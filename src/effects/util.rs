@@ -0,0 +1,43 @@
+//! Helpers for building effect graphs
+
+use fundsp::hacker32::*;
+
+/// Run a mono node on both channels of a stereo signal
+///
+/// The right channel gets a copy of `node`, so each channel keeps its own
+/// filter state while any `var` inside still reads the same `Shared`. This
+/// replaces building the same network twice as `left | right`.
+pub fn dual<X>(node: An<X>) -> An<impl AudioNode<Inputs = U2, Outputs = U2>>
+where
+    X: AudioNode<Inputs = U1, Outputs = U1>,
+{
+    node.clone() | node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dual_matches_hand_written_stereo() {
+        let cutoff = shared(800.0);
+        let res = shared(2.0);
+        let build = || (pass() | var(&cutoff) | var(&res)) >> lowpass();
+
+        let mut dual_unit = dual(build());
+        let mut stereo_unit = build() | build();
+        dual_unit.set_sample_rate(44100.0);
+        stereo_unit.set_sample_rate(44100.0);
+
+        for i in 0..2048 {
+            if i == 1024 {
+                cutoff.set_value(3000.0);
+            }
+            let input = [(i as f32 * 0.05).sin(), (i as f32 * 0.13).cos()];
+            let (mut a, mut b) = ([0.0; 2], [0.0; 2]);
+            dual_unit.tick(&input, &mut a);
+            stereo_unit.tick(&input, &mut b);
+            assert_eq!(a, b);
+        }
+    }
+}
@@ -12,6 +12,16 @@ pub enum Error {
     InvalidSynth(String),
     /// Effect not found in registry
     InvalidEffect(String),
+    /// Synth name not registered, with the closest registered name if any
+    UnknownSynth {
+        name: String,
+        suggestion: Option<String>,
+    },
+    /// Effect name not registered, with the closest registered name if any
+    UnknownEffect {
+        name: String,
+        suggestion: Option<String>,
+    },
     /// Invalid parameter name
     InvalidParameter(String),
     /// Invalid parameter value
@@ -20,6 +30,13 @@ pub enum Error {
         value: f32,
         reason: String,
     },
+    /// Parameter value outside its defined range
+    ParameterOutOfRange {
+        name: String,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
     /// Effect chain error
     ChainError(String),
     /// Index out of bounds
@@ -30,6 +47,12 @@ pub enum Error {
     /// SoundFont loading/playback error
     #[cfg(feature = "soundfont")]
     SoundFontError(String),
+    /// WAV file reading/writing error
+    #[cfg(feature = "wav")]
+    WavError(String),
+    /// MIDI file reading error
+    #[cfg(feature = "midi")]
+    MidiError(String),
 }
 
 impl fmt::Display for Error {
@@ -37,6 +60,14 @@ impl fmt::Display for Error {
         match self {
             Error::InvalidSynth(name) => write!(f, "synth not found: '{}'", name),
             Error::InvalidEffect(name) => write!(f, "effect not found: '{}'", name),
+            Error::UnknownSynth { name, suggestion } => {
+                write!(f, "unknown synth '{}'", name)?;
+                did_you_mean(f, suggestion)
+            }
+            Error::UnknownEffect { name, suggestion } => {
+                write!(f, "unknown effect '{}'", name)?;
+                did_you_mean(f, suggestion)
+            }
             Error::InvalidParameter(name) => write!(f, "invalid parameter: '{}'", name),
             Error::InvalidValue {
                 param,
@@ -45,6 +76,16 @@ impl fmt::Display for Error {
             } => {
                 write!(f, "invalid value {} for '{}': {}", value, param, reason)
             }
+            Error::ParameterOutOfRange {
+                name,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "value {} for '{}' out of range [{}, {}]",
+                value, name, min, max
+            ),
             Error::ChainError(msg) => write!(f, "effect chain error: {}", msg),
             Error::IndexOutOfBounds { index, len } => {
                 write!(f, "index {} out of bounds (len: {})", index, len)
@@ -53,8 +94,79 @@ impl fmt::Display for Error {
             Error::SerializationError(msg) => write!(f, "serialization error: {}", msg),
             #[cfg(feature = "soundfont")]
             Error::SoundFontError(msg) => write!(f, "soundfont error: {}", msg),
+            #[cfg(feature = "wav")]
+            Error::WavError(msg) => write!(f, "wav error: {}", msg),
+            #[cfg(feature = "midi")]
+            Error::MidiError(msg) => write!(f, "midi error: {}", msg),
         }
     }
 }
 
 impl std::error::Error for Error {}
+
+fn did_you_mean(f: &mut fmt::Formatter<'_>, suggestion: &Option<String>) -> fmt::Result {
+    match suggestion {
+        Some(suggestion) => write!(f, "; did you mean '{}'?", suggestion),
+        None => Ok(()),
+    }
+}
+
+/// Registered names close to `name`, closest first
+///
+/// Compares case-insensitively by edit distance, keeping names within a
+/// third of `name`'s length (at least one edit). Ties sort alphabetically.
+pub(crate) fn suggest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut close: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect();
+    close.sort_unstable();
+    close.into_iter().map(|(_, c)| c.to_string()).collect()
+}
+
+/// Levenshtein distance between two strings, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest() {
+        assert_eq!(edit_distance("tbe303", "tb303"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+
+        let names = ["tb303", "acid", "saw", "supersaw"];
+        assert_eq!(suggest("tbe303", names), ["tb303"]);
+        assert_eq!(suggest("SAW", names), ["saw"]);
+        assert!(suggest("xyz", names).is_empty());
+
+        let error = Error::UnknownSynth {
+            name: "tbe303".to_string(),
+            suggestion: Some("tb303".to_string()),
+        };
+        assert_eq!(
+            error.to_string(),
+            "unknown synth 'tbe303'; did you mean 'tb303'?"
+        );
+    }
+}
@@ -49,15 +49,20 @@
 pub mod effects;
 pub mod error;
 pub mod metrics;
+pub mod midi;
 pub mod params;
+pub mod render;
 #[cfg(feature = "soundfont")]
 pub mod soundfont;
 pub mod synth;
+#[cfg(feature = "wav")]
+pub mod wav;
 
 // Re-export common types at crate root
 pub use error::{Error, Result};
 pub use metrics::{CpuMeter, MetricsAggregator, PerformanceMetrics};
 pub use params::ParameterDef;
+pub use render::RenderBuffer;
 
 /// Prelude module - import everything you need
 pub mod prelude {
@@ -65,33 +70,48 @@ pub mod prelude {
     pub use crate::error::{Error, Result};
     pub use crate::metrics::{CpuMeter, MetricsAggregator, PerformanceMetrics};
     pub use crate::params::ParameterDef;
+    pub use crate::render::RenderBuffer;
 
     // Synth
     pub use crate::synth::{
-        midi_to_freq, EnvelopeConfig, FluentSynthBuilder, LFOConfig, LFOTarget, LFOWaveform,
-        PolySynth, PolySynthBuilder, Synth, SynthBuilder, SynthMetadata,
-        SynthRegistry, SynthRegistryExt, SynthRegistryPolyExt, VoiceControls, ADSR, AHD, AR,
+        chord, midi_to_freq, quantize_note, ArpMode, Arpeggiator, ChordKind, EnvelopeConfig,
+        FluentSynthBuilder, LFOConfig, LFOGenerator, LFOTarget, LFOWaveform, NoteDivision,
+        PolySynth, PolySynthBuilder, Scale, Synth, SynthBuilder, SynthMetadata, SynthRegistry,
+        SynthRegistryExt, SynthRegistryPolyExt, VoiceControls, ADSR, AHD, AR,
     };
     #[cfg(feature = "serde")]
-    pub use crate::synth::{PresetBank, SynthId, SynthPreset, Uuid};
+    pub use crate::synth::{PresetBank, Step, StepSequencer, SynthId, SynthPreset, Uuid};
 
     // Effects
     #[cfg(feature = "serde")]
     pub use crate::effects::{
-        mastering_bank, mixing_bank, ChainState, EffectPreset, EffectPresetBank, EffectState,
-        MasteringPresets, MixingPresets, PresetBankMasteringExt, PresetBankMixingExt,
+        all_builtin_presets, mastering_bank, mixing_bank, ChainState, EffectPreset,
+        EffectPresetBank, EffectState, LfoAttachment, MasteringPresets, MixingPresets,
+        PresetBankMasteringExt, PresetBankMixingExt,
     };
     pub use crate::effects::{
-        Effect, EffectBuilder, EffectChain, EffectControls, EffectId, EffectMetadata,
-        EffectRegistry, EffectRegistryExt, FluentEffectBuilder, ParameterRange,
-        SidechainAwareEffect, SmoothedParam, SmoothedParamBuilder,
+        BusId, ChainCpuReport, Effect, EffectBuilder, EffectChain, EffectControls, EffectCpuEntry,
+        EffectId, EffectLFO, EffectMetadata, EffectMeter, EffectRegistry, EffectRegistryExt,
+        FluentEffectBuilder, GainIssue, GainSuggestion, LFOPolarity, OverloadEvent, ParameterRange,
+        SidechainAwareEffect, SmoothedParam, SmoothedParamBuilder, StereoMeter,
+        DEFAULT_SMOOTHING_MS,
     };
 
     // SoundFont support (when enabled)
     #[cfg(feature = "soundfont")]
     pub use crate::soundfont::{
         create_soundfont_synth, freq_to_midi, gm_drum_name, gm_program_name, register_gm_programs,
-        ChannelAllocator, SoundFontManager, SoundFontPlayer, SoundFontSynthBuilder,
-        SoundFontSynthHandle, SoundFontUnit, SoundFontVoice, GM_DRUM_NOTES, GM_PROGRAM_NAMES,
+        register_loaded_programs, register_programs_filtered, ChannelAllocator, SoundFontManager,
+        SoundFontPlayer, SoundFontSynthBuilder, SoundFontSynthHandle, SoundFontUnit,
+        SoundFontVoice, GM_DRUM_NOTES, GM_PROGRAM_NAMES,
     };
+
+    // WAV export (when enabled)
+    #[cfg(feature = "wav")]
+    pub use crate::wav::{read_wav, write_wav, write_wav_with_format, WavFormat};
+
+    // MIDI
+    pub use crate::midi::{MidiEvent, MidiHandler, MidiTarget};
+    #[cfg(feature = "midi")]
+    pub use crate::midi::{MidiSequencer, TimedMidiEvent};
 }
@@ -2,9 +2,8 @@
 //!
 //! Provides real-time CPU usage tracking for synths and effects.
 
-#[cfg(test)]
-use std::time::Duration;
-use std::time::Instant;
+use crate::effects::EffectChain;
+use std::time::{Duration, Instant};
 
 /// Performance metrics for audio processing
 #[derive(Debug, Clone, Copy)]
@@ -20,6 +19,10 @@ pub struct PerformanceMetrics {
     pub samples_processed: u64,
     /// Total processing time
     pub total_time_ns: u64,
+    /// Processing latency in samples
+    pub latency_samples: usize,
+    /// Memory footprint of the processors in bytes
+    pub footprint_bytes: usize,
 }
 
 impl PerformanceMetrics {
@@ -31,6 +34,8 @@ impl PerformanceMetrics {
             cpu_usage: 0.0,
             samples_processed: 0,
             total_time_ns: 0,
+            latency_samples: 0,
+            footprint_bytes: 0,
         }
     }
 
@@ -77,11 +82,23 @@ impl Default for PerformanceMetrics {
 }
 
 /// CPU meter for tracking audio processing performance
+///
+/// The reported `cpu_usage` is an exponential moving average of the usage of
+/// each timed block. By default every block moves the average 1% of the way
+/// to its own usage, so the smoothing depends on the block size; use
+/// [`with_smoothing`](Self::with_smoothing) or
+/// [`set_smoothing_time`](Self::set_smoothing_time) for a time constant in
+/// seconds instead. [`peak_usage`](Self::peak_usage) holds the highest
+/// unsmoothed block usage until [`reset_peak`](Self::reset_peak).
 pub struct CpuMeter {
     metrics: PerformanceMetrics,
     sample_rate: f64,
     time_per_sample_ns: f64,
     smoothing_factor: f64,
+    /// Smoothing time constant in seconds, overriding `smoothing_factor`
+    smoothing_time: Option<f64>,
+    /// Highest block usage since the last peak reset
+    peak_usage: f64,
 }
 
 impl CpuMeter {
@@ -92,9 +109,21 @@ impl CpuMeter {
             sample_rate,
             time_per_sample_ns: 1_000_000_000.0 / sample_rate,
             smoothing_factor: 0.99,
+            smoothing_time: None,
+            peak_usage: 0.0,
         }
     }
 
+    /// Create a CPU meter that averages usage with time constant `tau_seconds`
+    ///
+    /// After `tau_seconds` of audio a step in usage is about 63% reflected
+    /// in the reported value, whatever the block size.
+    pub fn with_smoothing(sample_rate: f64, tau_seconds: f64) -> Self {
+        let mut meter = Self::new(sample_rate);
+        meter.set_smoothing_time(tau_seconds);
+        meter
+    }
+
     /// Start timing a processing block
     #[inline]
     pub fn start_timing(&self) -> Instant {
@@ -104,7 +133,14 @@ impl CpuMeter {
     /// Stop timing and update metrics
     #[inline]
     pub fn stop_timing(&mut self, start: Instant, num_samples: usize) {
-        let elapsed = start.elapsed().as_nanos() as u64;
+        self.record(start.elapsed(), num_samples);
+    }
+
+    /// Update metrics with a block of `num_samples` that took `elapsed`
+    ///
+    /// Use this when processing is timed externally.
+    pub fn record(&mut self, elapsed: Duration, num_samples: usize) {
+        let elapsed = elapsed.as_nanos() as u64;
 
         if num_samples == 0 {
             return;
@@ -119,12 +155,19 @@ impl CpuMeter {
             self.metrics.peak_sample_time_ns = time_per_sample;
         }
 
+        let usage = time_per_sample as f64 / self.time_per_sample_ns;
+        self.peak_usage = self.peak_usage.max(usage);
+
         if self.metrics.avg_sample_time_ns == 0.0 {
             self.metrics.avg_sample_time_ns = time_per_sample as f64;
         } else {
-            self.metrics.avg_sample_time_ns = self.metrics.avg_sample_time_ns
-                * self.smoothing_factor
-                + time_per_sample as f64 * (1.0 - self.smoothing_factor);
+            let factor = match self.smoothing_time {
+                Some(tau) if tau > 0.0 => (-(num_samples as f64) / (tau * self.sample_rate)).exp(),
+                Some(_) => 0.0,
+                None => self.smoothing_factor,
+            };
+            self.metrics.avg_sample_time_ns =
+                self.metrics.avg_sample_time_ns * factor + time_per_sample as f64 * (1.0 - factor);
         }
 
         self.metrics.cpu_usage = self.metrics.avg_sample_time_ns / self.time_per_sample_ns;
@@ -148,6 +191,18 @@ impl CpuMeter {
     /// Reset metrics
     pub fn reset(&mut self) {
         self.metrics.reset();
+        self.peak_usage = 0.0;
+    }
+
+    /// Highest unsmoothed block usage since the last peak reset
+    /// (1.0 = 100% CPU)
+    pub fn peak_usage(&self) -> f64 {
+        self.peak_usage
+    }
+
+    /// Start a new peak-hold window
+    pub fn reset_peak(&mut self) {
+        self.peak_usage = 0.0;
     }
 
     /// Set sample rate
@@ -157,8 +212,21 @@ impl CpuMeter {
     }
 
     /// Set smoothing factor (0.0 = no smoothing, 0.99 = heavy smoothing)
+    ///
+    /// The factor applies per timed block and replaces any smoothing time.
     pub fn set_smoothing(&mut self, factor: f64) {
         self.smoothing_factor = factor.clamp(0.0, 0.999);
+        self.smoothing_time = None;
+    }
+
+    /// Set the smoothing time constant in seconds (0.0 = no smoothing)
+    pub fn set_smoothing_time(&mut self, tau_seconds: f64) {
+        self.smoothing_time = Some(tau_seconds.max(0.0));
+    }
+
+    /// Get the smoothing time constant, if one is set
+    pub fn smoothing_time(&self) -> Option<f64> {
+        self.smoothing_time
     }
 
     /// Get sample rate
@@ -216,6 +284,22 @@ impl MetricsAggregator {
     pub fn is_empty(&self) -> bool {
         self.meters.is_empty()
     }
+
+    /// Take a resource snapshot of an effect chain
+    ///
+    /// Fills in the chain's total CPU usage, latency and memory footprint.
+    /// The average time per sample is derived from the CPU usage; per-sample
+    /// peaks and sample counts are kept per effect and left at zero here.
+    pub fn snapshot_chain(chain: &EffectChain) -> PerformanceMetrics {
+        let cpu_usage = chain.total_cpu_usage();
+        PerformanceMetrics {
+            avg_sample_time_ns: cpu_usage * 1_000_000_000.0 / chain.sample_rate(),
+            cpu_usage,
+            latency_samples: chain.total_latency(),
+            footprint_bytes: chain.total_footprint(),
+            ..PerformanceMetrics::new()
+        }
+    }
 }
 
 impl Default for MetricsAggregator {
@@ -259,6 +343,34 @@ mod tests {
         assert_eq!(metrics.samples_processed, 100);
     }
 
+    #[test]
+    fn test_cpu_meter_smoothing_time() {
+        // 64-sample blocks at 48kHz have 1333us to run in
+        let mut meter = CpuMeter::with_smoothing(48000.0, 0.05);
+        let heavy = Duration::from_micros(1000);
+        let light = Duration::from_micros(100);
+        for _ in 0..200 {
+            meter.record(heavy, 64);
+            meter.record(light, 64);
+        }
+
+        let usage = meter.metrics().cpu_usage;
+        let heavy_usage = 1000.0 / 1333.3;
+        let light_usage = 100.0 / 1333.3;
+        assert!(usage > light_usage * 1.5 && usage < heavy_usage * 0.9);
+        assert!((meter.peak_usage() - heavy_usage).abs() < 0.01);
+
+        // The peak window restarts on demand
+        meter.reset_peak();
+        meter.record(light, 64);
+        assert!((meter.peak_usage() - light_usage).abs() < 0.01);
+
+        // Without smoothing the usage follows the last block
+        meter.set_smoothing_time(0.0);
+        meter.record(heavy, 64);
+        assert!((meter.metrics().cpu_usage - heavy_usage).abs() < 0.01);
+    }
+
     #[test]
     fn test_overload_detection() {
         let mut metrics = PerformanceMetrics::new();
@@ -268,6 +380,25 @@ mod tests {
         assert!(!metrics.is_low());
     }
 
+    #[test]
+    fn test_snapshot_chain() {
+        let mut chain = EffectChain::with_registry(crate::effects::EffectRegistry::with_builtin());
+        let empty = MetricsAggregator::snapshot_chain(&chain);
+        assert_eq!(empty.footprint_bytes, 0);
+        assert_eq!(empty.latency_samples, 0);
+        assert_eq!(empty.cpu_usage, 0.0);
+
+        chain.add("reverb", &[]).unwrap();
+        let reverb = MetricsAggregator::snapshot_chain(&chain);
+        assert!(reverb.footprint_bytes > 0);
+        assert_eq!(reverb.footprint_bytes, chain.total_footprint());
+
+        chain.add("vibrato", &[]).unwrap();
+        let snapshot = MetricsAggregator::snapshot_chain(&chain);
+        assert!(snapshot.footprint_bytes > reverb.footprint_bytes);
+        assert_eq!(snapshot.latency_samples, chain.total_latency());
+    }
+
     #[test]
     fn test_aggregator() {
         let mut agg = MetricsAggregator::new();
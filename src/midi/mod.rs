@@ -0,0 +1,292 @@
+//! MIDI input
+//!
+//! [`MidiHandler`] decodes raw MIDI messages from hardware into
+//! [`MidiEvent`]s and sends them to a [`MidiTarget`]: a [`PolySynth`] or,
+//! with the `soundfont` feature, a
+//! [`SoundFontPlayer`](crate::soundfont::SoundFontPlayer). With the `midi`
+//! feature, `MidiSequencer` plays standard MIDI files into the same
+//! targets, triggering each event on the exact sample it falls on.
+//!
+//! # Example
+//! ```rust,no_run
+//! use fundsp_rack::prelude::*;
+//!
+//! let mut poly = PolySynth::new("electric_piano", 16);
+//! let mut handler = MidiHandler::new();
+//!
+//! // Note on C4, then a second note using running status
+//! handler.handle(&[0x90, 60, 100], &mut poly);
+//! handler.handle(&[64, 100], &mut poly);
+//! ```
+
+#[cfg(feature = "midi")]
+mod sequencer;
+
+#[cfg(feature = "midi")]
+pub use sequencer::{MidiSequencer, TimedMidiEvent};
+
+use crate::synth::PolySynth;
+
+/// Modulation wheel controller
+pub const CC_MOD_WHEEL: u8 = 1;
+/// Channel volume controller
+pub const CC_VOLUME: u8 = 7;
+/// Sustain pedal controller
+pub const CC_SUSTAIN: u8 = 64;
+/// Brightness controller, conventionally mapped to filter cutoff
+pub const CC_CUTOFF: u8 = 74;
+
+/// A MIDI channel message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiEvent {
+    /// Start a note (velocity 1-127)
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    /// Stop a note; note-ons with velocity 0 are read as note-offs
+    NoteOff { channel: u8, note: u8 },
+    /// Select the channel's instrument
+    ProgramChange { channel: u8, program: u8 },
+    /// Set a controller
+    ControlChange { channel: u8, control: u8, value: u8 },
+    /// Bend the channel's pitch (-8192 to 8191, 0 = centre)
+    PitchBend { channel: u8, value: i16 },
+}
+
+/// Something MIDI events can be played into
+///
+/// [`PolySynth`] plays every channel on its one instrument and ignores
+/// program changes. It maps the mod wheel ([`CC_MOD_WHEEL`]) to its LFO
+/// amount, [`CC_VOLUME`] to its volume, [`CC_SUSTAIN`] to its sustain pedal
+/// and [`CC_CUTOFF`] to the filter cutoff of its voices, swept
+/// exponentially from 20 Hz to 20 kHz. A `SoundFontPlayer` passes every
+/// event through on its channel.
+pub trait MidiTarget {
+    /// Apply a MIDI event
+    fn handle_midi(&mut self, event: &MidiEvent);
+}
+
+impl MidiTarget for PolySynth {
+    fn handle_midi(&mut self, event: &MidiEvent) {
+        match *event {
+            MidiEvent::NoteOn { note, velocity, .. } => {
+                self.note_on(note, velocity as f32 / 127.0);
+            }
+            MidiEvent::NoteOff { note, .. } => self.note_off(note),
+            MidiEvent::ControlChange { control, value, .. } => {
+                let amount = value as f32 / 127.0;
+                match control {
+                    CC_MOD_WHEEL => {
+                        self.set_lfo_amount(amount);
+                    }
+                    CC_VOLUME => {
+                        self.set_volume(amount);
+                    }
+                    CC_SUSTAIN => self.set_sustain_pedal(value >= 64),
+                    CC_CUTOFF => self.set_cutoff(20.0 * 1000.0_f32.powf(amount)),
+                    _ => {}
+                }
+            }
+            MidiEvent::PitchBend { value, .. } => self.pitch_bend_raw(value),
+            MidiEvent::ProgramChange { .. } => {}
+        }
+    }
+}
+
+#[cfg(feature = "soundfont")]
+impl MidiTarget for crate::soundfont::SoundFontPlayer {
+    fn handle_midi(&mut self, event: &MidiEvent) {
+        match *event {
+            MidiEvent::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => self.note_on(channel, note, velocity),
+            MidiEvent::NoteOff { channel, note } => self.note_off(channel, note),
+            MidiEvent::ProgramChange { channel, program } => self.program_change(channel, program),
+            MidiEvent::ControlChange {
+                channel,
+                control,
+                value,
+            } => self.control_change(channel, control, value),
+            MidiEvent::PitchBend { channel, value } => {
+                self.pitch_bend(channel, (value as i32 + 8192) as u16)
+            }
+        }
+    }
+}
+
+/// Decodes raw MIDI messages and dispatches them to a [`MidiTarget`]
+///
+/// Each call takes one message as it arrives from a MIDI port. Messages
+/// that start with a data byte reuse the last channel status (running
+/// status). System messages, aftertouch and incomplete messages are
+/// skipped.
+#[derive(Debug, Clone, Default)]
+pub struct MidiHandler {
+    /// Status byte of the last channel message
+    running_status: Option<u8>,
+}
+
+impl MidiHandler {
+    /// Create a handler with no running status
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode one message and send it to `target`
+    ///
+    /// Returns the event dispatched, if any.
+    pub fn handle<T: MidiTarget + ?Sized>(
+        &mut self,
+        message: &[u8],
+        target: &mut T,
+    ) -> Option<MidiEvent> {
+        let event = self.decode(message)?;
+        target.handle_midi(&event);
+        Some(event)
+    }
+
+    /// Decode one message without dispatching it
+    pub fn decode(&mut self, message: &[u8]) -> Option<MidiEvent> {
+        let (&first, rest) = message.split_first()?;
+        let (status, data) = if first & 0x80 == 0 {
+            (self.running_status?, message)
+        } else {
+            match first {
+                0x80..=0xef => self.running_status = Some(first),
+                // System common messages cancel running status; real-time
+                // messages leave it alone
+                0xf0..=0xf7 => self.running_status = None,
+                _ => {}
+            }
+            (first, rest)
+        };
+
+        let channel = status & 0x0f;
+        let byte = |i: usize| data.get(i).copied().filter(|b| b & 0x80 == 0);
+        Some(match status & 0xf0 {
+            0x80 => {
+                byte(1)?;
+                MidiEvent::NoteOff {
+                    channel,
+                    note: byte(0)?,
+                }
+            }
+            0x90 => match (byte(0)?, byte(1)?) {
+                (note, 0) => MidiEvent::NoteOff { channel, note },
+                (note, velocity) => MidiEvent::NoteOn {
+                    channel,
+                    note,
+                    velocity,
+                },
+            },
+            0xb0 => MidiEvent::ControlChange {
+                channel,
+                control: byte(0)?,
+                value: byte(1)?,
+            },
+            0xc0 => MidiEvent::ProgramChange {
+                channel,
+                program: byte(0)?,
+            },
+            0xe0 => MidiEvent::PitchBend {
+                channel,
+                value: (((byte(1)? as i16) << 7) | byte(0)? as i16) - 8192,
+            },
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let mut handler = MidiHandler::new();
+        assert_eq!(
+            handler.decode(&[0x92, 60, 100]),
+            Some(MidiEvent::NoteOn {
+                channel: 2,
+                note: 60,
+                velocity: 100
+            })
+        );
+        // Running status, and velocity 0 as note-off
+        assert_eq!(
+            handler.decode(&[64, 90]),
+            Some(MidiEvent::NoteOn {
+                channel: 2,
+                note: 64,
+                velocity: 90
+            })
+        );
+        // A real-time clock byte keeps the running status
+        assert_eq!(handler.decode(&[0xf8]), None);
+        assert_eq!(
+            handler.decode(&[60, 0]),
+            Some(MidiEvent::NoteOff {
+                channel: 2,
+                note: 60
+            })
+        );
+
+        assert_eq!(
+            handler.decode(&[0x80, 60, 64]),
+            Some(MidiEvent::NoteOff {
+                channel: 0,
+                note: 60
+            })
+        );
+        assert_eq!(
+            handler.decode(&[0xb0, 64, 127]),
+            Some(MidiEvent::ControlChange {
+                channel: 0,
+                control: 64,
+                value: 127
+            })
+        );
+        assert_eq!(
+            handler.decode(&[0xc9, 5]),
+            Some(MidiEvent::ProgramChange {
+                channel: 9,
+                program: 5
+            })
+        );
+        for (bytes, value) in [
+            ([0xe0, 0x00, 0x40], 0),
+            ([0xe0, 0x00, 0x00], -8192),
+            ([0xe0, 0x7f, 0x7f], 8191),
+        ] {
+            assert_eq!(
+                handler.decode(&bytes),
+                Some(MidiEvent::PitchBend { channel: 0, value })
+            );
+        }
+
+        // Incomplete messages, stray data and system messages
+        assert_eq!(handler.decode(&[]), None);
+        assert_eq!(handler.decode(&[0x90, 60]), None);
+        assert_eq!(handler.decode(&[0xf0, 0x7e, 0xf7]), None);
+        assert_eq!(handler.decode(&[60, 100]), None);
+    }
+
+    #[test]
+    fn test_poly_synth_target() {
+        let mut poly = PolySynth::new("sine", 4);
+        let mut handler = MidiHandler::new();
+
+        handler.handle(&[0x90, 60, 127], &mut poly);
+        handler.handle(&[0xb0, CC_SUSTAIN, 127], &mut poly);
+        handler.handle(&[0x80, 60, 0], &mut poly);
+        assert!(poly.is_sustain_pedal_down());
+        assert_eq!(poly.playing_notes(), vec![60]);
+        handler.handle(&[0xb0, CC_SUSTAIN, 0], &mut poly);
+        assert_eq!(poly.active_voices(), 0);
+
+        handler.handle(&[CC_VOLUME, 0], &mut poly);
+        assert_eq!(poly.volume(), 0.0);
+        handler.handle(&[CC_MOD_WHEEL, 127], &mut poly);
+        assert_eq!(poly.lfo_amount(), 1.0);
+    }
+}
@@ -0,0 +1,317 @@
+//! Standard MIDI file playback (requires the `midi` feature)
+
+use std::path::Path;
+
+use midly::{MetaMessage, MidiMessage, Smf, Timing, TrackEventKind};
+
+use super::{MidiEvent, MidiTarget};
+use crate::render::RenderBuffer;
+use crate::synth::PolySynth;
+use crate::{Error, Result};
+
+/// Tempo assumed until the first tempo event (120 BPM)
+const DEFAULT_TEMPO: u32 = 500_000;
+
+/// A [`MidiEvent`] at its position in the song
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedMidiEvent {
+    /// Time from the start of the song in seconds, tempo changes applied
+    pub time: f64,
+    /// The message
+    pub event: MidiEvent,
+}
+
+/// Plays a standard MIDI file into a synth, sample-accurately
+///
+/// Events from all tracks are merged into one timeline when the file is
+/// parsed, with tempo changes converted into absolute times, so playback
+/// is just a walk through a sorted list. Sequential (format 2) files are
+/// played as if their tracks were simultaneous.
+#[derive(Debug, Clone)]
+pub struct MidiSequencer {
+    events: Vec<TimedMidiEvent>,
+    /// Index of the next event to dispatch
+    next_event: usize,
+    /// Frames rendered since the start of the song
+    position: u64,
+}
+
+impl MidiSequencer {
+    /// Parse a standard MIDI file from memory
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let smf = Smf::parse(data)
+            .map_err(|e| Error::MidiError(format!("Failed to parse MIDI file: {}", e)))?;
+
+        // Merge all tracks into one list of (tick, event) by absolute tick
+        let mut merged = Vec::new();
+        for track in &smf.tracks {
+            let mut tick = 0u64;
+            for event in track {
+                tick += event.delta.as_int() as u64;
+                merged.push((tick, event.kind));
+            }
+        }
+        // Stable, so simultaneous events keep their track order
+        merged.sort_by_key(|&(tick, _)| tick);
+
+        let mut events = Vec::new();
+        let mut time = 0.0;
+        let mut last_tick = 0u64;
+        let mut tempo = DEFAULT_TEMPO;
+        for (tick, kind) in merged {
+            time += ticks_to_seconds(tick - last_tick, smf.header.timing, tempo);
+            last_tick = tick;
+
+            match kind {
+                TrackEventKind::Meta(MetaMessage::Tempo(t)) => tempo = t.as_int().max(1),
+                TrackEventKind::Midi { channel, message } => {
+                    if let Some(event) = convert(channel.as_int(), message) {
+                        events.push(TimedMidiEvent { time, event });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            events,
+            next_event: 0,
+            position: 0,
+        })
+    }
+
+    /// Read and parse a standard MIDI file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read(path.as_ref())
+            .map_err(|e| Error::MidiError(format!("Failed to read MIDI file: {}", e)))?;
+        Self::from_bytes(&data)
+    }
+
+    /// All events in the song, in playback order
+    pub fn events(&self) -> &[TimedMidiEvent] {
+        &self.events
+    }
+
+    /// Time of the last event in seconds
+    pub fn duration(&self) -> f64 {
+        self.events.last().map_or(0.0, |e| e.time)
+    }
+
+    /// Check whether every event has been played
+    pub fn is_finished(&self) -> bool {
+        self.next_event >= self.events.len()
+    }
+
+    /// Frames rendered since the start of the song
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Go back to the start of the song
+    ///
+    /// Notes still sounding on the synth are not released.
+    pub fn rewind(&mut self) {
+        self.next_event = 0;
+        self.position = 0;
+    }
+
+    /// Render the next `frames` frames of the song through `synth`
+    ///
+    /// Each event is sent to the synth right before the frame it falls on.
+    /// All channels play on the one synth, as described for
+    /// [`MidiTarget`]. Switches the synth to `sample_rate` first if it
+    /// differs.
+    pub fn render(
+        &mut self,
+        synth: &mut PolySynth,
+        sample_rate: f64,
+        frames: usize,
+    ) -> RenderBuffer {
+        if synth.sample_rate() != sample_rate {
+            synth.set_sample_rate(sample_rate);
+        }
+        self.play(synth, sample_rate, frames)
+    }
+
+    /// Render the next `frames` frames of the song through a SoundFont player
+    ///
+    /// Channels, program changes and controllers are passed through, so
+    /// multi-instrument files play with their own instruments. `sample_rate`
+    /// only times the events; audio comes out at the rate the player's
+    /// [`SoundFontManager`](crate::soundfont::SoundFontManager) was created
+    /// with, and the two should match.
+    #[cfg(feature = "soundfont")]
+    pub fn render_soundfont(
+        &mut self,
+        player: &mut crate::soundfont::SoundFontPlayer,
+        sample_rate: f64,
+        frames: usize,
+    ) -> RenderBuffer {
+        let player_rate = player.unit_mut().manager().sample_rate() as f64;
+        if player_rate != sample_rate {
+            tracing::warn!(
+                "MIDI events timed at {} Hz but the SoundFont renders at {} Hz",
+                sample_rate,
+                player_rate
+            );
+        }
+        self.play(player, sample_rate, frames)
+    }
+
+    /// Render `frames` frames, dispatching events as their frames come up
+    fn play<T: MidiRenderer>(
+        &mut self,
+        target: &mut T,
+        sample_rate: f64,
+        frames: usize,
+    ) -> RenderBuffer {
+        let mut left = vec![0.0; frames];
+        let mut right = vec![0.0; frames];
+        let end = self.position + frames as u64;
+
+        let mut done = 0;
+        while done < frames {
+            // Send everything due at the current frame
+            while let Some(event) = self.events.get(self.next_event) {
+                if event_frame(event.time, sample_rate) > self.position {
+                    break;
+                }
+                target.handle_midi(&event.event);
+                self.next_event += 1;
+            }
+
+            // Render up to the next event or the end of the block
+            let next = self
+                .events
+                .get(self.next_event)
+                .map_or(end, |event| event_frame(event.time, sample_rate).min(end));
+            let len = (next - self.position) as usize;
+            target.render(&mut left[done..done + len], &mut right[done..done + len]);
+            done += len;
+            self.position = next;
+        }
+
+        RenderBuffer::new(left, right, sample_rate)
+    }
+}
+
+/// Length of `ticks` ticks in seconds at `tempo` microseconds per beat
+fn ticks_to_seconds(ticks: u64, timing: Timing, tempo: u32) -> f64 {
+    match timing {
+        Timing::Metrical(ticks_per_beat) => {
+            (ticks * tempo as u64) as f64 / (1e6 * ticks_per_beat.as_int().max(1) as f64)
+        }
+        Timing::Timecode(fps, subframes) => {
+            ticks as f64 / (fps.as_f32() as f64 * subframes.max(1) as f64)
+        }
+    }
+}
+
+/// Frame an event at `time` seconds falls on
+fn event_frame(time: f64, sample_rate: f64) -> u64 {
+    (time * sample_rate).round() as u64
+}
+
+/// Convert a midly channel message, dropping the ones we don't play
+fn convert(channel: u8, message: MidiMessage) -> Option<MidiEvent> {
+    Some(match message {
+        MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => MidiEvent::NoteOn {
+            channel,
+            note: key.as_int(),
+            velocity: vel.as_int(),
+        },
+        MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => MidiEvent::NoteOff {
+            channel,
+            note: key.as_int(),
+        },
+        MidiMessage::ProgramChange { program } => MidiEvent::ProgramChange {
+            channel,
+            program: program.as_int(),
+        },
+        MidiMessage::Controller { controller, value } => MidiEvent::ControlChange {
+            channel,
+            control: controller.as_int(),
+            value: value.as_int(),
+        },
+        MidiMessage::PitchBend { bend } => MidiEvent::PitchBend {
+            channel,
+            value: bend.as_int(),
+        },
+        _ => return None,
+    })
+}
+
+/// Something a [`MidiSequencer`] can play into
+trait MidiRenderer: MidiTarget {
+    /// Render audio into `left` and `right`, which have equal lengths
+    fn render(&mut self, left: &mut [f32], right: &mut [f32]);
+}
+
+impl MidiRenderer for PolySynth {
+    fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            (*l, *r) = self.get_stereo();
+        }
+    }
+}
+
+#[cfg(feature = "soundfont")]
+impl MidiRenderer for crate::soundfont::SoundFontPlayer {
+    fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.unit_mut().render(left, right);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A format 0 file at 480 ticks per beat holding `track` (without the
+    /// end-of-track event)
+    fn smf(track: &[u8]) -> Vec<u8> {
+        let mut data = b"MThd\x00\x00\x00\x06\x00\x00\x00\x01\x01\xe0MTrk".to_vec();
+        data.extend_from_slice(&(track.len() as u32 + 4).to_be_bytes());
+        data.extend_from_slice(track);
+        data.extend_from_slice(&[0x00, 0xff, 0x2f, 0x00]);
+        data
+    }
+
+    #[test]
+    fn test_two_notes_start_on_their_frames() {
+        #[rustfmt::skip]
+        let data = smf(&[
+            0x00, 0x90, 60, 100,            // C4 at tick 0
+            0x81, 0x70, 0x90, 64, 100,      // E4 at tick 240 (0.25 s)
+            0x00, 0xff, 0x51, 0x03, 0x03, 0xd0, 0x90, // 240 BPM from here
+            0x81, 0x70, 0x80, 60, 0,        // both off at tick 480 (0.375 s)
+            0x00, 0x90, 64, 0,
+        ]);
+        let mut sequencer = MidiSequencer::from_bytes(&data).unwrap();
+        assert_eq!(sequencer.events().len(), 4);
+        assert!((sequencer.duration() - 0.375).abs() < 1e-9);
+
+        let mut poly = PolySynth::new("sine", 4);
+        let buffer = sequencer.render(&mut poly, 48000.0, 12000);
+        assert_eq!(buffer.len(), 12000);
+        assert_eq!(poly.playing_notes(), vec![60]);
+
+        // The second note lands on frame 12000 exactly
+        sequencer.render(&mut poly, 48000.0, 1);
+        assert_eq!(poly.active_voices(), 2);
+
+        sequencer.render(&mut poly, 48000.0, 5999);
+        assert_eq!(poly.active_voices(), 2);
+        sequencer.render(&mut poly, 48000.0, 1);
+        assert_eq!(poly.active_voices(), 0);
+        assert!(sequencer.is_finished());
+        assert_eq!(sequencer.position(), 18001);
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        assert!(matches!(
+            MidiSequencer::from_bytes(b"not a midi file"),
+            Err(Error::MidiError(_))
+        ));
+    }
+}
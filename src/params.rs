@@ -1,7 +1,11 @@
 //! Parameter definitions for synths and effects
 
+use crate::{Error, Result};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
 /// Parameter definition with name, default value, and range
 #[derive(Debug, Clone, PartialEq)]
@@ -29,6 +33,11 @@ impl ParameterDef {
         value.clamp(self.min, self.max)
     }
 
+    /// Check if a value lies within this parameter's range (NaN never does)
+    pub fn contains(&self, value: f32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
     /// Normalize a value to 0.0-1.0 range
     pub fn normalize(&self, value: f32) -> f32 {
         if self.max == self.min {
@@ -44,6 +53,177 @@ impl ParameterDef {
     }
 }
 
+/// Parameters that have already warned about being clamped, by `owner.name`
+///
+/// Shared by a registry and its clones so a synth built on every note warns
+/// about a bad parameter once rather than on every note.
+#[derive(Debug, Default)]
+pub(crate) struct ClampWarnings(Mutex<HashSet<String>>);
+
+impl ClampWarnings {
+    /// Whether `owner.name` is warning for the first time, marking it as warned
+    fn first(&self, owner: &str, name: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(format!("{}.{}", owner, name))
+    }
+}
+
+/// Check build parameters against their definitions
+///
+/// A value outside its definition's range is clamped (NaN falls back to the
+/// default), or rejected with
+/// [`Error::ParameterOutOfRange`] when `strict` is set. Parameters without a
+/// definition, such as `res_raw` or `seed`, pass through unchanged. `params`
+/// is borrowed as is when every value is in range. Clamping logs a warning
+/// the first time each parameter is clamped, as tracked by `warnings`.
+pub(crate) fn check_ranges<'a>(
+    owner: &str,
+    defs: &[ParameterDef],
+    params: &'a HashMap<String, f32>,
+    strict: bool,
+    warnings: &ClampWarnings,
+) -> Result<Cow<'a, HashMap<String, f32>>> {
+    let mut checked = Cow::Borrowed(params);
+    for def in defs {
+        let Some(&value) = params.get(&def.name) else {
+            continue;
+        };
+        if def.contains(value) {
+            continue;
+        }
+        if strict {
+            return Err(Error::ParameterOutOfRange {
+                name: def.name.clone(),
+                value,
+                min: def.min,
+                max: def.max,
+            });
+        }
+        let clamped = if value.is_nan() {
+            def.default
+        } else {
+            def.clamp(value)
+        };
+        if warnings.first(owner, &def.name) {
+            tracing::warn!(
+                "'{}': {} = {} is out of range [{}, {}], using {}",
+                owner,
+                def.name,
+                value,
+                def.min,
+                def.max,
+                clamped
+            );
+        }
+        checked.to_mut().insert(def.name.clone(), clamped);
+    }
+    Ok(checked)
+}
+
+/// Filter Q at `res = 0.0` (Butterworth, no resonant peak)
+pub const MIN_RES_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+/// Filter Q at `res = 1.0` (strong, ringing peak)
+pub const MAX_RES_Q: f32 = 20.0;
+
+/// Map normalized resonance (0.0-1.0) to biquad filter Q
+///
+/// All filter effects and filtered synths take `res` in 0.0-1.0 and map it
+/// through this curve, so a preset's `res` sounds alike wherever it is used.
+/// The curve is exponential: 0.0 is Q 0.707 (flat), 0.5 is about Q 3.8 and
+/// 1.0 is Q 20. Pass `res_raw` instead of `res` to set Q directly.
+pub fn res_to_q(res: f32) -> f32 {
+    MIN_RES_Q * (MAX_RES_Q / MIN_RES_Q).powf(res.clamp(0.0, 1.0))
+}
+
+/// Map normalized resonance (0.0-1.0) to ladder (moog) filter resonance
+///
+/// The ladder's peak at cutoff, relative to its passband, is `(1 + k) / (4 - k)`
+/// for feedback `k = 4 * resonance`; this solves that for the Q given by
+/// [`res_to_q`], so a ladder and a biquad ring alike at the same `res`. With
+/// `res_raw` the ladder resonance (1.0 = self-oscillation) is set directly.
+pub fn res_to_moog(res: f32) -> f32 {
+    let q = res_to_q(res);
+    (4.0 * q - 1.0) / (q + 1.0) / 4.0
+}
+
+/// Read resonance from build parameters
+///
+/// Returns `res_raw` and `true` if present, otherwise `res` (or `default_res`)
+/// and `false`.
+pub(crate) fn resonance_param(params: &HashMap<String, f32>, default_res: f32) -> (f32, bool) {
+    match params.get("res_raw") {
+        Some(&raw) => (raw, true),
+        None => (params.get("res").copied().unwrap_or(default_res), false),
+    }
+}
+
+/// Mapping from a resonance control to biquad Q (identity for `res_raw`)
+pub(crate) fn q_mapping(raw: bool) -> fn(f32) -> f32 {
+    if raw {
+        |q| q
+    } else {
+        res_to_q
+    }
+}
+
+/// Mapping from a resonance control to ladder resonance (identity for `res_raw`)
+pub(crate) fn moog_mapping(raw: bool) -> fn(f32) -> f32 {
+    if raw {
+        |q| q
+    } else {
+        res_to_moog
+    }
+}
+
+/// Version of the chain state and preset formats written by this crate
+///
+/// - 0: presets saved before they carried a version
+/// - 1: chain states saved before resonance was normalized
+/// - 2: `res` is normalized 0.0-1.0; raw Q and ladder values use `res_raw`
+///
+/// Older data is upgraded when it is loaded.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Effects whose `res` held a raw Q (or ladder resonance) before version 2
+#[cfg(feature = "serde")]
+const RAW_RES_EFFECTS: &[&str] = &[
+    "lpf", "lowpass", "hpf", "highpass", "bpf", "bandpass", "nlpf", "nhpf", "nbpf", "rlpf",
+    "nrlpf", "rhpf", "nrhpf", "wobble",
+];
+
+/// Synths whose `res` held a raw ladder resonance before version 2
+#[cfg(feature = "serde")]
+const RAW_RES_SYNTHS: &[&str] = &["tb303", "prophet", "hoover", "lead"];
+
+/// Upgrade the parameters of effect `name` saved at format version `from`
+#[cfg(feature = "serde")]
+pub(crate) fn migrate_effect_params(name: &str, params: &mut HashMap<String, f32>, from: u32) {
+    if from < 2 && RAW_RES_EFFECTS.contains(&name) {
+        raw_res_to_res_raw(params);
+    }
+}
+
+/// Upgrade the parameters of synth `name` saved at format version `from`
+#[cfg(feature = "serde")]
+pub(crate) fn migrate_synth_params(name: &str, params: &mut HashMap<String, f32>, from: u32) {
+    if from < 2 && RAW_RES_SYNTHS.contains(&name) {
+        raw_res_to_res_raw(params);
+    }
+}
+
+/// Move a pre-normalization `res` to `res_raw`, which keeps its meaning
+#[cfg(feature = "serde")]
+fn raw_res_to_res_raw(params: &mut HashMap<String, f32>) {
+    if params.contains_key("res_raw") {
+        return;
+    }
+    if let Some(res) = params.remove("res") {
+        params.insert("res_raw".to_string(), res);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,6 +237,31 @@ mod tests {
         assert_eq!(param.clamp(30000.0), 20000.0);
     }
 
+    #[test]
+    fn test_check_ranges() {
+        let defs = [ParameterDef::new("cutoff", 1000.0, 20.0, 20000.0)];
+        let warnings = ClampWarnings::default();
+
+        let params = HashMap::from([("cutoff".to_string(), 500.0), ("res_raw".to_string(), 9.0)]);
+        let checked = check_ranges("lpf", &defs, &params, true, &warnings).unwrap();
+        assert!(matches!(checked, Cow::Borrowed(_)));
+
+        let params = HashMap::from([("cutoff".to_string(), 50000.0)]);
+        let checked = check_ranges("lpf", &defs, &params, false, &warnings).unwrap();
+        assert_eq!(checked["cutoff"], 20000.0);
+        let params = HashMap::from([("cutoff".to_string(), f32::NAN)]);
+        let checked = check_ranges("lpf", &defs, &params, false, &warnings).unwrap();
+        assert_eq!(checked["cutoff"], 1000.0);
+        assert!(matches!(
+            check_ranges("lpf", &defs, &params, true, &warnings),
+            Err(Error::ParameterOutOfRange { .. })
+        ));
+
+        // Clamping keeps working after the first warning
+        assert!(!warnings.first("lpf", "cutoff"));
+        assert!(warnings.first("hpf", "cutoff"));
+    }
+
     #[test]
     fn test_normalize() {
         let param = ParameterDef::new("volume", 0.5, 0.0, 1.0);
@@ -72,4 +277,21 @@ mod tests {
         assert_eq!(param.denormalize(1.0), 1000.0);
         assert_eq!(param.denormalize(0.5), 550.0);
     }
+
+    #[test]
+    fn test_res_mapping() {
+        assert!((res_to_q(0.0) - MIN_RES_Q).abs() < 1e-6);
+        assert!((res_to_q(1.0) - MAX_RES_Q).abs() < 1e-3);
+        assert!(res_to_q(0.3) < res_to_q(0.6));
+        // Out-of-range values are clamped
+        assert_eq!(res_to_q(-1.0), res_to_q(0.0));
+
+        // Ladder resonance stays below self-oscillation
+        assert!(res_to_moog(0.0) > 0.0);
+        assert!(res_to_moog(1.0) < 1.0);
+
+        let params = HashMap::from([("res".to_string(), 0.4), ("res_raw".to_string(), 2.0)]);
+        assert_eq!(resonance_param(&params, 0.0), (2.0, true));
+        assert_eq!(resonance_param(&HashMap::new(), 0.3), (0.3, false));
+    }
 }
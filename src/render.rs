@@ -0,0 +1,84 @@
+//! Offline rendering helpers
+//!
+//! [`PolySynth::render_to_buffer`](crate::synth::PolySynth::render_to_buffer)
+//! and [`EffectChain::render_to_buffer`](crate::effects::EffectChain::render_to_buffer)
+//! render audio in one call and return it as a [`RenderBuffer`].
+
+/// Rendered stereo audio with its sample rate
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RenderBuffer {
+    /// Left channel samples
+    pub left: Vec<f32>,
+    /// Right channel samples
+    pub right: Vec<f32>,
+    /// Sample rate the audio was rendered at (Hz)
+    pub sample_rate: f64,
+}
+
+impl RenderBuffer {
+    /// Create a buffer from planar channels
+    pub fn new(left: Vec<f32>, right: Vec<f32>, sample_rate: f64) -> Self {
+        debug_assert_eq!(left.len(), right.len());
+        Self {
+            left,
+            right,
+            sample_rate,
+        }
+    }
+
+    /// Number of frames
+    pub fn len(&self) -> usize {
+        self.left.len().min(self.right.len())
+    }
+
+    /// Check whether the buffer holds no frames
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f64 {
+        if self.sample_rate > 0.0 {
+            self.len() as f64 / self.sample_rate
+        } else {
+            0.0
+        }
+    }
+
+    /// Samples interleaved as L, R, L, R, ...
+    pub fn interleaved(&self) -> Vec<f32> {
+        self.left
+            .iter()
+            .zip(&self.right)
+            .flat_map(|(&l, &r)| [l, r])
+            .collect()
+    }
+
+    /// Split into planar (left, right) channels
+    pub fn into_planar(self) -> (Vec<f32>, Vec<f32>) {
+        (self.left, self.right)
+    }
+
+    /// Highest absolute sample value across both channels
+    pub fn peak(&self) -> f32 {
+        self.left
+            .iter()
+            .chain(&self.right)
+            .fold(0.0, |peak, x| peak.max(x.abs()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interleaved_layout() {
+        let buffer = RenderBuffer::new(vec![1.0, 2.0, 3.0], vec![-1.0, -2.0, -3.0], 48000.0);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.interleaved(), vec![1.0, -1.0, 2.0, -2.0, 3.0, -3.0]);
+        assert_eq!(buffer.peak(), 3.0);
+        assert!((buffer.duration() - 3.0 / 48000.0).abs() < 1e-12);
+        assert!(RenderBuffer::default().is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 //! SoundFont Manager - loads and manages SF2 files
 
 use rustysynth::{SoundFont, Synthesizer, SynthesizerSettings};
-use std::fs::File;
+use std::io::Cursor;
 use std::path::Path;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
@@ -14,6 +14,7 @@ use crate::{Error, Result};
 /// - Multiple SoundFont loading with fallback
 /// - Per-channel program/bank management
 /// - Volume/pan control per channel
+/// - Reverb and chorus send levels
 #[derive(Clone)]
 pub struct SoundFontManager {
     /// The underlying synthesizer
@@ -30,8 +31,17 @@ pub struct SoundFontManager {
     master_volume: f32,
     /// Path to loaded soundfont (for reference)
     soundfont_path: Option<String>,
+    /// Reverb enabled and send level (None keeps the channel default)
+    reverb: (bool, Option<f32>),
+    /// Chorus enabled and send level (None keeps the channel default)
+    chorus: (bool, Option<f32>),
 }
 
+/// MIDI CC number of the reverb send
+const CC_REVERB_SEND: u8 = 91;
+/// MIDI CC number of the chorus send
+const CC_CHORUS_SEND: u8 = 93;
+
 impl SoundFontManager {
     /// Create a new SoundFont manager
     ///
@@ -46,6 +56,8 @@ impl SoundFontManager {
             channel_banks: [0; 16],
             master_volume: 1.0,
             soundfont_path: None,
+            reverb: (true, None),
+            chorus: (true, None),
         }
     }
 
@@ -77,16 +89,43 @@ impl SoundFontManager {
 
         info!("Loading SoundFont: {}", path_str);
 
-        let mut file = File::open(path_ref)
+        let data = std::fs::read(path_ref)
             .map_err(|e| Error::SoundFontError(format!("Failed to open SF2 file: {}", e)))?;
+        self.load_from_bytes(&data, path_str)
+    }
+
+    /// Load a SoundFont from SF2 data in memory
+    ///
+    /// Useful for SoundFonts embedded with `include_bytes!` or read from a
+    /// virtual filesystem. [`soundfont_path`](Self::soundfont_path) reports
+    /// `"<memory>"` afterwards.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fundsp_rack::soundfont::SoundFontManager;
+    ///
+    /// // Or `include_bytes!("TimGM6mb.sf2")`
+    /// let data = std::fs::read("TimGM6mb.sf2").unwrap();
+    ///
+    /// let mut manager = SoundFontManager::new(44100);
+    /// manager.load_soundfont_bytes(&data).unwrap();
+    /// ```
+    pub fn load_soundfont_bytes(&mut self, data: &[u8]) -> Result<()> {
+        info!("Loading SoundFont from memory ({} bytes)", data.len());
+        self.load_from_bytes(data, "<memory>".to_string())
+    }
 
-        let soundfont = SoundFont::new(&mut file)
+    /// Parse SF2 data and replace the current SoundFont and synthesizer
+    fn load_from_bytes(&mut self, data: &[u8], path_str: String) -> Result<()> {
+        let soundfont = SoundFont::new(&mut Cursor::new(data))
             .map_err(|e| Error::SoundFontError(format!("Failed to parse SF2 file: {:?}", e)))?;
 
         let soundfont = Arc::new(soundfont);
 
-        // Create synthesizer settings
-        let settings = SynthesizerSettings::new(self.sample_rate as i32);
+        // Create synthesizer settings; the effect processors only exist if
+        // one of them is enabled when the synthesizer is created
+        let mut settings = SynthesizerSettings::new(self.sample_rate as i32);
+        settings.enable_reverb_and_chorus = self.reverb.0 || self.chorus.0;
 
         // Create synthesizer
         let synth = Synthesizer::new(&soundfont, &settings)
@@ -94,9 +133,10 @@ impl SoundFontManager {
 
         self.soundfont = Some(soundfont);
         self.synth = Some(synth);
-        self.soundfont_path = Some(path_str.clone());
+        self.apply_effect_sends();
 
         info!("SoundFont loaded successfully: {}", path_str);
+        self.soundfont_path = Some(path_str);
         Ok(())
     }
 
@@ -241,6 +281,46 @@ impl SoundFontManager {
         self.control_change(channel, 11, expression);
     }
 
+    /// Configure the SoundFont's built-in reverb
+    ///
+    /// `level` (0.0 - 1.0) is the reverb send of every channel, applied live
+    /// as CC 91 and re-sent whenever a SoundFont is loaded. Disabling sends 0.
+    ///
+    /// `enabled` also decides at synthesizer creation (the next load) whether
+    /// the reverb and chorus processors exist at all: they are skipped only if
+    /// both are disabled, and re-enabling one afterwards needs a reload.
+    pub fn set_reverb(&mut self, enabled: bool, level: f32) {
+        self.reverb = (enabled, Some(level.clamp(0.0, 1.0)));
+        self.apply_effect_sends();
+    }
+
+    /// Configure the SoundFont's built-in chorus
+    ///
+    /// `level` (0.0 - 1.0) is the chorus send of every channel, applied live
+    /// as CC 93 and re-sent whenever a SoundFont is loaded. Disabling sends 0.
+    /// See [`set_reverb`](Self::set_reverb) for what `enabled` does at load.
+    pub fn set_chorus(&mut self, enabled: bool, level: f32) {
+        self.chorus = (enabled, Some(level.clamp(0.0, 1.0)));
+        self.apply_effect_sends();
+    }
+
+    /// Send the configured reverb and chorus levels to every channel
+    fn apply_effect_sends(&mut self) {
+        for (control, (enabled, level)) in
+            [(CC_REVERB_SEND, self.reverb), (CC_CHORUS_SEND, self.chorus)]
+        {
+            let Some(level) = level else { continue };
+            let value = if enabled {
+                (level * 127.0).round() as u8
+            } else {
+                0
+            };
+            for channel in 0..16 {
+                self.control_change(channel, control, value);
+            }
+        }
+    }
+
     /// Render audio samples
     ///
     /// # Arguments
@@ -280,6 +360,14 @@ impl SoundFontManager {
         self.channel_programs.get(channel as usize).copied().unwrap_or(0)
     }
 
+    /// Get the current bank for a channel
+    pub fn channel_bank(&self, channel: u8) -> u8 {
+        self.channel_banks
+            .get(channel as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
     /// Reset the synthesizer to initial state
     pub fn reset(&mut self) {
         if let Some(synth) = &mut self.synth {
@@ -293,13 +381,36 @@ impl SoundFontManager {
         self.channel_banks = [0; 16];
     }
 
-    /// List available presets in the loaded SoundFont
+    /// List the presets in the loaded SoundFont as `(bank, program, name)`
+    ///
+    /// Sorted by bank, then program. Empty when no SoundFont is loaded.
     pub fn list_presets(&self) -> Vec<(u8, u8, String)> {
-        // Note: rustysynth doesn't expose preset enumeration directly
-        // We return the standard GM preset names as a reference
-        (0..128)
-            .map(|p| (0, p, super::GM_PROGRAM_NAMES[p as usize].to_string()))
-            .collect()
+        let Some(soundfont) = &self.soundfont else {
+            return Vec::new();
+        };
+        let mut presets: Vec<(u8, u8, String)> = soundfont
+            .get_presets()
+            .iter()
+            .map(|preset| {
+                (
+                    preset.get_bank_number().clamp(0, 255) as u8,
+                    preset.get_patch_number().clamp(0, 127) as u8,
+                    preset.get_name().to_string(),
+                )
+            })
+            .collect();
+        presets.sort_by_key(|&(bank, program, _)| (bank, program));
+        presets
+    }
+
+    /// Check whether the loaded SoundFont has a preset at `bank`/`program`
+    pub fn has_preset(&self, bank: u8, program: u8) -> bool {
+        self.soundfont.as_ref().is_some_and(|soundfont| {
+            soundfont.get_presets().iter().any(|preset| {
+                preset.get_bank_number() == bank as i32
+                    && preset.get_patch_number() == program as i32
+            })
+        })
     }
 }
 
@@ -310,9 +421,107 @@ impl Default for SoundFontManager {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    /// Build a minimal SF2 file with one sample, one instrument and the
+    /// given `(name, bank, program)` presets
+    pub(crate) fn tiny_sf2(presets: &[(&str, u16, u16)]) -> Vec<u8> {
+        fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+            let mut out = id.to_vec();
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                out.push(0);
+            }
+            out
+        }
+        fn list(kind: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+            let mut data = kind.to_vec();
+            for sub in chunks {
+                data.extend_from_slice(sub);
+            }
+            chunk(b"LIST", &data)
+        }
+        fn name(text: &str) -> Vec<u8> {
+            let mut out = vec![0u8; 20];
+            out[..text.len()].copy_from_slice(text.as_bytes());
+            out
+        }
+        fn words(values: &[u16]) -> Vec<u8> {
+            values.iter().flat_map(|v| v.to_le_bytes()).collect()
+        }
+
+        let info = list(
+            b"INFO",
+            &[chunk(b"ifil", &words(&[2, 1])), chunk(b"INAM", b"Test\0\0")],
+        );
+        // A square wave, so notes are audible when rendered
+        let wave: Vec<u16> = (0..64)
+            .map(|i| {
+                if (i / 4) % 2 == 0 {
+                    8000
+                } else {
+                    (-8000i16) as u16
+                }
+            })
+            .collect();
+        let sdta = list(b"sdta", &[chunk(b"smpl", &words(&wave))]);
+
+        // Every preset has one zone playing instrument 0
+        let count = presets.len() as u16;
+        let mut phdr = Vec::new();
+        for (i, &(preset, bank, program)) in presets.iter().enumerate() {
+            phdr.extend(name(preset));
+            phdr.extend(words(&[program, bank, i as u16]));
+            phdr.extend([0; 12]);
+        }
+        phdr.extend(name("EOP"));
+        phdr.extend(words(&[0, 0, count]));
+        phdr.extend([0; 12]);
+        let pbag: Vec<u8> = (0..=count).flat_map(|i| words(&[i, 0])).collect();
+        let mut pgen: Vec<u8> = (0..count).flat_map(|_| words(&[41, 0])).collect();
+        pgen.extend(words(&[0, 0]));
+
+        // One instrument with one zone looping sample 0
+        let mut inst = name("Instrument");
+        inst.extend(words(&[0]));
+        inst.extend(name("EOI"));
+        inst.extend(words(&[1]));
+        let ibag = words(&[0, 0, 2, 0]);
+        let igen = words(&[54, 1, 53, 0, 0, 0]);
+
+        let mut shdr = name("Sample");
+        for value in [0u32, 32, 8, 24, 44100] {
+            shdr.extend(value.to_le_bytes());
+        }
+        shdr.extend([60, 0]);
+        shdr.extend(words(&[0, 1]));
+        shdr.extend(name("EOS"));
+        shdr.extend([0; 26]);
+
+        let pdta = list(
+            b"pdta",
+            &[
+                chunk(b"phdr", &phdr),
+                chunk(b"pbag", &pbag),
+                chunk(b"pmod", &[0; 10]),
+                chunk(b"pgen", &pgen),
+                chunk(b"inst", &inst),
+                chunk(b"ibag", &ibag),
+                chunk(b"imod", &[0; 10]),
+                chunk(b"igen", &igen),
+                chunk(b"shdr", &shdr),
+            ],
+        );
+
+        let mut body = b"sfbk".to_vec();
+        body.extend(info);
+        body.extend(sdta);
+        body.extend(pdta);
+        chunk(b"RIFF", &body)
+    }
+
     #[test]
     fn test_manager_creation() {
         let manager = SoundFontManager::new(48000);
@@ -332,4 +541,71 @@ mod tests {
         assert!(left.iter().all(|&s| s == 0.0));
         assert!(right.iter().all(|&s| s == 0.0));
     }
+
+    /// Peak level of the tail after a held note is released
+    fn release_tail_peak(configure: impl FnOnce(&mut SoundFontManager)) -> f32 {
+        let mut manager = SoundFontManager::new(44100);
+        manager
+            .load_soundfont_bytes(&tiny_sf2(&[("Square", 0, 0)]))
+            .unwrap();
+        configure(&mut manager);
+
+        let mut left = vec![0.0; 4410];
+        let mut right = vec![0.0; 4410];
+        manager.note_on(0, 60, 127);
+        for _ in 0..4 {
+            manager.render(&mut left, &mut right);
+        }
+
+        // Let the dry note's release finish, then measure what rings on
+        manager.note_off(0, 60);
+        manager.render(&mut left, &mut right);
+        manager.render(&mut left, &mut right);
+        left.iter()
+            .chain(&right)
+            .fold(0.0, |peak, x| peak.max(x.abs()))
+    }
+
+    #[test]
+    fn test_reverb_send_controls_tail() {
+        let wet = release_tail_peak(|_| {});
+        assert!(wet > 0.0, "default reverb send should leave a tail");
+
+        let dry = release_tail_peak(|m| m.set_reverb(true, 0.0));
+        assert!(dry < wet * 0.01, "tail {} with the send at 0", dry);
+
+        let disabled = release_tail_peak(|m| m.set_reverb(false, 1.0));
+        assert!(disabled < wet * 0.01);
+    }
+
+    #[test]
+    fn test_load_invalid_bytes() {
+        let mut manager = SoundFontManager::new(44100);
+        let result = manager.load_soundfont_bytes(b"RIFF\0\0\0\0not a soundfont");
+
+        assert!(matches!(result, Err(Error::SoundFontError(_))));
+        assert!(!manager.is_loaded());
+        assert_eq!(manager.soundfont_path(), None);
+    }
+
+    #[test]
+    fn test_list_presets_from_file() {
+        let mut manager = SoundFontManager::new(44100);
+        assert!(manager.list_presets().is_empty());
+
+        let sf2 = tiny_sf2(&[("Standard Kit", 128, 0), ("Grand", 0, 0), ("Pad", 0, 88)]);
+        manager.load_soundfont_bytes(&sf2).unwrap();
+        assert_eq!(manager.soundfont_path(), Some("<memory>"));
+
+        assert_eq!(
+            manager.list_presets(),
+            vec![
+                (0, 0, "Grand".to_string()),
+                (0, 88, "Pad".to_string()),
+                (128, 0, "Standard Kit".to_string()),
+            ]
+        );
+        assert!(manager.has_preset(0, 88));
+        assert!(!manager.has_preset(0, 40));
+    }
 }
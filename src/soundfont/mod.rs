@@ -33,8 +33,9 @@ mod synth_adapter;
 
 pub use manager::SoundFontManager;
 pub use synth_adapter::{
-    create_soundfont_synth, freq_to_midi, midi_to_freq, register_gm_programs, ChannelAllocator,
-    SoundFontPlayer, SoundFontSynthBuilder, SoundFontSynthHandle, SoundFontUnit, SoundFontVoice,
+    create_soundfont_synth, freq_to_midi, midi_to_freq, register_gm_programs,
+    register_loaded_programs, register_programs_filtered, ChannelAllocator, SoundFontPlayer,
+    SoundFontSynthBuilder, SoundFontSynthHandle, SoundFontUnit, SoundFontVoice,
 };
 
 /// General MIDI program names (128 programs)
@@ -14,13 +14,14 @@
 //!    share the same underlying synthesizer.
 
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
 
 use fundsp::audiounit::AudioUnit;
 use fundsp::hacker32::*;
 use fundsp::shared::Shared;
 
 use super::SoundFontManager;
+use crate::synth::envelope::{apply_envelope, ENVELOPE_PARAMS};
 use crate::synth::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 
 // ============================================================================
@@ -46,12 +47,24 @@ use crate::synth::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 /// // Use in audio graph
 /// // let graph = unit >> ...
 /// ```
+///
+/// # Block sizes
+///
+/// `tick` renders ahead in 64-frame chunks. `process` and
+/// [`render`](Self::render) render exactly the frames the block needs, after
+/// playing out anything `tick` left buffered, so the output stream is the
+/// same however the calls are mixed and note timing is unaffected.
 #[derive(Clone)]
 pub struct SoundFontUnit {
     manager: SoundFontManager,
     buffer_l: Vec<f32>,
     buffer_r: Vec<f32>,
+    /// Frames rendered into the buffers
+    buffer_len: usize,
     buffer_pos: usize,
+    /// Number of synthesizer render calls made
+    #[cfg(test)]
+    render_calls: usize,
 }
 
 impl SoundFontUnit {
@@ -64,7 +77,52 @@ impl SoundFontUnit {
             manager,
             buffer_l: vec![0.0; Self::BUFFER_SIZE],
             buffer_r: vec![0.0; Self::BUFFER_SIZE],
+            buffer_len: Self::BUFFER_SIZE,
             buffer_pos: Self::BUFFER_SIZE,
+            #[cfg(test)]
+            render_calls: 0,
+        }
+    }
+
+    /// Render into host buffers of any length
+    ///
+    /// Frames left buffered by `tick` are played out first, then the rest is
+    /// rendered straight into `left` and `right` in a single call: a
+    /// 512-frame host block costs one synthesizer render instead of eight
+    /// 64-frame ones.
+    pub fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        debug_assert_eq!(left.len(), right.len());
+        let size = Ord::min(left.len(), right.len());
+
+        let available = Ord::min(self.buffer_len - self.buffer_pos, size);
+        let buffered = self.buffer_pos..self.buffer_pos + available;
+        left[..available].copy_from_slice(&self.buffer_l[buffered.clone()]);
+        right[..available].copy_from_slice(&self.buffer_r[buffered]);
+        self.buffer_pos += available;
+
+        if available < size {
+            self.manager
+                .render(&mut left[available..size], &mut right[available..size]);
+            #[cfg(test)]
+            {
+                self.render_calls += 1;
+            }
+        }
+    }
+
+    /// Render the next `frames` frames into the internal buffers
+    fn fill_buffer(&mut self, frames: usize) {
+        if self.buffer_l.len() < frames {
+            self.buffer_l.resize(frames, 0.0);
+            self.buffer_r.resize(frames, 0.0);
+        }
+        self.manager
+            .render(&mut self.buffer_l[..frames], &mut self.buffer_r[..frames]);
+        self.buffer_len = frames;
+        self.buffer_pos = 0;
+        #[cfg(test)]
+        {
+            self.render_calls += 1;
         }
     }
 
@@ -92,6 +150,21 @@ impl SoundFontUnit {
     pub fn program_change(&mut self, channel: u8, program: u8) {
         self.manager.program_change(channel, program);
     }
+
+    /// Set channel volume (CC 7)
+    pub fn set_channel_volume(&mut self, channel: u8, volume: u8) {
+        self.manager.set_channel_volume(channel, volume);
+    }
+
+    /// Set channel pan (CC 10)
+    pub fn set_channel_pan(&mut self, channel: u8, pan: u8) {
+        self.manager.set_channel_pan(channel, pan);
+    }
+
+    /// Set channel expression (CC 11)
+    pub fn set_channel_expression(&mut self, channel: u8, expression: u8) {
+        self.manager.set_channel_expression(channel, expression);
+    }
 }
 
 impl AudioUnit for SoundFontUnit {
@@ -104,7 +177,7 @@ impl AudioUnit for SoundFontUnit {
     }
 
     fn reset(&mut self) {
-        self.buffer_pos = Self::BUFFER_SIZE;
+        self.buffer_pos = self.buffer_len;
         self.manager.reset();
     }
 
@@ -114,9 +187,8 @@ impl AudioUnit for SoundFontUnit {
 
     fn tick(&mut self, _input: &[f32], output: &mut [f32]) {
         // Refill buffer if needed
-        if self.buffer_pos >= Self::BUFFER_SIZE {
-            self.manager.render(&mut self.buffer_l, &mut self.buffer_r);
-            self.buffer_pos = 0;
+        if self.buffer_pos >= self.buffer_len {
+            self.fill_buffer(Self::BUFFER_SIZE);
         }
 
         output[0] = self.buffer_l[self.buffer_pos];
@@ -133,14 +205,13 @@ impl AudioUnit for SoundFontUnit {
         let mut pos = 0;
 
         while pos < size {
-            // Refill buffer if needed
-            if self.buffer_pos >= Self::BUFFER_SIZE {
-                self.manager.render(&mut self.buffer_l, &mut self.buffer_r);
-                self.buffer_pos = 0;
+            // Once buffered frames run out, render exactly what the block needs
+            if self.buffer_pos >= self.buffer_len {
+                self.fill_buffer(size - pos);
             }
 
             // Copy available samples
-            let available = Ord::min(Self::BUFFER_SIZE - self.buffer_pos, size - pos);
+            let available = Ord::min(self.buffer_len - self.buffer_pos, size - pos);
 
             for i in 0..available {
                 let l = self.buffer_l[self.buffer_pos + i];
@@ -176,7 +247,7 @@ impl AudioUnit for SoundFontUnit {
     }
 
     fn footprint(&self) -> usize {
-        std::mem::size_of::<Self>() + Self::BUFFER_SIZE * 2 * std::mem::size_of::<f32>()
+        std::mem::size_of::<Self>() + self.buffer_l.len() * 2 * std::mem::size_of::<f32>()
     }
 
     fn allocate(&mut self) {}
@@ -191,18 +262,26 @@ impl AudioUnit for SoundFontUnit {
 struct ActiveNote {
     channel: u8,
     note: u8,
+    /// Released while the sustain pedal was down; ends when it is lifted
+    sustained: bool,
 }
 
+/// MIDI CC number of the sustain (damper) pedal
+const CC_SUSTAIN: u8 = 64;
+
 /// Higher-level SoundFont player with note tracking
 ///
 /// This wraps SoundFontUnit and adds:
 /// - Note tracking for proper note-off handling
+/// - Sustain pedal handling per channel
 /// - Program management per channel
 /// - Master volume control
 pub struct SoundFontPlayer {
     unit: SoundFontUnit,
     active_notes: Vec<ActiveNote>,
     master_volume: f32,
+    /// Sustain pedal state per channel
+    sustain: [bool; 16],
 }
 
 impl SoundFontPlayer {
@@ -212,6 +291,7 @@ impl SoundFontPlayer {
             unit: SoundFontUnit::new(manager),
             active_notes: Vec::with_capacity(64),
             master_volume: 1.0,
+            sustain: [false; 16],
         }
     }
 
@@ -223,13 +303,76 @@ impl SoundFontPlayer {
     /// Play a note (velocity 0-127)
     pub fn note_on(&mut self, channel: u8, note: u8, velocity: u8) {
         self.unit.note_on(channel, note, velocity);
-        self.active_notes.push(ActiveNote { channel, note });
+        self.active_notes
+            .retain(|n| !(n.channel == channel && n.note == note));
+        self.active_notes.push(ActiveNote {
+            channel,
+            note,
+            sustained: false,
+        });
     }
 
     /// Stop a note
+    ///
+    /// While the channel's sustain pedal is down the note keeps sounding and
+    /// stays active until the pedal is released.
     pub fn note_off(&mut self, channel: u8, note: u8) {
         self.unit.note_off(channel, note);
-        self.active_notes.retain(|n| !(n.channel == channel && n.note == note));
+        if self.is_sustained(channel) {
+            for active in &mut self.active_notes {
+                if active.channel == channel && active.note == note {
+                    active.sustained = true;
+                }
+            }
+        } else {
+            self.active_notes
+                .retain(|n| !(n.channel == channel && n.note == note));
+        }
+    }
+
+    /// Press or release the sustain pedal (CC64) on a channel
+    pub fn sustain(&mut self, channel: u8, on: bool) {
+        self.control_change(channel, CC_SUSTAIN, if on { 127 } else { 0 });
+    }
+
+    /// Check whether the sustain pedal is down on a channel
+    pub fn is_sustained(&self, channel: u8) -> bool {
+        self.sustain.get(channel as usize).copied().unwrap_or(false)
+    }
+
+    /// Send pitch bend (0-16383, 8192 = center)
+    pub fn pitch_bend(&mut self, channel: u8, value: u16) {
+        self.unit.manager_mut().pitch_bend(channel, value);
+    }
+
+    /// Send a control change
+    ///
+    /// CC64 (sustain) values of 64 and above press the pedal; releasing it
+    /// ends the notes that were let go while it was down.
+    pub fn control_change(&mut self, channel: u8, control: u8, value: u8) {
+        self.unit
+            .manager_mut()
+            .control_change(channel, control, value);
+        if control == CC_SUSTAIN && (channel as usize) < self.sustain.len() {
+            let on = value >= 64;
+            self.sustain[channel as usize] = on;
+            if !on {
+                self.active_notes
+                    .retain(|n| !(n.channel == channel && n.sustained));
+            }
+        }
+    }
+
+    /// Number of notes currently sounding, including sustained ones
+    pub fn active_note_count(&self) -> usize {
+        self.active_notes.len()
+    }
+
+    /// Check whether a note is sounding (held or sustained)
+    pub fn is_note_active(&self, channel: u8, note: u8) -> bool {
+        self.active_notes
+            .iter()
+            .any(|n| n.channel == channel && n.note == note)
     }
 
     /// Stop all notes
@@ -244,12 +387,37 @@ impl SoundFontPlayer {
         self.unit.program_change(channel, program);
     }
 
+    /// Set channel volume (CC 7)
+    pub fn set_channel_volume(&mut self, channel: u8, volume: u8) {
+        self.unit.set_channel_volume(channel, volume);
+    }
+
+    /// Set channel pan (CC 10)
+    pub fn set_channel_pan(&mut self, channel: u8, pan: u8) {
+        self.unit.set_channel_pan(channel, pan);
+    }
+
+    /// Set channel expression (CC 11)
+    pub fn set_channel_expression(&mut self, channel: u8, expression: u8) {
+        self.unit.set_channel_expression(channel, expression);
+    }
+
     /// Set master volume (0.0 - 1.0)
     pub fn set_master_volume(&mut self, volume: f32) {
         self.master_volume = volume.clamp(0.0, 2.0);
         self.unit.manager_mut().set_master_volume(volume);
     }
 
+    /// Configure the built-in reverb; see [`SoundFontManager::set_reverb`]
+    pub fn set_reverb(&mut self, enabled: bool, level: f32) {
+        self.unit.manager_mut().set_reverb(enabled, level);
+    }
+
+    /// Configure the built-in chorus; see [`SoundFontManager::set_chorus`]
+    pub fn set_chorus(&mut self, enabled: bool, level: f32) {
+        self.unit.manager_mut().set_chorus(enabled, level);
+    }
+
     /// Get the underlying unit for use in audio graphs
     pub fn into_unit(self) -> SoundFontUnit {
         self.unit
@@ -287,6 +455,23 @@ pub fn create_soundfont_synth(sample_rate: u32) -> SoundFontSynthHandle {
 ///
 /// Note: This uses Arc<Mutex> which has some overhead. For lowest latency,
 /// use SoundFontUnit directly in your audio graph.
+///
+/// # Audio-thread locking
+///
+/// The render path never blocks: it uses `try_lock` on the shared manager and
+/// outputs silence for that render block if another thread holds the lock
+/// (e.g. during `program_change` or while a new voice is being built). Holding
+/// the lock for long on a control thread therefore causes short dropouts
+/// rather than stalling the audio thread. `new`, `stop` and the channel
+/// setters still take the lock normally, so they should be called from a
+/// control thread.
+///
+/// # Thread safety
+///
+/// Voices sharing a manager may live on different threads. If a thread
+/// panics while holding the lock, the mutex is poisoned; voices recover the
+/// manager and keep rendering rather than going silent, and record the event
+/// so [`had_render_error`](Self::had_render_error) can report it.
 #[derive(Clone)]
 pub struct SoundFontVoice {
     /// Shared synth manager
@@ -302,24 +487,59 @@ pub struct SoundFontVoice {
     /// Internal buffer for rendering
     buffer_l: Vec<f32>,
     buffer_r: Vec<f32>,
+    /// Frames rendered into the buffers
+    buffer_len: usize,
     buffer_pos: usize,
+    /// Level below which rendered output counts as silence
+    silence_threshold: f32,
+    /// Samples of continuous silence after note_off before the voice is finished
+    silence_grace: usize,
+    /// Consecutive silent samples rendered since note_off
+    silent_samples: usize,
+    /// Samples rendered since note_off
+    released_samples: usize,
+    /// Samples after note_off after which the voice is finished regardless of level
+    max_release: usize,
+    /// Gate that releases the note when it drops to 0.0
+    gate: Option<Shared>,
+    /// Set once rendering found the shared manager's lock poisoned
+    render_error: bool,
+}
+
+/// Lock the shared manager, recovering it if the lock is poisoned
+fn lock_manager(synth: &SoundFontSynthHandle) -> MutexGuard<'_, SoundFontManager> {
+    synth.lock().unwrap_or_else(|e| e.into_inner())
 }
 
 impl SoundFontVoice {
     /// Buffer size for internal rendering
     const BUFFER_SIZE: usize = 64;
 
+    /// Default silence threshold (-80 dBFS)
+    const DEFAULT_SILENCE_THRESHOLD: f32 = 1e-4;
+
+    /// Default grace period (about 50ms at 44.1kHz)
+    const DEFAULT_SILENCE_GRACE: usize = 2048;
+
+    /// Default release cap (about 5s at 44.1kHz)
+    const DEFAULT_MAX_RELEASE: usize = 220_500;
+
     /// Create a new voice
+    ///
+    /// Selects `bank` and `program` on `channel` before starting the note.
     pub fn new(
         synth: SoundFontSynthHandle,
         channel: u8,
         note: u8,
         velocity: u8,
+        bank: u8,
         program: u8,
         amp: Shared,
     ) -> Self {
         // Set up the channel and start the note
-        if let Ok(mut mgr) = synth.lock() {
+        {
+            let mut mgr = lock_manager(&synth);
+            mgr.bank_select(channel, bank);
             mgr.program_change(channel, program);
             mgr.note_on(channel, note, velocity);
         }
@@ -332,24 +552,167 @@ impl SoundFontVoice {
             amp,
             buffer_l: vec![0.0; Self::BUFFER_SIZE],
             buffer_r: vec![0.0; Self::BUFFER_SIZE],
+            buffer_len: Self::BUFFER_SIZE,
             buffer_pos: Self::BUFFER_SIZE, // Start at end to trigger first render
+            silence_threshold: Self::DEFAULT_SILENCE_THRESHOLD,
+            silence_grace: Self::DEFAULT_SILENCE_GRACE,
+            silent_samples: 0,
+            released_samples: 0,
+            max_release: Self::DEFAULT_MAX_RELEASE,
+            gate: None,
+            render_error: false,
         }
     }
 
+    /// Configure release detection used by [`has_finished`](Self::has_finished)
+    ///
+    /// # Arguments
+    /// * `threshold` - Linear peak level below which output counts as silence
+    /// * `grace_samples` - Samples of continuous silence required after note_off
+    pub fn with_release_detection(mut self, threshold: f32, grace_samples: usize) -> Self {
+        self.silence_threshold = threshold.max(0.0);
+        self.silence_grace = grace_samples;
+        self
+    }
+
+    /// Cap the release used by [`has_finished`](Self::has_finished) at `samples` after note_off
+    pub fn with_max_release(mut self, samples: usize) -> Self {
+        self.max_release = samples;
+        self
+    }
+
+    /// Send note_off when `gate` drops to 0.0, as [`VoiceControls::gate`] does on release
+    ///
+    /// The gate is checked once per render block, inside the lock the render
+    /// already takes.
+    pub fn with_gate(mut self, gate: Shared) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
     /// Stop this voice
     pub fn stop(&mut self) {
         if self.active {
-            if let Ok(mut mgr) = self.synth.lock() {
-                mgr.note_off(self.channel, self.note);
-            }
+            lock_manager(&self.synth).note_off(self.channel, self.note);
             self.active = false;
         }
     }
 
+    /// Set the volume (CC 7) of this voice's channel
+    ///
+    /// Takes the shared manager's lock, so call it from a control thread.
+    pub fn set_channel_volume(&self, volume: u8) {
+        lock_manager(&self.synth).set_channel_volume(self.channel, volume);
+    }
+
+    /// Set the pan (CC 10) of this voice's channel
+    ///
+    /// Takes the shared manager's lock, so call it from a control thread.
+    pub fn set_channel_pan(&self, pan: u8) {
+        lock_manager(&self.synth).set_channel_pan(self.channel, pan);
+    }
+
+    /// Set the expression (CC 11) of this voice's channel
+    ///
+    /// Takes the shared manager's lock, so call it from a control thread.
+    pub fn set_channel_expression(&self, expression: u8) {
+        lock_manager(&self.synth).set_channel_expression(self.channel, expression);
+    }
+
+    /// MIDI channel this voice plays on
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    /// Check whether rendering ever found the shared manager's lock poisoned
+    ///
+    /// The voice recovers and keeps rendering, so this is the only sign that
+    /// another thread panicked while holding the manager. The flag stays set.
+    pub fn had_render_error(&self) -> bool {
+        self.render_error
+    }
+
     /// Check if voice is active
     pub fn is_active(&self) -> bool {
         self.active
     }
+
+    /// Check if the voice has been released and its output has faded out
+    ///
+    /// Unlike [`is_active`](Self::is_active), which only reflects whether
+    /// note_off was sent, this returns true only once the rendered output has
+    /// stayed below the silence threshold for the configured grace period
+    /// after note_off. The rendered output is the shared manager's whole mix,
+    /// so other notes can hold it up; the release is therefore also capped
+    /// per voice (see [`with_max_release`](Self::with_max_release)).
+    ///
+    /// A finished voice outputs silence, so a [`PolySynth`](crate::synth::PolySynth)
+    /// holding it reclaims it like any other voice whose release has died away.
+    pub fn has_finished(&self) -> bool {
+        !self.active
+            && (self.silent_samples >= self.silence_grace
+                || self.released_samples >= self.max_release)
+    }
+
+    /// Update the post-release silence counter from the current render block
+    fn track_silence(&mut self) {
+        if self.active {
+            self.silent_samples = 0;
+            self.released_samples = 0;
+            return;
+        }
+        let threshold = self.silence_threshold;
+        let len = self.buffer_len;
+        self.released_samples = self.released_samples.saturating_add(len);
+        let silent = self.buffer_l[..len]
+            .iter()
+            .chain(self.buffer_r[..len].iter())
+            .all(|x| x.abs() <= threshold);
+        if silent {
+            self.silent_samples = self.silent_samples.saturating_add(len);
+        } else {
+            self.silent_samples = 0;
+        }
+    }
+
+    /// Render the next `frames` frames into the internal buffers without blocking
+    ///
+    /// Falls back to silence if the shared manager is locked elsewhere. A
+    /// poisoned lock is recovered and flagged as a render error.
+    fn render_block(&mut self, frames: usize) {
+        if self.buffer_l.len() < frames {
+            self.buffer_l.resize(frames, 0.0);
+            self.buffer_r.resize(frames, 0.0);
+        }
+        self.buffer_len = frames;
+        self.buffer_pos = 0;
+
+        let left = &mut self.buffer_l[..frames];
+        let right = &mut self.buffer_r[..frames];
+        let mgr = match self.synth.try_lock() {
+            Ok(mgr) => Some(mgr),
+            Err(TryLockError::Poisoned(e)) => {
+                self.render_error = true;
+                Some(e.into_inner())
+            }
+            Err(TryLockError::WouldBlock) => None,
+        };
+        match mgr {
+            Some(mut mgr) => {
+                if self.active && self.gate.as_ref().is_some_and(|gate| gate.value() <= 0.0) {
+                    mgr.note_off(self.channel, self.note);
+                    self.active = false;
+                }
+                mgr.render(left, right);
+                drop(mgr);
+                self.track_silence();
+            }
+            None => {
+                left.fill(0.0);
+                right.fill(0.0);
+            }
+        }
+    }
 }
 
 impl Drop for SoundFontVoice {
@@ -368,7 +731,9 @@ impl AudioUnit for SoundFontVoice {
     }
 
     fn reset(&mut self) {
-        self.buffer_pos = Self::BUFFER_SIZE;
+        self.buffer_pos = self.buffer_len;
+        self.silent_samples = 0;
+        self.released_samples = 0;
     }
 
     fn set_sample_rate(&mut self, _sample_rate: f64) {
@@ -376,12 +741,15 @@ impl AudioUnit for SoundFontVoice {
     }
 
     fn tick(&mut self, _input: &[f32], output: &mut [f32]) {
+        if self.has_finished() {
+            output[0] = 0.0;
+            output[1] = 0.0;
+            return;
+        }
+
         // Refill buffer if needed
-        if self.buffer_pos >= Self::BUFFER_SIZE {
-            if let Ok(mut mgr) = self.synth.lock() {
-                mgr.render(&mut self.buffer_l, &mut self.buffer_r);
-            }
-            self.buffer_pos = 0;
+        if self.buffer_pos >= self.buffer_len {
+            self.render_block(Self::BUFFER_SIZE);
         }
 
         let amp = self.amp.value();
@@ -396,20 +764,25 @@ impl AudioUnit for SoundFontVoice {
         _input: &fundsp::buffer::BufferRef,
         output: &mut fundsp::buffer::BufferMut,
     ) {
+        if self.has_finished() {
+            for i in 0..size {
+                output.set(0, i, F32x::splat(0.0));
+                output.set(1, i, F32x::splat(0.0));
+            }
+            return;
+        }
+
         let amp = self.amp.value();
         let mut pos = 0;
 
         while pos < size {
-            // Refill buffer if needed
-            if self.buffer_pos >= Self::BUFFER_SIZE {
-                if let Ok(mut mgr) = self.synth.lock() {
-                    mgr.render(&mut self.buffer_l, &mut self.buffer_r);
-                }
-                self.buffer_pos = 0;
+            // Once buffered frames run out, render exactly what the block needs
+            if self.buffer_pos >= self.buffer_len {
+                self.render_block(size - pos);
             }
 
             // Copy available samples
-            let available = Ord::min(Self::BUFFER_SIZE - self.buffer_pos, size - pos);
+            let available = Ord::min(self.buffer_len - self.buffer_pos, size - pos);
 
             for i in 0..available {
                 let l = self.buffer_l[self.buffer_pos + i] * amp;
@@ -445,7 +818,7 @@ impl AudioUnit for SoundFontVoice {
     }
 
     fn footprint(&self) -> usize {
-        std::mem::size_of::<Self>() + Self::BUFFER_SIZE * 2 * std::mem::size_of::<f32>()
+        std::mem::size_of::<Self>() + self.buffer_l.len() * 2 * std::mem::size_of::<f32>()
     }
 
     fn allocate(&mut self) {}
@@ -525,6 +898,8 @@ impl ChannelAllocator {
 /// SynthBuilder implementation for SoundFont instruments
 pub struct SoundFontSynthBuilder {
     synth: SoundFontSynthHandle,
+    /// SoundFont bank; 128 and above address percussion kits
+    bank: u8,
     program: u8,
     name: String,
     channel_allocator: Arc<Mutex<ChannelAllocator>>,
@@ -540,11 +915,21 @@ impl SoundFontSynthBuilder {
     ) -> Self {
         Self {
             synth,
+            bank: 0,
             program,
             name: name.into(),
             channel_allocator,
         }
     }
+
+    /// Builder pattern: play the program from `bank` (default 0)
+    ///
+    /// Banks 128 and above are percussion banks; their voices play on the
+    /// drum channel, which selects bank `bank - 128` there.
+    pub fn with_bank(mut self, bank: u8) -> Self {
+        self.bank = bank;
+        self
+    }
 }
 
 impl SynthBuilder for SoundFontSynthBuilder {
@@ -552,36 +937,61 @@ impl SynthBuilder for SoundFontSynthBuilder {
         let note = freq_to_midi(freq);
         let velocity = (params.get("velocity").copied().unwrap_or(0.8) * 127.0) as u8;
 
-        // Allocate a channel
-        let channel = self
-            .channel_allocator
-            .lock()
-            .map(|mut alloc| alloc.allocate().unwrap_or(0))
-            .unwrap_or(0);
+        // Allocate a channel; percussion banks live on the drum channel
+        let (channel, bank) = if self.bank >= 128 {
+            let channel = self
+                .channel_allocator
+                .lock()
+                .map(|mut alloc| alloc.allocate_drums())
+                .unwrap_or(9);
+            (channel, self.bank - 128)
+        } else {
+            let channel = self
+                .channel_allocator
+                .lock()
+                .map(|mut alloc| alloc.allocate().unwrap_or(0))
+                .unwrap_or(0);
+            (channel, self.bank)
+        };
 
         // Create controls
         let amp = shared(params.get("amp").copied().unwrap_or(1.0));
         let pitch_bend = shared(1.0);
         let pressure = shared(0.0);
 
+        let gate = shared(1.0);
+
         let voice = SoundFontVoice::new(
             Arc::clone(&self.synth),
             channel,
             note,
             velocity,
+            bank,
             self.program,
             amp.clone(),
-        );
+        )
+        .with_gate(gate.clone());
 
         let controls = VoiceControls {
             amp,
             pitch_bend,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pressure,
+            gate,
         };
 
-        (Box::new(voice), controls)
+        // The voice releases its own note on the gate so the SoundFont's
+        // release plays out; only gate it in fundsp if an envelope is asked for
+        if ENVELOPE_PARAMS
+            .iter()
+            .any(|name| params.contains_key(*name))
+        {
+            apply_envelope(Box::new(voice), controls, params)
+        } else {
+            (Box::new(voice), controls)
+        }
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -605,17 +1015,23 @@ impl SynthBuilder for SoundFontSynthBuilder {
             _ => "other",
         };
 
-        SynthMetadata::new(
-            &self.name,
+        let description = if self.bank == 0 {
             format!(
                 "GM Program {} - {}",
                 self.program,
                 super::GM_PROGRAM_NAMES[self.program as usize]
-            ),
-        )
-        .with_param("velocity", 0.8, 0.0, 1.0)
-        .with_param("amp", 1.0, 0.0, 2.0)
-        .with_tags(["soundfont", "gm", category, "source:soundfont"])
+            )
+        } else {
+            format!(
+                "Bank {} Program {} - {}",
+                self.bank, self.program, self.name
+            )
+        };
+
+        SynthMetadata::new(&self.name, description)
+            .with_param("velocity", 0.8, 0.0, 1.0)
+            .with_param("amp", 1.0, 0.0, 2.0)
+            .with_tags(["soundfont", "gm", category, "source:soundfont"])
     }
 }
 
@@ -638,24 +1054,86 @@ pub fn midi_to_freq(note: u8) -> f32 {
 ///
 /// Synth names are prefixed with "sf_" to avoid conflicts with built-in synths.
 pub fn register_gm_programs(registry: &mut crate::synth::SynthRegistry, synth: SoundFontSynthHandle) {
+    register_programs_filtered(registry, synth, (0..128u8).map(|program| (0, program)));
+}
+
+/// Register SoundFont programs from any bank into a SynthRegistry
+///
+/// `programs` yields `(bank, program)` pairs; each synth selects its bank
+/// before its program when a voice is built. Bank 0 programs are named like
+/// [`register_gm_programs`] (`sf_acoustic_grand_piano`); others are named
+/// `sf_bank<bank>_<preset name>`, taking the name from the loaded
+/// SoundFont (`sf_bank128_standard_kit`) or `program_<n>` if it has no
+/// such preset. Returns the number of synths registered.
+pub fn register_programs_filtered(
+    registry: &mut crate::synth::SynthRegistry,
+    synth: SoundFontSynthHandle,
+    programs: impl IntoIterator<Item = (u8, u8)>,
+) -> usize {
+    let presets = synth
+        .lock()
+        .map(|manager| manager.list_presets())
+        .unwrap_or_default();
     let channel_allocator = Arc::new(Mutex::new(ChannelAllocator::new()));
 
-    for program in 0..128u8 {
-        let name = format!("sf_{}", gm_program_to_name(program));
-        let builder = Arc::new(SoundFontSynthBuilder::new(
+    let mut count = 0;
+    for (bank, program) in programs {
+        let program = program.min(127);
+        let (name, display_name) = if bank == 0 {
+            let display_name = super::GM_PROGRAM_NAMES[program as usize].to_string();
+            (format!("sf_{}", gm_program_to_name(program)), display_name)
+        } else {
+            let display_name = presets
+                .iter()
+                .find(|(b, p, _)| (*b, *p) == (bank, program))
+                .map(|(_, _, name)| name.clone())
+                .unwrap_or_else(|| format!("Program {}", program));
+            (
+                format!("sf_bank{}_{}", bank, snake_case(&display_name)),
+                display_name,
+            )
+        };
+        let builder = SoundFontSynthBuilder::new(
             Arc::clone(&synth),
             program,
-            super::GM_PROGRAM_NAMES[program as usize],
+            display_name,
             Arc::clone(&channel_allocator),
-        ));
-        registry.register(&name, builder);
+        )
+        .with_bank(bank);
+        registry.register(&name, Arc::new(builder));
+        count += 1;
     }
+    count
+}
+
+/// Register the GM programs the loaded SoundFont actually has
+///
+/// Like [`register_gm_programs`], but skips bank 0 programs missing from the
+/// SoundFont loaded into `synth`, so the registry only offers instruments
+/// that make sound. Returns the number of programs registered; 0 if no
+/// SoundFont is loaded.
+pub fn register_loaded_programs(
+    registry: &mut crate::synth::SynthRegistry,
+    synth: SoundFontSynthHandle,
+) -> usize {
+    let programs: Vec<(u8, u8)> = match synth.lock() {
+        Ok(manager) => (0..128u8)
+            .filter(|&p| manager.has_preset(0, p))
+            .map(|p| (0, p))
+            .collect(),
+        Err(_) => return 0,
+    };
+    register_programs_filtered(registry, synth, programs)
 }
 
 /// Convert GM program number to a snake_case name
 fn gm_program_to_name(program: u8) -> String {
-    super::GM_PROGRAM_NAMES[program as usize]
-        .to_lowercase()
+    snake_case(super::GM_PROGRAM_NAMES[program as usize])
+}
+
+/// Convert an instrument name to a snake_case synth name
+fn snake_case(name: &str) -> String {
+    name.to_lowercase()
         .replace([' ', '-', '(', ')'], "_")
         .replace("__", "_")
         .trim_end_matches('_')
@@ -668,6 +1146,7 @@ fn gm_program_to_name(program: u8) -> String {
 
 #[cfg(test)]
 mod tests {
+    use super::super::manager::tests::tiny_sf2;
     use super::*;
 
     #[test]
@@ -721,6 +1200,154 @@ mod tests {
         assert_eq!(gm_program_to_name(40), "violin");
     }
 
+    #[test]
+    fn test_register_loaded_programs() {
+        let synth = create_soundfont_synth(44100);
+        let mut registry = crate::synth::SynthRegistry::new();
+        assert_eq!(
+            register_loaded_programs(&mut registry, Arc::clone(&synth)),
+            0
+        );
+
+        let sf2 = tiny_sf2(&[("Grand", 0, 0), ("Violin", 0, 40), ("Kit", 128, 0)]);
+        synth.lock().unwrap().load_soundfont_bytes(&sf2).unwrap();
+        assert_eq!(register_loaded_programs(&mut registry, synth), 2);
+
+        assert!(registry.contains("sf_acoustic_grand_piano"));
+        assert!(registry.contains("sf_violin"));
+        assert!(!registry.contains("sf_viola"));
+    }
+
+    #[test]
+    fn test_register_programs_with_banks() {
+        let synth = create_soundfont_synth(44100);
+        let sf2 = tiny_sf2(&[("Grand", 0, 0), ("Brass", 1, 61), ("Standard Kit", 128, 0)]);
+        synth.lock().unwrap().load_soundfont_bytes(&sf2).unwrap();
+
+        let mut registry = crate::synth::SynthRegistry::new();
+        let count = register_programs_filtered(
+            &mut registry,
+            Arc::clone(&synth),
+            [(0, 0), (1, 61), (128, 0), (2, 5)],
+        );
+        assert_eq!(count, 4);
+        assert!(registry.contains("sf_acoustic_grand_piano"));
+        assert!(registry.contains("sf_bank1_brass"));
+        assert!(registry.contains("sf_bank128_standard_kit"));
+        assert!(registry.contains("sf_bank2_program_5"));
+
+        // Building a voice selects the bank before the program
+        registry
+            .create("sf_bank1_brass", 440.0, &HashMap::new())
+            .unwrap();
+        let manager = synth.lock().unwrap();
+        assert_eq!(manager.channel_bank(0), 1);
+        assert_eq!(manager.channel_program(0), 61);
+    }
+
+    #[test]
+    fn test_player_sustain_defers_note_off() {
+        let mut player = SoundFontPlayer::new(SoundFontManager::new(44100));
+
+        // Without the pedal note_off ends the note
+        player.note_on(0, 60, 100);
+        player.note_off(0, 60);
+        assert_eq!(player.active_note_count(), 0);
+
+        // With the pedal down the note rings on until it is lifted
+        player.sustain(0, true);
+        player.note_on(0, 60, 100);
+        player.note_on(0, 64, 100);
+        player.note_on(1, 67, 100);
+        player.note_off(0, 60);
+        player.note_off(1, 67);
+        assert!(player.is_note_active(0, 60));
+        assert!(!player.is_note_active(1, 67));
+
+        player.control_change(0, 64, 0);
+        assert!(!player.is_sustained(0));
+        assert!(!player.is_note_active(0, 60));
+        // Notes still held on the keyboard survive the pedal release
+        assert!(player.is_note_active(0, 64));
+    }
+
+    /// Peak level of `frames` frames rendered by the player
+    fn render_peak(player: &mut SoundFontPlayer, frames: usize) -> f32 {
+        let mut left = vec![0.0; frames];
+        let mut right = vec![0.0; frames];
+        player
+            .unit_mut()
+            .manager_mut()
+            .render(&mut left, &mut right);
+        left.iter()
+            .chain(&right)
+            .fold(0.0, |peak, x| peak.max(x.abs()))
+    }
+
+    #[test]
+    fn test_player_channel_volume() {
+        let mut player = SoundFontPlayer::new(SoundFontManager::new(44100));
+        player
+            .unit_mut()
+            .manager_mut()
+            .load_soundfont_bytes(&tiny_sf2(&[("Square", 0, 0)]))
+            .unwrap();
+
+        player.note_on(0, 60, 100);
+        player.note_on(1, 60, 100);
+        render_peak(&mut player, 1024);
+        let full = render_peak(&mut player, 512);
+        assert!(full > 0.0);
+
+        // Muting one channel leaves the other playing
+        player.set_channel_volume(0, 0);
+        player.set_channel_pan(1, 64);
+        render_peak(&mut player, 1024);
+        let half = render_peak(&mut player, 512);
+        assert!(half > 0.0 && half < full * 0.75);
+
+        player.set_channel_expression(1, 0);
+        render_peak(&mut player, 1024);
+        assert!(render_peak(&mut player, 512) < full * 0.01);
+    }
+
+    #[test]
+    fn test_soundfont_unit_renders_host_block_in_one_call() {
+        let mut manager = SoundFontManager::new(44100);
+        manager
+            .load_soundfont_bytes(&tiny_sf2(&[("Square", 0, 0)]))
+            .unwrap();
+        manager.note_on(0, 60, 100);
+        let mut chunked = SoundFontUnit::new(manager.clone());
+        let mut direct = SoundFontUnit::new(manager);
+
+        // Ticking through a 512-frame block renders eight 64-frame chunks
+        let mut expected = vec![0.0; 512];
+        let mut output = [0.0; 2];
+        for frame in expected.iter_mut() {
+            chunked.tick(&[], &mut output);
+            *frame = output[0];
+        }
+        assert_eq!(chunked.render_calls, 8);
+
+        // Rendering the block directly takes one call and yields the same audio
+        let mut left = vec![0.0; 512];
+        let mut right = vec![0.0; 512];
+        direct.render(&mut left, &mut right);
+        assert_eq!(direct.render_calls, 1);
+        assert_eq!(left, expected);
+
+        // Frames buffered by tick are played out before rendering the rest
+        chunked.tick(&[], &mut output);
+        direct.tick(&[], &mut output);
+        let mut chunked_block = vec![0.0; 100];
+        let mut direct_block = vec![0.0; 100];
+        chunked.render(&mut chunked_block, &mut vec![0.0; 100]);
+        direct.render(&mut direct_block, &mut vec![0.0; 100]);
+        assert_eq!(chunked_block, direct_block);
+        assert_eq!(chunked.render_calls, 10);
+    }
+
     #[test]
     fn test_soundfont_unit_creation() {
         let manager = SoundFontManager::new(44100);
@@ -728,4 +1355,145 @@ mod tests {
         assert_eq!(unit.inputs(), 0);
         assert_eq!(unit.outputs(), 2);
     }
+
+    #[test]
+    fn test_soundfont_voice_contended_lock_yields_silence() {
+        let synth = create_soundfont_synth(44100);
+        let mut voice = SoundFontVoice::new(Arc::clone(&synth), 0, 60, 100, 0, 0, shared(1.0));
+
+        // Stale data that would leak through if the render were skipped
+        voice.buffer_l.fill(1.0);
+        voice.buffer_r.fill(1.0);
+        voice.buffer_pos = SoundFontVoice::BUFFER_SIZE;
+
+        {
+            let _guard = synth.lock().unwrap();
+            let mut output = [1.0; 2];
+            for _ in 0..SoundFontVoice::BUFFER_SIZE * 2 {
+                voice.tick(&[], &mut output);
+                assert_eq!(output, [0.0, 0.0]);
+            }
+        }
+
+        // Once the lock is released the voice renders normally again
+        let mut output = [0.0; 2];
+        voice.tick(&[], &mut output);
+        assert!(output[0].is_finite() && output[1].is_finite());
+    }
+
+    #[test]
+    fn test_soundfont_voice_recovers_from_poisoned_lock() {
+        let synth = create_soundfont_synth(44100);
+        synth
+            .lock()
+            .unwrap()
+            .load_soundfont_bytes(&tiny_sf2(&[("Square", 0, 0)]))
+            .unwrap();
+        let mut voice = SoundFontVoice::new(Arc::clone(&synth), 0, 60, 100, 0, 0, shared(1.0));
+
+        // Panic on another thread while holding the lock
+        let poisoner = Arc::clone(&synth);
+        let result = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poisoning the shared manager");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(synth.is_poisoned());
+        assert!(!voice.had_render_error());
+
+        // The voice keeps producing sound and reports the failure
+        let mut output = [0.0; 2];
+        let mut peak: f32 = 0.0;
+        for _ in 0..1024 {
+            voice.tick(&[], &mut output);
+            peak = peak.max(output[0].abs());
+        }
+        assert!(peak > 0.0);
+        assert!(voice.had_render_error());
+
+        // Control calls recover the lock too
+        voice.set_channel_volume(100);
+        voice.stop();
+        assert!(!voice.is_active());
+    }
+
+    #[test]
+    fn test_soundfont_voice_has_finished_after_decay() {
+        let synth = create_soundfont_synth(44100);
+        let mut voice = SoundFontVoice::new(synth, 0, 60, 100, 0, 0, shared(1.0))
+            .with_release_detection(1e-3, 256);
+
+        // A held note is never finished, even when silent
+        voice.buffer_l.fill(0.0);
+        voice.buffer_r.fill(0.0);
+        for _ in 0..16 {
+            voice.track_silence();
+        }
+        assert!(!voice.has_finished());
+
+        // After note_off, the release tail decays block by block
+        voice.stop();
+        let mut level = 0.5;
+        while level > 1e-3 {
+            voice.buffer_l.fill(level);
+            voice.buffer_r.fill(-level);
+            voice.track_silence();
+            assert!(!voice.has_finished(), "finished while still audible");
+            level *= 0.5;
+        }
+
+        // Silence must persist for the whole grace period
+        voice.buffer_l.fill(0.0);
+        voice.buffer_r.fill(0.0);
+        for _ in 0..256 / SoundFontVoice::BUFFER_SIZE - 1 {
+            voice.track_silence();
+            assert!(!voice.has_finished());
+        }
+        voice.track_silence();
+        assert!(voice.has_finished());
+    }
+
+    #[test]
+    fn test_soundfont_voice_release_is_capped_per_voice() {
+        let synth = create_soundfont_synth(44100);
+        let mut voice = SoundFontVoice::new(synth, 0, 60, 100, 0, 0, shared(1.0))
+            .with_release_detection(1e-3, 256)
+            .with_max_release(SoundFontVoice::BUFFER_SIZE * 4);
+        voice.stop();
+
+        // Other notes keep the shared mix loud, but the voice still finishes
+        voice.buffer_l.fill(0.5);
+        voice.buffer_r.fill(0.5);
+        for _ in 0..3 {
+            voice.track_silence();
+            assert!(!voice.has_finished());
+        }
+        voice.track_silence();
+        assert!(voice.has_finished());
+
+        // ...and then goes quiet so a PolySynth can reclaim it
+        let mut output = [1.0; 2];
+        voice.tick(&[], &mut output);
+        assert_eq!(output, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_soundfont_voice_releases_on_gate() {
+        let synth = create_soundfont_synth(44100);
+        let gate = shared(1.0);
+        let mut voice =
+            SoundFontVoice::new(synth, 0, 60, 100, 0, 0, shared(1.0)).with_gate(gate.clone());
+        let mut output = [0.0; 2];
+        for _ in 0..SoundFontVoice::BUFFER_SIZE {
+            voice.tick(&[], &mut output);
+        }
+        assert!(voice.is_active());
+
+        gate.set(0.0);
+        for _ in 0..SoundFontVoice::BUFFER_SIZE {
+            voice.tick(&[], &mut output);
+        }
+        assert!(!voice.is_active());
+    }
 }
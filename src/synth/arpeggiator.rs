@@ -0,0 +1,328 @@
+//! Arpeggiator on top of a [`PolySynth`]
+//!
+//! An [`Arpeggiator`] plays the notes of a held chord one at a time, in a
+//! chosen order, at a tempo-synced rate.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use fundsp_rack::prelude::*;
+//!
+//! let mut arp = Arpeggiator::new(PolySynth::new("pluck", 4), 120.0, 44100.0);
+//! arp.set_mode(ArpMode::UpDown)
+//!     .set_rate(NoteDivision::Sixteenth)
+//!     .set_octaves(2);
+//! arp.hold_notes(&[60, 64, 67]);
+//!
+//! let (left, right) = arp.render(44100);
+//! ```
+
+use super::lfo::NoteDivision;
+use super::poly::PolySynth;
+
+/// Order an [`Arpeggiator`] plays the held notes in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArpMode {
+    /// Lowest to highest
+    Up,
+    /// Highest to lowest
+    Down,
+    /// Up then back down, without repeating the top and bottom notes
+    UpDown,
+    /// A random held note each step
+    Random,
+    /// The order the notes were passed to [`Arpeggiator::hold_notes`]
+    AsPlayed,
+}
+
+/// Plays a held chord as an arpeggio on a [`PolySynth`]
+///
+/// Each step plays one note for `gate` of the step length. The octave
+/// range repeats the chord that many octaves up.
+pub struct Arpeggiator {
+    synth: PolySynth,
+    /// Held notes in the order they were given
+    held: Vec<u8>,
+    /// One pass of the arpeggio
+    sequence: Vec<u8>,
+    mode: ArpMode,
+    rate: NoteDivision,
+    octaves: u8,
+    gate: f32,
+    velocity: f32,
+    bpm: f32,
+    sample_rate: f64,
+    /// Position in `sequence` of the next note
+    index: usize,
+    /// Samples ticked since the start
+    position: u64,
+    /// Time of the next step in samples
+    next_time: f64,
+    /// Note sounding and the time it is released
+    playing: Option<(u8, f64)>,
+    /// Random state for [`ArpMode::Random`]
+    rng: u32,
+}
+
+impl Arpeggiator {
+    /// Wrap `synth`, playing upward in sixteenths over one octave
+    pub fn new(mut synth: PolySynth, bpm: f32, sample_rate: f64) -> Self {
+        let bpm = bpm.max(1.0);
+        synth.set_bpm(bpm);
+        synth.set_sample_rate(sample_rate);
+        Self {
+            synth,
+            held: Vec::new(),
+            sequence: Vec::new(),
+            mode: ArpMode::Up,
+            rate: NoteDivision::Sixteenth,
+            octaves: 1,
+            gate: 0.5,
+            velocity: 0.8,
+            bpm,
+            sample_rate,
+            index: 0,
+            position: 0,
+            next_time: 0.0,
+            playing: None,
+            rng: 1,
+        }
+    }
+
+    /// Set the chord to arpeggiate, replacing the held notes
+    ///
+    /// The arpeggio restarts from its first note when notes are held after
+    /// none were; an empty slice stops it.
+    pub fn hold_notes(&mut self, notes: &[u8]) {
+        if self.held.is_empty() {
+            self.index = 0;
+            self.next_time = self.position as f64;
+        }
+        self.held.clear();
+        for &note in notes {
+            if note <= 127 && !self.held.contains(&note) {
+                self.held.push(note);
+            }
+        }
+        if self.held.is_empty() {
+            self.release();
+        }
+        self.rebuild();
+    }
+
+    /// The held notes, in the order they were given
+    pub fn held_notes(&self) -> &[u8] {
+        &self.held
+    }
+
+    /// One pass of the arpeggio; [`ArpMode::Random`] picks from these
+    pub fn sequence(&self) -> &[u8] {
+        &self.sequence
+    }
+
+    /// Set the order notes are played in
+    pub fn set_mode(&mut self, mode: ArpMode) -> &mut Self {
+        self.mode = mode;
+        self.rebuild();
+        self
+    }
+
+    /// Get the play order
+    pub fn mode(&self) -> ArpMode {
+        self.mode
+    }
+
+    /// Set the step length (default sixteenths)
+    pub fn set_rate(&mut self, rate: NoteDivision) -> &mut Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Set the number of octaves the chord is repeated over (1 to 4)
+    pub fn set_octaves(&mut self, octaves: u8) -> &mut Self {
+        self.octaves = octaves.clamp(1, 4);
+        self.rebuild();
+        self
+    }
+
+    /// Set the fraction of a step each note sounds for (0.0 to 1.0, default 0.5)
+    pub fn set_gate(&mut self, gate: f32) -> &mut Self {
+        self.gate = gate.clamp(0.01, 1.0);
+        self
+    }
+
+    /// Set the velocity notes are played at (default 0.8)
+    pub fn set_velocity(&mut self, velocity: f32) -> &mut Self {
+        self.velocity = velocity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the tempo in BPM, for the arpeggio and the synth's LFOs
+    pub fn set_bpm(&mut self, bpm: f32) -> &mut Self {
+        self.bpm = bpm.max(1.0);
+        self.synth.set_bpm(self.bpm);
+        self
+    }
+
+    /// Get the tempo in BPM
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Set the sample rate of the arpeggiator and its synth
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.synth.set_sample_rate(sample_rate);
+    }
+
+    /// Seed the note choices of [`ArpMode::Random`]
+    pub fn set_seed(&mut self, seed: u32) -> &mut Self {
+        self.rng = seed;
+        self
+    }
+
+    /// The wrapped synth
+    pub fn synth(&self) -> &PolySynth {
+        &self.synth
+    }
+
+    /// Mutable access to the wrapped synth
+    pub fn synth_mut(&mut self) -> &mut PolySynth {
+        &mut self.synth
+    }
+
+    /// Unwrap the synth
+    pub fn into_inner(self) -> PolySynth {
+        self.synth
+    }
+
+    /// Play any note due now, then produce the next stereo sample
+    pub fn tick(&mut self) -> (f32, f32) {
+        let now = self.position as f64;
+        if let Some((note, off)) = self.playing {
+            if now >= off.round() {
+                self.synth.note_off(note);
+                self.playing = None;
+            }
+        }
+
+        if !self.sequence.is_empty() && now >= self.next_time.round() {
+            let step = self.step_samples();
+            let note = self.next_note();
+            self.release();
+            self.synth.note_on(note, self.velocity);
+            self.playing = Some((note, self.next_time + step * self.gate as f64));
+            self.next_time += step;
+        }
+
+        self.position += 1;
+        self.synth.get_stereo()
+    }
+
+    /// Render `frames` frames into planar (left, right) buffers
+    pub fn render(&mut self, frames: usize) -> (Vec<f32>, Vec<f32>) {
+        (0..frames).map(|_| self.tick()).unzip()
+    }
+
+    /// Length of a step in samples at the current tempo
+    fn step_samples(&self) -> f64 {
+        self.sample_rate * 60.0 * self.rate.beats() as f64 / self.bpm as f64
+    }
+
+    /// Pick the note for the next step
+    fn next_note(&mut self) -> u8 {
+        if self.mode == ArpMode::Random {
+            self.rng = self.rng.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            return self.sequence[(self.rng >> 8) as usize % self.sequence.len()];
+        }
+        let note = self.sequence[self.index % self.sequence.len()];
+        self.index = (self.index + 1) % self.sequence.len();
+        note
+    }
+
+    /// Release the sounding note, if any
+    fn release(&mut self) {
+        if let Some((note, _)) = self.playing.take() {
+            self.synth.note_off(note);
+        }
+    }
+
+    /// Recompute the arpeggio from the held notes and settings
+    fn rebuild(&mut self) {
+        let mut chord = self.held.clone();
+        if self.mode != ArpMode::AsPlayed {
+            chord.sort_unstable();
+        }
+        let mut notes: Vec<u8> = (0..self.octaves)
+            .flat_map(|octave| {
+                chord
+                    .iter()
+                    .map(move |&note| note as u16 + 12 * octave as u16)
+            })
+            .filter(|&note| note <= 127)
+            .map(|note| note as u8)
+            .collect();
+
+        match self.mode {
+            ArpMode::Down => notes.reverse(),
+            ArpMode::UpDown if notes.len() > 2 => {
+                let down: Vec<u8> = notes[1..notes.len() - 1].iter().rev().copied().collect();
+                notes.extend(down);
+            }
+            _ => {}
+        }
+        self.sequence = notes;
+        if !self.sequence.is_empty() {
+            self.index %= self.sequence.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_up_steps_at_rate() {
+        // 120 BPM sixteenths at 48 kHz: 6000 samples a step
+        let mut arp = Arpeggiator::new(PolySynth::new("sine", 4), 120.0, 48000.0);
+        arp.hold_notes(&[67, 60, 64]);
+        assert_eq!(arp.sequence(), &[60, 64, 67]);
+
+        for expected in [60, 64, 67, 60] {
+            arp.tick();
+            assert_eq!(arp.synth().playing_notes(), vec![expected]);
+            arp.render(2999);
+            assert_eq!(arp.synth().playing_notes(), vec![expected]);
+            // Half-step gate
+            arp.render(3000);
+            assert!(arp.synth().playing_notes().is_empty());
+        }
+
+        arp.hold_notes(&[]);
+        arp.render(12000);
+        assert!(arp.synth().playing_notes().is_empty());
+    }
+
+    #[test]
+    fn test_modes() {
+        let mut arp = Arpeggiator::new(PolySynth::new("sine", 4), 120.0, 48000.0);
+        arp.hold_notes(&[64, 60, 67]);
+
+        arp.set_mode(ArpMode::Down);
+        assert_eq!(arp.sequence(), &[67, 64, 60]);
+        arp.set_mode(ArpMode::AsPlayed);
+        assert_eq!(arp.sequence(), &[64, 60, 67]);
+        arp.set_mode(ArpMode::UpDown).set_octaves(2);
+        assert_eq!(arp.sequence(), &[60, 64, 67, 72, 76, 79, 76, 72, 67, 64]);
+
+        arp.set_mode(ArpMode::Random).set_octaves(1).set_gate(1.0);
+        for _ in 0..8 {
+            arp.tick();
+            let playing = arp.synth().playing_notes();
+            assert_eq!(playing.len(), 1);
+            assert!([60, 64, 67].contains(&playing[0]));
+            arp.render(5999);
+        }
+    }
+}
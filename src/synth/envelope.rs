@@ -3,7 +3,9 @@
 //! This module provides ADSR (Attack, Decay, Sustain, Release) envelopes
 //! for shaping synth parameters over time.
 
+use super::registry::VoiceControls;
 use fundsp::hacker32::*;
+use std::collections::HashMap;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -181,6 +183,46 @@ impl Default for EnvelopeConfig {
     }
 }
 
+/// Parameters that switch on the amplitude envelope in [`apply_envelope`]
+pub const ENVELOPE_PARAMS: [&str; 4] = ["attack", "decay", "sustain", "release"];
+
+/// Gate a synth voice, adding an ADSR amplitude envelope if requested
+///
+/// If any of [`ENVELOPE_PARAMS`] is in `params`, the voice is shaped by an
+/// `adsr_live` envelope driven by `controls.gate`: the attack starts while the
+/// gate is high and the release starts when it drops to 0.0. Missing stages
+/// default to an instant attack, full sustain and a 1ms release. Without
+/// envelope params the gate simply switches the voice on and off.
+///
+/// Synth builders call this on the voice they return.
+pub fn apply_envelope(
+    voice: Box<dyn AudioUnit>,
+    controls: VoiceControls,
+    params: &HashMap<String, f32>,
+) -> (Box<dyn AudioUnit>, VoiceControls) {
+    let voice = unit::<U0, U2>(voice);
+    let gate = var(&controls.gate);
+
+    let gated: Box<dyn AudioUnit> = if ENVELOPE_PARAMS
+        .iter()
+        .any(|name| params.contains_key(*name))
+    {
+        let get = |name: &str, default: f32| params.get(name).copied().unwrap_or(default);
+        let adsr = ADSR::new(
+            get("attack", 0.001),
+            get("decay", 0.1),
+            get("sustain", 1.0),
+            get("release", 0.001),
+        );
+        let envelope = gate >> adsr_live(adsr.attack, adsr.decay, adsr.sustain, adsr.release);
+        Box::new(voice * (envelope >> split::<U2>()))
+    } else {
+        Box::new(voice * (gate >> split::<U2>()))
+    };
+
+    (gated, controls)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +253,45 @@ mod tests {
         assert!(pad.release > 0.5);
     }
 
+    #[test]
+    fn test_apply_envelope_follows_gate() {
+        let render = |params: &HashMap<String, f32>| {
+            let controls = VoiceControls {
+                amp: shared(1.0),
+                cutoff: None,
+                resonance: None,
+                pulse_width: None,
+                pitch_bend: shared(1.0),
+                pressure: shared(0.0),
+                gate: shared(1.0),
+            };
+            let (mut unit, controls) = apply_envelope(Box::new(dc((1.0, 1.0))), controls, params);
+            unit.set_sample_rate(48000.0);
+            let mut levels: Vec<f32> = (0..4800).map(|_| unit.get_stereo().0).collect();
+            controls.gate.set(0.0);
+            levels.extend((0..4800).map(|_| unit.get_stereo().0));
+            levels
+        };
+
+        // No envelope params: the gate switches the voice
+        let levels = render(&HashMap::new());
+        assert_eq!(levels[10], 1.0);
+        assert_eq!(levels[4810], 0.0);
+
+        // 50ms attack to 0.5 sustain, 50ms release
+        let params = HashMap::from([
+            ("attack".to_string(), 0.05),
+            ("decay".to_string(), 0.01),
+            ("sustain".to_string(), 0.5),
+            ("release".to_string(), 0.05),
+        ]);
+        let levels = render(&params);
+        assert!(levels[1200] > 0.3 && levels[1200] < 0.7, "{}", levels[1200]);
+        assert!((levels[4799] - 0.5).abs() < 0.01);
+        assert!(levels[4800 + 1200] > 0.05, "release should ring out");
+        assert!(levels[9599] < 0.01);
+    }
+
     #[test]
     fn test_envelope_total_time() {
         let adsr = ADSR::new(0.1, 0.2, 0.7, 0.3);
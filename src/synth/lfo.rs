@@ -4,6 +4,7 @@
 //! filter cutoff, etc. at sub-audio rates (typically 0.1 - 20 Hz).
 
 use fundsp::hacker32::*;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -20,8 +21,69 @@ pub enum LFOWaveform {
     Sawtooth,
     /// Square wave (on/off modulation)
     Square,
-    /// Random sample & hold
+    /// Random sample & hold: a new random value held for each cycle (stepped)
     Random,
+    /// Random values joined by smooth glides, one per cycle
+    SmoothRandom,
+}
+
+/// Note length for tempo-synced LFOs
+///
+/// Dotted values are 1.5 times, triplets 2/3 of the straight length.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteDivision {
+    Whole,
+    DottedWhole,
+    WholeTriplet,
+    Half,
+    DottedHalf,
+    HalfTriplet,
+    Quarter,
+    DottedQuarter,
+    QuarterTriplet,
+    Eighth,
+    DottedEighth,
+    EighthTriplet,
+    Sixteenth,
+    DottedSixteenth,
+    SixteenthTriplet,
+    ThirtySecond,
+    DottedThirtySecond,
+    ThirtySecondTriplet,
+}
+
+impl NoteDivision {
+    /// Length in beats (quarter notes)
+    pub fn beats(&self) -> f32 {
+        use NoteDivision::*;
+        let (straight, factor) = match self {
+            Whole => (4.0, 1.0),
+            DottedWhole => (4.0, 1.5),
+            WholeTriplet => (4.0, 2.0 / 3.0),
+            Half => (2.0, 1.0),
+            DottedHalf => (2.0, 1.5),
+            HalfTriplet => (2.0, 2.0 / 3.0),
+            Quarter => (1.0, 1.0),
+            DottedQuarter => (1.0, 1.5),
+            QuarterTriplet => (1.0, 2.0 / 3.0),
+            Eighth => (0.5, 1.0),
+            DottedEighth => (0.5, 1.5),
+            EighthTriplet => (0.5, 2.0 / 3.0),
+            Sixteenth => (0.25, 1.0),
+            DottedSixteenth => (0.25, 1.5),
+            SixteenthTriplet => (0.25, 2.0 / 3.0),
+            ThirtySecond => (0.125, 1.0),
+            DottedThirtySecond => (0.125, 1.5),
+            ThirtySecondTriplet => (0.125, 2.0 / 3.0),
+        };
+        straight * factor
+    }
+
+    /// Frequency in Hz of one cycle per note at the given tempo
+    pub fn hz(&self, bpm: f32) -> f32 {
+        bpm / 60.0 / self.beats()
+    }
 }
 
 /// LFO configuration
@@ -34,8 +96,24 @@ pub struct LFOConfig {
     pub depth: f32,
     /// Waveform shape
     pub waveform: LFOWaveform,
-    /// Initial phase offset (0.0 to 1.0)
+    /// Phase the LFO starts at (0.0 to 1.0)
     pub phase: f32,
+    /// Restart from `phase` on every note instead of running freely
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub retrigger: bool,
+    /// Cycle length when synced to tempo (overrides `rate`)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub sync: Option<NoteDivision>,
+    /// Seed for the random waveforms; generators with the same seed produce
+    /// the same values, unseeded ones each get their own sequence
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub seed: Option<u32>,
 }
 
 impl LFOConfig {
@@ -46,6 +124,45 @@ impl LFOConfig {
             depth: depth.clamp(0.0, 1.0),
             waveform,
             phase: 0.0,
+            retrigger: false,
+            sync: None,
+            seed: None,
+        }
+    }
+
+    /// Builder pattern: restart the LFO on every note
+    pub fn with_retrigger(mut self, retrigger: bool) -> Self {
+        self.retrigger = retrigger;
+        self
+    }
+
+    /// Builder pattern: set the phase the LFO starts at (0.0 to 1.0)
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase.rem_euclid(1.0);
+        self
+    }
+
+    /// Builder pattern: seed the random waveforms
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Create a sine LFO synced to tempo, one cycle per `division`
+    pub fn synced(division: NoteDivision) -> Self {
+        Self {
+            sync: Some(division),
+            ..Self::default()
+        }
+    }
+
+    /// Effective rate in Hz at the given tempo
+    ///
+    /// Synced LFOs follow `bpm`; free-running ones return `rate`.
+    pub fn rate_hz(&self, bpm: f32) -> f32 {
+        match self.sync {
+            Some(division) => division.hz(bpm),
+            None => self.rate,
         }
     }
 
@@ -81,6 +198,123 @@ impl Default for LFOConfig {
     }
 }
 
+/// Source of seeds for unseeded LFO generators
+static NEXT_SEED: AtomicU32 = AtomicU32::new(0x9E37_79B9);
+
+/// A running LFO producing values from an [`LFOConfig`]
+///
+/// Values are in -1.0 to 1.0; scaling by `depth` is left to the caller.
+/// The random waveforms draw a new value at the start of each cycle from a
+/// generator seeded by `config.seed`, so two LFOs with the same config move
+/// in lockstep.
+#[derive(Debug, Clone)]
+pub struct LFOGenerator {
+    /// LFO settings
+    config: LFOConfig,
+    /// Current phase (0.0 to 1.0)
+    phase: f32,
+    /// Seed the random generator restarts from
+    seed: u32,
+    /// Random generator state
+    rng: u32,
+    /// Random value at the start of the current cycle
+    from: f32,
+    /// Random value at the end of the current cycle
+    to: f32,
+}
+
+impl LFOGenerator {
+    /// Create a generator starting at the configured phase
+    pub fn new(config: LFOConfig) -> Self {
+        let seed = config
+            .seed
+            .unwrap_or_else(|| NEXT_SEED.fetch_add(0x6C07_8965, Ordering::Relaxed));
+        let mut generator = Self {
+            config,
+            phase: 0.0,
+            seed,
+            rng: seed,
+            from: 0.0,
+            to: 0.0,
+        };
+        generator.reset();
+        generator
+    }
+
+    /// LFO settings
+    pub fn config(&self) -> &LFOConfig {
+        &self.config
+    }
+
+    /// Current phase (0.0 to 1.0)
+    pub fn phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// Restart from the configured phase and the start of the random sequence
+    pub fn reset(&mut self) {
+        self.phase = self.config.phase.rem_euclid(1.0);
+        self.rng = self.seed;
+        self.from = self.next_random();
+        self.to = self.next_random();
+    }
+
+    /// Value (-1.0 to 1.0) at the current phase
+    pub fn value(&self) -> f32 {
+        let phase = self.phase;
+        match self.config.waveform {
+            LFOWaveform::Sine => (phase * std::f32::consts::TAU).sin(),
+            LFOWaveform::Triangle => {
+                if phase < 0.25 {
+                    4.0 * phase
+                } else if phase < 0.75 {
+                    2.0 - 4.0 * phase
+                } else {
+                    4.0 * phase - 4.0
+                }
+            }
+            LFOWaveform::Sawtooth => 2.0 * phase - 1.0,
+            LFOWaveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            LFOWaveform::Random => self.from,
+            LFOWaveform::SmoothRandom => {
+                // Cosine glide, so the slope is zero at each random point
+                let t = 0.5 - 0.5 * (phase * std::f32::consts::PI).cos();
+                self.from + (self.to - self.from) * t
+            }
+        }
+    }
+
+    /// Advance by `samples` samples at the rate for tempo `bpm`
+    pub fn advance(&mut self, samples: usize, sample_rate: f32, bpm: f32) {
+        let phase = self.phase + self.config.rate_hz(bpm) * samples as f32 / sample_rate;
+        for _ in 0..phase as usize {
+            self.from = self.to;
+            self.to = self.next_random();
+        }
+        self.phase = phase.fract();
+    }
+
+    /// Return the current value and advance by one sample
+    #[inline]
+    pub fn tick(&mut self, sample_rate: f32, bpm: f32) -> f32 {
+        let value = self.value();
+        self.advance(1, sample_rate, bpm);
+        value
+    }
+
+    /// Next value of the random sequence (-1.0 to 1.0)
+    fn next_random(&mut self) -> f32 {
+        self.rng = self.rng.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (self.rng >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    }
+}
+
 /// Create an LFO AudioNode from configuration
 /// Returns a mono signal that oscillates between -depth and +depth
 pub fn create_lfo_sine(rate: f32, depth: f32) -> An<impl AudioNode> {
@@ -125,6 +359,25 @@ pub enum LFOTarget {
     Pan,
 }
 
+impl LFOTarget {
+    /// Modulate `base` by an LFO value (-1.0 to 1.0) scaled by `depth`
+    ///
+    /// At full depth pitch swings a semitone either way, cutoff two octaves,
+    /// resonance and pulse width by half their range, and amplitude dips to
+    /// silence. Pan is returned unchanged as voices have no pan control.
+    pub fn modulate(&self, base: f32, lfo: f32, depth: f32) -> f32 {
+        let amount = lfo * depth;
+        match self {
+            LFOTarget::Pitch => base * 2.0_f32.powf(amount / 12.0),
+            LFOTarget::Amplitude => base * (1.0 - depth * 0.5 * (1.0 - lfo)),
+            LFOTarget::FilterCutoff => base * 2.0_f32.powf(2.0 * amount),
+            LFOTarget::FilterResonance => (base + 0.5 * amount).clamp(0.0, 1.0),
+            LFOTarget::PulseWidth => (base + 0.45 * amount).clamp(0.05, 0.95),
+            LFOTarget::Pan => base,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,4 +406,58 @@ mod tests {
         let random = LFOConfig::random_slow();
         assert_eq!(random.waveform, LFOWaveform::Random);
     }
+
+    #[test]
+    fn test_sample_and_hold_steps_once_per_cycle() {
+        // 10 Hz at 1 kHz: a new value every 100 samples
+        let config = LFOConfig::new(10.0, 1.0, LFOWaveform::Random).with_seed(7);
+        let mut lfo = LFOGenerator::new(config);
+        let values: Vec<f32> = (0..1000).map(|_| lfo.tick(1000.0, 120.0)).collect();
+
+        assert!(values.iter().all(|v| (-1.0..=1.0).contains(v)));
+        let changes: Vec<usize> = (1..values.len())
+            .filter(|&i| values[i] != values[i - 1])
+            .collect();
+        assert!(changes.len() >= 8, "{changes:?}");
+        for pair in changes.windows(2) {
+            assert!((pair[1] - pair[0]).abs_diff(100) <= 1, "{changes:?}");
+        }
+
+        // Same seed, same sequence; another seed differs
+        let mut twin = LFOGenerator::new(config);
+        let twin_values: Vec<f32> = (0..1000).map(|_| twin.tick(1000.0, 120.0)).collect();
+        assert_eq!(values, twin_values);
+        let mut other = LFOGenerator::new(config.with_seed(8));
+        let other_values: Vec<f32> = (0..1000).map(|_| other.tick(1000.0, 120.0)).collect();
+        assert_ne!(values, other_values);
+    }
+
+    #[test]
+    fn test_smooth_random_glides() {
+        let config = LFOConfig::new(10.0, 1.0, LFOWaveform::SmoothRandom).with_seed(3);
+        let mut lfo = LFOGenerator::new(config);
+        let values: Vec<f32> = (0..1000).map(|_| lfo.tick(1000.0, 120.0)).collect();
+
+        assert!(values.iter().all(|v| (-1.0..=1.0).contains(v)));
+        // At most a 2.0 swing over 100 samples: no jumps
+        for pair in values.windows(2) {
+            assert!((pair[1] - pair[0]).abs() < 0.05);
+        }
+        assert!(values.iter().any(|&v| v != values[0]));
+    }
+
+    #[test]
+    fn test_lfo_tempo_sync() {
+        let lfo = LFOConfig::synced(NoteDivision::Quarter);
+        assert_eq!(lfo.rate_hz(120.0), 2.0);
+        assert_eq!(lfo.rate_hz(90.0), 1.5);
+
+        assert_eq!(NoteDivision::Whole.hz(120.0), 0.5);
+        assert_eq!(NoteDivision::DottedEighth.beats(), 0.75);
+        assert!((NoteDivision::EighthTriplet.hz(120.0) - 6.0).abs() < 1e-5);
+        assert_eq!(NoteDivision::ThirtySecond.hz(120.0), 16.0);
+
+        // Free-running LFOs ignore the tempo
+        assert_eq!(LFOConfig::vibrato().rate_hz(120.0), 5.0);
+    }
 }
@@ -5,9 +5,12 @@
 //! - **Synth registry** - Register and manage synths by name
 //! - **30+ built-in synths** - Sine, saw, square, FM, organ, pad, strings, and more
 //! - **Real-time parameter control** - Lock-free parameter updates via `fundsp::shared::Shared`
-//! - **Voice controls** - Amplitude, pitch bend, cutoff, resonance, and pressure
+//! - **Voice controls** - Amplitude, pitch bend, cutoff, resonance, pressure, and gate
 //! - **Polyphony** - Easy voice management for chords
+//! - **Arpeggiator** - Tempo-synced arpeggios over a held chord
+//! - **Step sequencer** - Drum patterns played on the drum presets
 
+pub mod arpeggiator;
 pub mod builder;
 pub mod envelope;
 pub mod lfo;
@@ -15,17 +18,26 @@ pub mod poly;
 #[cfg(feature = "serde")]
 pub mod preset;
 pub mod registry;
+#[cfg(feature = "serde")]
+pub mod sequencer;
 pub mod synths;
 
+pub use arpeggiator::{ArpMode, Arpeggiator};
 pub use builder::{Synth, SynthBuilder as FluentSynthBuilder, SynthRegistryExt};
-pub use envelope::{EnvelopeConfig, ADSR, AHD, AR};
-pub use lfo::{LFOConfig, LFOTarget, LFOWaveform};
-pub use poly::{midi_to_freq, PolySynth, PolySynthBuilder, SynthRegistryPolyExt};
+pub use envelope::{apply_envelope, EnvelopeConfig, ADSR, AHD, AR};
+pub use lfo::{LFOConfig, LFOGenerator, LFOTarget, LFOWaveform, NoteDivision};
+pub use poly::{
+    chord, midi_to_freq, quantize_note, ChordKind, PolySynth, PolySynthBuilder, Scale,
+    SynthRegistryPolyExt,
+};
 #[cfg(feature = "serde")]
 pub use preset::{
-    drum_bank, midi_note_for_token, preset_for_token, DrumPresets, PresetBank, PresetBankDrumsExt, SynthPreset,
+    drum_bank, midi_note_for_token, preset_for_token, DrumPresets, PresetBank, PresetBankDrumsExt,
+    PresetSynthBuilder, SynthPreset,
 };
 pub use registry::{SynthBuilder, SynthMetadata, SynthRegistry, VoiceControls};
+#[cfg(feature = "serde")]
+pub use sequencer::{Step, StepSequencer};
 
 // Re-export UUID for synth instance tracking (only with serde feature)
 #[cfg(feature = "serde")]
@@ -23,7 +23,9 @@
 //! poly.note_off(60);
 //! ```
 
+use super::lfo::{LFOConfig, LFOGenerator, LFOTarget};
 use super::registry::{SynthRegistry, VoiceControls};
+use crate::render::RenderBuffer;
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 
@@ -32,16 +34,258 @@ pub fn midi_to_freq(note: u8) -> f32 {
     440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
 }
 
+/// Output level below which a releasing voice counts as silent (-80 dBFS)
+const RELEASE_SILENCE_THRESHOLD: f32 = 1e-4;
+
+/// Seconds a releasing voice must stay silent before it is free again
+const RELEASE_SILENCE_TIME: f64 = 0.05;
+
+/// Chord qualities understood by [`chord`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordKind {
+    Major,
+    Minor,
+    /// Dominant seventh
+    Seventh,
+    Major7,
+    Minor7,
+    Diminished,
+    Augmented,
+    Sus2,
+    Sus4,
+}
+
+impl ChordKind {
+    /// Semitones above the root of each chord tone, root included
+    pub fn intervals(&self) -> &'static [u8] {
+        match self {
+            ChordKind::Major => &[0, 4, 7],
+            ChordKind::Minor => &[0, 3, 7],
+            ChordKind::Seventh => &[0, 4, 7, 10],
+            ChordKind::Major7 => &[0, 4, 7, 11],
+            ChordKind::Minor7 => &[0, 3, 7, 10],
+            ChordKind::Diminished => &[0, 3, 6],
+            ChordKind::Augmented => &[0, 4, 8],
+            ChordKind::Sus2 => &[0, 2, 7],
+            ChordKind::Sus4 => &[0, 5, 7],
+        }
+    }
+}
+
+/// MIDI notes of a chord built on `root`, lowest first
+///
+/// Tones above note 127 are left out.
+pub fn chord(root: u8, kind: ChordKind) -> Vec<u8> {
+    kind.intervals()
+        .iter()
+        .map(|&interval| root as u16 + interval as u16)
+        .filter(|&note| note <= 127)
+        .map(|note| note as u8)
+        .collect()
+}
+
+/// Scales notes can be snapped to with [`quantize_note`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scale {
+    Major,
+    /// Natural minor
+    Minor,
+    /// Major pentatonic
+    Pentatonic,
+    Dorian,
+    /// Semitones above the root (0 to 11) of each degree
+    Custom(Vec<u8>),
+}
+
+impl Scale {
+    /// Semitones above the root of each scale degree
+    pub fn intervals(&self) -> &[u8] {
+        match self {
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::Pentatonic => &[0, 2, 4, 7, 9],
+            Scale::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Scale::Custom(intervals) => intervals,
+        }
+    }
+}
+
+/// Snap `note` to the nearest degree of `scale` in the key of `root`
+///
+/// `root` is any note of the key's tonic, e.g. 60 or 0 for C. A note
+/// halfway between two degrees goes down. Only degrees inside the MIDI
+/// range are considered, so notes near the top snap down rather than
+/// clamping to 127. A scale with no degrees leaves notes unchanged.
+pub fn quantize_note(note: u8, root: u8, scale: &Scale) -> u8 {
+    let offset = (note as i32 - root as i32).rem_euclid(12);
+    let shift = scale
+        .intervals()
+        .iter()
+        .flat_map(|&interval| {
+            let interval = interval as i32 % 12;
+            [interval - 12, interval, interval + 12]
+        })
+        .map(|degree| degree - offset)
+        .filter(|&shift| (0..=127).contains(&(note as i32 + shift)))
+        .min_by_key(|&shift| (shift.abs(), shift > 0))
+        .unwrap_or(0);
+    (note as i32 + shift) as u8
+}
+
 /// A single voice in the polyphonic synth
 struct Voice {
     /// The audio unit for this voice
     unit: Box<dyn AudioUnit>,
     /// Controls for this voice
     controls: VoiceControls,
-    /// The MIDI note this voice is playing (None if free)
+    /// The MIDI note held on this voice (None once released)
     note: Option<u8>,
+    /// Released but still ringing out; the voice is free once this clears
+    releasing: bool,
+    /// Consecutive silent samples rendered while releasing
+    silent_samples: usize,
     /// Voice age (for voice stealing - older voices get stolen first)
     age: u64,
+    /// The voice's LFO, when one is set on the synth
+    lfo: Option<VoiceLfo>,
+    /// Released while the sustain pedal was down; ends when it comes up
+    sustained: bool,
+}
+
+impl Voice {
+    /// Drop the gate and let the voice ring out until it falls silent
+    fn release(&mut self) {
+        self.controls.gate.set(0.0);
+        self.note = None;
+        self.sustained = false;
+        self.releasing = true;
+        self.silent_samples = 0;
+    }
+
+    /// Whether the voice is neither held nor ringing out
+    fn is_idle(&self) -> bool {
+        self.note.is_none() && !self.releasing
+    }
+}
+
+/// LFO applied to every voice of a [`PolySynth`]
+struct PolyLfo {
+    /// Voice control being modulated
+    target: LFOTarget,
+    /// Free-running LFO whose phase new voices pick up unless retriggering
+    clock: LFOGenerator,
+}
+
+impl PolyLfo {
+    /// Start the LFO of a voice that was just triggered
+    fn voice(&self, controls: &VoiceControls) -> VoiceLfo {
+        let generator = if self.clock.config().retrigger {
+            LFOGenerator::new(*self.clock.config())
+        } else {
+            self.clock.clone()
+        };
+        let shared = match self.target {
+            LFOTarget::Pitch => Some(controls.pitch_bend.clone()),
+            LFOTarget::Amplitude => Some(controls.amp.clone()),
+            LFOTarget::FilterCutoff => controls.cutoff.clone(),
+            LFOTarget::FilterResonance => controls.resonance.clone(),
+            LFOTarget::PulseWidth => controls.pulse_width.clone(),
+            LFOTarget::Pan => None,
+        };
+        let base = shared.as_ref().map_or(0.0, |shared| shared.value());
+        VoiceLfo {
+            generator,
+            shared,
+            base,
+            last: base,
+        }
+    }
+}
+
+/// A voice's LFO and the control it modulates
+struct VoiceLfo {
+    /// Waveform generator
+    generator: LFOGenerator,
+    /// Modulated control, if the synth has it
+    shared: Option<Shared>,
+    /// Unmodulated value of the control
+    base: f32,
+    /// Last value written, used to detect external writes
+    last: f32,
+}
+
+impl VoiceLfo {
+    /// Write the modulated control value and advance by one sample
+    #[inline]
+    fn tick(&mut self, target: LFOTarget, sample_rate: f32, bpm: f32, amount: f32) {
+        let lfo = self.generator.tick(sample_rate, bpm);
+        if let Some(shared) = &self.shared {
+            // Someone else wrote the control (pitch bend, cutoff, ...)
+            let value = shared.value();
+            if value != self.last {
+                self.base = value;
+            }
+            let depth = self.generator.config().depth * amount;
+            let value = target.modulate(self.base, lfo, depth);
+            shared.set_value(value);
+            self.last = value;
+        }
+    }
+
+    /// Stop modulating, leaving the control at its unmodulated value
+    fn detach(self) {
+        if let Some(shared) = self.shared {
+            if shared.value() == self.last {
+                shared.set_value(self.base);
+            }
+        }
+    }
+}
+
+/// Peak limiter guarding the summed PolySynth output
+///
+/// Gain drops instantly when a sample would exceed the ceiling and recovers
+/// exponentially with the release time, so the output never crosses the ceiling.
+#[derive(Debug, Clone)]
+struct SafetyLimiter {
+    /// Output ceiling (linear amplitude)
+    ceiling: f32,
+    /// Current gain applied to the output
+    gain: f32,
+    /// Release time in seconds
+    release: f32,
+    /// Per-sample release coefficient
+    release_coeff: f32,
+}
+
+impl SafetyLimiter {
+    fn new(ceiling_db: f32, release: f32, sample_rate: f64) -> Self {
+        let mut limiter = Self {
+            ceiling: 10.0_f32.powf(ceiling_db / 20.0),
+            gain: 1.0,
+            release,
+            release_coeff: 0.0,
+        };
+        limiter.set_sample_rate(sample_rate);
+        limiter
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.release_coeff = (-1.0 / (self.release * sample_rate as f32)).exp();
+    }
+
+    #[inline]
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        // Recover towards unity gain
+        self.gain = 1.0 + self.release_coeff * (self.gain - 1.0);
+
+        let peak = left.abs().max(right.abs());
+        if peak * self.gain > self.ceiling {
+            self.gain = self.ceiling / peak;
+        }
+
+        (left * self.gain, right * self.gain)
+    }
 }
 
 /// Polyphonic synthesizer that manages multiple voices
@@ -60,6 +304,22 @@ pub struct PolySynth {
     age_counter: u64,
     /// Sample rate
     sample_rate: f64,
+    /// Optional output limiter (off by default)
+    limiter: Option<SafetyLimiter>,
+    /// LFO applied to each voice, if any
+    lfo: Option<PolyLfo>,
+    /// Tempo in BPM for tempo-synced LFOs
+    bpm: f32,
+    /// Semitones covered by a full-scale MIDI pitch bend
+    pitch_bend_range: f32,
+    /// Scale applied to the LFO depth (mod wheel)
+    lfo_amount: f32,
+    /// Output gain
+    volume: f32,
+    /// Whether the sustain pedal is down
+    sustain_pedal: bool,
+    /// Root and scale incoming notes are snapped to
+    quantize: Option<(u8, Scale)>,
 }
 
 impl PolySynth {
@@ -98,6 +358,14 @@ impl PolySynth {
             max_voices,
             age_counter: 0,
             sample_rate: 44100.0,
+            limiter: None,
+            lfo: None,
+            bpm: 120.0,
+            pitch_bend_range: 2.0,
+            lfo_amount: 1.0,
+            volume: 1.0,
+            sustain_pedal: false,
+            quantize: None,
         }
     }
 
@@ -113,12 +381,146 @@ impl PolySynth {
         for voice in &mut self.voices {
             voice.unit.set_sample_rate(sample_rate);
         }
+        if let Some(limiter) = &mut self.limiter {
+            limiter.set_sample_rate(sample_rate);
+        }
+    }
+
+    /// Get the sample rate
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Enable a safety limiter on the summed output
+    ///
+    /// The limiter has instant attack and a 100 ms release, so the output
+    /// never exceeds `ceiling_db` (dBFS) no matter how many voices are playing.
+    pub fn enable_safety_limiter(&mut self, ceiling_db: f32) -> &mut Self {
+        self.limiter = Some(SafetyLimiter::new(ceiling_db, 0.1, self.sample_rate));
+        self
+    }
+
+    /// Disable the safety limiter
+    pub fn disable_safety_limiter(&mut self) -> &mut Self {
+        self.limiter = None;
+        self
+    }
+
+    /// Check if the safety limiter is enabled
+    pub fn is_safety_limiter_enabled(&self) -> bool {
+        self.limiter.is_some()
+    }
+
+    /// Modulate a control of every voice with an LFO
+    ///
+    /// Each voice runs its own copy of the LFO. With `config.retrigger` set
+    /// it restarts at `config.phase` on every note-on; otherwise a new
+    /// voice picks up the phase of a free-running LFO, so all voices move
+    /// together. The LFO applies to notes started after this call and
+    /// replaces any previous one.
+    pub fn set_lfo(&mut self, config: LFOConfig, target: LFOTarget) -> &mut Self {
+        self.lfo = Some(PolyLfo {
+            target,
+            clock: LFOGenerator::new(config),
+        });
+        self
+    }
+
+    /// Remove the LFO, returning voice controls to their unmodulated values
+    pub fn clear_lfo(&mut self) -> &mut Self {
+        self.lfo = None;
+        for voice in &mut self.voices {
+            if let Some(lfo) = voice.lfo.take() {
+                lfo.detach();
+            }
+        }
+        self
+    }
+
+    /// Scale the LFO depth (0.0 to 1.0, default 1.0), as a mod wheel does
+    pub fn set_lfo_amount(&mut self, amount: f32) -> &mut Self {
+        self.lfo_amount = amount.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Get the LFO depth scale
+    pub fn lfo_amount(&self) -> f32 {
+        self.lfo_amount
+    }
+
+    /// Set the output gain (default 1.0)
+    pub fn set_volume(&mut self, volume: f32) -> &mut Self {
+        self.volume = volume.max(0.0);
+        self
+    }
+
+    /// Get the output gain
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Press or release the sustain pedal
+    ///
+    /// While the pedal is down, released notes keep sounding and stay active;
+    /// releasing the pedal ends them.
+    pub fn set_sustain_pedal(&mut self, on: bool) {
+        self.sustain_pedal = on;
+        if !on {
+            for voice in &mut self.voices {
+                if voice.sustained {
+                    voice.release();
+                }
+            }
+        }
+    }
+
+    /// Check whether the sustain pedal is down
+    pub fn is_sustain_pedal_down(&self) -> bool {
+        self.sustain_pedal
+    }
+
+    /// Snap incoming notes to a scale, or stop snapping with `None`
+    ///
+    /// Applies to every method taking a note, so a note is released with
+    /// the same number it was played with. See [`quantize_note`].
+    pub fn set_quantize(&mut self, quantize: Option<(u8, Scale)>) -> &mut Self {
+        self.quantize = quantize;
+        self
+    }
+
+    /// Root and scale incoming notes are snapped to, if any
+    pub fn quantize(&self) -> Option<&(u8, Scale)> {
+        self.quantize.as_ref()
+    }
+
+    /// `note` snapped to the scale, if one is set
+    fn quantized(&self, note: u8) -> u8 {
+        match &self.quantize {
+            Some((root, scale)) => quantize_note(note, *root, scale),
+            None => note,
+        }
+    }
+
+    /// Set the tempo used by tempo-synced LFOs (default 120 BPM)
+    pub fn set_bpm(&mut self, bpm: f32) -> &mut Self {
+        self.bpm = bpm.max(1.0);
+        self
+    }
+
+    /// Current LFO phase (0.0 to 1.0) of a voice, if it has an LFO
+    pub fn voice_lfo_phase(&self, voice: usize) -> Option<f32> {
+        self.voices
+            .get(voice)?
+            .lfo
+            .as_ref()
+            .map(|lfo| lfo.generator.phase())
     }
 
     /// Trigger a note on
     ///
     /// Returns the voice index that was used, or None if failed
     pub fn note_on(&mut self, note: u8, velocity: f32) -> Option<usize> {
+        let note = self.quantized(note);
         let freq = midi_to_freq(note);
 
         // First, check if this note is already playing (retrigger)
@@ -127,15 +529,22 @@ impl PolySynth {
                 // Retrigger: reset the voice
                 voice.controls.amp.set(velocity);
                 voice.controls.pitch_bend.set(1.0);
+                voice.controls.gate.set(1.0);
+                voice.sustained = false;
+                if let Some(lfo) = &mut voice.lfo {
+                    if lfo.generator.config().retrigger {
+                        lfo.generator.reset();
+                    }
+                }
                 voice.age = self.age_counter;
                 self.age_counter += 1;
                 return Some(i);
             }
         }
 
-        // Try to find a free voice
+        // Try to find an idle voice, leaving releasing ones to ring out
         for (i, voice) in self.voices.iter_mut().enumerate() {
-            if voice.note.is_none() {
+            if voice.is_idle() {
                 // Reuse this voice with new frequency
                 // We need to create a new unit since fundsp synths have fixed frequency
                 if let Ok((unit, controls)) =
@@ -144,7 +553,11 @@ impl PolySynth {
                     voice.unit = unit;
                     voice.controls = controls;
                     voice.controls.amp.set(velocity);
+                    voice.lfo = self.lfo.as_ref().map(|lfo| lfo.voice(&voice.controls));
                     voice.note = Some(note);
+                    voice.releasing = false;
+                    voice.silent_samples = 0;
+                    voice.sustained = false;
                     voice.age = self.age_counter;
                     self.age_counter += 1;
                     voice.unit.set_sample_rate(self.sample_rate);
@@ -154,20 +567,25 @@ impl PolySynth {
             }
         }
 
-        // No free voice - either allocate a new one or steal the oldest
+        // No idle voice - either allocate a new one or steal the oldest,
+        // cutting a releasing voice short before a held one
         if self.voices.len() < self.max_voices {
             // Allocate new voice
             if let Ok((mut unit, controls)) =
                 self.registry.create(&self.synth_name, freq, &self.params)
             {
                 unit.set_sample_rate(self.sample_rate);
+                controls.amp.set(velocity);
                 let voice = Voice {
+                    lfo: self.lfo.as_ref().map(|lfo| lfo.voice(&controls)),
                     unit,
                     controls,
                     note: Some(note),
+                    releasing: false,
+                    silent_samples: 0,
                     age: self.age_counter,
+                    sustained: false,
                 };
-                voice.controls.amp.set(velocity);
                 self.age_counter += 1;
                 self.voices.push(voice);
                 return Some(self.voices.len() - 1);
@@ -178,20 +596,24 @@ impl PolySynth {
                 .voices
                 .iter()
                 .enumerate()
-                .min_by_key(|(_, v)| v.age)
+                .min_by_key(|(_, v)| (!v.releasing, v.age))
                 .map(|(i, _)| i)?;
 
             if let Ok((mut unit, controls)) =
                 self.registry.create(&self.synth_name, freq, &self.params)
             {
                 unit.set_sample_rate(self.sample_rate);
+                controls.amp.set(velocity);
                 self.voices[oldest_idx] = Voice {
+                    lfo: self.lfo.as_ref().map(|lfo| lfo.voice(&controls)),
                     unit,
                     controls,
                     note: Some(note),
+                    releasing: false,
+                    silent_samples: 0,
                     age: self.age_counter,
+                    sustained: false,
                 };
-                self.voices[oldest_idx].controls.amp.set(velocity);
                 self.age_counter += 1;
                 return Some(oldest_idx);
             }
@@ -200,36 +622,126 @@ impl PolySynth {
         None
     }
 
+    /// Play a chord built on `root`
+    ///
+    /// Returns the notes started, to pass to [`stop_chord`](Self::stop_chord).
+    pub fn play_chord(&mut self, root: u8, kind: ChordKind, velocity: f32) -> Vec<u8> {
+        let notes = chord(root, kind);
+        for &note in &notes {
+            self.note_on(note, velocity);
+        }
+        notes
+    }
+
+    /// Release the notes of a chord started with [`play_chord`](Self::play_chord)
+    pub fn stop_chord(&mut self, notes: &[u8]) {
+        for &note in notes {
+            self.note_off(note);
+        }
+    }
+
     /// Release a note
+    ///
+    /// Drops the voice's gate, so synths built with envelope params ring out
+    /// through their release stage; others fall silent immediately. The
+    /// voice is only reused by a new note once its output has died away. While
+    /// the sustain pedal is down the note keeps sounding until it comes up.
     pub fn note_off(&mut self, note: u8) {
+        let note = self.quantized(note);
         for voice in &mut self.voices {
             if voice.note == Some(note) {
-                // For now, just silence the voice
-                // A proper implementation would trigger release envelope
-                voice.controls.amp.set(0.0);
-                voice.note = None;
+                if self.sustain_pedal {
+                    voice.sustained = true;
+                } else {
+                    voice.release();
+                }
             }
         }
     }
 
-    /// Release all notes
+    /// Release all notes, including sustained ones
     pub fn all_notes_off(&mut self) {
         for voice in &mut self.voices {
-            voice.controls.amp.set(0.0);
-            voice.note = None;
+            if voice.note.is_some() {
+                voice.release();
+            }
         }
     }
 
-    /// Set pitch bend for all active voices (in semitones)
+    /// Set pitch bend for all sounding voices (in semitones)
     pub fn pitch_bend(&mut self, semitones: f32) {
         let bend = 2.0_f32.powf(semitones / 12.0);
         for voice in &mut self.voices {
-            if voice.note.is_some() {
+            if !voice.is_idle() {
                 voice.controls.pitch_bend.set(bend);
             }
         }
     }
 
+    /// Set pitch bend for all active voices from a 14-bit MIDI bend value
+    ///
+    /// `value` runs from -8192 (full bend down) through 0 (centre) to 8191
+    /// (full bend up) and is scaled to the pitch bend range.
+    pub fn pitch_bend_raw(&mut self, value: i16) {
+        let value = value.clamp(-8192, 8191) as f32;
+        let amount = if value < 0.0 {
+            value / 8192.0
+        } else {
+            value / 8191.0
+        };
+        self.pitch_bend(amount * self.pitch_bend_range);
+    }
+
+    /// Set the range of [`pitch_bend_raw`](Self::pitch_bend_raw) in semitones (default 2)
+    pub fn set_pitch_bend_range(&mut self, semitones: f32) -> &mut Self {
+        self.pitch_bend_range = semitones.abs();
+        self
+    }
+
+    /// Get the pitch bend range in semitones
+    pub fn pitch_bend_range(&self) -> f32 {
+        self.pitch_bend_range
+    }
+
+    /// Set pitch bend for a single playing note (in semitones), for MPE input
+    ///
+    /// Other voices are left untouched. Does nothing if `note` is not playing.
+    pub fn note_pitch_bend(&mut self, note: u8, semitones: f32) {
+        if let Some(voice) = self.playing_voice(note) {
+            voice
+                .controls
+                .pitch_bend
+                .set(2.0_f32.powf(semitones / 12.0));
+        }
+    }
+
+    /// Set pressure (aftertouch, 0.0 to 1.0) for a single playing note
+    pub fn note_pressure(&mut self, note: u8, value: f32) {
+        if let Some(voice) = self.playing_voice(note) {
+            voice.controls.pressure.set(value.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Set slide (MPE's third dimension, CC74) for a single playing note
+    ///
+    /// `value` (0.0 to 1.0) sweeps the voice's filter cutoff exponentially
+    /// from 20 Hz to 20 kHz. Voices without a filter ignore it.
+    pub fn note_slide(&mut self, note: u8, value: f32) {
+        if let Some(voice) = self.playing_voice(note) {
+            if let Some(cutoff) = &voice.controls.cutoff {
+                cutoff.set(20.0 * 1000.0_f32.powf(value.clamp(0.0, 1.0)));
+            }
+        }
+    }
+
+    /// The voice playing `note`, if any
+    fn playing_voice(&mut self, note: u8) -> Option<&mut Voice> {
+        let note = self.quantized(note);
+        self.voices
+            .iter_mut()
+            .find(|voice| voice.note == Some(note))
+    }
+
     /// Set cutoff for all active voices (if applicable)
     pub fn set_cutoff(&mut self, cutoff: f32) {
         for voice in &mut self.voices {
@@ -252,11 +764,35 @@ impl PolySynth {
     pub fn get_stereo(&mut self) -> (f32, f32) {
         let mut left = 0.0;
         let mut right = 0.0;
+        let sample_rate = self.sample_rate as f32;
+
+        if let Some(lfo) = &mut self.lfo {
+            lfo.clock.advance(1, sample_rate, self.bpm);
+        }
+        let target = self.lfo.as_ref().map(|lfo| lfo.target);
+        let silence_samples = (RELEASE_SILENCE_TIME * self.sample_rate) as usize;
 
         for voice in &mut self.voices {
+            if voice.is_idle() {
+                continue;
+            }
+            if let (Some(lfo), Some(target)) = (&mut voice.lfo, target) {
+                lfo.tick(target, sample_rate, self.bpm, self.lfo_amount);
+            }
             let (l, r) = voice.unit.get_stereo();
             left += l;
             right += r;
+
+            if voice.releasing {
+                if l.abs().max(r.abs()) <= RELEASE_SILENCE_THRESHOLD {
+                    voice.silent_samples += 1;
+                    if voice.silent_samples >= silence_samples {
+                        voice.releasing = false;
+                    }
+                } else {
+                    voice.silent_samples = 0;
+                }
+            }
         }
 
         // Simple limiting to prevent clipping
@@ -264,16 +800,53 @@ impl PolySynth {
             1.0 / (self.voices.len() as f32).sqrt()
         } else {
             1.0
-        };
+        } * self.volume;
 
-        (left * scale, right * scale)
+        match &mut self.limiter {
+            Some(limiter) => limiter.process(left * scale, right * scale),
+            None => (left * scale, right * scale),
+        }
+    }
+
+    /// Render `frames` frames offline into planar (left, right) buffers
+    ///
+    /// Equivalent to calling [`get_stereo`](Self::get_stereo) `frames` times
+    /// at the current sample rate.
+    pub fn render(&mut self, frames: usize) -> (Vec<f32>, Vec<f32>) {
+        let mut left = Vec::with_capacity(frames);
+        let mut right = Vec::with_capacity(frames);
+        for _ in 0..frames {
+            let (l, r) = self.get_stereo();
+            left.push(l);
+            right.push(r);
+        }
+        (left, right)
+    }
+
+    /// Render `frames` frames at `sample_rate`
+    ///
+    /// Switches the synth to `sample_rate` first if it differs.
+    pub fn render_to_buffer(&mut self, frames: usize, sample_rate: f64) -> RenderBuffer {
+        if self.sample_rate != sample_rate {
+            self.set_sample_rate(sample_rate);
+        }
+        let (left, right) = self.render(frames);
+        RenderBuffer::new(left, right, sample_rate)
     }
 
     /// Get the number of currently active voices
+    ///
+    /// Counts held notes only; see [`sounding_voices`](Self::sounding_voices)
+    /// to include released notes that are still ringing out.
     pub fn active_voices(&self) -> usize {
         self.voices.iter().filter(|v| v.note.is_some()).count()
     }
 
+    /// Get the number of voices producing sound, held or releasing
+    pub fn sounding_voices(&self) -> usize {
+        self.voices.iter().filter(|v| !v.is_idle()).count()
+    }
+
     /// Get the total number of allocated voices
     pub fn allocated_voices(&self) -> usize {
         self.voices.len()
@@ -407,7 +980,7 @@ impl<'a> PolySynthBuilder<'a> {
         let registry = self.registry.unwrap_or_else(SynthRegistry::with_builtin);
         let mut poly = PolySynth::with_registry(self.synth_name, self.max_voices, registry);
         poly.params = self.params;
-        poly.sample_rate = self.sample_rate;
+        poly.set_sample_rate(self.sample_rate);
         poly
     }
 }
@@ -431,6 +1004,7 @@ impl SynthRegistryPolyExt for SynthRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::synth::lfo::LFOWaveform;
 
     #[test]
     fn test_midi_to_freq() {
@@ -440,6 +1014,66 @@ mod tests {
         assert!((midi_to_freq(60) - 261.63).abs() < 0.1);
     }
 
+    #[test]
+    fn test_chord() {
+        assert_eq!(chord(60, ChordKind::Major), vec![60, 64, 67]);
+        assert_eq!(chord(57, ChordKind::Minor7), vec![57, 60, 64, 67]);
+        assert_eq!(chord(124, ChordKind::Major), vec![124]);
+
+        let mut poly = PolySynth::new("sine", 8);
+        let notes = poly.play_chord(62, ChordKind::Sus4, 0.8);
+        assert_eq!(notes, vec![62, 67, 69]);
+        assert_eq!(poly.active_voices(), 3);
+        poly.stop_chord(&notes);
+        assert_eq!(poly.active_voices(), 0);
+    }
+
+    #[test]
+    fn test_quantize_note() {
+        const WHITE_KEYS: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+        for note in 60..72 {
+            let quantized = quantize_note(note, 60, &Scale::Major);
+            assert!(WHITE_KEYS.contains(&(quantized % 12)));
+            assert!(quantized.abs_diff(note) <= 1);
+        }
+        assert_eq!(quantize_note(61, 0, &Scale::Major), 60);
+        assert_eq!(quantize_note(66, 0, &Scale::Pentatonic), 67);
+        assert_eq!(quantize_note(71, 0, &Scale::Custom(vec![0])), 72);
+        assert_eq!(quantize_note(127, 0, &Scale::Custom(vec![0])), 120);
+
+        let mut poly = PolySynth::new("sine", 4);
+        poly.set_quantize(Some((57, Scale::Minor)));
+        poly.note_on(61, 0.8);
+        assert_eq!(poly.playing_notes(), vec![60]);
+        poly.note_off(61);
+        assert_eq!(poly.active_voices(), 0);
+    }
+
+    #[test]
+    fn test_render_matches_get_stereo() {
+        let play = || {
+            let mut poly = PolySynth::new("sine", 4);
+            poly.note_on(60, 0.8);
+            poly.note_on(67, 0.8);
+            poly
+        };
+
+        let mut reference = play();
+        let expected: Vec<(f32, f32)> = (0..300).map(|_| reference.get_stereo()).collect();
+
+        let mut poly = play();
+        let (left, right) = poly.render(300);
+        assert_eq!(left.len(), 300);
+        let rendered: Vec<(f32, f32)> = left.into_iter().zip(right).collect();
+        assert_eq!(rendered, expected);
+
+        let buffer = poly.render_to_buffer(441, 44100.0);
+        assert_eq!(buffer.len(), 441);
+        assert_eq!(buffer.sample_rate, 44100.0);
+        assert!((buffer.duration() - 0.01).abs() < 1e-9);
+        assert_eq!(buffer.interleaved().len(), 882);
+    }
+
     #[test]
     fn test_poly_synth_basic() {
         let mut poly = PolySynth::new("sine", 4);
@@ -460,4 +1094,212 @@ mod tests {
         poly.all_notes_off();
         assert_eq!(poly.active_voices(), 0);
     }
+
+    #[test]
+    fn test_note_off_rings_out_release() {
+        let render_after_release = |release: Option<f32>| {
+            let mut poly = PolySynth::new("sine", 1);
+            poly.set_sample_rate(48000.0);
+            if let Some(release) = release {
+                poly.set_param("release", release);
+            }
+            poly.note_on(69, 1.0);
+            for _ in 0..4800 {
+                poly.get_stereo();
+            }
+            poly.note_off(69);
+            (0..4800)
+                .map(|_| poly.get_stereo().0.abs())
+                .skip(2400)
+                .fold(0.0f32, f32::max)
+        };
+
+        // Without envelope params the voice stops at note-off
+        assert!(render_after_release(None) < 1e-6);
+        // A 200ms release is still sounding 50ms after note-off
+        assert!(render_after_release(Some(0.2)) > 0.3);
+    }
+
+    #[test]
+    fn test_new_note_keeps_release_tail() {
+        let mut poly = PolySynth::new("sine", 2);
+        poly.set_sample_rate(48000.0);
+        poly.set_param("release", 0.3);
+
+        let held = poly.note_on(60, 0.8).unwrap();
+        poly.note_on(64, 0.8);
+        poly.note_off(64);
+        // Long enough for the 64 voice to ring out and fall silent
+        poly.render(24000);
+        assert_eq!(poly.sounding_voices(), 1);
+
+        // The next note takes the idle voice, not the one still releasing
+        poly.note_off(60);
+        assert_eq!(poly.active_voices(), 0);
+        assert_eq!(poly.sounding_voices(), 1);
+        let next = poly.note_on(67, 0.8).unwrap();
+        assert_ne!(next, held);
+        poly.render(2400);
+        assert_eq!(poly.sounding_voices(), 2);
+
+        // With every voice busy, a releasing voice is stolen before a held one
+        poly.note_on(72, 0.8);
+        assert_eq!(poly.playing_notes().len(), 2);
+        assert!(poly.playing_notes().contains(&67));
+
+        poly.all_notes_off();
+        poly.render(24000);
+        assert_eq!(poly.sounding_voices(), 0);
+    }
+
+    #[test]
+    fn test_safety_limiter_holds_ceiling() {
+        let render_peak = |limit: bool| {
+            let mut poly = PolySynth::new("sine", 4);
+            poly.set_sample_rate(48000.0);
+            if limit {
+                poly.enable_safety_limiter(-1.0);
+            }
+            for note in [48, 55, 60, 64] {
+                poly.note_on(note, 1.0);
+            }
+            let mut peak = 0.0f32;
+            for _ in 0..48000 {
+                let (l, r) = poly.get_stereo();
+                peak = peak.max(l.abs()).max(r.abs());
+            }
+            peak
+        };
+
+        // Without the limiter the chord clips
+        assert!(render_peak(false) > 1.0);
+
+        let ceiling = 10.0_f32.powf(-1.0 / 20.0);
+        let peak = render_peak(true);
+        assert!(
+            peak <= ceiling + 1e-6,
+            "peak {peak} exceeds ceiling {ceiling}"
+        );
+        assert!(peak > ceiling * 0.5);
+    }
+
+    #[test]
+    fn test_pitch_bend_raw_uses_range() {
+        let mut poly = PolySynth::new("sine", 1);
+        poly.note_on(69, 0.8);
+        let bend = poly.voices[0].controls.pitch_bend.clone();
+        let whole_tone = 2.0_f32.powf(2.0 / 12.0);
+
+        // Default range is two semitones
+        poly.pitch_bend_raw(8191);
+        assert!((bend.value() - whole_tone).abs() < 1e-6);
+        poly.pitch_bend_raw(-8192);
+        assert!((bend.value() - 1.0 / whole_tone).abs() < 1e-6);
+        poly.pitch_bend_raw(0);
+        assert_eq!(bend.value(), 1.0);
+
+        // An octave range halves the frequency at full bend down
+        poly.set_pitch_bend_range(12.0);
+        poly.pitch_bend_raw(-8192);
+        assert!((bend.value() - 0.5).abs() < 1e-6);
+        poly.pitch_bend_raw(4096);
+        assert!((bend.value() - 2.0_f32.powf(6.0 / 12.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_per_note_expression() {
+        let mut poly = PolySynth::new("tb303", 3);
+        for note in [60, 64, 67] {
+            poly.note_on(note, 0.8);
+        }
+        let controls = |poly: &PolySynth, note: u8| {
+            let voice = poly.voices.iter().find(|v| v.note == Some(note)).unwrap();
+            let cutoff = voice.controls.cutoff.as_ref().unwrap().value();
+            (
+                voice.controls.pitch_bend.value(),
+                voice.controls.pressure.value(),
+                cutoff,
+            )
+        };
+        let untouched = controls(&poly, 60);
+
+        poly.note_pitch_bend(64, 1.0);
+        poly.note_pressure(64, 0.7);
+        poly.note_slide(64, 0.5);
+
+        let (bend, pressure, cutoff) = controls(&poly, 64);
+        assert!((bend - 2.0_f32.powf(1.0 / 12.0)).abs() < 1e-6);
+        assert_eq!(pressure, 0.7);
+        assert!((cutoff - 20.0 * 1000.0_f32.sqrt()).abs() < 1e-2);
+
+        // The rest of the chord is unaffected
+        assert_eq!(controls(&poly, 60), untouched);
+        assert_eq!(controls(&poly, 67), untouched);
+
+        // Notes that aren't playing are ignored
+        poly.note_pitch_bend(72, 2.0);
+    }
+
+    #[test]
+    fn test_lfo_retrigger_aligns_note_phase() {
+        // Phase of each voice's LFO right as its note starts
+        let trigger_phases = |retrigger: bool| {
+            let mut poly = PolySynth::new("sine", 2);
+            poly.set_sample_rate(48000.0);
+            let config = LFOConfig::new(3.0, 0.5, LFOWaveform::Sine)
+                .with_retrigger(retrigger)
+                .with_phase(0.25);
+            poly.set_lfo(config, LFOTarget::Pitch);
+
+            let first = poly.note_on(60, 0.8).unwrap();
+            let first_phase = poly.voice_lfo_phase(first).unwrap();
+            // 100ms later
+            for _ in 0..4800 {
+                poly.get_stereo();
+            }
+            let second = poly.note_on(64, 0.8).unwrap();
+            let second_phase = poly.voice_lfo_phase(second).unwrap();
+            let first_now = poly.voice_lfo_phase(first).unwrap();
+            (first_phase, second_phase, first_now)
+        };
+
+        // Retriggered: every note starts at the phase offset
+        let (first, second, first_now) = trigger_phases(true);
+        assert!((first - 0.25).abs() < 1e-6);
+        assert!((second - 0.25).abs() < 1e-6);
+        assert!((first_now - 0.55).abs() < 1e-3);
+
+        // Free-running: the second note joins the LFO 0.3 cycles later,
+        // in step with the first
+        let (first, second, first_now) = trigger_phases(false);
+        assert!((first - 0.25).abs() < 1e-6);
+        assert!((second - 0.55).abs() < 1e-3);
+        assert!((second - first_now).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lfo_modulates_voice_pitch() {
+        let mut poly = PolySynth::new("sine", 1);
+        poly.set_sample_rate(48000.0);
+        poly.set_lfo(
+            LFOConfig::new(5.0, 1.0, LFOWaveform::Sine),
+            LFOTarget::Pitch,
+        );
+        poly.note_on(69, 0.8);
+        let bend = poly.voices[0].controls.pitch_bend.clone();
+
+        let (mut low, mut high) = (f32::MAX, f32::MIN);
+        for _ in 0..9600 {
+            poly.get_stereo();
+            low = low.min(bend.value());
+            high = high.max(bend.value());
+        }
+        // A semitone either way at full depth
+        let semitone = 2.0_f32.powf(1.0 / 12.0);
+        assert!((high - semitone).abs() < 1e-3, "{high}");
+        assert!((low - 1.0 / semitone).abs() < 1e-3, "{low}");
+
+        poly.clear_lfo();
+        assert_eq!(bend.value(), 1.0);
+    }
 }
@@ -22,7 +22,7 @@ impl DrumPresets {
 
     /// Snare drum - noise + tone
     pub fn snare() -> SynthPreset {
-        SynthPreset::new("snare", "noise")
+        SynthPreset::new("snare", "snare")
             .with_description("Snare with noise and tone")
             .with_tag("drum")
             .with_parameter("amp", 0.7)
@@ -11,10 +11,14 @@
 
 pub mod drums;
 
+use crate::params::{migrate_synth_params, FORMAT_VERSION};
 use crate::synth::envelope::EnvelopeConfig;
 use crate::synth::lfo::LFOConfig;
+use crate::synth::registry::{SynthBuilder, SynthMetadata, VoiceControls};
+use fundsp::hacker32::AudioUnit;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 pub use drums::{drum_bank, midi_note_for_token, preset_for_token, DrumPresets, PresetBankDrumsExt};
@@ -22,6 +26,9 @@ pub use drums::{drum_bank, midi_note_for_token, preset_for_token, DrumPresets, P
 /// A complete synthesizer preset
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SynthPreset {
+    /// Format version (see [`FORMAT_VERSION`]); 0 when absent
+    #[serde(default)]
+    pub version: u32,
     /// Unique ID for this preset
     pub id: Uuid,
     /// Preset name
@@ -46,6 +53,7 @@ impl SynthPreset {
     /// Create a new preset
     pub fn new(name: impl Into<String>, synth_type: impl Into<String>) -> Self {
         Self {
+            version: FORMAT_VERSION,
             id: Uuid::new_v4(),
             name: name.into(),
             synth_type: synth_type.into(),
@@ -111,9 +119,20 @@ impl SynthPreset {
         serde_json::to_string_pretty(self)
     }
 
-    /// Deserialize preset from JSON string
+    /// Deserialize preset from JSON string, upgrading older formats
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+        let mut preset: Self = serde_json::from_str(json)?;
+        preset.migrate();
+        Ok(preset)
+    }
+
+    /// Upgrade a preset saved by an older version to [`FORMAT_VERSION`]
+    ///
+    /// [`from_json`](Self::from_json) and [`load`](Self::load) do this
+    /// already. Current presets are left unchanged.
+    pub fn migrate(&mut self) {
+        migrate_synth_params(&self.synth_type, &mut self.parameters, self.version);
+        self.version = self.version.max(FORMAT_VERSION);
     }
 
     /// Save preset to file
@@ -136,6 +155,92 @@ impl SynthPreset {
     }
 }
 
+/// Synth builder that plays a [`SynthPreset`] through its underlying synth
+///
+/// The preset's parameters and envelope act as defaults; parameters passed at
+/// build time override them. Envelopes are passed on as `attack`, `hold`,
+/// `decay`, `sustain`, and `release` parameters.
+pub struct PresetSynthBuilder {
+    preset: SynthPreset,
+    base: Arc<dyn SynthBuilder>,
+}
+
+impl PresetSynthBuilder {
+    /// Create a builder for `preset` on top of the `synth_type` builder `base`
+    pub fn new(preset: SynthPreset, base: Arc<dyn SynthBuilder>) -> Self {
+        Self { preset, base }
+    }
+
+    /// The preset this builder plays
+    pub fn preset(&self) -> &SynthPreset {
+        &self.preset
+    }
+
+    /// Preset parameters with the envelope expanded into named parameters
+    fn preset_params(&self) -> HashMap<String, f32> {
+        let mut params = self.preset.parameters.clone();
+        let envelope: &[(&str, f32)] = match &self.preset.envelope {
+            Some(EnvelopeConfig::ADSR(adsr)) => &[
+                ("attack", adsr.attack),
+                ("decay", adsr.decay),
+                ("sustain", adsr.sustain),
+                ("release", adsr.release),
+            ],
+            Some(EnvelopeConfig::AHD(ahd)) => &[
+                ("attack", ahd.attack),
+                ("hold", ahd.hold),
+                ("decay", ahd.decay),
+                ("sustain", 0.0),
+            ],
+            Some(EnvelopeConfig::AR(ar)) => &[
+                ("attack", ar.attack),
+                ("sustain", 1.0),
+                ("release", ar.release),
+            ],
+            Some(EnvelopeConfig::None) | None => &[],
+        };
+        for (name, value) in envelope {
+            params.entry(name.to_string()).or_insert(*value);
+        }
+        params
+    }
+}
+
+impl SynthBuilder for PresetSynthBuilder {
+    fn build(
+        &self,
+        freq: f32,
+        params: &HashMap<String, f32>,
+    ) -> (Box<dyn AudioUnit>, VoiceControls) {
+        let mut merged = self.preset_params();
+        merged.extend(params.iter().map(|(k, v)| (k.clone(), *v)));
+        self.base.build(freq, &merged)
+    }
+
+    fn metadata(&self) -> SynthMetadata {
+        let base = self.base.metadata();
+        let description = self
+            .preset
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("{} preset", self.preset.synth_type));
+
+        let mut metadata = SynthMetadata::new(self.preset.name.clone(), description)
+            .with_tags(base.tags)
+            .with_tags(self.preset.tags.iter().cloned())
+            .with_tag("preset");
+        let preset_params = self.preset_params();
+        for param in base.parameters {
+            let default = preset_params
+                .get(&param.name)
+                .copied()
+                .unwrap_or(param.default);
+            metadata = metadata.with_param(param.name, default, param.min, param.max);
+        }
+        metadata
+    }
+}
+
 /// A collection of presets (preset bank)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresetBank {
@@ -190,9 +295,11 @@ impl PresetBank {
         serde_json::to_string_pretty(self)
     }
 
-    /// Deserialize bank from JSON
+    /// Deserialize bank from JSON, upgrading presets in older formats
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+        let mut bank: Self = serde_json::from_str(json)?;
+        bank.presets.iter_mut().for_each(SynthPreset::migrate);
+        Ok(bank)
     }
 
     /// Save bank to file
@@ -236,6 +343,32 @@ mod tests {
         assert_eq!(preset.tags.len(), 2);
     }
 
+    #[test]
+    fn test_unversioned_preset_upgrades() {
+        let legacy = r#"{
+            "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "name": "Old Acid",
+            "synth_type": "tb303",
+            "parameters": {"cutoff": 600.0, "res": 0.9},
+            "envelope": null,
+            "lfo": {"rate": 2.0, "depth": 0.5, "waveform": "Sine", "phase": 0.25},
+            "author": null,
+            "description": null,
+            "tags": []
+        }"#;
+        let preset = SynthPreset::from_json(legacy).unwrap();
+        assert_eq!(preset.version, FORMAT_VERSION);
+        assert_eq!(preset.parameters.get("res_raw"), Some(&0.9));
+        assert!(!preset.parameters.contains_key("res"));
+        assert_eq!(preset.lfo.unwrap().phase, 0.25);
+
+        let current = SynthPreset::new("New", "tb303").with_parameter("res", 0.4);
+        assert_eq!(
+            SynthPreset::from_json(&current.to_json().unwrap()).unwrap(),
+            current
+        );
+    }
+
     #[test]
     fn test_preset_serialization() {
         let preset = SynthPreset::new("Test", "sine").with_parameter("amp", 0.5);
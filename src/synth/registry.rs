@@ -4,11 +4,13 @@
 //! Each synth implements the SynthBuilder trait, allowing for easy registration
 //! and extensibility without modifying core backend code.
 
-use crate::params::ParameterDef;
+use crate::params::{check_ranges, ClampWarnings, ParameterDef};
 use crate::Result;
 use fundsp::hacker32::*;
+use fundsp::prelude::AttoHash;
 use fundsp::shared::Shared;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use super::synths::*;
@@ -16,6 +18,11 @@ use super::synths::*;
 /// Trait for building custom synths
 pub trait SynthBuilder: Send + Sync {
     /// Build the synth with given parameters
+    ///
+    /// The registry seeds the returned unit by calling
+    /// [`AudioUnit::ping`] with `probe` false. Custom units with their own
+    /// pseudorandom state should override `ping` and seed themselves from
+    /// the hash, so seeded builds stay reproducible.
     fn build(
         &self,
         freq: f32,
@@ -34,12 +41,19 @@ pub struct VoiceControls {
     pub amp: Shared,
     /// Filter cutoff frequency (Hz) - if applicable
     pub cutoff: Option<Shared>,
-    /// Filter resonance (0.0 to 1.0) - if applicable
+    /// Filter resonance (0.0 to 1.0, mapped by [`res_to_moog`](crate::params::res_to_moog);
+    /// raw ladder resonance if built with `res_raw`) - if applicable
     pub resonance: Option<Shared>,
+    /// Pulse width / duty cycle (0.0 to 1.0), or the table position of
+    /// wavetable synths - if applicable
+    pub pulse_width: Option<Shared>,
     /// Pitch bend multiplier (1.0 = no bend, 2.0 = up one octave, 0.5 = down one octave)
     pub pitch_bend: Shared,
     /// Aftertouch/pressure (0.0 to 1.0, normalized from MIDI 0-127)
     pub pressure: Shared,
+    /// Note gate (1.0 = held, 0.0 = released), see
+    /// [`apply_envelope`](crate::synth::envelope::apply_envelope)
+    pub gate: Shared,
 }
 
 /// Metadata about a synth
@@ -93,12 +107,30 @@ impl SynthMetadata {
     pub fn has_tag(&self, tag: &str) -> bool {
         self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
     }
+
+    /// Category of the synth: its first tag that isn't namespaced like
+    /// `source:builtin`
+    pub fn category(&self) -> Option<&str> {
+        self.tags
+            .iter()
+            .map(String::as_str)
+            .find(|t| !t.contains(':'))
+    }
 }
 
+/// Seed for the next synth built without an explicit one
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0x5EED);
+
 /// Registry for all available synths
 #[derive(Clone)]
 pub struct SynthRegistry {
     builders: HashMap<String, Arc<dyn SynthBuilder>>,
+    /// Parameter schema of each synth, captured at registration
+    parameters: HashMap<String, Vec<ParameterDef>>,
+    /// Reject out-of-range parameters instead of clamping them
+    strict_params: bool,
+    /// Parameters that already warned about being clamped
+    clamp_warnings: Arc<ClampWarnings>,
 }
 
 impl SynthRegistry {
@@ -106,9 +138,28 @@ impl SynthRegistry {
     pub fn new() -> Self {
         Self {
             builders: HashMap::new(),
+            parameters: HashMap::new(),
+            strict_params: false,
+            clamp_warnings: Arc::default(),
         }
     }
 
+    /// Reject out-of-range parameters when building synths
+    ///
+    /// By default a parameter outside its [`ParameterDef`] range is clamped,
+    /// with a warning the first time each parameter is clamped. In strict
+    /// mode building fails with
+    /// [`Error::ParameterOutOfRange`](crate::Error::ParameterOutOfRange)
+    /// instead.
+    pub fn set_strict_params(&mut self, strict: bool) {
+        self.strict_params = strict;
+    }
+
+    /// Whether out-of-range parameters are rejected
+    pub fn strict_params(&self) -> bool {
+        self.strict_params
+    }
+
     /// Create a new registry with all built-in synths registered
     pub fn with_builtin() -> Self {
         let mut registry = Self::new();
@@ -147,6 +198,7 @@ impl SynthRegistry {
 
         // FM synthesis
         self.register("fm", Arc::new(FMSynthBuilder));
+        self.register("fm2", Arc::new(FM2SynthBuilder));
 
         // Bells
         self.register("pretty_bell", Arc::new(PrettyBellSynthBuilder));
@@ -155,9 +207,11 @@ impl SynthRegistry {
         // Physical modeling
         self.register("piano", Arc::new(PianoSynthBuilder));
         self.register("pluck", Arc::new(PluckSynthBuilder));
+        self.register("string_ks", Arc::new(KarplusStrongSynthBuilder));
 
         // Keyboard instruments
         self.register("organ", Arc::new(OrganSynthBuilder));
+        self.register("drawbar_organ", Arc::new(DrawbarOrganSynthBuilder));
         self.register("hammond", Arc::new(OrganSynthBuilder)); // alias
         self.register("electric_piano", Arc::new(ElectricPianoSynthBuilder));
         self.register("rhodes", Arc::new(ElectricPianoSynthBuilder)); // alias
@@ -178,6 +232,10 @@ impl SynthRegistry {
 
         // Noise
         self.register("noise", Arc::new(NoiseSynthBuilder));
+        self.register("snare", Arc::new(SnareSynthBuilder));
+
+        // Wavetable (sine, triangle, saw and square tables)
+        self.register("wavetable", Arc::new(WavetableSynthBuilder::default()));
 
         // Bass synths
         self.register("bass_foundation", Arc::new(BassFoundationSynthBuilder));
@@ -199,21 +257,95 @@ impl SynthRegistry {
 
     /// Register a custom synth
     pub fn register(&mut self, name: &str, builder: Arc<dyn SynthBuilder>) {
+        self.parameters
+            .insert(name.to_string(), builder.metadata().parameters);
         self.builders.insert(name.to_string(), builder);
     }
 
+    /// Register every preset in a bank as a playable synth
+    ///
+    /// Each preset is registered under its name and builds its `synth_type`
+    /// with the preset's parameters and envelope applied. Presets whose
+    /// `synth_type` is not registered are skipped. Returns the number of
+    /// presets registered.
+    #[cfg(feature = "serde")]
+    pub fn register_presets(&mut self, bank: &super::preset::PresetBank) -> usize {
+        let mut registered = 0;
+        for preset in &bank.presets {
+            let Some(base) = self.builders.get(&preset.synth_type).cloned() else {
+                tracing::warn!(
+                    "Skipping preset '{}': unknown synth type '{}'",
+                    preset.name,
+                    preset.synth_type
+                );
+                continue;
+            };
+            self.register(
+                &preset.name,
+                Arc::new(super::preset::PresetSynthBuilder::new(preset.clone(), base)),
+            );
+            registered += 1;
+        }
+        registered
+    }
+
     /// Build a synth by name
+    ///
+    /// Pseudorandom sources such as noise are seeded from the `seed`
+    /// parameter when it is present (a whole number; f32 parameters hold
+    /// integers exactly up to 2^24), so the same seed renders the same audio.
+    /// Without it every build gets a fresh seed. See also
+    /// [`create_seeded`](Self::create_seeded).
+    ///
+    /// Parameters are checked against the synth's [`ParameterDef`] ranges,
+    /// see [`set_strict_params`](Self::set_strict_params).
     pub fn build(
         &self,
         name: &str,
         freq: f32,
         params: &HashMap<String, f32>,
     ) -> Result<(Box<dyn AudioUnit>, VoiceControls)> {
-        let builder = self
+        let seed = match params.get("seed") {
+            Some(&seed) => seed.max(0.0) as u64,
+            None => NEXT_SEED.fetch_add(1, Ordering::Relaxed),
+        };
+        self.build_seeded(name, freq, params, seed)
+    }
+
+    /// Create a synth whose pseudorandom sources are seeded with `seed`
+    ///
+    /// Two synths created with the same name, parameters and seed render
+    /// identical audio, which makes offline renders reproducible.
+    pub fn create_seeded(
+        &self,
+        name: &str,
+        freq: f32,
+        params: &HashMap<String, f32>,
+        seed: u64,
+    ) -> Result<(Box<dyn AudioUnit>, VoiceControls)> {
+        self.build_seeded(name, freq, params, seed)
+    }
+
+    fn build_seeded(
+        &self,
+        name: &str,
+        freq: f32,
+        params: &HashMap<String, f32>,
+        seed: u64,
+    ) -> Result<(Box<dyn AudioUnit>, VoiceControls)> {
+        let (builder, defs) = self
             .builders
             .get(name)
-            .ok_or_else(|| crate::error::Error::InvalidSynth(name.to_string()))?;
-        Ok(builder.build(freq, params))
+            .zip(self.parameters.get(name))
+            .ok_or_else(|| crate::Error::UnknownSynth {
+                name: name.to_string(),
+                suggestion: self.suggest(name).into_iter().next(),
+            })?;
+        let params = check_ranges(name, defs, params, self.strict_params, &self.clamp_warnings)?;
+        let (mut unit, controls) = builder.build(freq, &params);
+        // Give every node in the graph its own state derived from the seed
+        unit.ping(false, AttoHash::new(seed));
+        Ok((unit, controls))
     }
 
     /// Create a synth by name (alias for build)
@@ -231,11 +363,52 @@ impl SynthRegistry {
         self.builders.get(name)
     }
 
+    /// Registered names close to `name`, closest first
+    ///
+    /// Used to suggest a correction for a misspelled synth name.
+    pub fn suggest(&self, name: &str) -> Vec<String> {
+        crate::error::suggest(name, self.builders.keys().map(String::as_str))
+    }
+
+    /// Get metadata for a synth without building it
+    pub fn metadata(&self, name: &str) -> Option<SynthMetadata> {
+        self.builders.get(name).map(|builder| builder.metadata())
+    }
+
     /// List all available synths
     pub fn list_synths(&self) -> Vec<SynthMetadata> {
         self.builders.values().map(|b| b.metadata()).collect()
     }
 
+    /// List each synth once, with every name it is registered under
+    ///
+    /// Aliases such as `hammond` and `organ` share metadata, so entries are
+    /// grouped by the metadata name. Sorted by that name.
+    pub fn list_unique(&self) -> Vec<(SynthMetadata, BTreeSet<String>)> {
+        let mut unique: BTreeMap<String, (SynthMetadata, BTreeSet<String>)> = BTreeMap::new();
+        for (key, builder) in &self.builders {
+            let metadata = builder.metadata();
+            unique
+                .entry(metadata.name.clone())
+                .or_insert_with(|| (metadata, BTreeSet::new()))
+                .1
+                .insert(key.clone());
+        }
+        unique.into_values().collect()
+    }
+
+    /// Registered synth names, aliases included, in no particular order
+    ///
+    /// Borrows the names without building any metadata.
+    pub fn names(&self) -> Vec<&str> {
+        self.builders.keys().map(String::as_str).collect()
+    }
+
+    /// Number of registered synth names, aliases included
+    pub fn count(&self) -> usize {
+        self.builders.len()
+    }
+
     /// Check if a synth exists
     pub fn contains(&self, name: &str) -> bool {
         self.builders.contains_key(name)
@@ -252,6 +425,25 @@ impl SynthRegistry {
             .collect()
     }
 
+    /// Find synths by category
+    ///
+    /// Returns a list of synth names whose [`category`](SynthMetadata::category)
+    /// is `category`. Unlike [`find_by_tag`](Self::find_by_tag) this only
+    /// matches the primary tag, so a bass synth also tagged `analog` is not
+    /// found under `analog`.
+    pub fn find_by_category(&self, category: &str) -> Vec<String> {
+        self.builders
+            .iter()
+            .filter(|(_, builder)| {
+                builder
+                    .metadata()
+                    .category()
+                    .is_some_and(|c| c.eq_ignore_ascii_case(category))
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
     /// Get the first synth that matches a tag, or a fallback if none found
     ///
     /// Useful for MIDI program change where we need a single synth for a category.
@@ -351,3 +543,145 @@ impl Default for SynthRegistry {
         Self::new()
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::synth::envelope::{EnvelopeConfig, ADSR};
+    use crate::synth::preset::{PresetBank, SynthPreset};
+
+    /// Render the left channel of a freshly built synth
+    fn render(unit: &mut Box<dyn AudioUnit>) -> Vec<f32> {
+        let mut output = [0.0; 2];
+        (0..256)
+            .map(|_| {
+                unit.tick(&[], &mut output);
+                output[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seeded_noise_is_reproducible() {
+        let registry = SynthRegistry::with_builtin();
+        let params = HashMap::new();
+        let build_seeded = |seed| {
+            registry
+                .create_seeded("noise", 440.0, &params, seed)
+                .unwrap()
+                .0
+        };
+
+        assert_eq!(render(&mut build_seeded(7)), render(&mut build_seeded(7)));
+        assert_ne!(render(&mut build_seeded(7)), render(&mut build_seeded(8)));
+
+        // The seed parameter does the same through the regular build path
+        let seeded = HashMap::from([("seed".to_string(), 7.0)]);
+        let (mut unit, _) = registry.build("noise", 440.0, &seeded).unwrap();
+        assert_eq!(render(&mut unit), render(&mut build_seeded(7)));
+
+        // Unseeded builds differ
+        let (mut a, _) = registry.build("noise", 440.0, &params).unwrap();
+        let (mut b, _) = registry.build("noise", 440.0, &params).unwrap();
+        assert_ne!(render(&mut a), render(&mut b));
+    }
+
+    #[test]
+    fn test_build_checks_param_ranges() {
+        let mut registry = SynthRegistry::with_builtin();
+        let params = HashMap::from([("amp".to_string(), 5.0)]);
+        let (_, controls) = registry.build("sine", 440.0, &params).unwrap();
+        assert_eq!(controls.amp.value(), 2.0);
+
+        registry.set_strict_params(true);
+        assert!(matches!(
+            registry.build("sine", 440.0, &params),
+            Err(crate::Error::ParameterOutOfRange { min, max, .. }) if (min, max) == (0.0, 2.0)
+        ));
+        assert!(registry.build("sine", 440.0, &HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_synth_suggestion() {
+        let registry = SynthRegistry::with_builtin();
+        assert_eq!(registry.suggest("tbe303"), ["tb303"]);
+
+        let error = registry
+            .build("tbe303", 440.0, &HashMap::new())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "unknown synth 'tbe303'; did you mean 'tb303'?"
+        );
+    }
+
+    #[test]
+    fn test_find_by_tag_and_category() {
+        let registry = SynthRegistry::with_builtin();
+
+        let analog = registry.find_by_tag("analog");
+        assert!(analog.contains(&"tb303".to_string()));
+        assert!(analog.contains(&"prophet".to_string()));
+
+        let bass = registry.find_by_category("Bass");
+        assert!(bass.contains(&"tb303".to_string()));
+        assert!(bass.contains(&"acid".to_string()));
+        assert!(!bass.contains(&"prophet".to_string()));
+        assert!(registry.find_by_category("analog").is_empty());
+
+        assert_eq!(
+            registry.metadata("prophet").unwrap().category(),
+            Some("synth")
+        );
+    }
+
+    #[test]
+    fn test_list_unique_groups_aliases() {
+        let registry = SynthRegistry::with_builtin();
+        let unique = registry.list_unique();
+        let (_, aliases) = unique
+            .iter()
+            .find(|(_, aliases)| aliases.contains("hammond"))
+            .unwrap();
+        assert!(aliases.contains("organ"));
+        assert_eq!(
+            unique.iter().filter(|(_, a)| a.contains("organ")).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_register_presets() {
+        let mut bank = PresetBank::new("Test");
+        bank.add_preset(
+            SynthPreset::new("acid_line", "tb303")
+                .with_parameter("cutoff", 1234.0)
+                .with_parameter("res", 0.8)
+                .with_envelope(EnvelopeConfig::ADSR(ADSR::pluck())),
+        );
+        bank.add_preset(SynthPreset::new("broken", "no_such_synth"));
+
+        let mut registry = SynthRegistry::with_builtin();
+        let builtin = registry.count();
+        assert_eq!(registry.register_presets(&bank), 1);
+        assert_eq!(registry.count(), builtin + 1);
+        assert!(registry.names().contains(&"acid_line"));
+        assert!(registry.contains("acid_line"));
+        assert!(!registry.contains("broken"));
+
+        let (_, controls) = registry.build("acid_line", 110.0, &HashMap::new()).unwrap();
+        assert_eq!(controls.cutoff.unwrap().value(), 1234.0);
+        assert_eq!(controls.resonance.unwrap().value(), 0.8);
+
+        // Build-time parameters override the preset
+        let overrides = HashMap::from([("cutoff".to_string(), 500.0)]);
+        let (_, controls) = registry.build("acid_line", 110.0, &overrides).unwrap();
+        assert_eq!(controls.cutoff.unwrap().value(), 500.0);
+
+        let metadata = registry.metadata("acid_line").unwrap();
+        assert_eq!(metadata.name, "acid_line");
+        assert!(metadata.has_tag("preset"));
+        assert!(registry.metadata("broken").is_none());
+    }
+}
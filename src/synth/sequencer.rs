@@ -0,0 +1,384 @@
+//! Step sequencer for the drum presets
+//!
+//! A [`StepSequencer`] plays a grid of drum hits at a tempo. Each track is
+//! a drum token (`"bd"`, `"sd"`, `"hh"`, ...) played by its own
+//! [`PolySynth`] running the matching drum preset, and holds its own
+//! pattern, so tracks of different lengths cycle independently.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use fundsp_rack::prelude::*;
+//!
+//! let mut seq = StepSequencer::new(120.0, 44100.0);
+//! let kick = seq.add_track("bd").unwrap();
+//! let hat = seq.add_track("hh").unwrap();
+//! seq.set_pattern(kick, "x...x...x...x...").unwrap();
+//! seq.set_pattern(hat, "..x...x...x...X.").unwrap();
+//! seq.set_swing(0.2);
+//!
+//! let (left, right) = seq.render(44100);
+//! ```
+
+use super::poly::PolySynth;
+use super::preset::{midi_note_for_token, preset_for_token, PresetBank};
+use super::registry::SynthRegistry;
+use crate::{Error, Result};
+
+/// Voices per track, enough for a hit to ring over the next few
+const TRACK_VOICES: usize = 4;
+
+/// Velocity of an `x` in a pattern string
+const NORMAL_VELOCITY: f32 = 0.8;
+
+/// A hit in a pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    /// Velocity (0.0 to 1.0)
+    pub velocity: f32,
+    /// Chance the step plays each time it comes round (0.0 to 1.0)
+    pub probability: f32,
+}
+
+impl Step {
+    /// Create a hit that always plays
+    pub fn new(velocity: f32) -> Self {
+        Self {
+            velocity: velocity.clamp(0.0, 1.0),
+            probability: 1.0,
+        }
+    }
+
+    /// Builder pattern: set the chance the step plays
+    pub fn with_probability(mut self, probability: f32) -> Self {
+        self.probability = probability.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// One drum and its pattern
+struct Track {
+    /// Synth playing the drum preset
+    synth: PolySynth,
+    /// Note the drum is triggered with
+    note: u8,
+    /// Hits and rests
+    steps: Vec<Option<Step>>,
+}
+
+/// Plays drum patterns at a tempo, sample-accurately
+///
+/// Steps are sixteenth notes by default. Swing delays every second step
+/// by a fraction of a step: 0.0 is straight, 1/3 gives a triplet feel.
+pub struct StepSequencer {
+    tracks: Vec<Track>,
+    bpm: f32,
+    steps_per_beat: u32,
+    swing: f32,
+    sample_rate: f64,
+    /// Samples ticked since the start
+    position: u64,
+    /// Index of the next step, counted from the start
+    next_step: u64,
+    /// Unswung time of the next step in samples
+    next_time: f64,
+    /// Last step played
+    current_step: Option<u64>,
+    /// Random state for step probabilities
+    rng: u32,
+}
+
+impl StepSequencer {
+    /// Create a sequencer with no tracks
+    pub fn new(bpm: f32, sample_rate: f64) -> Self {
+        Self {
+            tracks: Vec::new(),
+            bpm: bpm.max(1.0),
+            steps_per_beat: 4,
+            swing: 0.0,
+            sample_rate,
+            position: 0,
+            next_step: 0,
+            next_time: 0.0,
+            current_step: None,
+            rng: 1,
+        }
+    }
+
+    /// Add a track playing the drum preset for `token`, with an empty pattern
+    ///
+    /// Returns the track index. Tokens are those understood by
+    /// [`preset_for_token`]; the drum is played on the note given by
+    /// [`midi_note_for_token`].
+    pub fn add_track(&mut self, token: &str) -> Result<usize> {
+        let token = token.to_lowercase();
+        let preset = preset_for_token(&token).ok_or_else(|| Error::InvalidSynth(token.clone()))?;
+        let note = midi_note_for_token(&token).unwrap_or(60);
+
+        let mut bank = PresetBank::new(token);
+        let name = preset.name.clone();
+        bank.add_preset(preset);
+        let mut registry = SynthRegistry::with_builtin();
+        registry.register_presets(&bank);
+
+        let mut synth = PolySynth::with_registry(&name, TRACK_VOICES, registry);
+        synth.set_sample_rate(self.sample_rate);
+        self.tracks.push(Track {
+            synth,
+            note,
+            steps: Vec::new(),
+        });
+        Ok(self.tracks.len() - 1)
+    }
+
+    /// Number of tracks
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Set a track's pattern from a string such as `"x...x...x.x.x..."`
+    ///
+    /// `x` is a hit, `X` an accented (full velocity) hit and `.` or `-` a
+    /// rest. Spaces and `|` are ignored, so bars can be marked out.
+    pub fn set_pattern(&mut self, track: usize, pattern: &str) -> Result<()> {
+        let steps = pattern
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '|')
+            .map(|c| match c {
+                'x' => Ok(Some(Step::new(NORMAL_VELOCITY))),
+                'X' => Ok(Some(Step::new(1.0))),
+                '.' | '-' => Ok(None),
+                _ => Err(Error::InvalidParameter(pattern.to_string())),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.track_mut(track)?.steps = steps;
+        Ok(())
+    }
+
+    /// Set one step of a track's pattern, growing the pattern if needed
+    pub fn set_step(&mut self, track: usize, index: usize, step: Option<Step>) -> Result<()> {
+        let steps = &mut self.track_mut(track)?.steps;
+        if index >= steps.len() {
+            steps.resize(index + 1, None);
+        }
+        steps[index] = step;
+        Ok(())
+    }
+
+    /// A track's pattern
+    pub fn pattern(&self, track: usize) -> Option<&[Option<Step>]> {
+        self.tracks.get(track).map(|t| t.steps.as_slice())
+    }
+
+    /// Set the tempo in BPM
+    pub fn set_bpm(&mut self, bpm: f32) -> &mut Self {
+        self.bpm = bpm.max(1.0);
+        self
+    }
+
+    /// Get the tempo in BPM
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Set the number of steps per beat (default 4, sixteenth notes)
+    pub fn set_steps_per_beat(&mut self, steps: u32) -> &mut Self {
+        self.steps_per_beat = steps.max(1);
+        self
+    }
+
+    /// Set the swing (0.0 to 0.5): the fraction of a step every second step is delayed by
+    pub fn set_swing(&mut self, swing: f32) -> &mut Self {
+        self.swing = swing.clamp(0.0, 0.5);
+        self
+    }
+
+    /// Get the swing
+    pub fn swing(&self) -> f32 {
+        self.swing
+    }
+
+    /// Seed the random choices made for step probabilities
+    pub fn set_seed(&mut self, seed: u32) -> &mut Self {
+        self.rng = seed;
+        self
+    }
+
+    /// Set the sample rate of the sequencer and its tracks
+    ///
+    /// Step lengths change from the step after next.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        for track in &mut self.tracks {
+            track.synth.set_sample_rate(sample_rate);
+        }
+    }
+
+    /// Samples ticked since the start
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Index of the last step played, counted from the start
+    pub fn current_step(&self) -> Option<u64> {
+        self.current_step
+    }
+
+    /// Go back to the first step and release every track
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.next_step = 0;
+        self.next_time = 0.0;
+        self.current_step = None;
+        for track in &mut self.tracks {
+            track.synth.all_notes_off();
+        }
+    }
+
+    /// Play any step due now, then produce the next stereo sample
+    pub fn tick(&mut self) -> (f32, f32) {
+        while self.position as f64 >= self.next_step_time() {
+            self.play_step();
+        }
+        self.position += 1;
+
+        self.tracks
+            .iter_mut()
+            .fold((0.0, 0.0), |(left, right), track| {
+                let (l, r) = track.synth.get_stereo();
+                (left + l, right + r)
+            })
+    }
+
+    /// Render `frames` frames into planar (left, right) buffers
+    pub fn render(&mut self, frames: usize) -> (Vec<f32>, Vec<f32>) {
+        (0..frames).map(|_| self.tick()).unzip()
+    }
+
+    /// Length of a step in samples at the current tempo
+    fn step_samples(&self) -> f64 {
+        self.sample_rate * 60.0 / (self.bpm as f64 * self.steps_per_beat as f64)
+    }
+
+    /// Sample the next step plays on, swing applied
+    fn next_step_time(&self) -> f64 {
+        let swing = if self.next_step % 2 == 1 {
+            self.swing as f64 * self.step_samples()
+        } else {
+            0.0
+        };
+        (self.next_time + swing).round()
+    }
+
+    /// Trigger the hits of the next step and move on
+    fn play_step(&mut self) {
+        let step = self.next_step;
+        for track in &mut self.tracks {
+            if track.steps.is_empty() {
+                continue;
+            }
+            let Some(hit) = track.steps[(step % track.steps.len() as u64) as usize] else {
+                continue;
+            };
+            if hit.probability < 1.0 {
+                self.rng = self.rng.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                if (self.rng >> 8) as f32 / (1u32 << 24) as f32 >= hit.probability {
+                    continue;
+                }
+            }
+            // Release first so a repeated hit restarts the envelope
+            track.synth.note_off(track.note);
+            track.synth.note_on(track.note, hit.velocity);
+        }
+
+        self.current_step = Some(step);
+        self.next_step += 1;
+        self.next_time += self.step_samples();
+    }
+
+    fn track_mut(&mut self, track: usize) -> Result<&mut Track> {
+        let len = self.tracks.len();
+        self.tracks
+            .get_mut(track)
+            .ok_or(Error::IndexOutOfBounds { index: track, len })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn test_steps_follow_tempo() {
+        // 120 BPM sixteenths at 48 kHz: 6000 samples a step
+        let mut seq = StepSequencer::new(120.0, 48000.0);
+        let kick = seq.add_track("bd").unwrap();
+        seq.set_pattern(kick, "x.x. x.x.").unwrap();
+        assert_eq!(seq.pattern(kick).unwrap().len(), 8);
+        assert_eq!(seq.current_step(), None);
+
+        seq.tick();
+        assert_eq!(seq.current_step(), Some(0));
+        seq.render(5999);
+        assert_eq!(seq.current_step(), Some(0));
+        seq.tick();
+        assert_eq!(seq.current_step(), Some(1));
+        seq.render(5999);
+        seq.tick();
+        assert_eq!(seq.current_step(), Some(2));
+
+        // Swing pushes the off-beat back by a quarter step
+        seq.reset();
+        seq.set_swing(0.25);
+        seq.render(7500);
+        assert_eq!(seq.current_step(), Some(0));
+        seq.tick();
+        assert_eq!(seq.current_step(), Some(1));
+        seq.render(4499);
+        assert_eq!(seq.current_step(), Some(1));
+        seq.tick();
+        assert_eq!(seq.current_step(), Some(2));
+        assert_eq!(seq.position(), 12001);
+    }
+
+    #[test]
+    fn test_step_probability() {
+        let mut seq = StepSequencer::new(120.0, 48000.0);
+        let kick = seq.add_track("kick").unwrap();
+        seq.set_pattern(kick, "xxxx").unwrap();
+        let (left, _) = seq.render(24000);
+        assert!(peak(&left) > 0.01);
+
+        let mut seq = StepSequencer::new(120.0, 48000.0);
+        let kick = seq.add_track("kick").unwrap();
+        for step in 0..4 {
+            seq.set_step(kick, step, Some(Step::new(1.0).with_probability(0.0)))
+                .unwrap();
+        }
+        let (left, right) = seq.render(24000);
+        assert_eq!(peak(&left), 0.0);
+        assert_eq!(peak(&right), 0.0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut seq = StepSequencer::new(120.0, 48000.0);
+        assert!(matches!(
+            seq.add_track("frobnicator"),
+            Err(Error::InvalidSynth(_))
+        ));
+        assert!(matches!(
+            seq.set_pattern(0, "x..."),
+            Err(Error::IndexOutOfBounds { index: 0, len: 0 })
+        ));
+        let hat = seq.add_track("HH").unwrap();
+        assert!(matches!(
+            seq.set_pattern(hat, "x.?."),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+}
@@ -5,6 +5,7 @@
 //! - GrowlSynthBuilder: Growling bass sound with low-frequency modulation
 //! - HollowSynthBuilder: Hollow, airy ambient sound with detuned sines
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -31,15 +32,18 @@ impl SynthBuilder for DarkAmbienceSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -72,15 +76,18 @@ impl SynthBuilder for GrowlSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -118,15 +125,18 @@ impl SynthBuilder for HollowSynthBuilder {
         let synth =
             Box::new(((left * 0.5) | (right * 0.5)) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -6,7 +6,9 @@
 //! - Supersaw
 //! - Hoover rave synth
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
+use crate::params::{moog_mapping, resonance_param};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 
@@ -21,7 +23,7 @@ impl SynthBuilder for TB303SynthBuilder {
     ) -> (Box<dyn AudioUnit>, VoiceControls) {
         let initial_amp = params.get("amp").copied().unwrap_or(1.0);
         let initial_cutoff = params.get("cutoff").copied().unwrap_or(1000.0);
-        let initial_resonance = params.get("res").copied().unwrap_or(0.5);
+        let (initial_resonance, raw_res) = resonance_param(params, 0.5);
 
         let amp_shared = shared(initial_amp);
         let pitch_bend_shared = shared(1.0);
@@ -31,11 +33,11 @@ impl SynthBuilder for TB303SynthBuilder {
 
         let left = ((var_fn(&pitch_bend_shared, move |bend| freq * bend) >> saw())
             | var(&cutoff_shared)
-            | var(&resonance_shared))
+            | var_fn(&resonance_shared, moog_mapping(raw_res)))
             >> moog();
         let right = ((var_fn(&pitch_bend_shared, move |bend| freq * bend) >> saw())
             | var(&cutoff_shared)
-            | var(&resonance_shared))
+            | var_fn(&resonance_shared, moog_mapping(raw_res)))
             >> moog();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
@@ -43,11 +45,13 @@ impl SynthBuilder for TB303SynthBuilder {
             amp: amp_shared,
             cutoff: Some(cutoff_shared),
             resonance: Some(resonance_shared),
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -57,6 +61,7 @@ impl SynthBuilder for TB303SynthBuilder {
             .with_param("res", 0.5, 0.0, 1.0)
             .with_tag("bass")
             .with_tag("acid")
+            .with_tag("analog")
     }
 }
 
@@ -71,7 +76,7 @@ impl SynthBuilder for ProphetSynthBuilder {
     ) -> (Box<dyn AudioUnit>, VoiceControls) {
         let initial_amp = params.get("amp").copied().unwrap_or(1.0);
         let initial_cutoff = params.get("cutoff").copied().unwrap_or(2000.0);
-        let initial_resonance = params.get("res").copied().unwrap_or(0.3);
+        let (initial_resonance, raw_res) = resonance_param(params, 0.3);
 
         let amp_shared = shared(initial_amp);
         let pitch_bend_shared = shared(1.0);
@@ -82,15 +87,17 @@ impl SynthBuilder for ProphetSynthBuilder {
         // Mix saw and square waves for classic analog sound
         let left_saw = var_fn(&pitch_bend_shared, move |bend| freq * bend) >> saw();
         let left_square = var_fn(&pitch_bend_shared, move |bend| freq * bend) >> square();
-        let left =
-            ((left_saw * 0.5 + left_square * 0.5) | var(&cutoff_shared) | var(&resonance_shared))
-                >> moog();
+        let left = ((left_saw * 0.5 + left_square * 0.5)
+            | var(&cutoff_shared)
+            | var_fn(&resonance_shared, moog_mapping(raw_res)))
+            >> moog();
 
         let right_saw = var_fn(&pitch_bend_shared, move |bend| freq * bend) >> saw();
         let right_square = var_fn(&pitch_bend_shared, move |bend| freq * bend) >> square();
-        let right =
-            ((right_saw * 0.5 + right_square * 0.5) | var(&cutoff_shared) | var(&resonance_shared))
-                >> moog();
+        let right = ((right_saw * 0.5 + right_square * 0.5)
+            | var(&cutoff_shared)
+            | var_fn(&resonance_shared, moog_mapping(raw_res)))
+            >> moog();
 
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
@@ -98,11 +105,13 @@ impl SynthBuilder for ProphetSynthBuilder {
             amp: amp_shared,
             cutoff: Some(cutoff_shared),
             resonance: Some(resonance_shared),
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -155,11 +164,13 @@ impl SynthBuilder for SupersawSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -168,6 +179,7 @@ impl SynthBuilder for SupersawSynthBuilder {
             .with_param("detune", 0.02, 0.0, 0.1)
             .with_tag("lead")
             .with_tag("trance")
+            .with_tag("analog")
     }
 }
 
@@ -183,7 +195,7 @@ impl SynthBuilder for HooverSynthBuilder {
         // Hoover is multiple detuned saws with resonant filter
         let initial_amp = params.get("amp").copied().unwrap_or(1.0);
         let initial_cutoff = params.get("cutoff").copied().unwrap_or(1500.0);
-        let initial_resonance = params.get("res").copied().unwrap_or(0.7);
+        let (initial_resonance, raw_res) = resonance_param(params, 0.7);
 
         let amp_shared = shared(initial_amp);
         let pitch_bend_shared = shared(1.0);
@@ -199,7 +211,7 @@ impl SynthBuilder for HooverSynthBuilder {
             + (var_fn(&pitch_bend_shared, move |bend| freq * bend * (1.0 + detune)) >> saw()))
             * 0.33)
             | var(&cutoff_shared)
-            | var(&resonance_shared))
+            | var_fn(&resonance_shared, moog_mapping(raw_res)))
             >> (moog() * var(&amp_shared));
 
         let pitch_bend_shared2 = pitch_bend_shared.clone();
@@ -216,20 +228,23 @@ impl SynthBuilder for HooverSynthBuilder {
             }) >> saw()))
             * 0.33)
             | var(&cutoff_shared2)
-            | var(&resonance_shared2))
+            | var_fn(&resonance_shared2, moog_mapping(raw_res)))
             >> (moog() * var(&amp_shared2));
 
         let synth = Box::new(left | right);
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: Some(cutoff_shared),
                 resonance: Some(resonance_shared),
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -240,5 +255,6 @@ impl SynthBuilder for HooverSynthBuilder {
             .with_param("res", 0.7, 0.0, 1.0)
             .with_tag("bass")
             .with_tag("rave")
+            .with_tag("analog")
     }
 }
@@ -7,6 +7,7 @@
 //! - Triangle wave
 //! - Pulse wave
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -33,11 +34,13 @@ impl SynthBuilder for SineSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -69,11 +72,13 @@ impl SynthBuilder for SawSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -105,11 +110,13 @@ impl SynthBuilder for SquareSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -141,11 +148,13 @@ impl SynthBuilder for TriangleSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -165,32 +174,101 @@ impl SynthBuilder for PulseSynthBuilder {
         params: &HashMap<String, f32>,
     ) -> (Box<dyn AudioUnit>, VoiceControls) {
         let initial_amp = params.get("amp").copied().unwrap_or(1.0);
-        let _duty = params.get("duty").copied().unwrap_or(0.5);
+        let initial_width = params
+            .get("pulse_width")
+            .or_else(|| params.get("duty"))
+            .copied()
+            .unwrap_or(0.5);
         let amp_shared = shared(initial_amp);
         let pitch_bend_shared = shared(1.0);
         let pressure_shared = shared(0.0);
+        let pulse_width_shared = shared(initial_width.clamp(0.0, 1.0));
 
-        // FunDSP doesn't have a built-in pulse with variable duty cycle
-        // Use square for now (50% duty cycle)
-        let left = var_fn(&pitch_bend_shared, move |bend| freq * bend) >> square();
-        let right = var_fn(&pitch_bend_shared, move |bend| freq * bend) >> square();
+        // Bandlimited pulse: input 0 is frequency, input 1 is duty cycle
+        let left = (var_fn(&pitch_bend_shared, move |bend| freq * bend)
+            | var_fn(&pulse_width_shared, |w| w.clamp(0.01, 0.99)))
+            >> pulse();
+        let right = (var_fn(&pitch_bend_shared, move |bend| freq * bend)
+            | var_fn(&pulse_width_shared, |w| w.clamp(0.01, 0.99)))
+            >> pulse();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
         let controls = VoiceControls {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: Some(pulse_width_shared),
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
         SynthMetadata::new("pulse", "Pulse wave oscillator")
             .with_param("amp", 1.0, 0.0, 2.0)
-            .with_param("duty", 0.5, 0.0, 1.0)
+            .with_param("pulse_width", 0.5, 0.0, 1.0)
             .with_tag("synth")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render one second and return (fraction of positive samples, RMS)
+    fn analyze(builder: &dyn SynthBuilder, params: &HashMap<String, f32>) -> (f32, f32) {
+        let (mut unit, _) = builder.build(100.0, params);
+        unit.set_sample_rate(48000.0);
+        let mut positive = 0usize;
+        let mut sum_sq = 0.0f32;
+        let n = 48000;
+        for _ in 0..n {
+            let (l, _) = unit.get_stereo();
+            if l > 0.0 {
+                positive += 1;
+            }
+            sum_sq += l * l;
+        }
+        (positive as f32 / n as f32, (sum_sq / n as f32).sqrt())
+    }
+
+    #[test]
+    fn test_pulse_half_duty_matches_square() {
+        let params = HashMap::from([("pulse_width".to_string(), 0.5)]);
+        let (pulse_pos, pulse_rms) = analyze(&PulseSynthBuilder, &params);
+        let (square_pos, square_rms) = analyze(&SquareSynthBuilder, &HashMap::new());
+
+        assert!((pulse_pos - square_pos).abs() < 0.05);
+        assert!((pulse_rms - square_rms).abs() / square_rms < 0.1);
+    }
+
+    #[test]
+    fn test_pulse_narrow_duty() {
+        let params = HashMap::from([("pulse_width".to_string(), 0.1)]);
+        let (pos, _) = analyze(&PulseSynthBuilder, &params);
+        // 10% of the cycle sits on one side of zero, regardless of polarity
+        let narrow = pos.min(1.0 - pos);
+        assert!(narrow > 0.05 && narrow < 0.15, "narrow fraction {narrow}");
+    }
+
+    #[test]
+    fn test_pulse_width_is_live() {
+        let (mut unit, controls) = PulseSynthBuilder.build(100.0, &HashMap::new());
+        unit.set_sample_rate(48000.0);
+        let width = controls
+            .pulse_width
+            .expect("pulse synth exposes pulse_width");
+        width.set(0.1);
+        let mut positive = 0usize;
+        for _ in 0..48000 {
+            if unit.get_stereo().0 > 0.0 {
+                positive += 1;
+            }
+        }
+        let pos = positive as f32 / 48000.0;
+        assert!(pos.min(1.0 - pos) < 0.15);
+    }
+}
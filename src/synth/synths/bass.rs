@@ -4,6 +4,7 @@
 //! - BassFoundationSynthBuilder: Deep sine bass
 //! - BassHighendSynthBuilder: Saw bass with harmonics
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -27,15 +28,18 @@ impl SynthBuilder for BassFoundationSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -65,15 +69,18 @@ impl SynthBuilder for BassHighendSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
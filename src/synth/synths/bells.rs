@@ -4,6 +4,7 @@
 //! - PrettyBellSynthBuilder: Bell sound with inharmonic partials
 //! - DullBellSynthBuilder: Duller bell using triangle wave base
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -33,15 +34,18 @@ impl SynthBuilder for PrettyBellSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -76,15 +80,18 @@ impl SynthBuilder for DullBellSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -5,6 +5,7 @@
 //! - DPulseSynthBuilder: Detuned pulse/square waves
 //! - DTriSynthBuilder: Detuned triangle waves
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -30,15 +31,18 @@ impl SynthBuilder for DSawSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * 0.5 * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -72,15 +76,18 @@ impl SynthBuilder for DPulseSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * 0.5 * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -114,15 +121,18 @@ impl SynthBuilder for DTriSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * 0.5 * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -2,7 +2,9 @@
 //!
 //! This module contains synth builders that use frequency modulation:
 //! - FMSynthBuilder: Simple FM synthesis with harmonic modulator relationship
+//! - FM2SynthBuilder: Two-operator FM with modulator feedback and envelope
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -29,15 +31,18 @@ impl SynthBuilder for FMSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -49,3 +54,235 @@ impl SynthBuilder for FMSynthBuilder {
             .with_tag("synth")
     }
 }
+
+/// Two-operator FM voice (modulator -> carrier)
+///
+/// Both operators are sines. The modulator output, scaled by the modulation
+/// index, is added to the carrier's phase (in radians), as in classic
+/// phase-modulation "FM". The modulator also feeds back into its own phase
+/// through a two-sample average, which keeps high feedback stable.
+#[derive(Clone)]
+pub struct FM2Voice {
+    freq: f32,
+    /// Carrier frequency as a multiple of the note frequency
+    car_ratio: f32,
+    /// Modulator frequency as a multiple of the note frequency
+    mod_ratio: f32,
+    /// Peak modulation index in radians
+    index: f32,
+    /// Modulator self-feedback in radians
+    feedback: f32,
+    /// Time constant of the modulation index decay in seconds (0 = sustained)
+    mod_decay: f32,
+    amp: Shared,
+    pitch_bend: Shared,
+    /// Operator phases (0.0 to 1.0)
+    car_phase: f64,
+    mod_phase: f64,
+    /// Last two modulator outputs, for feedback
+    history: [f32; 2],
+    /// Seconds since the note started
+    elapsed: f32,
+    sample_rate: f32,
+}
+
+impl FM2Voice {
+    #[inline]
+    fn next_sample(&mut self) -> f32 {
+        let tau = std::f32::consts::TAU;
+        let envelope = if self.mod_decay > 0.0 {
+            (-self.elapsed / self.mod_decay).exp()
+        } else {
+            1.0
+        };
+
+        let feedback = self.feedback * (self.history[0] + self.history[1]) * 0.5;
+        let modulator = (tau * self.mod_phase as f32 + feedback).sin();
+        self.history = [modulator, self.history[0]];
+        let carrier = (tau * self.car_phase as f32 + self.index * envelope * modulator).sin();
+
+        let freq = self.freq as f64 * self.pitch_bend.value() as f64 / self.sample_rate as f64;
+        self.car_phase += freq * self.car_ratio as f64;
+        self.car_phase -= self.car_phase.floor();
+        self.mod_phase += freq * self.mod_ratio as f64;
+        self.mod_phase -= self.mod_phase.floor();
+        self.elapsed += 1.0 / self.sample_rate;
+
+        carrier * self.amp.value()
+    }
+}
+
+impl AudioUnit for FM2Voice {
+    fn inputs(&self) -> usize {
+        0
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.car_phase = 0.0;
+        self.mod_phase = 0.0;
+        self.history = [0.0; 2];
+        self.elapsed = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn tick(&mut self, _input: &[f32], output: &mut [f32]) {
+        let value = self.next_sample();
+        output[0] = value;
+        output[1] = value;
+    }
+
+    fn process(&mut self, size: usize, _input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let value = self.next_sample();
+            output.set_f32(0, i, value);
+            output.set_f32(1, i, value);
+        }
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        SignalFrame::new(2)
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"fm2";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Two-operator FM synth with modulator feedback
+///
+/// See [`FM2Voice`] for the algorithm. `car_ratio` and `mod_ratio` set the
+/// operator frequencies relative to the note, `index` the modulation depth,
+/// `feedback` the modulator self-modulation (bright, saw-like and eventually
+/// metallic or noisy) and `mod_decay` how fast the modulation index decays
+/// in seconds (0 keeps it constant), giving plucked and struck FM tones.
+pub struct FM2SynthBuilder;
+
+impl SynthBuilder for FM2SynthBuilder {
+    fn build(
+        &self,
+        freq: f32,
+        params: &HashMap<String, f32>,
+    ) -> (Box<dyn AudioUnit>, VoiceControls) {
+        let initial_amp = params.get("amp").copied().unwrap_or(1.0);
+        let car_ratio = params.get("car_ratio").copied().unwrap_or(1.0);
+        let mod_ratio = params.get("mod_ratio").copied().unwrap_or(1.0);
+        let index = params.get("index").copied().unwrap_or(2.0);
+        let feedback = params.get("feedback").copied().unwrap_or(0.0);
+        let mod_decay = params.get("mod_decay").copied().unwrap_or(0.0).max(0.0);
+
+        let amp_shared = shared(initial_amp);
+        let pitch_bend_shared = shared(1.0);
+        let pressure_shared = shared(0.0);
+
+        let synth = Box::new(FM2Voice {
+            freq,
+            car_ratio,
+            mod_ratio,
+            index,
+            feedback,
+            mod_decay,
+            amp: amp_shared.clone(),
+            pitch_bend: pitch_bend_shared.clone(),
+            car_phase: 0.0,
+            mod_phase: 0.0,
+            history: [0.0; 2],
+            elapsed: 0.0,
+            sample_rate: 48000.0,
+        });
+
+        let controls = VoiceControls {
+            amp: amp_shared,
+            cutoff: None,
+            resonance: None,
+            pulse_width: None,
+            pitch_bend: pitch_bend_shared,
+            pressure: pressure_shared,
+            gate: shared(1.0),
+        };
+
+        apply_envelope(synth, controls, params)
+    }
+
+    fn metadata(&self) -> SynthMetadata {
+        SynthMetadata::new("fm2", "Two-operator FM (modulator feedback and decay)")
+            .with_param("amp", 1.0, 0.0, 2.0)
+            .with_param("car_ratio", 1.0, 0.25, 16.0)
+            .with_param("mod_ratio", 1.0, 0.25, 16.0)
+            .with_param("index", 2.0, 0.0, 10.0)
+            .with_param("feedback", 0.0, 0.0, 3.0)
+            .with_param("mod_decay", 0.0, 0.0, 10.0)
+            .with_tag("fm")
+            .with_tag("digital")
+            .with_tag("synth")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Amplitudes of the first `count` harmonics of a 200 Hz note over a window
+    fn harmonics(params: &[(&str, f32)], skip: usize, count: usize) -> Vec<f32> {
+        let params: HashMap<String, f32> =
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        let (mut unit, _) = FM2SynthBuilder.build(200.0, &params);
+        unit.set_sample_rate(48000.0);
+        let signal: Vec<f32> = (0..skip + 4800).map(|_| unit.get_stereo().0).collect();
+        let signal = &signal[skip..];
+
+        (1..=count)
+            .map(|n| {
+                let (mut sin_sum, mut cos_sum) = (0.0f32, 0.0f32);
+                for (i, x) in signal.iter().enumerate() {
+                    let phase = std::f32::consts::TAU * 200.0 * n as f32 * i as f32 / 48000.0;
+                    sin_sum += x * phase.sin();
+                    cos_sum += x * phase.cos();
+                }
+                2.0 * (sin_sum * sin_sum + cos_sum * cos_sum).sqrt() / signal.len() as f32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fm2_index_and_ratio() {
+        // No modulation is a pure sine at the carrier ratio
+        let pure = harmonics(&[("index", 0.0), ("car_ratio", 2.0)], 0, 4);
+        assert!((pure[1] - 1.0).abs() < 0.01, "{pure:?}");
+        assert!(pure[0] < 0.01 && pure[2] < 0.01 && pure[3] < 0.01);
+
+        // Modulation spreads energy into sidebands
+        let modulated = harmonics(&[("index", 2.0)], 0, 4);
+        assert!(modulated[1] > 0.2 && modulated[2] > 0.2, "{modulated:?}");
+    }
+
+    #[test]
+    fn test_fm2_feedback_and_mod_decay() {
+        // Feedback adds harmonics a plain modulator sine cannot produce
+        let plain = harmonics(&[("index", 1.0)], 0, 8);
+        let fed_back = harmonics(&[("index", 1.0), ("feedback", 1.5)], 0, 8);
+        let upper = |h: &[f32]| h[4..].iter().sum::<f32>();
+        assert!(
+            upper(&fed_back) > upper(&plain) * 2.0,
+            "{plain:?} vs {fed_back:?}"
+        );
+
+        // The decaying index leaves a nearly pure carrier
+        let decayed = harmonics(&[("index", 5.0), ("mod_decay", 0.05)], 48000, 4);
+        assert!(decayed[0] > 0.95 && decayed[1] < 0.01, "{decayed:?}");
+    }
+}
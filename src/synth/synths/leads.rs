@@ -4,7 +4,9 @@
 //! - Lead: Classic mono lead with filter
 //! - Sub: Pure sub bass
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
+use crate::params::{moog_mapping, resonance_param};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 
@@ -19,7 +21,7 @@ impl SynthBuilder for LeadSynthBuilder {
     ) -> (Box<dyn AudioUnit>, VoiceControls) {
         let initial_amp = params.get("amp").copied().unwrap_or(1.0);
         let initial_cutoff = params.get("cutoff").copied().unwrap_or(2500.0);
-        let initial_resonance = params.get("res").copied().unwrap_or(0.4);
+        let (initial_resonance, raw_res) = resonance_param(params, 0.4);
 
         let amp_shared = shared(initial_amp);
         let pitch_bend_shared = shared(1.0);
@@ -31,7 +33,9 @@ impl SynthBuilder for LeadSynthBuilder {
         let osc = (var_fn(&pitch_bend_shared, move |bend| freq * bend) >> saw()) * 0.6
             + (var_fn(&pitch_bend_shared, move |bend| freq * bend) >> square()) * 0.4;
 
-        let filtered = (osc | var(&cutoff_shared) | var(&resonance_shared)) >> moog();
+        let filtered =
+            (osc | var(&cutoff_shared) | var_fn(&resonance_shared, moog_mapping(raw_res)))
+                >> moog();
 
         let left = filtered.clone();
         let right = filtered;
@@ -41,11 +45,13 @@ impl SynthBuilder for LeadSynthBuilder {
             amp: amp_shared,
             cutoff: Some(cutoff_shared),
             resonance: Some(resonance_shared),
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -89,11 +95,13 @@ impl SynthBuilder for SubSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -140,11 +148,13 @@ impl SynthBuilder for BrassSynthBuilder {
             amp: amp_shared,
             cutoff: Some(cutoff_shared),
             resonance: Some(resonance_shared),
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -16,6 +16,7 @@
 //! - `ambient` - Ambient and pad sounds
 //! - `tech` - Tech/trance/electronic sounds
 //! - `noise` - Noise generators
+//! - `wavetable` - Wavetable oscillator playing user-supplied cycles
 
 pub mod ambient;
 pub mod analog;
@@ -31,6 +32,7 @@ pub mod noise;
 pub mod pads;
 pub mod physical;
 pub mod tech;
+pub mod wavetable;
 
 // Re-export all synth builders for easy access
 pub use ambient::*;
@@ -47,3 +49,4 @@ pub use noise::*;
 pub use pads::*;
 pub use physical::*;
 pub use tech::*;
+pub use wavetable::*;
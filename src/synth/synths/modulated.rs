@@ -4,10 +4,12 @@
 //! - ModSawSynthBuilder: Modulated sawtooth wave
 //! - ModSineSynthBuilder: Modulated sine wave
 //! - ModTriSynthBuilder: Modulated triangle wave
-//! - ModPulseSynthBuilder: Modulated pulse/square wave
+//! - ModPulseSynthBuilder: Pulse wave with LFO-swept duty cycle (PWM)
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
+use numeric_array::typenum::U1;
 use std::collections::HashMap;
 
 /// Modulated saw wave
@@ -31,15 +33,18 @@ impl SynthBuilder for ModSawSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -71,15 +76,18 @@ impl SynthBuilder for ModSineSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -111,15 +119,18 @@ impl SynthBuilder for ModTriSynthBuilder {
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: None,
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
@@ -142,31 +153,42 @@ impl SynthBuilder for ModPulseSynthBuilder {
     ) -> (Box<dyn AudioUnit>, VoiceControls) {
         let initial_amp = params.get("amp").copied().unwrap_or(1.0);
         let mod_freq = params.get("mod_freq").copied().unwrap_or(5.0);
+        let mod_depth = params.get("mod_depth").copied().unwrap_or(0.4);
+        let initial_width = params.get("pulse_width").copied().unwrap_or(0.5);
 
         let amp_shared = shared(initial_amp);
         let pitch_bend_shared = shared(1.0);
         let pressure_shared = shared(0.0);
+        let pulse_width_shared = shared(initial_width.clamp(0.0, 1.0));
 
-        let left = (sine_hz(mod_freq) * freq * 0.1 + dc(freq)) >> square();
+        // LFO sweeps the duty cycle around the pulse_width center
+        let duty = (sine_hz(mod_freq) * mod_depth * 0.5 + var(&pulse_width_shared))
+            >> map(|x: &Frame<f32, U1>| x[0].clamp(0.01, 0.99));
+        let left = (var_fn(&pitch_bend_shared, move |bend| freq * bend) | duty) >> pulse();
         let right = left.clone();
         let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
 
-        (
+        apply_envelope(
             synth,
             VoiceControls {
                 amp: amp_shared,
                 cutoff: None,
                 resonance: None,
+                pulse_width: Some(pulse_width_shared),
                 pitch_bend: pitch_bend_shared,
                 pressure: pressure_shared,
+                gate: shared(1.0),
             },
+            params,
         )
     }
 
     fn metadata(&self) -> SynthMetadata {
-        SynthMetadata::new("mod_pulse", "Modulated pulse wave")
+        SynthMetadata::new("mod_pulse", "Pulse wave with LFO-swept duty cycle")
             .with_param("amp", 1.0, 0.0, 2.0)
             .with_param("mod_freq", 5.0, 0.1, 20.0)
+            .with_param("mod_depth", 0.4, 0.0, 1.0)
+            .with_param("pulse_width", 0.5, 0.0, 1.0)
             .with_tag("synth")
     }
 }
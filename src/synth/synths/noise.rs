@@ -1,44 +1,256 @@
 //! Noise synth builders
 //!
 //! This module contains synth builders for noise-based sounds:
-//! - NoiseSynthBuilder: White noise generator
+//! - NoiseSynthBuilder: Colored noise with optional filters and tone
+//! - SnareSynthBuilder: Noise snap with a pitched body
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
 
-/// White noise generator
+/// Q of the noise synth's highpass and lowpass stages (Butterworth)
+const NOISE_FILTER_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+/// Q of the noise synth's bandpass stage
+const NOISE_BANDPASS_Q: f32 = 1.0;
+
+/// Mono noise source of the given color (0 = white, 1 = pink, 2 = brown)
+fn colored_noise(color: f32) -> Box<dyn AudioUnit> {
+    match color.round() as i32 {
+        1 => Box::new(pink()),
+        2 => Box::new(brown()),
+        _ => Box::new(noise()),
+    }
+}
+
+/// Shared cutoff for a filter parameter, `None` if absent or 0 (off)
+fn cutoff_param(params: &HashMap<String, f32>, name: &str) -> Option<Shared> {
+    params.get(name).copied().filter(|&hz| hz > 0.0).map(shared)
+}
+
+/// Cutoffs of the filters inserted after the noise source
+struct NoiseFilters {
+    highpass: Option<Shared>,
+    lowpass: Option<Shared>,
+    bandpass: Option<Shared>,
+}
+
+impl NoiseFilters {
+    /// One channel of filtered noise; only filters with a cutoff are inserted
+    fn channel(&self, color: f32) -> Box<dyn AudioUnit> {
+        let mut chain = colored_noise(color);
+        if let Some(cutoff) = &self.highpass {
+            chain =
+                Box::new((unit::<U0, U1>(chain) | var(cutoff) | dc(NOISE_FILTER_Q)) >> highpass());
+        }
+        if let Some(cutoff) = &self.lowpass {
+            chain =
+                Box::new((unit::<U0, U1>(chain) | var(cutoff) | dc(NOISE_FILTER_Q)) >> lowpass());
+        }
+        if let Some(cutoff) = &self.bandpass {
+            chain = Box::new(
+                (unit::<U0, U1>(chain) | var(cutoff) | dc(NOISE_BANDPASS_Q)) >> bandpass(),
+            );
+        }
+        chain
+    }
+}
+
+/// Noise generator with selectable color and filtering
+///
+/// `color` picks white (0), pink (1) or brown (2) noise. `highpass`,
+/// `lowpass` and `bandpass` are cutoffs in Hz; each filter is only inserted
+/// when its parameter is above 0, in that order. The `cutoff` voice control
+/// drives the bandpass if present, else the highpass, else the lowpass.
+/// `tone_mix` blends in a sine at the note frequency (0.0 = noise only,
+/// 1.0 = tone only), e.g. for a snare body.
 pub struct NoiseSynthBuilder;
 
 impl SynthBuilder for NoiseSynthBuilder {
+    fn build(
+        &self,
+        freq: f32,
+        params: &HashMap<String, f32>,
+    ) -> (Box<dyn AudioUnit>, VoiceControls) {
+        let initial_amp = params.get("amp").copied().unwrap_or(1.0);
+        let color = params.get("color").copied().unwrap_or(0.0);
+        let tone_mix = params
+            .get("tone_mix")
+            .copied()
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0);
+        let filters = NoiseFilters {
+            highpass: cutoff_param(params, "highpass"),
+            lowpass: cutoff_param(params, "lowpass"),
+            bandpass: cutoff_param(params, "bandpass"),
+        };
+
+        let amp_shared = shared(initial_amp);
+        let pitch_bend_shared = shared(1.0);
+        let pressure_shared = shared(0.0);
+
+        let left = unit::<U0, U1>(filters.channel(color)) * (1.0 - tone_mix);
+        let right = unit::<U0, U1>(filters.channel(color)) * (1.0 - tone_mix);
+        let tone = (var_fn(&pitch_bend_shared, move |bend| freq * bend) >> sine() * tone_mix)
+            >> split::<U2>();
+        let synth = Box::new(((left | right) + tone) * (var(&amp_shared) | var(&amp_shared)));
+
+        let cutoff = filters.bandpass.or(filters.highpass).or(filters.lowpass);
+
+        let controls = VoiceControls {
+            amp: amp_shared,
+            cutoff,
+            resonance: None,
+            pulse_width: None,
+            pitch_bend: pitch_bend_shared,
+            pressure: pressure_shared,
+            gate: shared(1.0),
+        };
+
+        apply_envelope(synth, controls, params)
+    }
+
+    fn metadata(&self) -> SynthMetadata {
+        SynthMetadata::new("noise", "Noise generator (white/pink/brown, filtered)")
+            .with_param("amp", 1.0, 0.0, 2.0)
+            .with_param("color", 0.0, 0.0, 2.0)
+            .with_param("highpass", 0.0, 0.0, 20000.0)
+            .with_param("lowpass", 0.0, 0.0, 20000.0)
+            .with_param("bandpass", 0.0, 0.0, 20000.0)
+            .with_param("tone_mix", 0.0, 0.0, 1.0)
+            .with_tag("noise")
+    }
+}
+
+/// Snare drum: decaying noise snap mixed with a short pitched body
+///
+/// The body is a sine at `tone_freq` (follows pitch bend) and the snap is
+/// white noise, each with its own exponential decay. `tone_mix` sets the
+/// balance (0.0 = noise only, 1.0 = body only).
+pub struct SnareSynthBuilder;
+
+impl SynthBuilder for SnareSynthBuilder {
     fn build(
         &self,
         _freq: f32,
         params: &HashMap<String, f32>,
     ) -> (Box<dyn AudioUnit>, VoiceControls) {
         let initial_amp = params.get("amp").copied().unwrap_or(1.0);
+        let tone_mix = params
+            .get("tone_mix")
+            .copied()
+            .unwrap_or(0.3)
+            .clamp(0.0, 1.0);
+        let tone_freq = params.get("tone_freq").copied().unwrap_or(180.0);
+        let tone_decay = params.get("tone_decay").copied().unwrap_or(0.06).max(0.001);
+        let noise_decay = params
+            .get("noise_decay")
+            .copied()
+            .unwrap_or(0.15)
+            .max(0.001);
+
         let amp_shared = shared(initial_amp);
         let pitch_bend_shared = shared(1.0);
         let pressure_shared = shared(0.0);
 
-        let left = noise();
-        let right = noise();
-        let synth = Box::new((left | right) * (var(&amp_shared) | var(&amp_shared)));
+        let tone = (var_fn(&pitch_bend_shared, move |bend| tone_freq * bend) >> sine())
+            * envelope(move |t: f32| (-t / tone_decay).exp());
+        let snap = noise() * envelope(move |t: f32| (-t / noise_decay).exp());
+        let body = (tone * tone_mix + snap * (1.0 - tone_mix)) * var(&amp_shared);
+        let synth = Box::new(body >> split::<U2>());
 
         let controls = VoiceControls {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
-        SynthMetadata::new("noise", "White noise generator")
+        SynthMetadata::new("snare", "Snare drum (noise snap + pitched body)")
             .with_param("amp", 1.0, 0.0, 2.0)
+            .with_param("tone_mix", 0.3, 0.0, 1.0)
+            .with_param("tone_freq", 180.0, 80.0, 400.0)
+            .with_param("tone_decay", 0.06, 0.01, 0.5)
+            .with_param("noise_decay", 0.15, 0.01, 1.0)
+            .with_tag("drum")
             .with_tag("noise")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ratio of first-difference energy to signal energy (higher = brighter)
+    fn brightness(params: &[(&str, f32)]) -> f32 {
+        let params: HashMap<String, f32> =
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        let (mut unit, _) = NoiseSynthBuilder.build(440.0, &params);
+        unit.set_sample_rate(48000.0);
+        let (mut last, mut diff, mut energy) = (0.0f32, 0.0f32, 0.0f32);
+        for i in 0..48000 {
+            let (l, _) = unit.get_stereo();
+            if i >= 4800 {
+                diff += (l - last) * (l - last);
+                energy += l * l;
+            }
+            last = l;
+        }
+        diff / energy
+    }
+
+    #[test]
+    fn test_noise_color_and_filters() {
+        let white = brightness(&[]);
+        let pink = brightness(&[("color", 1.0)]);
+        let brown = brightness(&[("color", 2.0)]);
+        assert!(white > pink && pink > brown, "{white} {pink} {brown}");
+
+        let highpassed = brightness(&[("highpass", 8000.0)]);
+        let lowpassed = brightness(&[("lowpass", 500.0)]);
+        assert!(highpassed > white, "{highpassed} vs {white}");
+        assert!(lowpassed < white * 0.1, "{lowpassed} vs {white}");
+    }
+
+    #[test]
+    fn test_cutoff_control_follows_filter() {
+        let (_, controls) = NoiseSynthBuilder.build(0.0, &HashMap::new());
+        assert!(controls.cutoff.is_none());
+
+        let params = HashMap::from([("highpass".to_string(), 8000.0)]);
+        let (_, controls) = NoiseSynthBuilder.build(0.0, &params);
+        assert_eq!(controls.cutoff.unwrap().value(), 8000.0);
+    }
+
+    /// Energy at `freq` relative to total energy over the first 100ms
+    fn tonal_ratio(params: &HashMap<String, f32>, freq: f32) -> f32 {
+        let (mut unit, _) = SnareSynthBuilder.build(0.0, params);
+        unit.set_sample_rate(48000.0);
+        let (mut sin_sum, mut cos_sum, mut energy) = (0.0f32, 0.0f32, 0.0f32);
+        for i in 0..4800 {
+            let (l, _) = unit.get_stereo();
+            let phase = std::f32::consts::TAU * freq * i as f32 / 48000.0;
+            sin_sum += l * phase.sin();
+            cos_sum += l * phase.cos();
+            energy += l * l;
+        }
+        (sin_sum * sin_sum + cos_sum * cos_sum) / energy
+    }
+
+    #[test]
+    fn test_tone_mix_adds_pitched_body() {
+        let noise_only = HashMap::from([("tone_mix".to_string(), 0.0)]);
+        let with_body = HashMap::from([("tone_mix".to_string(), 0.5)]);
+
+        let broadband = tonal_ratio(&noise_only, 180.0);
+        let pitched = tonal_ratio(&with_body, 180.0);
+        assert!(pitched > broadband * 10.0, "{pitched} vs {broadband}");
+    }
+}
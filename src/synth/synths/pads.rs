@@ -4,6 +4,7 @@
 //! - Strings: String ensemble pad
 //! - Pad: Generic warm pad
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -49,11 +50,13 @@ impl SynthBuilder for StringsSynthBuilder {
             amp: amp_shared,
             cutoff: Some(cutoff_shared),
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -102,11 +105,13 @@ impl SynthBuilder for PadSynthBuilder {
             amp: amp_shared,
             cutoff: Some(cutoff_shared),
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
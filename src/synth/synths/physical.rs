@@ -3,7 +3,9 @@
 //! This module contains synth builders based on physical modeling techniques:
 //! - PianoSynthBuilder: Simple piano-like sound with harmonics
 //! - PluckSynthBuilder: Karplus-Strong plucked string algorithm
+//! - KarplusStrongSynthBuilder: Karplus-Strong string with damping and decay controls
 
+use super::super::envelope::apply_envelope;
 use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
 use fundsp::hacker32::*;
 use std::collections::HashMap;
@@ -36,11 +38,13 @@ impl SynthBuilder for PianoSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -74,11 +78,13 @@ impl SynthBuilder for PluckSynthBuilder {
             amp: amp_shared,
             cutoff: None,
             resonance: None,
+            pulse_width: None,
             pitch_bend: pitch_bend_shared,
             pressure: pressure_shared,
+            gate: shared(1.0),
         };
 
-        (synth, controls)
+        apply_envelope(synth, controls, params)
     }
 
     fn metadata(&self) -> SynthMetadata {
@@ -87,3 +93,257 @@ impl SynthBuilder for PluckSynthBuilder {
             .with_tag("pluck")
     }
 }
+
+/// Karplus-Strong string: an excitation burst circulating in a damped delay loop
+///
+/// The loop length follows `pitch_bend`, which fundsp's `pluck()` cannot do
+/// since it fixes the frequency at construction.
+#[derive(Clone)]
+pub struct KarplusStrong {
+    freq: f32,
+    /// Loop lowpass coefficient (0.0 = bright, towards 1.0 = dark)
+    damping: f32,
+    /// Time for the loop to decay by 60 dB in seconds
+    decay: f32,
+    /// Excitation shape (0.0 = noise burst, 1.0 = smooth pluck)
+    excitation: f32,
+    /// Seed of the excitation noise, set through `ping`
+    seed: u64,
+    amp: Shared,
+    pitch_bend: Shared,
+    buffer: Vec<f32>,
+    pos: usize,
+    /// Loop filter state
+    filter: f32,
+    excited: bool,
+    sample_rate: f32,
+}
+
+impl KarplusStrong {
+    /// Lowest playable frequency, which sizes the delay line
+    const MIN_FREQ: f32 = 20.0;
+
+    /// Fill the loop with one period of excitation
+    fn excite(&mut self) {
+        let period = (self.sample_rate / self.freq).clamp(2.0, self.buffer.len() as f32 - 2.0);
+        let length = period as usize;
+        // Fold the 64-bit seed into the 32-bit generator state
+        let mut seed = (self.seed ^ (self.seed >> 32)) as u32;
+
+        // Written straight into the loop, so triggering never allocates
+        self.buffer.fill(0.0);
+        self.filter = 0.0;
+        let burst = &mut self.buffer[..length];
+        for (i, sample) in burst.iter_mut().enumerate() {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let noise = (seed >> 8) as f32 / (1u32 << 23) as f32 - 1.0;
+            // Triangle peaking a quarter of the way along the string
+            let x = i as f32 / length as f32;
+            let pluck = if x < 0.25 { x * 4.0 } else { (1.0 - x) / 0.75 };
+            *sample = noise + (pluck - noise) * self.excitation;
+        }
+        let mean = burst.iter().sum::<f32>() / length as f32;
+        burst.iter_mut().for_each(|x| *x -= mean);
+        self.pos = length % self.buffer.len();
+        self.excited = true;
+    }
+
+    #[inline]
+    fn next_sample(&mut self) -> f32 {
+        if !self.excited {
+            self.excite();
+        }
+
+        let freq = self.freq * self.pitch_bend.value();
+        let len = self.buffer.len();
+        // The loop filter delays low frequencies by about d / (1 - d) samples
+        let filter_delay = self.damping / (1.0 - self.damping);
+        let delay = (self.sample_rate / freq - filter_delay).clamp(1.0, len as f32 - 2.0);
+
+        let whole = delay.floor();
+        let frac = delay - whole;
+        let a = self.buffer[(self.pos + len - whole as usize) % len];
+        let b = self.buffer[(self.pos + len - whole as usize - 1) % len];
+        let out = a + (b - a) * frac;
+
+        let loop_gain = 10.0_f32.powf(-3.0 / (self.decay.max(0.01) * freq));
+        self.filter = out + (self.filter - out) * self.damping;
+        self.buffer[self.pos] = self.filter * loop_gain;
+        self.pos = (self.pos + 1) % len;
+
+        out * self.amp.value()
+    }
+}
+
+impl AudioUnit for KarplusStrong {
+    fn inputs(&self) -> usize {
+        0
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.excited = false;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate as f32;
+        self.buffer = vec![0.0; (self.sample_rate / Self::MIN_FREQ) as usize + 4];
+        self.pos = 0;
+        self.excited = false;
+    }
+
+    fn tick(&mut self, _input: &[f32], output: &mut [f32]) {
+        let value = self.next_sample();
+        output[0] = value;
+        output[1] = value;
+    }
+
+    fn process(&mut self, size: usize, _input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let value = self.next_sample();
+            output.set_f32(0, i, value);
+            output.set_f32(1, i, value);
+        }
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        SignalFrame::new(2)
+    }
+
+    fn ping(&mut self, probe: bool, hash: fundsp::prelude::AttoHash) -> fundsp::prelude::AttoHash {
+        if !probe {
+            // Reseed the excitation noise, as fundsp does for its noise nodes
+            self.seed = hash.state();
+        }
+        hash.hash(self.get_id())
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"string_ks";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Karplus-Strong string with controllable damping and decay
+///
+/// `damping` darkens the string as it rings (higher = duller and shorter),
+/// `decay` is the ring time in seconds and `excitation` morphs the initial
+/// burst from noise (0.0, harp/guitar attack) to a smooth pluck (1.0).
+pub struct KarplusStrongSynthBuilder;
+
+impl SynthBuilder for KarplusStrongSynthBuilder {
+    fn build(
+        &self,
+        freq: f32,
+        params: &HashMap<String, f32>,
+    ) -> (Box<dyn AudioUnit>, VoiceControls) {
+        let initial_amp = params.get("amp").copied().unwrap_or(1.0);
+        let damping = params.get("damping").copied().unwrap_or(0.5);
+        let decay = params.get("decay").copied().unwrap_or(2.0);
+        let excitation = params.get("excitation").copied().unwrap_or(0.0);
+
+        let amp_shared = shared(initial_amp);
+        let pitch_bend_shared = shared(1.0);
+        let pressure_shared = shared(0.0);
+
+        let mut string = KarplusStrong {
+            freq: freq.max(KarplusStrong::MIN_FREQ),
+            damping: damping.clamp(0.0, 0.95),
+            decay,
+            excitation: excitation.clamp(0.0, 1.0),
+            seed: 0x2545_f491,
+            amp: amp_shared.clone(),
+            pitch_bend: pitch_bend_shared.clone(),
+            buffer: Vec::new(),
+            pos: 0,
+            filter: 0.0,
+            excited: false,
+            sample_rate: 48000.0,
+        };
+        string.set_sample_rate(48000.0);
+
+        let controls = VoiceControls {
+            amp: amp_shared,
+            cutoff: None,
+            resonance: None,
+            pulse_width: None,
+            pitch_bend: pitch_bend_shared,
+            pressure: pressure_shared,
+            gate: shared(1.0),
+        };
+
+        apply_envelope(Box::new(string), controls, params)
+    }
+
+    fn metadata(&self) -> SynthMetadata {
+        SynthMetadata::new("string_ks", "Karplus-Strong string (damping and decay)")
+            .with_param("amp", 1.0, 0.0, 2.0)
+            .with_param("damping", 0.5, 0.0, 0.95)
+            .with_param("decay", 2.0, 0.1, 10.0)
+            .with_param("excitation", 0.0, 0.0, 1.0)
+            .with_tag("physical")
+            .with_tag("pluck")
+            .with_tag("string")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Energy of the second half of a one second note
+    fn tail_energy(damping: f32) -> f32 {
+        let params = HashMap::from([("damping".to_string(), damping)]);
+        let (mut unit, _) = KarplusStrongSynthBuilder.build(220.0, &params);
+        (0..48000)
+            .map(|_| unit.get_stereo().0)
+            .skip(24000)
+            .map(|x| x * x)
+            .sum()
+    }
+
+    #[test]
+    fn test_damping_shortens_tail() {
+        let bright = tail_energy(0.1);
+        let dark = tail_energy(0.9);
+        assert!(bright > 0.0);
+        assert!(dark < bright * 0.5, "dark {dark}, bright {bright}");
+    }
+
+    #[test]
+    fn test_excitation_follows_seed() {
+        let registry = crate::synth::SynthRegistry::with_builtin();
+        let render = |seed| {
+            let (mut unit, _) = registry
+                .create_seeded("string_ks", 220.0, &HashMap::new(), seed)
+                .unwrap();
+            (0..1000).map(|_| unit.get_stereo().0).collect::<Vec<_>>()
+        };
+        assert_eq!(render(7), render(7));
+        assert_ne!(render(7), render(8));
+    }
+
+    #[test]
+    fn test_pitch_follows_bend() {
+        use crate::effects::PitchDetector;
+
+        let (mut unit, controls) = KarplusStrongSynthBuilder.build(220.0, &HashMap::new());
+        controls.pitch_bend.set_value(2.0);
+        let mut detector = PitchDetector::new(48000.0);
+        for _ in 0..8192 {
+            detector.push(unit.get_stereo().0);
+        }
+        let pitch = detector.pitch().unwrap();
+        assert!((pitch - 440.0).abs() < 440.0 * 0.02, "{pitch}");
+    }
+}
@@ -0,0 +1,381 @@
+//! Wavetable synthesizer
+//!
+//! This module contains a synth that plays user-supplied single-cycle
+//! waveforms:
+//! - WavetableSynthBuilder: Wavetable oscillator with morphing between tables
+
+use super::super::envelope::apply_envelope;
+use super::super::registry::{SynthBuilder, SynthMetadata, VoiceControls};
+use crate::effects::builtin::convolution::{Complex, Fft};
+use crate::Result;
+use fundsp::hacker32::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Length every table is resampled to
+const TABLE_SIZE: usize = 2048;
+/// Harmonics a full-bandwidth table holds
+const MAX_HARMONICS: usize = TABLE_SIZE / 2;
+/// Mipmap levels, level `k` keeping the lowest `MAX_HARMONICS >> k` harmonics
+const LEVELS: usize = MAX_HARMONICS.trailing_zeros() as usize + 1;
+
+/// Band-limited copies of one table, one per octave
+type Mipmap = Vec<Vec<f32>>;
+
+/// Resample a single-cycle waveform to `TABLE_SIZE` with linear interpolation
+fn resample_cycle(samples: &[f32]) -> Vec<f32> {
+    let len = samples.len();
+    (0..TABLE_SIZE)
+        .map(|i| {
+            let pos = i as f32 * len as f32 / TABLE_SIZE as f32;
+            let index = pos as usize;
+            let frac = pos - index as f32;
+            let a = samples[index % len];
+            let b = samples[(index + 1) % len];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Build the mipmap of a `TABLE_SIZE` cycle by truncating its spectrum
+fn band_limit(cycle: &[f32]) -> Mipmap {
+    let fft = Fft::new(TABLE_SIZE);
+    let mut spectrum: Vec<Complex> = cycle.iter().map(|&x| (x, 0.0)).collect();
+    fft.transform(&mut spectrum, false);
+    (0..LEVELS)
+        .map(|level| {
+            let harmonics = MAX_HARMONICS >> level;
+            let mut data: Vec<Complex> = spectrum
+                .iter()
+                .enumerate()
+                .map(|(bin, &value)| {
+                    if bin.min(TABLE_SIZE - bin) <= harmonics {
+                        value
+                    } else {
+                        (0.0, 0.0)
+                    }
+                })
+                .collect();
+            fft.transform(&mut data, true);
+            data.iter().map(|&(re, _)| re / TABLE_SIZE as f32).collect()
+        })
+        .collect()
+}
+
+/// Oscillator reading single-cycle tables, crossfading between neighbours
+///
+/// `morph` moves through the tables: 0.0 plays the first, 1.0 the last and
+/// values in between blend the two nearest tables. It is read every sample,
+/// so it can be changed while the note plays. Each table is stored as an
+/// octave mipmap and the oscillator reads the level whose harmonics all stay
+/// below Nyquist at the current pitch, so high notes do not alias.
+#[derive(Clone)]
+pub struct WavetableOscillator {
+    tables: Arc<Vec<Mipmap>>,
+    freq: f32,
+    morph: Shared,
+    amp: Shared,
+    pitch_bend: Shared,
+    /// Position in the cycle (0.0 to 1.0)
+    phase: f64,
+    sample_rate: f64,
+}
+
+impl WavetableOscillator {
+    #[inline]
+    fn next_sample(&mut self) -> f32 {
+        let freq = self.freq as f64 * self.pitch_bend.value() as f64;
+        // Fewest octaves down whose top harmonic stays below Nyquist
+        let level = (MAX_HARMONICS as f64 * 2.0 * freq / self.sample_rate)
+            .log2()
+            .ceil()
+            .clamp(0.0, (LEVELS - 1) as f64) as usize;
+
+        let last = (self.tables.len() - 1) as f32;
+        let pos = self.morph.value().clamp(0.0, 1.0) * last;
+        let first = pos as usize;
+        let second = (first + 1).min(self.tables.len() - 1);
+        let blend = pos - first as f32;
+
+        let index = self.phase * TABLE_SIZE as f64;
+        let i0 = index as usize % TABLE_SIZE;
+        let i1 = (i0 + 1) % TABLE_SIZE;
+        let frac = (index - index.floor()) as f32;
+        let read = |mipmap: &Mipmap| {
+            let table = &mipmap[level];
+            table[i0] + (table[i1] - table[i0]) * frac
+        };
+
+        let a = read(&self.tables[first]);
+        let b = read(&self.tables[second]);
+        let value = a + (b - a) * blend;
+
+        self.phase += freq / self.sample_rate;
+        self.phase -= self.phase.floor();
+
+        value * self.amp.value()
+    }
+}
+
+impl AudioUnit for WavetableOscillator {
+    fn inputs(&self) -> usize {
+        0
+    }
+    fn outputs(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    fn tick(&mut self, _input: &[f32], output: &mut [f32]) {
+        let value = self.next_sample();
+        output[0] = value;
+        output[1] = value;
+    }
+
+    fn process(&mut self, size: usize, _input: &BufferRef, output: &mut BufferMut) {
+        for i in 0..size {
+            let value = self.next_sample();
+            output.set_f32(0, i, value);
+            output.set_f32(1, i, value);
+        }
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        SignalFrame::new(2)
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: &[u8] = b"wavetable";
+        let mut hash = 0u64;
+        for &byte in ID {
+            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Wavetable synth playing single-cycle waveforms
+///
+/// Unlike the other builders this one carries its tables, so it is created
+/// with [`from_samples`](Self::from_samples) and registered as an instance:
+///
+/// ```no_run
+/// # use fundsp_rack::synth::synths::WavetableSynthBuilder;
+/// # use fundsp_rack::synth::SynthRegistry;
+/// # use std::sync::Arc;
+/// # fn main() -> fundsp_rack::Result<()> {
+/// let cycle: Vec<f32> = (0..256).map(|i| (i as f32 / 128.0) - 1.0).collect();
+/// let mut registry = SynthRegistry::with_builtin();
+/// registry.register("my_saw", Arc::new(WavetableSynthBuilder::from_samples(vec![cycle])?));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The `morph` parameter selects the position between tables. The built-in
+/// `"wavetable"` synth morphs through sine, triangle, saw and square. While a
+/// note plays, the position is the voice's
+/// [`pulse_width`](VoiceControls::pulse_width) control, so it can be set
+/// directly or swept with an [`LFOTarget::PulseWidth`](crate::synth::LFOTarget) LFO.
+#[derive(Clone)]
+pub struct WavetableSynthBuilder {
+    tables: Arc<Vec<Mipmap>>,
+}
+
+impl WavetableSynthBuilder {
+    /// Create a builder from one or more single-cycle waveforms
+    ///
+    /// Each table holds exactly one cycle and may have any length; tables
+    /// are resampled to a common length. Returns an error if there are no
+    /// tables or one of them is empty.
+    pub fn from_samples(tables: Vec<Vec<f32>>) -> Result<Self> {
+        if tables.is_empty() || tables.iter().any(|table| table.is_empty()) {
+            return Err(crate::Error::InvalidParameter(
+                "wavetable needs at least one non-empty table".to_string(),
+            ));
+        }
+        Ok(Self {
+            tables: Arc::new(
+                tables
+                    .iter()
+                    .map(|table| band_limit(&resample_cycle(table)))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Number of tables to morph between
+    pub fn table_count(&self) -> usize {
+        self.tables.len()
+    }
+}
+
+impl Default for WavetableSynthBuilder {
+    /// Sine, triangle, saw and square tables
+    fn default() -> Self {
+        let cycle = |f: fn(f32) -> f32| -> Vec<f32> {
+            (0..TABLE_SIZE)
+                .map(|i| f(i as f32 / TABLE_SIZE as f32))
+                .collect()
+        };
+        let tables = vec![
+            cycle(|t| (std::f32::consts::TAU * t).sin()),
+            cycle(|t| 1.0 - 4.0 * (t - 0.25 - (t - 0.25).round()).abs()),
+            cycle(|t| 2.0 * (t - (t + 0.5).floor())),
+            cycle(|t| if t < 0.5 { 1.0 } else { -1.0 }),
+        ];
+        Self {
+            tables: Arc::new(tables.iter().map(|table| band_limit(table)).collect()),
+        }
+    }
+}
+
+impl SynthBuilder for WavetableSynthBuilder {
+    fn build(
+        &self,
+        freq: f32,
+        params: &HashMap<String, f32>,
+    ) -> (Box<dyn AudioUnit>, VoiceControls) {
+        let initial_amp = params.get("amp").copied().unwrap_or(1.0);
+        let morph_shared = shared(params.get("morph").copied().unwrap_or(0.0));
+        let amp_shared = shared(initial_amp);
+        let pitch_bend_shared = shared(1.0);
+        let pressure_shared = shared(0.0);
+
+        let synth = Box::new(WavetableOscillator {
+            tables: self.tables.clone(),
+            freq,
+            morph: morph_shared.clone(),
+            amp: amp_shared.clone(),
+            pitch_bend: pitch_bend_shared.clone(),
+            phase: 0.0,
+            sample_rate: 48000.0,
+        });
+
+        let controls = VoiceControls {
+            amp: amp_shared,
+            cutoff: None,
+            resonance: None,
+            pulse_width: Some(morph_shared),
+            pitch_bend: pitch_bend_shared,
+            pressure: pressure_shared,
+            gate: shared(1.0),
+        };
+
+        apply_envelope(synth, controls, params)
+    }
+
+    fn metadata(&self) -> SynthMetadata {
+        SynthMetadata::new("wavetable", "Wavetable oscillator (morphs between tables)")
+            .with_param("amp", 1.0, 0.0, 2.0)
+            .with_param("morph", 0.0, 0.0, 1.0)
+            .with_tag("synth")
+            .with_tag("wavetable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::PitchDetector;
+
+    fn sine_cycle(len: usize, gain: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| (std::f32::consts::TAU * i as f32 / len as f32).sin() * gain)
+            .collect()
+    }
+
+    #[test]
+    fn test_plays_table_at_note_frequency() {
+        let builder = WavetableSynthBuilder::from_samples(vec![sine_cycle(100, 0.5)]).unwrap();
+        let (mut unit, _) = builder.build(440.0, &HashMap::new());
+        unit.set_sample_rate(48000.0);
+
+        let mut detector = PitchDetector::new(48000.0);
+        for _ in 0..8192 {
+            detector.push(unit.get_stereo().0);
+        }
+        let pitch = detector.pitch().unwrap();
+        assert!((pitch - 440.0).abs() < 2.0, "{pitch}");
+    }
+
+    #[test]
+    fn test_morph_blends_tables() {
+        let builder =
+            WavetableSynthBuilder::from_samples(vec![sine_cycle(64, 1.0), sine_cycle(64, -1.0)])
+                .unwrap();
+        assert_eq!(builder.table_count(), 2);
+
+        // Halfway between a sine and its inverse cancels out
+        let params = HashMap::from([("morph".to_string(), 0.5)]);
+        let (mut unit, _) = builder.build(220.0, &params);
+        for _ in 0..1000 {
+            assert!(unit.get_stereo().0.abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_morph_follows_voice_control() {
+        let builder =
+            WavetableSynthBuilder::from_samples(vec![sine_cycle(64, 1.0), sine_cycle(64, -1.0)])
+                .unwrap();
+        let (mut unit, controls) = builder.build(220.0, &HashMap::new());
+        let peak = (0..1000)
+            .map(|_| unit.get_stereo().0.abs())
+            .fold(0.0, f32::max);
+        assert!(peak > 0.9);
+
+        controls.pulse_width.unwrap().set(0.5);
+        for _ in 0..1000 {
+            assert!(unit.get_stereo().0.abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_high_notes_drop_harmonics_above_nyquist() {
+        // Fundamental plus a ninth harmonic
+        let cycle: Vec<f32> = (0..256)
+            .map(|i| {
+                let t = std::f32::consts::TAU * i as f32 / 256.0;
+                t.sin() + (9.0 * t).sin()
+            })
+            .collect();
+        let builder = WavetableSynthBuilder::from_samples(vec![cycle]).unwrap();
+
+        // At 100 Hz both partials play
+        let (mut unit, _) = builder.build(100.0, &HashMap::new());
+        unit.set_sample_rate(48000.0);
+        let peak = (0..480)
+            .map(|_| unit.get_stereo().0.abs())
+            .fold(0.0, f32::max);
+        assert!(peak > 1.5, "{peak}");
+
+        // At 3 kHz the ninth harmonic would be 27 kHz and is left out
+        let (mut unit, _) = builder.build(3000.0, &HashMap::new());
+        unit.set_sample_rate(48000.0);
+        for n in 0..1000 {
+            let expected = (std::f32::consts::TAU * 3000.0 * n as f32 / 48000.0).sin();
+            let value = unit.get_stereo().0;
+            assert!(
+                (value - expected).abs() < 1e-2,
+                "{n}: {value} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_empty_tables() {
+        assert!(WavetableSynthBuilder::from_samples(vec![]).is_err());
+        assert!(WavetableSynthBuilder::from_samples(vec![vec![0.0; 8], vec![]]).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! WAV file export (requires the `wav` feature)
+//!
+//! Writes rendered stereo audio to disk so presets can be auditioned without
+//! an audio backend.
+//!
+//! # Example
+//! ```rust,no_run
+//! use fundsp_rack::prelude::*;
+//!
+//! let mut poly = PolySynth::new("pad", 8);
+//! poly.note_on(60, 0.8);
+//! poly.render_to_wav("pad.wav", 44100, 44100.0).unwrap();
+//! ```
+
+use std::path::Path;
+
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+
+use crate::effects::EffectChain;
+use crate::render::RenderBuffer;
+use crate::synth::PolySynth;
+use crate::{Error, Result};
+
+/// Sample format of written WAV files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WavFormat {
+    /// 32-bit IEEE float, written without clipping
+    #[default]
+    Float32,
+    /// 16-bit integer PCM, clamped to [-1.0, 1.0]
+    Pcm16,
+}
+
+impl WavFormat {
+    fn spec(self, sample_rate: u32) -> WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            WavFormat::Float32 => (32, SampleFormat::Float),
+            WavFormat::Pcm16 => (16, SampleFormat::Int),
+        };
+        WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+/// Write stereo audio to a 32-bit float WAV file
+///
+/// Only the first `min(left.len(), right.len())` frames are written.
+pub fn write_wav<P: AsRef<Path>>(
+    path: P,
+    left: &[f32],
+    right: &[f32],
+    sample_rate: u32,
+) -> Result<()> {
+    write_wav_with_format(path, left, right, sample_rate, WavFormat::Float32)
+}
+
+/// Write stereo audio to a WAV file in the given format
+pub fn write_wav_with_format<P: AsRef<Path>>(
+    path: P,
+    left: &[f32],
+    right: &[f32],
+    sample_rate: u32,
+    format: WavFormat,
+) -> Result<()> {
+    let mut writer = WavWriter::create(path.as_ref(), format.spec(sample_rate))
+        .map_err(|e| Error::WavError(format!("Failed to create WAV file: {}", e)))?;
+
+    for (&l, &r) in left.iter().zip(right) {
+        for sample in [l, r] {
+            let written = match format {
+                WavFormat::Float32 => writer.write_sample(sample),
+                WavFormat::Pcm16 => {
+                    writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+                }
+            };
+            written.map_err(|e| Error::WavError(format!("Failed to write sample: {}", e)))?;
+        }
+    }
+
+    writer
+        .finalize()
+        .map_err(|e| Error::WavError(format!("Failed to finalize WAV file: {}", e)))
+}
+
+/// Read a stereo WAV file written by [`write_wav`] or [`write_wav_with_format`]
+///
+/// Integer samples are scaled to [-1.0, 1.0]. Mono files are duplicated to
+/// both channels.
+pub fn read_wav<P: AsRef<Path>>(path: P) -> Result<RenderBuffer> {
+    let mut reader = WavReader::open(path.as_ref())
+        .map_err(|e| Error::WavError(format!("Failed to open WAV file: {}", e)))?;
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<std::result::Result<_, _>>(),
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<std::result::Result<_, _>>()
+        }
+    }
+    .map_err(|e| Error::WavError(format!("Failed to read samples: {}", e)))?;
+
+    let channels = spec.channels.max(1) as usize;
+    let (left, right) = samples
+        .chunks_exact(channels)
+        .map(|frame| (frame[0], frame[channels.min(2) - 1]))
+        .unzip();
+    Ok(RenderBuffer::new(left, right, spec.sample_rate as f64))
+}
+
+impl RenderBuffer {
+    /// Write the buffer to a 32-bit float WAV file at its sample rate
+    pub fn write_wav<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write_wav_with_format(path, WavFormat::Float32)
+    }
+
+    /// Write the buffer to a WAV file in the given format at its sample rate
+    pub fn write_wav_with_format<P: AsRef<Path>>(&self, path: P, format: WavFormat) -> Result<()> {
+        write_wav_with_format(
+            path,
+            &self.left,
+            &self.right,
+            self.sample_rate.round() as u32,
+            format,
+        )
+    }
+}
+
+impl PolySynth {
+    /// Render `frames` frames at `sample_rate` to a 32-bit float WAV file
+    pub fn render_to_wav<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        frames: usize,
+        sample_rate: f64,
+    ) -> Result<()> {
+        self.render_to_buffer(frames, sample_rate).write_wav(path)
+    }
+}
+
+impl EffectChain {
+    /// Process input audio at `sample_rate` and write the result to a 32-bit
+    /// float WAV file
+    pub fn render_to_wav<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        left: &[f32],
+        right: &[f32],
+        sample_rate: f64,
+    ) -> Result<()> {
+        self.render_to_buffer(left, right, sample_rate)
+            .write_wav(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("fundsp_rack_{}_{}.wav", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_wav_round_trip() {
+        let left: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.01).sin() * 0.9).collect();
+        let right: Vec<f32> = left.iter().map(|x| -x * 0.5).collect();
+
+        let path = temp_path("float");
+        write_wav(&path, &left, &right, 44100).unwrap();
+        let read = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.sample_rate, 44100.0);
+        assert_eq!(read.left, left);
+        assert_eq!(read.right, right);
+
+        let path = temp_path("pcm16");
+        write_wav_with_format(&path, &left, &right, 48000, WavFormat::Pcm16).unwrap();
+        let read = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.sample_rate, 48000.0);
+        assert_eq!(read.len(), 1000);
+        for (a, b) in read.left.iter().zip(&left) {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_poly_render_to_wav() {
+        let mut poly = PolySynth::new("sine", 2);
+        poly.note_on(69, 0.8);
+        let path = temp_path("poly");
+        poly.render_to_wav(&path, 512, 22050.0).unwrap();
+        let read = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.len(), 512);
+        assert_eq!(read.sample_rate, 22050.0);
+        assert!(read.peak() > 0.0);
+    }
+}